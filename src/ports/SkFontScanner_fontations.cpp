@@ -60,7 +60,7 @@ bool SkFontScanner_Fontations::scanFont(
 
     if (style != nullptr) {
         fontations_ffi::BridgeFontStyle fontStyle;
-        if (fontations_ffi::get_font_style(*fontRef, fontStyle)) {
+        if (fontations_ffi::font_style(*fontRef, fontStyle)) {
             *style = SkFontStyle(fontStyle.weight, fontStyle.width,  (SkFontStyle::Slant)fontStyle.slant);
         } else {
             *style = SkFontStyle::Normal();
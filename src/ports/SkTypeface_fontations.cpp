@@ -14,11 +14,16 @@
 #include "include/core/SkStream.h"
 #include "include/effects/SkGradientShader.h"
 #include "include/pathops/SkPathOps.h"
+#include "include/private/base/SkMalloc.h"
+#include "include/private/base/SkTo.h"
 #include "src/core/SkFontDescriptor.h"
 #include "src/core/SkFontPriv.h"
 #include "src/ports/SkTypeface_fontations_priv.h"
 #include "src/ports/fontations/src/skpath_bridge.h"
 
+extern "C" void fontations_ffi_set_panic_callback(void (*callback)(const uint8_t* message,
+                                                                  size_t length));
+
 namespace {
 
 [[maybe_unused]] static inline const constexpr bool kSkShowTextBlitCoverage = false;
@@ -31,8 +36,16 @@ sk_sp<SkData> streamToData(const std::unique_ptr<SkStreamAsset>& font_data) {
     return SkData::MakeFromStream(font_data.get(), font_data->getLength());
 }
 
+void log_fontations_panic(const uint8_t* message, size_t length) {
+    SkDEBUGF("Fontations: recovered from panic: %.*s\n",
+             SkToInt(length),
+             reinterpret_cast<const char*>(message));
+}
+
 rust::Box<::fontations_ffi::BridgeFontRef> make_bridge_font_ref(sk_sp<SkData> fontData,
                                                                 uint32_t index) {
+    static SkOnce registerPanicCallback;
+    registerPanicCallback([] { fontations_ffi_set_panic_callback(log_fontations_panic); });
     rust::Slice<const uint8_t> slice{fontData->bytes(), fontData->size()};
     return fontations_ffi::make_font_ref(slice, index);
 }
@@ -81,7 +94,7 @@ sk_sp<SkTypeface> SkTypeface_Make_Fontations(std::unique_ptr<SkStreamAsset> font
 }
 
 SkTypeface_Fontations::SkTypeface_Fontations(sk_sp<SkData> fontData, const SkFontArguments& args)
-        : SkTypeface(SkFontStyle(), true)
+        : SkTypeface(SkFontStyle(), false)
         , fFontData(fontData)
         , fTtcIndex(args.getCollectionIndex())
         , fBridgeFontRef(make_bridge_font_ref(fFontData, fTtcIndex))
@@ -93,7 +106,15 @@ SkTypeface_Fontations::SkTypeface_Fontations(sk_sp<SkData> fontData, const SkFon
                   rust::Slice<const fontations_ffi::PaletteOverride>(
                           reinterpret_cast<const ::fontations_ffi::PaletteOverride*>(
                                   args.getPalette().overrides),
-                          args.getPalette().overrideCount))) {}
+                          args.getPalette().overrideCount))) {
+    this->setIsFixedPitch(fontations_ffi::is_fixed_pitch(*fBridgeFontRef));
+    fontations_ffi::BridgeFontStyle fontStyle;
+    if (fontations_ffi::variable_font_style(
+                *fBridgeFontRef, *fBridgeNormalizedCoords, fontStyle)) {
+        this->setFontStyle(SkFontStyle(
+                fontStyle.weight, fontStyle.width, (SkFontStyle::Slant)fontStyle.slant));
+    }
+}
 
 sk_sp<SkTypeface> SkTypeface_Fontations::MakeFromStream(std::unique_ptr<SkStreamAsset> stream,
                                                         const SkFontArguments& args) {
@@ -155,8 +176,82 @@ void PathGeometrySink::curve_to(float cx0, float cy0, float cx1, float cy1, floa
 
 void PathGeometrySink::close() { fPath.close(); }
 
+void PathGeometrySink::reset() {
+    fPath.reset();
+    fStarted = false;
+}
+
 SkPath PathGeometrySink::into_inner() && { return std::move(fPath); }
 
+fontations_ffi::PathWrapper& GlyphPathsSink::begin_glyph(uint16_t) {
+    fCurrent.reset();
+    return fCurrent;
+}
+
+void GlyphPathsSink::end_glyph(uint16_t,
+                               fontations_ffi::PathStatus status,
+                               const fontations_ffi::BridgeScalerMetrics& scaler_metrics) {
+    SkPath path;
+    if (status == fontations_ffi::PathStatus::Success ||
+        status == fontations_ffi::PathStatus::EmptyOutline) {
+        path = std::move(fCurrent).into_inner();
+        ResolveOverlaps(scaler_metrics, &path);
+    }
+    fPaths.push_back(std::move(path));
+    fStatuses.push_back(status);
+}
+
+void ResolveOverlaps(const fontations_ffi::BridgeScalerMetrics& scalerMetrics, SkPath* path) {
+    if (scalerMetrics.has_overlaps) {
+        // See SkScalerContext_FreeType_Base::generateGlyphPath.
+        Simplify(*path, path);
+        AsWinding(*path, path);
+    }
+}
+
+SkPath PathFromPackedVerbs(const rust::Vec<uint8_t>& verbs, const rust::Vec<float>& points) {
+    using fontations_ffi::PathVerb;
+    // Replaying through PathGeometrySink sanitizes the segments exactly like drawing through it.
+    PathGeometrySink sink;
+    const float* pts = points.data();
+    size_t numCoords = points.size();
+    size_t i = 0;
+    auto hasPoints = [&](size_t count) { return 2 * count <= numCoords - i; };
+    for (uint8_t verb : verbs) {
+        switch (static_cast<PathVerb>(verb)) {
+            case PathVerb::MoveTo:
+                if (!hasPoints(1)) return SkPath();
+                sink.move_to(pts[i], pts[i + 1]);
+                i += 2;
+                break;
+            case PathVerb::LineTo:
+                if (!hasPoints(1)) return SkPath();
+                sink.line_to(pts[i], pts[i + 1]);
+                i += 2;
+                break;
+            case PathVerb::QuadTo:
+                if (!hasPoints(2)) return SkPath();
+                sink.quad_to(pts[i], pts[i + 1], pts[i + 2], pts[i + 3]);
+                i += 4;
+                break;
+            case PathVerb::CurveTo:
+                if (!hasPoints(3)) return SkPath();
+                sink.curve_to(pts[i], pts[i + 1], pts[i + 2], pts[i + 3], pts[i + 4], pts[i + 5]);
+                i += 6;
+                break;
+            case PathVerb::Close:
+                sink.close();
+                break;
+            default:
+                return SkPath();
+        }
+    }
+    if (i != numCoords) {
+        return SkPath();
+    }
+    return std::move(sink).into_inner();
+}
+
 AxisWrapper::AxisWrapper(SkFontParameters::Variation::Axis axisArray[], size_t axisCount)
         : fAxisArray(axisArray), fAxisCount(axisCount) {}
 
@@ -189,7 +284,9 @@ void SkTypeface_Fontations::onGetFamilyName(SkString* familyName) const {
 
 bool SkTypeface_Fontations::onGetPostScriptName(SkString* postscriptName) const {
     rust::String readPsName;
-    if (fontations_ffi::postscript_name(*fBridgeFontRef, readPsName)) {
+    // Like FreeType, sanitize the name and synthesize one if there is none.
+    if (fontations_ffi::postscript_name_or_synthesized(*fBridgeFontRef, readPsName) !=
+        fontations_ffi::PostScriptNameSource::Missing) {
         *postscriptName = SkString(readPsName.data(), readPsName.size());
         return true;
     }
@@ -208,22 +305,54 @@ bool SkTypeface_Fontations::onGlyphMaskNeedsCurrentColor() const {
 void SkTypeface_Fontations::onCharsToGlyphs(const SkUnichar* chars,
                                             int count,
                                             SkGlyphID glyphs[]) const {
-    sk_bzero(glyphs, count * sizeof(glyphs[0]));
-
-    for (int i = 0; i < count; ++i) {
-        glyphs[i] = fontations_ffi::lookup_glyph_or_zero(*fBridgeFontRef, chars[i]);
-    }
+    static_assert(sizeof(SkUnichar) == sizeof(uint32_t));
+    fontations_ffi::lookup_glyphs(
+            *fBridgeFontRef,
+            rust::Slice<const uint32_t>(reinterpret_cast<const uint32_t*>(chars), count),
+            rust::Slice<uint16_t>(glyphs, count));
 }
 int SkTypeface_Fontations::onCountGlyphs() const {
     return fontations_ffi::num_glyphs(*fBridgeFontRef);
 }
 
+void SkTypeface_Fontations::getPostScriptGlyphNames(SkString* dstArray) const {
+    if (!fontations_ffi::has_glyph_names(*fBridgeFontRef)) {
+        return;
+    }
+    uint16_t numGlyphs = fontations_ffi::num_glyphs(*fBridgeFontRef);
+    rust::String glyphName;
+    for (uint16_t glyphId = 0; glyphId < numGlyphs; ++glyphId) {
+        if (fontations_ffi::glyph_name(*fBridgeFontRef, glyphId, glyphName)) {
+            dstArray[glyphId] = SkString(glyphName.data(), glyphName.size());
+        }
+    }
+}
+
+void SkTypeface_Fontations::getGlyphToUnicodeMap(SkUnichar* codepointForGlyphMap) const {
+    size_t numGlyphs = SkToSizeT(onCountGlyphs());
+    fontations_ffi::glyphs_to_unicode(
+            *fBridgeFontRef,
+            rust::Slice<uint32_t>(reinterpret_cast<uint32_t*>(codepointForGlyphMap), numGlyphs));
+}
+
 bool SkTypeface_Fontations::hasValidBridgeFontRef() const {
     return fontations_ffi::font_ref_is_valid(*fBridgeFontRef);
 }
 
 void SkTypeface_Fontations::onFilterRec(SkScalerContextRec* rec) const {
-    rec->setHinting(SkFontHinting::kNone);
+    // Hinted outlines are scaled vertically only, see generatePath, so as in
+    // the FreeType port hinting is disabled for rotated and skewed text.
+    bool axisAligned = rec->fPreSkewX == 0 &&
+                       ((rec->fPost2x2[0][1] == 0 && rec->fPost2x2[1][0] == 0) ||
+                        (rec->fPost2x2[0][0] == 0 && rec->fPost2x2[1][1] == 0));
+    // Only CFF and CFF2 outlines are hinted. TrueType instructions are not
+    // executed yet, so report glyf fonts as unhinted rather than rounding
+    // their outlines and advances.
+    // TODO(AfterlifeOS/external_skia#synth-44): Hint glyf fonts once skrifa
+    // is rolled to a version with the TrueType interpreter.
+    if (!axisAligned || !fontations_ffi::outlines_support_hinting(*fOutlines)) {
+        rec->setHinting(SkFontHinting::kNone);
+    }
 }
 
 class SkrifaLocalizedStrings : public SkTypeface::LocalizedStrings {
@@ -248,7 +377,8 @@ private:
 };
 
 SkTypeface::LocalizedStrings* SkTypeface_Fontations::onCreateFamilyNameIterator() const {
-    return new SkrifaLocalizedStrings(fontations_ffi::get_localized_strings(*fBridgeFontRef));
+    // Name ID 1, the font family name.
+    return new SkrifaLocalizedStrings(fontations_ffi::get_localized_strings(*fBridgeFontRef, 1));
 }
 
 class SkFontationsScalerContext : public SkScalerContext {
@@ -262,33 +392,62 @@ public:
             , fBridgeNormalizedCoords(static_cast<SkTypeface_Fontations*>(this->getTypeface())
                                               ->getBridgeNormalizedCoords())
             , fOutlines(static_cast<SkTypeface_Fontations*>(this->getTypeface())->getOutlines())
-            , fPalette(static_cast<SkTypeface_Fontations*>(this->getTypeface())->getPalette()) {
+            , fPalette(static_cast<SkTypeface_Fontations*>(this->getTypeface())->getPalette())
+            , fOutlineLimits(fontations_ffi::default_outline_limits())
+            , fPathTransform(fontations_ffi::identity_path_transform())
+            , fHinting(ToHintingLevel(fRec.getHinting())) {
         fRec.getSingleMatrix(&fMatrix);
     }
 
-    // TODO(drott): Add parameter/control for hinting here once that is available from Fontations.
     bool generateYScalePathForGlyphId(uint16_t glyphId, SkPath* path, float yScale) {
-        sk_fontations::PathGeometrySink pathWrapper;
         fontations_ffi::BridgeScalerMetrics scalerMetrics;
 
-        if (!fontations_ffi::get_path(fOutlines,
-                                      glyphId,
-                                      yScale,
-                                      fBridgeNormalizedCoords,
-                                      pathWrapper,
-                                      scalerMetrics)) {
+        // The packed buffers are reused across glyphs to avoid a call across the bridge per
+        // segment and an allocation per glyph.
+        fontations_ffi::PathStatus status =
+                fontations_ffi::get_path_verbs(fOutlines,
+                                               glyphId,
+                                               yScale,
+                                               fBridgeNormalizedCoords,
+                                               fOutlineLimits,
+                                               fPathTransform,
+                                               this->emboldenStrength(yScale),
+                                               fHinting,
+                                               fPathVerbs,
+                                               fPathPoints,
+                                               scalerMetrics);
+        // An empty outline is a valid result, e.g. for a space glyph. Other
+        // failures, including outlines exceeding fOutlineLimits, are reported
+        // to the caller so that it can fall back.
+        if (status != fontations_ffi::PathStatus::Success &&
+            status != fontations_ffi::PathStatus::EmptyOutline) {
             return false;
         }
-        *path = std::move(pathWrapper).into_inner();
-        if (scalerMetrics.has_overlaps) {
-            // See SkScalerContext_FreeType_Base::generateGlyphPath.
-            Simplify(*path, path);
-            AsWinding(*path, path);
-        }
+        *path = sk_fontations::PathFromPackedVerbs(fPathVerbs, fPathPoints);
+        sk_fontations::ResolveOverlaps(scalerMetrics, path);
         return true;
     }
 
 protected:
+    static fontations_ffi::HintingLevel ToHintingLevel(SkFontHinting hinting) {
+        switch (hinting) {
+            case SkFontHinting::kNone:
+                return fontations_ffi::HintingLevel::None;
+            case SkFontHinting::kSlight:
+                return fontations_ffi::HintingLevel::Slight;
+            case SkFontHinting::kNormal:
+            case SkFontHinting::kFull:
+                return fontations_ffi::HintingLevel::Full;
+        }
+        SkUNREACHABLE;
+    }
+
+    // Matches the outline embolden strength of the FreeType port.
+    float emboldenStrength(float yScale) const {
+        static constexpr float kEmboldenDivisor = 24;
+        return (fRec.fFlags & SkScalerContext::kEmbolden_Flag) ? yScale / kEmboldenDivisor : 0;
+    }
+
     struct ScalerContextBits {
         using value_type = uint16_t;
         static const constexpr value_type PATH = 1;
@@ -305,9 +464,23 @@ protected:
                     SkScalerContextRec::PreMatrixScale::kVertical, &scale, &remainingMatrix)) {
             return mx;
         }
+        // Like FreeType's outline emboldening, fake bold leaves advances
+        // unchanged.
         float x_advance = 0.0f;
-        x_advance = fontations_ffi::advance_width_or_zero(
-                fBridgeFontRef, scale.y(), fBridgeNormalizedCoords, glyph.getGlyphID());
+        if (fHinting == fontations_ffi::HintingLevel::Full && !this->isLinearMetrics()) {
+            x_advance = fontations_ffi::hinted_advance_width_or_zero(fBridgeFontRef,
+                                                                     fOutlines,
+                                                                     scale.y(),
+                                                                     fBridgeNormalizedCoords,
+                                                                     glyph.getGlyphID(),
+                                                                     fHinting);
+        } else {
+            x_advance = fontations_ffi::advance_width_or_zero(fBridgeFontRef,
+                                                              scale.y(),
+                                                              fBridgeNormalizedCoords,
+                                                              glyph.getGlyphID(),
+                                                              fontations_ffi::SizeRounding::Exact);
+        }
         // TODO(drott): y-advance?
         mx.advance = remainingMatrix.mapXY(x_advance, SkFloatToScalar(0.f));
 
@@ -486,8 +659,19 @@ protected:
     }
 
     void generateFontMetrics(SkFontMetrics* out_metrics) override {
+        // Like generateMetrics, use the positive vertical scale rather than
+        // the matrix's y scale, which is 0 for rotated and negative for
+        // mirrored text.
+        SkVector scale;
+        SkMatrix remainingMatrix;
+        if (!fRec.computeMatrices(
+                    SkScalerContextRec::PreMatrixScale::kVertical, &scale, &remainingMatrix)) {
+            sk_bzero(out_metrics, sizeof(*out_metrics));
+            return;
+        }
         fontations_ffi::Metrics metrics = fontations_ffi::get_skia_metrics(
-                fBridgeFontRef, fMatrix.getScaleY(), fBridgeNormalizedCoords);
+                fBridgeFontRef, scale.y(), fBridgeNormalizedCoords, fHinting,
+                /*use_vdmx=*/true, fontations_ffi::SizeRounding::Exact);
         out_metrics->fTop = -metrics.top;
         out_metrics->fAscent = -metrics.ascent;
         out_metrics->fDescent = -metrics.descent;
@@ -500,6 +684,25 @@ protected:
         out_metrics->fXHeight = -metrics.x_height;
         out_metrics->fCapHeight = -metrics.cap_height;
         out_metrics->fFlags = 0;
+        if (!metrics.bounds_is_valid) {
+            out_metrics->fFlags |= SkFontMetrics::kBoundsInvalid_Flag;
+        }
+        if (metrics.underline_position_is_valid) {
+            out_metrics->fUnderlinePosition = -metrics.underline_position;
+            out_metrics->fFlags |= SkFontMetrics::kUnderlinePositionIsValid_Flag;
+        }
+        if (metrics.underline_thickness_is_valid) {
+            out_metrics->fUnderlineThickness = metrics.underline_thickness;
+            out_metrics->fFlags |= SkFontMetrics::kUnderlineThicknessIsValid_Flag;
+        }
+        if (metrics.strikeout_position_is_valid) {
+            out_metrics->fStrikeoutPosition = -metrics.strikeout_position;
+            out_metrics->fFlags |= SkFontMetrics::kStrikeoutPositionIsValid_Flag;
+        }
+        if (metrics.strikeout_thickness_is_valid) {
+            out_metrics->fStrikeoutThickness = metrics.strikeout_thickness;
+            out_metrics->fFlags |= SkFontMetrics::kStrikeoutThicknessIsValid_Flag;
+        }
         // TODO(drott): Is it necessary to transform metrics with remaining parts of matrix?
     }
 
@@ -510,6 +713,12 @@ private:
     const fontations_ffi::BridgeNormalizedCoords& fBridgeNormalizedCoords;
     const fontations_ffi::BridgeOutlineCollection& fOutlines;
     const SkSpan<SkColor> fPalette;
+    const fontations_ffi::BridgeOutlineLimits fOutlineLimits;
+    // Skew and other transforms are applied through fMatrix.
+    const fontations_ffi::BridgePathTransform fPathTransform;
+    const fontations_ffi::HintingLevel fHinting;
+    rust::Vec<uint8_t> fPathVerbs;
+    rust::Vec<float> fPathPoints;
     friend class sk_fontations::ColorPainter;
 };
 
@@ -550,13 +759,26 @@ size_t SkTypeface_Fontations::onGetTableData(SkFontTableTag tag,
     return std::min(copied, length);
 }
 
+sk_sp<SkData> SkTypeface_Fontations::onCopyTableData(SkFontTableTag tag) const {
+    rust::Slice<const uint8_t> tableData = fontations_ffi::table_data_slice(*fBridgeFontRef, tag);
+    if (tableData.empty()) {
+        return nullptr;
+    }
+    // The table data points into fFontData, keep it alive for the returned SkData.
+    return SkData::MakeWithProc(
+            tableData.data(),
+            tableData.size(),
+            [](const void*, void* context) { static_cast<SkData*>(context)->unref(); },
+            SkRef(fFontData.get()));
+}
+
 int SkTypeface_Fontations::onGetTableTags(SkFontTableTag tags[]) const {
-    uint16_t numTables = fontations_ffi::table_tags(*fBridgeFontRef, rust::Slice<uint32_t>());
+    size_t numTables = fontations_ffi::table_tags(*fBridgeFontRef, rust::Slice<uint32_t>());
     if (!tags) {
-        return numTables;
+        return SkToInt(numTables);
     }
     rust::Slice<uint32_t> copyToTags(tags, numTables);
-    return fontations_ffi::table_tags(*fBridgeFontRef, copyToTags);
+    return SkToInt(fontations_ffi::table_tags(*fBridgeFontRef, copyToTags));
 }
 
 int SkTypeface_Fontations::onGetVariationDesignPosition(
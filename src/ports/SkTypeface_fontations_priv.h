@@ -18,6 +18,7 @@
 #include "src/ports/fontations/src/ffi.rs.h"
 
 #include <memory>
+#include <vector>
 
 class SkStreamAsset;
 class SkFontationsScalerContext;
@@ -34,6 +35,7 @@ public:
     void quad_to(float cx0, float cy0, float x, float y) override;
     void curve_to(float cx0, float cy0, float cx1, float cy1, float x, float y) override;
     void close() override;
+    void reset() override;
 
     SkPath into_inner() &&;
 
@@ -42,10 +44,39 @@ private:
     bool current_is_not(SkPoint);
 
     SkPath fPath;
-    bool fStarted;
+    bool fStarted = false;
     SkPoint fCurrent;
 };
 
+/** Implementation of GlyphPathsWrapper FFI C++ interface, collecting one SkPath per glyph drawn
+ * by fontations_ffi::get_paths. */
+class GlyphPathsSink : public fontations_ffi::GlyphPathsWrapper {
+public:
+    /* From fontations_ffi::GlyphPathsWrapper. */
+    fontations_ffi::PathWrapper& begin_glyph(uint16_t glyph_id) override;
+    void end_glyph(uint16_t glyph_id,
+                   fontations_ffi::PathStatus status,
+                   const fontations_ffi::BridgeScalerMetrics& scaler_metrics) override;
+
+    /** Paths in the order the glyphs were drawn, empty for glyphs that failed to draw. */
+    const std::vector<SkPath>& paths() const { return fPaths; }
+    const std::vector<fontations_ffi::PathStatus>& statuses() const { return fStatuses; }
+
+private:
+    PathGeometrySink fCurrent;
+    std::vector<SkPath> fPaths;
+    std::vector<fontations_ffi::PathStatus> fStatuses;
+};
+
+/** Removes the overlaps of a path drawn by fontations_ffi if its scaler metrics report overlapping
+ * contours, so that it fills the same area under any fill type. */
+void ResolveOverlaps(const fontations_ffi::BridgeScalerMetrics& scalerMetrics, SkPath* path);
+
+/** Builds the SkPath for the buffers filled by fontations_ffi::get_path_verbs in one pass, equal to
+ * the path PathGeometrySink builds for the same outline. Returns an empty path if the points do not
+ * match the verbs. */
+SkPath PathFromPackedVerbs(const rust::Vec<uint8_t>& verbs, const rust::Vec<float>& points);
+
 /** Implementation of AxisWrapper FFI C++ interface, allowing Rust to call back into
  * C++ for populating variable axis availability information, see skpath_bridge.h. */
 class AxisWrapper : public fontations_ffi::AxisWrapper {
@@ -232,8 +263,8 @@ protected:
     void onGetFontDescriptor(SkFontDescriptor*, bool*) const override;
     void onCharsToGlyphs(const SkUnichar* chars, int count, SkGlyphID glyphs[]) const override;
     int onCountGlyphs() const override;
-    void getPostScriptGlyphNames(SkString*) const override {}
-    void getGlyphToUnicodeMap(SkUnichar*) const override {}
+    void getPostScriptGlyphNames(SkString*) const override;
+    void getGlyphToUnicodeMap(SkUnichar*) const override;
     int onGetUPEM() const override;
     void onGetFamilyName(SkString* familyName) const override;
     bool onGetPostScriptName(SkString*) const override;
@@ -245,6 +276,7 @@ protected:
                                        int parameterCount) const override;
     int onGetTableTags(SkFontTableTag tags[]) const override;
     size_t onGetTableData(SkFontTableTag, size_t, size_t, void*) const override;
+    sk_sp<SkData> onCopyTableData(SkFontTableTag) const override;
 
 private:
     sk_sp<SkData> fFontData;
@@ -0,0 +1,10 @@
+// Copyright 2023 Google LLC
+// Use of this source code is governed by a BSD-style license that can be found
+// in the LICENSE file.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    fontations_ffi::fuzzing::draw_glyphs(data);
+});
@@ -0,0 +1,24 @@
+#!/bin/sh
+# Copyright 2023 Google LLC
+# Use of this source code is governed by a BSD-style license that can be found
+# in the LICENSE file.
+#
+# Seeds the corpus of every fuzz target with the fonts used by the bridge
+# tests in ../src/ffi.rs. Run before `cargo fuzz run <target>`.
+set -e
+cd "$(dirname "$0")"
+fonts=../../../../resources/fonts
+for target in fuzz_targets/*.rs; do
+    corpus=corpus/$(basename "$target" .rs)
+    mkdir -p "$corpus"
+    for font in \
+        NotoSansCJK-VF-subset.otf.ttc \
+        Roboto-Regular.ttf \
+        Variable.ttf \
+        colr.ttf \
+        cond-bold-italic.ttf \
+        test.ttc \
+        test_glyphs-glyf_colr_1_variable.ttf; do
+        cp "$fonts/$font" "$corpus/"
+    done
+done
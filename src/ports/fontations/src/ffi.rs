@@ -2,62 +2,457 @@ use ffi::{FillLinearParams, FillRadialParams};
 // Copyright 2023 Google LLC
 // Use of this source code is governed by a BSD-style license that can be found
 // in the LICENSE file.
-use font_types::{BoundingBox, GlyphId, Pen};
-use read_fonts::{tables::colr::CompositeMode, FileRef, FontRef, ReadError, TableProvider};
+use font_types::{BoundingBox, F2Dot14, Fixed, GlyphId, Pen};
+use read_fonts::{
+    tables::{
+        avar::SegmentMaps,
+        base::Base,
+        cmap::{Cmap, Cmap0, CmapSubtable, PlatformId},
+        colr::CompositeMode,
+        glyf::{CompositeGlyphFlags, Glyf, Glyph},
+        head::MacStyle,
+        hvar::Hvar,
+        loca::Loca,
+        name::NameRecord,
+        os2::SelectionFlags,
+        postscript::{self, dict, BlendState, FdSelect, Index, Stack},
+        stat::{AxisValue, AxisValueTableFlags},
+        variations::{DeltaSetIndex, DeltaSetIndexMap, ItemVariationStore},
+    },
+    FileRef, FontData, FontRead, FontRef, ReadError, TableProvider,
+};
 use skrifa::{
+    charmap::MapVariant,
     color::{Brush, ColorGlyphFormat, ColorPainter, Transform},
-    instance::{Location, Size},
+    instance::{Location, LocationRef, NormalizedCoord, Size},
     metrics::{GlyphMetrics, Metrics},
-    outline::DrawSettings,
+    outline::{
+        DrawError, DrawSettings, EmbeddedHinting, EmbeddedHintingInstance, OutlineGlyphFormat,
+    },
     setting::VariationSetting,
-    string::{LocalizedStrings, StringId},
-    MetadataProvider, OutlineGlyphCollection, Tag,
+    string::{LocalizedString, StringId},
+    Axis, MetadataProvider, OutlineGlyphCollection, Tag,
+};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    ops::{Range, RangeInclusive},
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Mutex, PoisonError},
 };
-use std::pin::Pin;
-
-use skrifa::attribute::{Style};
 
 use crate::ffi::{
-    AxisWrapper, BridgeScalerMetrics, ColorPainterWrapper, ColorStop, PaletteOverride, PathWrapper,
-    SkiaDesignCoordinate,
+    AxisWrapper, BridgeAdvances, BridgeAxisInfo, BridgeCaretInfo, BridgeChecksumResult,
+    BridgeCmapSubtableInfo, BridgeGlyphMetrics, BridgeOutlineLimits, BridgePathBounds,
+    BridgePathTransform, BridgeRenderPrerequisites, BridgeScalerMetrics, BridgeScriptMetrics,
+    BridgeVdmxValues, BridgeVerticalGlyphMetrics, BridgeVerticalLineMetrics, ColorPainterWrapper,
+    ColorStop, CoordinateResolutionReport, GlyphPathsWrapper, HintingLevel, LineMetricsSource,
+    PaletteOverride, PathStatus, PathVerb, PathWrapper, PostScriptNameSource, SideBearingSource,
+    SizeRounding, SkiaDesignCoordinate,
 };
 
+/// Unicode codepoints of the Mac Roman encoding for bytes 0x80 to 0xFF. Bytes
+/// below 0x80 match ASCII.
+const MAC_ROMAN_TO_UNICODE: [u16; 128] = [
+    0x00C4, 0x00C5, 0x00C7, 0x00C9, 0x00D1, 0x00D6, 0x00DC, 0x00E1, 0x00E0, 0x00E2, 0x00E4, 0x00E3,
+    0x00E5, 0x00E7, 0x00E9, 0x00E8, 0x00EA, 0x00EB, 0x00ED, 0x00EC, 0x00EE, 0x00EF, 0x00F1, 0x00F3,
+    0x00F2, 0x00F4, 0x00F6, 0x00F5, 0x00FA, 0x00F9, 0x00FB, 0x00FC, 0x2020, 0x00B0, 0x00A2, 0x00A3,
+    0x00A7, 0x2022, 0x00B6, 0x00DF, 0x00AE, 0x00A9, 0x2122, 0x00B4, 0x00A8, 0x2260, 0x00C6, 0x00D8,
+    0x221E, 0x00B1, 0x2264, 0x2265, 0x00A5, 0x00B5, 0x2202, 0x2211, 0x220F, 0x03C0, 0x222B, 0x00AA,
+    0x00BA, 0x03A9, 0x00E6, 0x00F8, 0x00BF, 0x00A1, 0x00AC, 0x221A, 0x0192, 0x2248, 0x2206, 0x00AB,
+    0x00BB, 0x2026, 0x00A0, 0x00C0, 0x00C3, 0x00D5, 0x0152, 0x0153, 0x2013, 0x2014, 0x201C, 0x201D,
+    0x2018, 0x2019, 0x00F7, 0x25CA, 0x00FF, 0x0178, 0x2044, 0x20AC, 0x2039, 0x203A, 0xFB01, 0xFB02,
+    0x2021, 0x00B7, 0x201A, 0x201E, 0x2030, 0x00C2, 0x00CA, 0x00C1, 0x00CB, 0x00C8, 0x00CD, 0x00CE,
+    0x00CF, 0x00CC, 0x00D3, 0x00D4, 0xF8FF, 0x00D2, 0x00DA, 0x00DB, 0x00D9, 0x0131, 0x02C6, 0x02DC,
+    0x00AF, 0x02D8, 0x02D9, 0x02DA, 0x00B8, 0x02DD, 0x02DB, 0x02C7,
+];
+
+fn unicode_to_mac_roman(codepoint: u32) -> Option<u8> {
+    if codepoint < 0x80 {
+        return Some(codepoint as u8);
+    }
+    MAC_ROMAN_TO_UNICODE
+        .iter()
+        .position(|&unicode| u32::from(unicode) == codepoint)
+        .map(|index| 0x80 + index as u8)
+}
+
+/// The font's charmap, falling back to a (1,0) Mac Roman format 0 subtable for
+/// fonts without any Unicode or symbol subtable.
+struct NominalCharmap<'a> {
+    charmap: skrifa::charmap::Charmap<'a>,
+    mac_roman: Option<Cmap0<'a>>,
+}
+
+impl<'a> NominalCharmap<'a> {
+    fn new(font: &FontRef<'a>) -> Self {
+        let charmap = font.charmap();
+        let mac_roman = if charmap.has_map() {
+            None
+        } else {
+            font.cmap().ok().and_then(|cmap| {
+                cmap.encoding_records().iter().find_map(|record| {
+                    if record.platform_id() != PlatformId::Macintosh || record.encoding_id() != 0 {
+                        return None;
+                    }
+                    match record.subtable(cmap.offset_data()).ok()? {
+                        CmapSubtable::Format0(cmap0) => Some(cmap0),
+                        _ => None,
+                    }
+                })
+            })
+        };
+        Self { charmap, mac_roman }
+    }
+
+    fn map(&self, codepoint: u32) -> Option<GlyphId> {
+        match &self.mac_roman {
+            Some(cmap0) => {
+                let byte = unicode_to_mac_roman(codepoint)?;
+                let glyph_id = *cmap0.glyph_id_array().get(usize::from(byte))?;
+                (glyph_id != 0).then(|| GlyphId::new(glyph_id.into()))
+            }
+            None => self.charmap.map(codepoint),
+        }
+    }
+
+    /// Returns the mappings `map` uses, with Mac Roman bytes converted to
+    /// their Unicode codepoints.
+    fn mappings(&self) -> Vec<(u32, GlyphId)> {
+        match &self.mac_roman {
+            Some(cmap0) => (0u8..=u8::MAX)
+                .zip(cmap0.glyph_id_array())
+                .filter(|(_, glyph_id)| **glyph_id != 0)
+                .map(|(byte, glyph_id)| {
+                    let codepoint = match byte.checked_sub(0x80) {
+                        Some(index) => MAC_ROMAN_TO_UNICODE[usize::from(index)].into(),
+                        None => byte.into(),
+                    };
+                    (codepoint, GlyphId::new((*glyph_id).into()))
+                })
+                .collect(),
+            None => self.charmap.mappings().collect(),
+        }
+    }
+}
+
+/// Receives the UTF-8 message of a panic caught by `ffi_guard`.
+type PanicCallback = unsafe extern "C" fn(message: *const u8, length: usize);
+
+static PANIC_CALLBACK: Mutex<Option<PanicCallback>> = Mutex::new(None);
+
+/// Registers `callback` to receive the message of any panic caught inside a
+/// bridge function, or unregisters it if null. Such functions return their
+/// failure value (false, 0, empty or an invalid object) instead of unwinding
+/// into C++. cxx cannot pass function pointers from C++ to Rust, hence the
+/// plain C ABI.
+#[no_mangle]
+pub extern "C" fn fontations_ffi_set_panic_callback(callback: Option<PanicCallback>) {
+    *PANIC_CALLBACK
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = callback;
+}
+
+/// Runs the body of a bridge function, converting a panic into the function's
+/// failure value `T::default()` instead of unwinding into C++, and reports the
+/// panic message to the callback registered with
+/// `fontations_ffi_set_panic_callback`. A panicking body leaves its
+/// out-parameters in a valid but unspecified state, which callers already have
+/// to expect on failure, so unwind safety is asserted rather than required of
+/// every body.
+fn ffi_guard<T: Default>(body: impl FnOnce() -> T) -> T {
+    // Fuzz targets need panics to surface as crashes.
+    if cfg!(fuzzing) {
+        return body();
+    }
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        #[cfg(test)]
+        test::maybe_inject_panic();
+        body()
+    }));
+    result.unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic");
+        if let Some(callback) = *PANIC_CALLBACK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            // SAFETY: Registered callbacks accept any UTF-8 message for the
+            // duration of the call.
+            unsafe { callback(message.as_ptr(), message.len()) };
+        }
+        T::default()
+    })
+}
+
 fn lookup_glyph_or_zero(font_ref: &BridgeFontRef, codepoint: u32) -> u16 {
-    font_ref
-        .with_font(|f| Some(f.charmap().map(codepoint)?.to_u16()))
-        .unwrap_or_default()
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| Some(NominalCharmap::new(f).map(codepoint)?.to_u16()))
+            .unwrap_or_default()
+    })
+}
+
+fn has_symbol_charmap(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| Some(f.charmap().is_symbol()))
+            .unwrap_or_default()
+    })
+}
+
+/// Returns the index of the encoding record whose subtable `NominalCharmap`
+/// maps with. This mirrors skrifa's selection, which prefers a symbol subtable,
+/// then the last full repertoire Unicode subtable, then the last BMP one, and
+/// only considers formats 4 and 12.
+fn selected_cmap_subtable_index(cmap: &Cmap) -> Option<usize> {
+    let mut selected = None;
+    let mut selected_priority = 0;
+    let mut mac_roman = None;
+    for (index, record) in cmap.encoding_records().iter().enumerate() {
+        let Ok(subtable) = record.subtable(cmap.offset_data()) else {
+            continue;
+        };
+        if !matches!(
+            subtable,
+            CmapSubtable::Format4(_) | CmapSubtable::Format12(_)
+        ) {
+            if let (PlatformId::Macintosh, 0, CmapSubtable::Format0(_)) =
+                (record.platform_id(), record.encoding_id(), &subtable)
+            {
+                mac_roman = mac_roman.or(Some(index));
+            }
+            continue;
+        }
+        let priority = match (record.platform_id(), record.encoding_id()) {
+            (PlatformId::Windows, 0) => 3,
+            (PlatformId::Windows, 10) | (PlatformId::Unicode, 4) => 2,
+            (PlatformId::Unicode, 5) => 0,
+            (PlatformId::ISO, _) | (PlatformId::Unicode, _) | (PlatformId::Windows, 1) => 1,
+            _ => 0,
+        };
+        if priority > 0 && priority >= selected_priority {
+            selected = Some(index);
+            selected_priority = priority;
+        }
+    }
+    selected.or(mac_roman)
+}
+
+fn cmap_subtable_info(font_ref: &BridgeFontRef, out: &mut Vec<BridgeCmapSubtableInfo>) {
+    ffi_guard(|| {
+        out.clear();
+        let Some(cmap) = font_ref.with_font(|f| f.cmap().ok()) else {
+            return;
+        };
+        let selected = selected_cmap_subtable_index(&cmap);
+        out.extend(
+            cmap.encoding_records()
+                .iter()
+                .enumerate()
+                .map(|(index, record)| {
+                    let subtable = record.subtable(cmap.offset_data()).ok();
+                    let num_entries = match &subtable {
+                        Some(CmapSubtable::Format0(cmap0)) => cmap0
+                            .glyph_id_array()
+                            .iter()
+                            .filter(|gid| **gid != 0)
+                            .count(),
+                        Some(CmapSubtable::Format4(cmap4)) => {
+                            cmap4.iter().filter(|(_, gid)| gid.to_u16() != 0).count()
+                        }
+                        Some(CmapSubtable::Format12(cmap12)) => {
+                            cmap12.iter().filter(|(_, gid)| gid.to_u16() != 0).count()
+                        }
+                        Some(CmapSubtable::Format14(cmap14)) => cmap14.iter().count(),
+                        _ => 0,
+                    };
+                    BridgeCmapSubtableInfo {
+                        platform_id: record.platform_id() as u16,
+                        encoding_id: record.encoding_id(),
+                        format: subtable.as_ref().map_or(0, |subtable| subtable.format()),
+                        num_entries: num_entries as u32,
+                        selected: selected == Some(index),
+                    }
+                }),
+        );
+    })
+}
+
+fn lookup_glyphs(font_ref: &BridgeFontRef, codepoints: &[u32], glyphs: &mut [u16]) -> usize {
+    ffi_guard(|| {
+        let charmap = font_ref.with_font(|f| Some(NominalCharmap::new(f)));
+        let mut num_mapped = 0;
+        for (codepoint, glyph) in codepoints.iter().zip(glyphs.iter_mut()) {
+            *glyph = charmap
+                .as_ref()
+                .and_then(|charmap| charmap.map(*codepoint))
+                .map_or(0, |glyph_id| glyph_id.to_u16());
+            if *glyph != 0 {
+                num_mapped += 1;
+            }
+        }
+        num_mapped
+    })
+}
+
+/// Collects all codepoint to glyph mappings of the selected charmap subtable in
+/// ascending codepoint order, keeping the first mapping for codepoints covered
+/// more than once by malformed subtables.
+fn sorted_unichar_mappings(font_ref: &BridgeFontRef) -> Vec<(u32, u16)> {
+    let mut mappings: Vec<(u32, u16)> = font_ref
+        .with_font(|f| {
+            Some(
+                NominalCharmap::new(f)
+                    .mappings()
+                    .into_iter()
+                    .map(|(codepoint, glyph_id)| (codepoint, glyph_id.to_u16()))
+                    .collect(),
+            )
+        })
+        .unwrap_or_default();
+    mappings.sort_by_key(|(codepoint, _)| *codepoint);
+    mappings.dedup_by_key(|(codepoint, _)| *codepoint);
+    mappings
+}
+
+fn count_mapped_unichars(font_ref: &BridgeFontRef) -> usize {
+    ffi_guard(|| sorted_unichar_mappings(font_ref).len())
+}
+
+fn fill_unichar_map(
+    font_ref: &BridgeFontRef,
+    out_unichars: &mut [u32],
+    out_glyphs: &mut [u16],
+) -> usize {
+    ffi_guard(|| {
+        let mut num_filled = 0;
+        for ((codepoint, glyph_id), (out_unichar, out_glyph)) in sorted_unichar_mappings(font_ref)
+            .into_iter()
+            .zip(out_unichars.iter_mut().zip(out_glyphs.iter_mut()))
+        {
+            *out_unichar = codepoint;
+            *out_glyph = glyph_id;
+            num_filled += 1;
+        }
+        num_filled
+    })
+}
+
+fn glyphs_to_unicode(font_ref: &BridgeFontRef, out_codepoints: &mut [u32]) {
+    ffi_guard(|| {
+        out_codepoints.fill(0);
+        font_ref.with_font(|f| {
+            for (codepoint, glyph_id) in NominalCharmap::new(f).mappings() {
+                if let Some(out_codepoint) = out_codepoints.get_mut(usize::from(glyph_id.to_u16()))
+                {
+                    if *out_codepoint == 0 || codepoint < *out_codepoint {
+                        *out_codepoint = codepoint;
+                    }
+                }
+            }
+            Some(())
+        });
+    })
+}
+
+fn has_codepoint(font_ref: &BridgeFontRef, codepoint: u32) -> bool {
+    ffi_guard(|| {
+        // Format 4 subtables end with a sentinel segment mapping U+FFFF to
+        // .notdef, which is not a real mapping.
+        const FORMAT_4_SENTINEL: u32 = 0xFFFF;
+        font_ref
+            .with_font(|f| NominalCharmap::new(f).map(codepoint))
+            .is_some_and(|glyph_id| codepoint != FORMAT_4_SENTINEL || glyph_id != GlyphId::NOTDEF)
+    })
+}
+
+fn lookup_glyph_variant(font_ref: &BridgeFontRef, codepoint: u32, variation_selector: u32) -> u16 {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let charmap = f.charmap();
+                match charmap.map_variant(codepoint, variation_selector)? {
+                    MapVariant::UseDefault => charmap.map(codepoint),
+                    MapVariant::Variant(glyph_id) => Some(glyph_id),
+                }
+            })
+            .map_or(0, |glyph_id| glyph_id.to_u16())
+    })
 }
 
 fn num_glyphs(font_ref: &BridgeFontRef) -> u16 {
-    font_ref
-        .with_font(|f| Some(f.maxp().ok()?.num_glyphs()))
-        .unwrap_or_default()
+    ffi_guard(|| font_ref.num_glyphs)
+}
+
+/// The path recording calls of `ffi::PathWrapper`, so that tests can draw
+/// through `PathWrapperPen` to a Rust path.
+trait PathWrapperSink {
+    fn move_to(self: Pin<&mut Self>, x: f32, y: f32);
+    fn line_to(self: Pin<&mut Self>, x: f32, y: f32);
+    fn quad_to(self: Pin<&mut Self>, cx0: f32, cy0: f32, x: f32, y: f32);
+    #[allow(clippy::too_many_arguments)]
+    fn curve_to(self: Pin<&mut Self>, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32);
+    fn close(self: Pin<&mut Self>);
+    fn reset(self: Pin<&mut Self>);
 }
 
-struct PathWrapperPen<'a> {
-    path_wrapper: Pin<&'a mut ffi::PathWrapper>,
+impl PathWrapperSink for PathWrapper {
+    fn move_to(self: Pin<&mut Self>, x: f32, y: f32) {
+        PathWrapper::move_to(self, x, y);
+    }
+
+    fn line_to(self: Pin<&mut Self>, x: f32, y: f32) {
+        PathWrapper::line_to(self, x, y);
+    }
+
+    fn quad_to(self: Pin<&mut Self>, cx0: f32, cy0: f32, x: f32, y: f32) {
+        PathWrapper::quad_to(self, cx0, cy0, x, y);
+    }
+
+    fn curve_to(self: Pin<&mut Self>, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        PathWrapper::curve_to(self, cx0, cy0, cx1, cy1, x, y);
+    }
+
+    fn close(self: Pin<&mut Self>) {
+        PathWrapper::close(self);
+    }
+
+    fn reset(self: Pin<&mut Self>) {
+        PathWrapper::reset(self);
+    }
+}
+
+struct PathWrapperPen<'a, W: PathWrapperSink = PathWrapper> {
+    path_wrapper: Pin<&'a mut W>,
 }
 
 // We need to wrap ffi::PathWrapper in PathWrapperPen and forward the path
 // recording calls to the path wrapper as we can't define trait implementations
-// inside the cxx::bridge section.
-impl<'a> Pen for PathWrapperPen<'a> {
+// inside the cxx::bridge section. Points are forwarded unchanged; the y-flip
+// to Skia's coordinates is part of the transform the outline is drawn with,
+// see `BridgePathTransform::Y_FLIP`.
+impl<W: PathWrapperSink> Pen for PathWrapperPen<'_, W> {
     fn move_to(&mut self, x: f32, y: f32) {
-        self.path_wrapper.as_mut().move_to(x, -y);
+        self.path_wrapper.as_mut().move_to(x, y);
     }
 
     fn line_to(&mut self, x: f32, y: f32) {
-        self.path_wrapper.as_mut().line_to(x, -y);
+        self.path_wrapper.as_mut().line_to(x, y);
     }
 
     fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
-        self.path_wrapper.as_mut().quad_to(cx0, -cy0, x, -y);
+        self.path_wrapper.as_mut().quad_to(cx0, cy0, x, y);
     }
 
     fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
         self.path_wrapper
             .as_mut()
-            .curve_to(cx0, -cy0, cx1, cy1, x, -y);
+            .curve_to(cx0, cy0, cx1, cy1, x, y);
     }
 
     fn close(&mut self) {
@@ -65,6 +460,199 @@ impl<'a> Pen for PathWrapperPen<'a> {
     }
 }
 
+/// Points closer than this to the current point, in the coordinates of the
+/// emitted path, do not move the pen for `SegmentCleanupPen`. Well below the
+/// 1/64 pixel precision of hinted outlines.
+const DEGENERATE_SEGMENT_EPSILON: f32 = 1.0 / 1024.0;
+
+/// Forwards drawing commands to `pen` with degenerate geometry removed:
+/// segments whose control and end points all lie within
+/// `DEGENERATE_SEGMENT_EPSILON` of the current point are dropped, a move is
+/// only forwarded once a segment follows it, so that consecutive moves
+/// collapse into the last one and empty contours vanish, and an open contour
+/// is closed before the next one starts.
+struct SegmentCleanupPen<'a, P: Pen> {
+    pen: &'a mut P,
+    /// Move not forwarded yet, since no segment has followed it.
+    pending_move: Option<(f32, f32)>,
+    /// Start of the current contour, to which `close` returns.
+    start: (f32, f32),
+    current: (f32, f32),
+    /// True if segments were forwarded since the last move or close.
+    contour_open: bool,
+}
+
+impl<'a, P: Pen> SegmentCleanupPen<'a, P> {
+    fn new(pen: &'a mut P) -> Self {
+        SegmentCleanupPen {
+            pen,
+            pending_move: None,
+            start: (0.0, 0.0),
+            current: (0.0, 0.0),
+            contour_open: false,
+        }
+    }
+
+    fn is_degenerate(&self, points: &[(f32, f32)]) -> bool {
+        let (current_x, current_y) = self.current;
+        points.iter().all(|(x, y)| {
+            (x - current_x).abs() <= DEGENERATE_SEGMENT_EPSILON
+                && (y - current_y).abs() <= DEGENERATE_SEGMENT_EPSILON
+        })
+    }
+
+    /// Returns false if the segment to `points` is to be dropped, otherwise
+    /// forwards the pending move and makes the segment's end current.
+    fn begin_segment(&mut self, points: &[(f32, f32)]) -> bool {
+        if self.is_degenerate(points) {
+            return false;
+        }
+        if let Some((x, y)) = self.pending_move.take() {
+            self.pen.move_to(x, y);
+        }
+        self.current = points[points.len() - 1];
+        self.contour_open = true;
+        true
+    }
+}
+
+impl<'a, P: Pen> Pen for SegmentCleanupPen<'a, P> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        if self.contour_open {
+            self.close();
+        }
+        self.pending_move = Some((x, y));
+        self.start = (x, y);
+        self.current = (x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        if self.begin_segment(&[(x, y)]) {
+            self.pen.line_to(x, y);
+        }
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        if self.begin_segment(&[(cx0, cy0), (x, y)]) {
+            self.pen.quad_to(cx0, cy0, x, y);
+        }
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        if self.begin_segment(&[(cx0, cy0), (cx1, cy1), (x, y)]) {
+            self.pen.curve_to(cx0, cy0, cx1, cy1, x, y);
+        }
+    }
+
+    fn close(&mut self) {
+        if self.contour_open {
+            self.pen.close();
+            self.contour_open = false;
+        }
+        self.current = self.start;
+    }
+}
+
+/// Forwards drawing commands to `pen` while accumulating the bounds of all
+/// points passed through.
+struct BoundsPen<'a, P: Pen> {
+    pen: &'a mut P,
+    bounds: BridgePathBounds,
+}
+
+impl<'a, P: Pen> Pen for BoundsPen<'a, P> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.bounds.add(x, y);
+        self.pen.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.bounds.add(x, y);
+        self.pen.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.bounds.add(cx0, cy0);
+        self.bounds.add(x, y);
+        self.pen.quad_to(cx0, cy0, x, y);
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.bounds.add(cx0, cy0);
+        self.bounds.add(cx1, cy1);
+        self.bounds.add(x, y);
+        self.pen.curve_to(cx0, cy0, cx1, cy1, x, y);
+    }
+
+    fn close(&mut self) {
+        self.pen.close();
+    }
+}
+
+/// Pen that packs the outline into a verb and a point buffer, so that the
+/// path can be built without a call across the bridge per segment.
+struct PackedPathPen<'a> {
+    verbs: &'a mut Vec<u8>,
+    points: &'a mut Vec<f32>,
+}
+
+impl<'a> PackedPathPen<'a> {
+    fn push(&mut self, verb: PathVerb, points: &[(f32, f32)]) {
+        self.verbs.push(verb.repr);
+        self.points
+            .extend(points.iter().flat_map(|(x, y)| [*x, *y]));
+    }
+}
+
+impl<'a> Pen for PackedPathPen<'a> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.push(PathVerb::MoveTo, &[(x, y)]);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push(PathVerb::LineTo, &[(x, y)]);
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.push(PathVerb::QuadTo, &[(cx0, cy0), (x, y)]);
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.push(PathVerb::CurveTo, &[(cx0, cy0), (cx1, cy1), (x, y)]);
+    }
+
+    fn close(&mut self) {
+        self.push(PathVerb::Close, &[]);
+    }
+}
+
+/// Pen that only records whether any drawing command was issued, to test
+/// for empty outlines without building a path.
+#[derive(Default)]
+struct EmptyOutlinePen {
+    has_segments: bool,
+}
+
+impl Pen for EmptyOutlinePen {
+    fn move_to(&mut self, _x: f32, _y: f32) {
+        self.has_segments = true;
+    }
+
+    fn line_to(&mut self, _x: f32, _y: f32) {
+        self.has_segments = true;
+    }
+
+    fn quad_to(&mut self, _cx0: f32, _cy0: f32, _x: f32, _y: f32) {
+        self.has_segments = true;
+    }
+
+    fn curve_to(&mut self, _cx0: f32, _cy0: f32, _cx1: f32, _cy1: f32, _x: f32, _y: f32) {
+        self.has_segments = true;
+    }
+
+    fn close(&mut self) {}
+}
+
 struct ColorPainterImpl<'a> {
     color_painter_wrapper: Pin<&'a mut ffi::ColorPainterWrapper>,
 }
@@ -305,491 +893,5210 @@ impl<'a> ColorPainter for ColorPainterImpl<'a> {
     }
 }
 
-fn get_path(
-    outlines: &BridgeOutlineCollection,
-    glyph_id: u16,
-    size: f32,
-    coords: &BridgeNormalizedCoords,
-    path_wrapper: Pin<&mut PathWrapper>,
-    scaler_metrics: &mut BridgeScalerMetrics,
-) -> bool {
-    outlines
-        .0
-        .as_ref()
-        .map(|outlines| {
-            let glyph = outlines.get(GlyphId::new(glyph_id))?;
-            let draw_settings = DrawSettings::unhinted(Size::new(size), &coords.normalized_coords);
+impl Default for PathStatus {
+    /// Failure value of `get_path` when it panics.
+    fn default() -> Self {
+        PathStatus::DrawError
+    }
+}
 
-            let mut pen_dump = PathWrapperPen {
-                path_wrapper: path_wrapper,
-            };
-            match glyph.draw(draw_settings, &mut pen_dump) {
-                Err(_) => None,
-                Ok(metrics) => {
-                    scaler_metrics.has_overlaps = metrics.has_overlaps;
-                    Some(())
-                }
-            }
-        })
-        .is_some()
+impl Default for BridgeOutlineLimits {
+    /// Limits well above the largest glyphs of real fonts, including CJK
+    /// fonts built from deeply nested composites, which bound the work spent
+    /// on malicious outlines.
+    fn default() -> Self {
+        BridgeOutlineLimits {
+            max_points: 50_000,
+            max_contours: 10_000,
+            max_composite_depth: 16,
+            max_path_segments: 100_000,
+            // The Type 2 charstring limits, which FreeType enforces as well.
+            max_subroutine_depth: 10,
+            max_stem_hints: 96,
+            max_charstring_operations: 100_000,
+        }
+    }
 }
 
-fn advance_width_or_zero(
-    font_ref: &BridgeFontRef,
-    size: f32,
-    coords: &BridgeNormalizedCoords,
-    glyph_id: u16,
-) -> f32 {
-    font_ref
-        .with_font(|f| {
-            GlyphMetrics::new(f, Size::new(size), coords.normalized_coords.coords())
-                .advance_width(GlyphId::new(glyph_id))
-        })
-        .unwrap_or_default()
+fn default_outline_limits() -> BridgeOutlineLimits {
+    ffi_guard(BridgeOutlineLimits::default)
 }
 
-fn units_per_em_or_zero(font_ref: &BridgeFontRef) -> u16 {
-    font_ref
-        .with_font(|f| Some(f.head().ok()?.units_per_em()))
-        .unwrap_or_default()
+impl Default for LineMetricsSource {
+    fn default() -> Self {
+        LineMetricsSource::Hhea
+    }
 }
 
-fn convert_metrics(skrifa_metrics: &Metrics) -> ffi::Metrics {
-    ffi::Metrics {
-        top: skrifa_metrics.bounds.map_or_else(|| 0.0, |b| b.y_max),
-        bottom: skrifa_metrics.bounds.map_or_else(|| 0.0, |b| b.y_min),
-        x_min: skrifa_metrics.bounds.map_or_else(|| 0.0, |b| b.x_min),
-        x_max: skrifa_metrics.bounds.map_or_else(|| 0.0, |b| b.x_max),
-        ascent: skrifa_metrics.ascent,
-        descent: skrifa_metrics.descent,
-        leading: skrifa_metrics.leading,
-        avg_char_width: skrifa_metrics.average_width.unwrap_or(0.0),
-        max_char_width: skrifa_metrics.max_width.unwrap_or(0.0),
-        x_height: skrifa_metrics.x_height.unwrap_or(0.0),
-        cap_height: skrifa_metrics.cap_height.unwrap_or(0.0),
+impl Default for HintingLevel {
+    fn default() -> Self {
+        HintingLevel::None
     }
 }
 
-fn get_skia_metrics(
-    font_ref: &BridgeFontRef,
-    size: f32,
-    coords: &BridgeNormalizedCoords,
-) -> ffi::Metrics {
-    font_ref
-        .with_font(|f| {
-            let fontations_metrics =
-                Metrics::new(f, Size::new(size), coords.normalized_coords.coords());
-            Some(convert_metrics(&fontations_metrics))
-        })
-        .unwrap_or_default()
+impl Default for SideBearingSource {
+    fn default() -> Self {
+        SideBearingSource::Hmtx
+    }
 }
 
-fn get_localized_strings<'a>(font_ref: &'a BridgeFontRef<'a>) -> Box<BridgeLocalizedStrings<'a>> {
-    Box::new(BridgeLocalizedStrings {
-        localized_strings: font_ref
-            .with_font(|f| Some(f.localized_strings(StringId::FAMILY_NAME)))
-            .unwrap_or_default(),
-    })
+impl Default for SizeRounding {
+    fn default() -> Self {
+        SizeRounding::Exact
+    }
 }
 
-use crate::ffi::BridgeLocalizedName;
+impl Default for PostScriptNameSource {
+    fn default() -> Self {
+        PostScriptNameSource::Missing
+    }
+}
 
-fn localized_name_next(
-    bridge_localized_strings: &mut BridgeLocalizedStrings,
-    out_localized_name: &mut BridgeLocalizedName,
-) -> bool {
-    match bridge_localized_strings.localized_strings.next() {
-        Some(localized_string) => {
-            out_localized_name.string = localized_string.to_string();
-            // TODO(b/307906051): Remove the suffix before shipping.
-            out_localized_name.string.push_str(" (Fontations)");
-            out_localized_name.language = localized_string
-                .language()
-                .map(|l| l.to_string())
-                .unwrap_or_default();
-            true
+impl Default for BridgePathBounds {
+    fn default() -> Self {
+        BridgePathBounds {
+            is_empty: true,
+            x_min: 0.0,
+            y_min: 0.0,
+            x_max: 0.0,
+            y_max: 0.0,
         }
-        _ => false,
     }
 }
 
-fn english_or_first_font_name(font_ref: &BridgeFontRef, name_id: StringId) -> Option<String> {
-    font_ref.with_font(|f| {
-        f.localized_strings(name_id)
-            .english_or_first()
-            .map(|localized_string| localized_string.to_string())
-    })
+impl BridgePathBounds {
+    fn add(&mut self, x: f32, y: f32) {
+        if self.is_empty {
+            *self = BridgePathBounds {
+                is_empty: false,
+                x_min: x,
+                y_min: y,
+                x_max: x,
+                y_max: y,
+            };
+        } else {
+            self.x_min = self.x_min.min(x);
+            self.y_min = self.y_min.min(y);
+            self.x_max = self.x_max.max(x);
+            self.y_max = self.y_max.max(y);
+        }
+    }
 }
 
-fn family_name(font_ref: &BridgeFontRef) -> String {
-    english_or_first_font_name(font_ref, StringId::FAMILY_NAME).unwrap_or_default()
+impl Default for BridgePathTransform {
+    fn default() -> Self {
+        BridgePathTransform {
+            xx: 1.0,
+            xy: 0.0,
+            yx: 0.0,
+            yy: 1.0,
+            dx: 0.0,
+            dy: 0.0,
+        }
+    }
 }
 
-fn postscript_name(font_ref: &BridgeFontRef, out_string: &mut String) -> bool {
-    let postscript_name = english_or_first_font_name(font_ref, StringId::POSTSCRIPT_NAME);
-    match postscript_name {
-        Some(name) => {
-            *out_string = name;
-            true
+impl BridgePathTransform {
+    /// Converts font coordinates, with y pointing up, to Skia's y-down
+    /// coordinates. Composed after the caller's transform with `then` by the
+    /// functions emitting paths for Skia.
+    const Y_FLIP: BridgePathTransform = BridgePathTransform {
+        xx: 1.0,
+        xy: 0.0,
+        yx: 0.0,
+        yy: -1.0,
+        dx: 0.0,
+        dy: 0.0,
+    };
+
+    /// Returns the transform mapping points through `self`, then `next`.
+    fn then(&self, next: &BridgePathTransform) -> BridgePathTransform {
+        BridgePathTransform {
+            xx: next.xx * self.xx + next.xy * self.yx,
+            xy: next.xx * self.xy + next.xy * self.yy,
+            yx: next.yx * self.xx + next.yy * self.yx,
+            yy: next.yx * self.xy + next.yy * self.yy,
+            dx: next.xx * self.dx + next.xy * self.dy + next.dx,
+            dy: next.yx * self.dx + next.yy * self.dy + next.dy,
         }
-        _ => false,
+    }
+
+    fn map(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.xx * x + self.xy * y + self.dx,
+            self.yx * x + self.yy * y + self.dy,
+        )
     }
 }
 
-fn resolve_palette(
-    font_ref: &BridgeFontRef,
-    base_palette: u16,
-    palette_overrides: &[PaletteOverride],
-) -> Vec<u32> {
-    font_ref
-        .with_font(|f| {
-            let cpal = f.cpal().ok()?;
-
-            let start_index: usize = cpal
-                .color_record_indices()
-                .get(usize::from(base_palette))?
-                .get()
-                .into();
-            let num_entries: usize = cpal.num_palette_entries().into();
-
-            let color_records = cpal.color_records_array()?.ok()?;
-            let mut palette: Vec<u32> = color_records
-                .get(start_index..start_index + num_entries)?
-                .iter()
-                .map(|record| {
-                    u32::from_be_bytes([record.alpha, record.red, record.green, record.blue])
-                })
-                .collect();
+fn identity_path_transform() -> BridgePathTransform {
+    ffi_guard(BridgePathTransform::default)
+}
+
+/// Running totals of a glyf outline and its components, checked against
+/// `BridgeOutlineLimits` before the outline is drawn.
+#[derive(Default)]
+struct GlyfOutlineExtent {
+    num_points: u32,
+    num_contours: u32,
+}
 
-            for override_entry in palette_overrides {
-                let index = override_entry.index as usize;
-                if index < palette.len() {
-                    palette[index] = override_entry.color_8888;
+impl GlyfOutlineExtent {
+    /// Adds the outline of `glyph_id` and returns false as soon as a limit is
+    /// exceeded. Each component reference counts as a point, so composites
+    /// of empty glyphs cannot make the walk itself expensive. Glyphs that
+    /// fail to parse are left for the scaler to report.
+    fn add_glyph(
+        &mut self,
+        loca: &Loca,
+        glyf: &Glyf,
+        glyph_id: GlyphId,
+        depth: u16,
+        limits: &BridgeOutlineLimits,
+    ) -> bool {
+        match loca.get_glyf(glyph_id, glyf) {
+            Ok(Some(Glyph::Simple(simple))) => {
+                let num_points = u32::try_from(simple.num_points()).unwrap_or(u32::MAX);
+                let num_contours = simple.end_pts_of_contours().len() as u32;
+                self.num_points = self.num_points.saturating_add(num_points);
+                self.num_contours = self.num_contours.saturating_add(num_contours);
+                self.num_points <= limits.max_points && self.num_contours <= limits.max_contours
+            }
+            Ok(Some(Glyph::Composite(composite))) => {
+                if depth >= limits.max_composite_depth {
+                    return false;
                 }
+                composite.components().all(|component| {
+                    self.num_points = self.num_points.saturating_add(1);
+                    self.num_points <= limits.max_points
+                        && self.add_glyph(loca, glyf, component.glyph, depth + 1, limits)
+                })
             }
-            Some(palette)
-        })
-        .unwrap_or_default()
+            Ok(None) | Err(_) => true,
+        }
+    }
 }
 
-fn has_colr_glyph(font_ref: &BridgeFontRef, format: ColorGlyphFormat, glyph_id: u16) -> bool {
-    font_ref
-        .with_font(|f| {
-            let colrv1_paintable = f
-                .color_glyphs()
-                .get_with_format(GlyphId::new(glyph_id), format);
-            Some(colrv1_paintable.is_some())
-        })
-        .unwrap_or_default()
+/// Reasons to stop walking a charstring before its end.
+enum CharstringWalkStop {
+    LimitsExceeded,
+    /// The charstring fails to evaluate. Left for the scaler to report.
+    Malformed,
 }
 
-fn has_colrv1_glyph(font_ref: &BridgeFontRef, glyph_id: u16) -> bool {
-    has_colr_glyph(font_ref, ColorGlyphFormat::ColrV1, glyph_id)
+impl From<postscript::Error> for CharstringWalkStop {
+    fn from(_: postscript::Error) -> Self {
+        CharstringWalkStop::Malformed
+    }
 }
 
-fn has_colrv0_glyph(font_ref: &BridgeFontRef, glyph_id: u16) -> bool {
-    has_colr_glyph(font_ref, ColorGlyphFormat::ColrV0, glyph_id)
+impl From<ReadError> for CharstringWalkStop {
+    fn from(_: ReadError) -> Self {
+        CharstringWalkStop::Malformed
+    }
 }
 
-use crate::ffi::ClipBox;
+/// Follows a charstring through its subroutine calls the way the evaluator
+/// does, tracking only the operand stack and stem hints, and stops as soon
+/// as the work exceeds `BridgeOutlineLimits`. Operands are not counted since
+/// the stack bounds how many can precede each operator.
+struct CharstringWalker<'a, 'b> {
+    global_subrs: &'b Index<'a>,
+    subrs: Option<&'b Index<'a>>,
+    blend_state: Option<BlendState<'a>>,
+    limits: &'b BridgeOutlineLimits,
+    stack: Stack,
+    stem_count: usize,
+    have_read_width: bool,
+    num_operations: u32,
+}
 
-fn get_colrv1_clip_box(
-    font_ref: &BridgeFontRef,
-    coords: &BridgeNormalizedCoords,
-    glyph_id: u16,
-    size: f32,
-    clip_box: &mut ClipBox,
-) -> bool {
-    let size = match size {
-        x if x == 0.0 => {
-            return false;
+impl<'a, 'b> CharstringWalker<'a, 'b> {
+    fn walk(&mut self, charstring: &[u8], depth: u16) -> Result<(), CharstringWalkStop> {
+        if depth > self.limits.max_subroutine_depth {
+            return Err(CharstringWalkStop::LimitsExceeded);
         }
-        _ => Size::new(size),
-    };
-    font_ref
-        .with_font(|f| {
-            match f
-                .color_glyphs()
-                .get_with_format(GlyphId::new(glyph_id), ColorGlyphFormat::ColrV1)?
-                .bounding_box(coords.normalized_coords.coords(), size)
-            {
-                Some(bounding_box) => {
-                    *clip_box = ClipBox {
-                        x_min: bounding_box.x_min,
-                        y_min: bounding_box.y_min,
-                        x_max: bounding_box.x_max,
-                        y_max: bounding_box.y_max,
-                    };
-                    Some(true)
+        let mut pos = 0;
+        while let Some(&b0) = charstring.get(pos) {
+            pos += 1;
+            let operand_len = match b0 {
+                28 => 2,
+                32..=246 => 0,
+                247..=254 => 1,
+                255 => 4,
+                _ => {
+                    if !self.walk_operator(b0, charstring, &mut pos, depth)? {
+                        return Ok(());
+                    }
+                    continue;
                 }
-                _ => None,
+            };
+            let operand = charstring
+                .get(pos..pos + operand_len)
+                .ok_or(CharstringWalkStop::Malformed)?;
+            pos += operand_len;
+            match (b0, operand) {
+                (28, &[b1, b2]) => self.stack.push(i32::from(i16::from_be_bytes([b1, b2])))?,
+                (32..=246, _) => self.stack.push(i32::from(b0) - 139)?,
+                (247..=250, &[b1]) => self
+                    .stack
+                    .push((i32::from(b0) - 247) * 256 + i32::from(b1) + 108)?,
+                (251..=254, &[b1]) => self
+                    .stack
+                    .push(-(i32::from(b0) - 251) * 256 - i32::from(b1) - 108)?,
+                (_, &[b1, b2, b3, b4]) => self
+                    .stack
+                    .push(Fixed::from_bits(i32::from_be_bytes([b1, b2, b3, b4])))?,
+                _ => unreachable!("operand length matches its encoding"),
             }
-        })
-        .unwrap_or_default()
-}
-
-/// Implements the behavior expected for `SkTypeface::getTableData`, compare
-/// documentation for this method and the FreeType implementation in Skia.
-/// * If the target data array is empty, do not copy any data into it, but
-///   return the size of the table.
-/// * If the target data buffer is shorted than from offset to the end of the
-///   table, truncate the data.
-/// * If offset is longer than the table's length, return 0.
-fn table_data(font_ref: &BridgeFontRef, tag: u32, offset: usize, data: &mut [u8]) -> usize {
-    let table_data = font_ref
-        .with_font(|f| f.table_data(Tag::from_be_bytes(tag.to_be_bytes())))
-        .unwrap_or_default();
-    let table_data = table_data.as_ref();
-    // Remaining table data size measured from offset to end, or 0 if offset is
-    // too large.
-    let mut to_copy_length = table_data.len().saturating_sub(offset);
-    match data.len() {
-        0 => to_copy_length,
-        _ => {
-            to_copy_length = to_copy_length.min(data.len());
-            let table_offset_data = table_data
-                .get(offset..offset + to_copy_length)
-                .unwrap_or_default();
-            data.get_mut(..table_offset_data.len())
-                .map_or(0, |data_slice| {
-                    data_slice.copy_from_slice(table_offset_data);
-                    data_slice.len()
-                })
         }
+        Ok(())
     }
-}
 
-fn table_tags(font_ref: &BridgeFontRef, tags: &mut [u32]) -> u16 {
-    font_ref
-        .with_font(|f| {
-            let table_directory = &f.table_directory;
-            let table_tags_iter = table_directory
-                .table_records()
-                .iter()
-                .map(|table| u32::from_be_bytes(table.tag.get().into_bytes()));
-            tags.iter_mut()
-                .zip(table_tags_iter)
-                .for_each(|(out_tag, table_tag)| *out_tag = table_tag);
-            Some(table_directory.num_tables())
-        })
-        .unwrap_or_default()
-}
+    /// Applies the operator `b0` and returns whether the current charstring
+    /// continues after it.
+    fn walk_operator(
+        &mut self,
+        b0: u8,
+        charstring: &[u8],
+        pos: &mut usize,
+        depth: u16,
+    ) -> Result<bool, CharstringWalkStop> {
+        self.num_operations = self.num_operations.saturating_add(1);
+        if self.num_operations > self.limits.max_charstring_operations {
+            return Err(CharstringWalkStop::LimitsExceeded);
+        }
+        match b0 {
+            // hstem, vstem, hstemhm and vstemhm
+            1 | 3 | 18 | 23 => {
+                self.add_stems()?;
+                self.stack.clear();
+            }
+            // hintmask and cntrmask, followed by one mask bit per stem.
+            19 | 20 => {
+                self.add_stems()?;
+                *pos += self.stem_count.div_ceil(8);
+                if *pos > charstring.len() {
+                    return Err(CharstringWalkStop::Malformed);
+                }
+                self.stack.clear();
+            }
+            // rmoveto, hmoveto and vmoveto, which may be preceded by the
+            // advance width.
+            21 | 22 | 4 => {
+                let len_with_width = if b0 == 21 { 3 } else { 2 };
+                if self.stack.len() == len_with_width {
+                    self.have_read_width = true;
+                }
+                self.stack.clear();
+            }
+            // Line and curve operators.
+            5..=8 | 24..=27 | 30 | 31 => self.stack.clear(),
+            // callsubr and callgsubr
+            10 | 29 => {
+                let subrs = if b0 == 10 {
+                    self.subrs.ok_or(CharstringWalkStop::Malformed)?
+                } else {
+                    self.global_subrs
+                };
+                let index = self.stack.pop_i32()?.checked_add(subrs.subr_bias());
+                let index = index
+                    .and_then(|index| usize::try_from(index).ok())
+                    .ok_or(CharstringWalkStop::Malformed)?;
+                self.walk(subrs.get(index)?, depth + 1)?;
+            }
+            // return
+            11 => return Ok(false),
+            // endchar
+            14 => {
+                if !self.stack.is_empty() && !self.have_read_width {
+                    self.have_read_width = true;
+                    self.stack.clear();
+                }
+                return Ok(false);
+            }
+            // vsindex
+            15 => {
+                let blend_state = self
+                    .blend_state
+                    .as_mut()
+                    .ok_or(CharstringWalkStop::Malformed)?;
+                blend_state.set_store_index(self.stack.pop_i32()? as u16)?;
+            }
+            // blend
+            16 => {
+                let blend_state = self
+                    .blend_state
+                    .as_ref()
+                    .ok_or(CharstringWalkStop::Malformed)?;
+                self.stack.apply_blend(blend_state)?;
+            }
+            // Two byte operators, of which only the flex operators are
+            // supported.
+            12 => {
+                let b1 = charstring.get(*pos).ok_or(CharstringWalkStop::Malformed)?;
+                *pos += 1;
+                if !(34..=37).contains(b1) {
+                    return Err(CharstringWalkStop::Malformed);
+                }
+                self.stack.clear();
+            }
+            _ => return Err(CharstringWalkStop::Malformed),
+        }
+        Ok(true)
+    }
 
-fn variation_position(
-    coords: &BridgeNormalizedCoords,
-    coordinates: &mut [SkiaDesignCoordinate],
-) -> isize {
-    if !coordinates.is_empty() {
-        if coords.filtered_user_coords.len() > coordinates.len() {
-            return -1;
+    /// Adds the stems on the stack, ignoring a leading advance width.
+    fn add_stems(&mut self) -> Result<(), CharstringWalkStop> {
+        if self.stack.len_is_odd() {
+            self.have_read_width = true;
         }
-        let skia_design_coordinates =
-            coords
-                .filtered_user_coords
-                .iter()
-                .map(|setting| SkiaDesignCoordinate {
-                    axis: u32::from_be_bytes(setting.selector.into_bytes()),
-                    value: setting.value,
-                });
-        for (i, coord) in skia_design_coordinates.enumerate() {
-            coordinates[i] = coord;
+        self.stem_count += self.stack.len() / 2;
+        if self.stem_count > self.limits.max_stem_hints as usize {
+            return Err(CharstringWalkStop::LimitsExceeded);
         }
+        Ok(())
     }
-    coords.filtered_user_coords.len().try_into().unwrap()
 }
 
-fn populate_axes(font_ref: &BridgeFontRef, mut axis_wrapper: Pin<&mut AxisWrapper>) -> isize {
-    font_ref
-        .with_font(|f| {
-            let axes = f.axes();
-            // Populate incoming allocated SkFontParameters::Variation::Axis[] only when a
-            // buffer is passed.
-            if axis_wrapper.as_ref().size() > 0 {
-                for (i, axis) in axes.iter().enumerate() {
-                    if !axis_wrapper.as_mut().populate_axis(
-                        i,
-                        u32::from_be_bytes(axis.tag().into_bytes()),
-                        axis.min_value(),
-                        axis.default_value(),
-                        axis.max_value(),
-                        axis.is_hidden(),
-                    ) {
-                        return None;
-                    }
+/// CFF or CFF2 data for checking charstrings against `BridgeOutlineLimits`
+/// before drawing them.
+struct CffCharstrings<'a> {
+    table_data: &'a [u8],
+    is_cff2: bool,
+    global_subrs: Index<'a>,
+    charstrings: Index<'a>,
+    font_dicts: Option<Index<'a>>,
+    fd_select: Option<FdSelect<'a>>,
+    private_dict_range: Option<Range<usize>>,
+    var_store: Option<ItemVariationStore<'a>>,
+}
+
+impl<'a> CffCharstrings<'a> {
+    /// Reads the top DICT of the CFF2 or CFF table, preferring CFF2 like the
+    /// scaler does.
+    fn new(font: &'a FontRef) -> Option<Self> {
+        let (table_data, top_dict_data, global_subrs, is_cff2) = match font.cff2() {
+            Ok(cff2) => (
+                cff2.offset_data().as_bytes(),
+                cff2.top_dict_data(),
+                cff2.global_subrs().into(),
+                true,
+            ),
+            Err(_) => {
+                let cff = font.cff().ok()?;
+                let top_dict_data = cff.top_dicts().get(0).ok()?;
+                (
+                    cff.offset_data().as_bytes(),
+                    top_dict_data,
+                    cff.global_subrs().into(),
+                    false,
+                )
+            }
+        };
+        let index_at = |offset: usize| Index::new(table_data.get(offset..)?, is_cff2).ok();
+        let mut charstrings = None;
+        let mut font_dicts = None;
+        let mut fd_select = None;
+        let mut private_dict_range = None;
+        let mut var_store = None;
+        for entry in dict::entries(top_dict_data, None) {
+            match entry.ok()? {
+                dict::Entry::CharstringsOffset(offset) => charstrings = Some(index_at(offset)?),
+                dict::Entry::FdArrayOffset(offset) => font_dicts = Some(index_at(offset)?),
+                dict::Entry::FdSelectOffset(offset) => {
+                    let data = FontData::new(table_data.get(offset..)?);
+                    fd_select = Some(FdSelect::read(data).ok()?);
                 }
+                dict::Entry::PrivateDictRange(range) => private_dict_range = Some(range),
+                // The variation store is preceded by its length.
+                dict::Entry::VariationStoreOffset(offset) if is_cff2 => {
+                    let data = FontData::new(table_data.get(offset + 2..)?);
+                    var_store = Some(ItemVariationStore::read(data).ok()?);
+                }
+                _ => {}
             }
-            isize::try_from(axes.len()).ok()
+        }
+        Some(Self {
+            table_data,
+            is_cff2,
+            global_subrs,
+            charstrings: charstrings?,
+            font_dicts,
+            fd_select,
+            private_dict_range,
+            var_store,
         })
-        .unwrap_or(-1)
-}
+    }
 
-fn make_font_ref_internal<'a>(font_data: &'a [u8], index: u32) -> Result<FontRef<'a>, ReadError> {
-    match FileRef::new(font_data) {
-        Ok(file_ref) => match file_ref {
-            FileRef::Font(font_ref) => Ok(font_ref),
-            FileRef::Collection(collection) => collection.get(index),
-        },
-        Err(e) => Err(e),
+    /// Returns false if evaluating the charstring of `glyph_id` at `coords`
+    /// exceeds `limits`. Charstrings that fail to evaluate are left for the
+    /// scaler to report.
+    fn within_limits(
+        &self,
+        glyph_id: GlyphId,
+        coords: &[F2Dot14],
+        limits: &BridgeOutlineLimits,
+    ) -> bool {
+        !matches!(
+            self.walk_glyph(glyph_id, coords, limits),
+            Err(CharstringWalkStop::LimitsExceeded)
+        )
     }
-}
 
-fn make_font_ref<'a>(font_data: &'a [u8], index: u32) -> Box<BridgeFontRef<'a>> {
-    Box::new(BridgeFontRef(make_font_ref_internal(font_data, index).ok()))
+    fn walk_glyph(
+        &self,
+        glyph_id: GlyphId,
+        coords: &[F2Dot14],
+        limits: &BridgeOutlineLimits,
+    ) -> Result<(), CharstringWalkStop> {
+        let charstring = self.charstrings.get(usize::from(glyph_id.to_u16()))?;
+        let private_dict_range = match &self.font_dicts {
+            Some(font_dicts) => {
+                let subfont_index = self
+                    .fd_select
+                    .as_ref()
+                    .and_then(|fd_select| fd_select.font_index(glyph_id))
+                    .unwrap_or(0);
+                let font_dict_data = font_dicts.get(usize::from(subfont_index))?;
+                dict::entries(font_dict_data, None).find_map(|entry| match entry {
+                    Ok(dict::Entry::PrivateDictRange(range)) => Some(range),
+                    _ => None,
+                })
+            }
+            None => self.private_dict_range.clone(),
+        }
+        .ok_or(CharstringWalkStop::Malformed)?;
+        let private_dict_data = self
+            .table_data
+            .get(private_dict_range.clone())
+            .ok_or(CharstringWalkStop::Malformed)?;
+        let blend_state = |store_index| {
+            self.var_store
+                .clone()
+                .map(|store| BlendState::new(store, coords, store_index))
+                .transpose()
+        };
+        let mut subrs = None;
+        let mut store_index = 0;
+        for entry in dict::entries(private_dict_data, blend_state(0)?) {
+            match entry? {
+                // The local subroutines offset is relative to the private
+                // DICT.
+                dict::Entry::SubrsOffset(offset) => {
+                    let data = self
+                        .table_data
+                        .get(private_dict_range.start + offset..)
+                        .unwrap_or_default();
+                    subrs = Some(Index::new(data, self.is_cff2)?);
+                }
+                dict::Entry::VariationStoreIndex(index) => store_index = index,
+                _ => {}
+            }
+        }
+        let mut walker = CharstringWalker {
+            global_subrs: &self.global_subrs,
+            subrs: subrs.as_ref(),
+            blend_state: blend_state(store_index)?,
+            limits,
+            stack: Stack::new(),
+            stem_count: 0,
+            have_read_width: false,
+            num_operations: 0,
+        };
+        walker.walk(charstring, 0)
+    }
 }
 
-fn font_ref_is_valid(bridge_font_ref: &BridgeFontRef) -> bool {
-    bridge_font_ref.0.is_some()
+/// Forwards drawing commands to `pen` while counting the segments drawn.
+/// Commands beyond `max_segments` are dropped.
+struct SegmentTrackingPen<'a, P: Pen> {
+    pen: &'a mut P,
+    num_segments: u32,
+    max_segments: u32,
 }
 
-fn get_outline_collection<'a>(font_ref: &'a BridgeFontRef<'a>) -> Box<BridgeOutlineCollection<'a>> {
-    Box::new(
-        font_ref
-            .with_font(|f| Some(BridgeOutlineCollection(Some(f.outline_glyphs()))))
-            .unwrap_or_default(),
-    )
+impl<'a, P: Pen> SegmentTrackingPen<'a, P> {
+    /// Counts a segment and returns whether it is within the limit.
+    fn add_segment(&mut self) -> bool {
+        self.num_segments = self.num_segments.saturating_add(1);
+        !self.limit_exceeded()
+    }
+
+    fn limit_exceeded(&self) -> bool {
+        self.num_segments > self.max_segments
+    }
 }
 
-fn font_or_collection<'a>(font_data: &'a [u8], num_fonts: &mut u32) -> bool {
-    match FileRef::new(font_data) {
-        Ok(FileRef::Collection(collection)) => {
-            *num_fonts = collection.len();
-            true
+impl<'a, P: Pen> Pen for SegmentTrackingPen<'a, P> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        if self.add_segment() {
+            self.pen.move_to(x, y);
         }
-        Ok(FileRef::Font(_)) => {
-            *num_fonts = 0u32;
-            true
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        if self.add_segment() {
+            self.pen.line_to(x, y);
         }
-        _ => false,
     }
-}
 
-fn resolve_into_normalized_coords(
-    font_ref: &BridgeFontRef,
-    design_coords: &[SkiaDesignCoordinate],
-) -> Box<BridgeNormalizedCoords> {
-    let variation_tuples = design_coords
-        .iter()
-        .map(|coord| (Tag::from_be_bytes(coord.axis.to_be_bytes()), coord.value));
-    let bridge_normalized_coords = font_ref
-        .with_font(|f| {
-            Some(BridgeNormalizedCoords {
-                filtered_user_coords: f.axes().filter(variation_tuples.clone()).collect(),
-                normalized_coords: f.axes().location(variation_tuples),
-            })
-        })
-        .unwrap_or_default();
-    Box::new(bridge_normalized_coords)
-}
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        if self.add_segment() {
+            self.pen.quad_to(cx0, cy0, x, y);
+        }
+    }
 
-fn draw_colr_glyph(
-    font_ref: &BridgeFontRef,
-    coords: &BridgeNormalizedCoords,
-    glyph_id: u16,
-    color_painter: Pin<&mut ColorPainterWrapper>,
-) -> bool {
-    let mut color_painter_impl = ColorPainterImpl {
-        color_painter_wrapper: color_painter,
-    };
-    font_ref
-        .with_font(|f| {
-            let paintable = f.color_glyphs().get(GlyphId::new(glyph_id))?;
-            paintable
-                .paint(coords.normalized_coords.coords(), &mut color_painter_impl)
-                .ok()
-        })
-        .is_some()
-}
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        if self.add_segment() {
+            self.pen.curve_to(cx0, cy0, cx1, cy1, x, y);
+        }
+    }
 
-fn next_color_stop(color_stops: &mut BridgeColorStops, out_stop: &mut ColorStop) -> bool {
-    if let Some(color_stop) = color_stops.stops_iterator.next() {
-        out_stop.alpha = color_stop.alpha;
-        out_stop.stop = color_stop.offset;
-        out_stop.palette_index = color_stop.palette_index;
-        return true;
-    } else {
-        return false;
+    fn close(&mut self) {
+        if !self.limit_exceeded() {
+            self.pen.close();
+        }
     }
 }
 
-fn num_color_stops(color_stops: &BridgeColorStops) -> usize {
-    return color_stops.num_stops;
+/// Forwards drawing commands to `pen` with all points, including control
+/// points, mapped through `transform`.
+struct TransformPen<'a, P: Pen> {
+    pen: &'a mut P,
+    transform: BridgePathTransform,
 }
 
-use crate::ffi::BridgeFontStyle;
+impl<'a, P: Pen> Pen for TransformPen<'a, P> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.transform.map(x, y);
+        self.pen.move_to(x, y);
+    }
 
-fn get_font_style(font_ref: &BridgeFontRef, style: &mut BridgeFontStyle) -> bool {
-    font_ref
-        .with_font(|f| {
-            let attrs = f.attributes();
-            let skia_weight = attrs.weight.value().round() as i32;
-            let skia_slant = match attrs.style {
-                x if x == Style::Normal => 0,
-                x if x == Style::Italic => 1,
-                        _ /* kOblique_Slant */=> 2
-            };
-            // Match back the skrifa values to get the system values (more or less)
-            let skia_width = match (attrs.stretch.ratio()*1000.0).round() as i32 {
-                x if x <=  500 => 1,
-                x if x <=  625 => 2,
-                x if x <=  725 => 3,
-                x if x <=  875 => 4,
-                x if x <= 1000 => 5,
-                x if x <= 1125 => 6,
-                x if x <= 1250 => 7,
-                x if x <= 1500 => 8,
-                x if x <= 2000 => 9,
-                _ => 9,
-            };
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.transform.map(x, y);
+        self.pen.line_to(x, y);
+    }
 
-            *style = BridgeFontStyle {
-                weight: skia_weight,
-                slant: skia_slant,
-                width: skia_width,
-            };
-            Some(true)
-        })
-        .unwrap_or_default()
-}
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        let (cx0, cy0) = self.transform.map(cx0, cy0);
+        let (x, y) = self.transform.map(x, y);
+        self.pen.quad_to(cx0, cy0, x, y);
+    }
 
-struct BridgeFontRef<'a>(Option<FontRef<'a>>);
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        let (cx0, cy0) = self.transform.map(cx0, cy0);
+        let (cx1, cy1) = self.transform.map(cx1, cy1);
+        let (x, y) = self.transform.map(x, y);
+        self.pen.curve_to(cx0, cy0, cx1, cy1, x, y);
+    }
 
-impl<'a> BridgeFontRef<'a> {
-    fn with_font<T>(&'a self, f: impl FnOnce(&'a FontRef) -> Option<T>) -> Option<T> {
-        f(self.0.as_ref()?)
+    fn close(&mut self) {
+        self.pen.close();
     }
 }
 
-#[derive(Default)]
-struct BridgeOutlineCollection<'a>(Option<OutlineGlyphCollection<'a>>);
+impl PathVerb {
+    fn num_points(self) -> usize {
+        match self {
+            PathVerb::MoveTo | PathVerb::LineTo => 1,
+            PathVerb::QuadTo => 2,
+            PathVerb::CurveTo => 3,
+            _ => 0,
+        }
+    }
+}
 
+/// Records the drawing commands of an outline, for processing that needs the
+/// whole outline before it is forwarded to another pen.
 #[derive(Default)]
-struct BridgeNormalizedCoords {
-    normalized_coords: Location,
-    filtered_user_coords: Vec<VariationSetting>,
+struct OutlineRecorder {
+    verbs: Vec<PathVerb>,
+    points: Vec<(f32, f32)>,
 }
 
-struct BridgeLocalizedStrings<'a> {
-    #[allow(dead_code)]
-    localized_strings: LocalizedStrings<'a>,
-}
+impl Pen for OutlineRecorder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.verbs.push(PathVerb::MoveTo);
+        self.points.push((x, y));
+    }
 
-pub struct BridgeColorStops<'a> {
-    pub stops_iterator: Box<dyn Iterator<Item = &'a skrifa::color::ColorStop> + 'a>,
-    pub num_stops: usize,
-}
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.verbs.push(PathVerb::LineTo);
+        self.points.push((x, y));
+    }
 
-#[cxx::bridge(namespace = "fontations_ffi")]
-mod ffi {
-    struct ColorStop {
-        stop: f32,
-        palette_index: u16,
-        alpha: f32,
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.verbs.push(PathVerb::QuadTo);
+        self.points.extend([(cx0, cy0), (x, y)]);
     }
 
-    #[derive(Default)]
-    struct Metrics {
-        top: f32,
-        ascent: f32,
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.verbs.push(PathVerb::CurveTo);
+        self.points.extend([(cx0, cy0), (cx1, cy1), (x, y)]);
+    }
+
+    fn close(&mut self) {
+        self.verbs.push(PathVerb::Close);
+    }
+}
+
+impl OutlineRecorder {
+    fn replay(&self, pen: &mut impl Pen) {
+        let mut points = self.points.iter().copied();
+        let mut next = || points.next().unwrap_or_default();
+        for verb in &self.verbs {
+            match *verb {
+                PathVerb::MoveTo => {
+                    let (x, y) = next();
+                    pen.move_to(x, y);
+                }
+                PathVerb::LineTo => {
+                    let (x, y) = next();
+                    pen.line_to(x, y);
+                }
+                PathVerb::QuadTo => {
+                    let ((cx0, cy0), (x, y)) = (next(), next());
+                    pen.quad_to(cx0, cy0, x, y);
+                }
+                PathVerb::CurveTo => {
+                    let ((cx0, cy0), (cx1, cy1), (x, y)) = (next(), next(), next());
+                    pen.curve_to(cx0, cy0, cx1, cy1, x, y);
+                }
+                PathVerb::Close => pen.close(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the ranges of `points` making up each contour.
+    fn contours(&self) -> Vec<Range<usize>> {
+        let mut contours = Vec::new();
+        let mut start = 0;
+        let mut end = 0;
+        for verb in &self.verbs {
+            if *verb == PathVerb::MoveTo && end > start {
+                contours.push(start..end);
+                start = end;
+            }
+            end += verb.num_points();
+        }
+        if end > start {
+            contours.push(start..end);
+        }
+        contours
+    }
+
+    /// Grows the outline by `strength` horizontally and vertically, like
+    /// FreeType's FT_Outline_Embolden: every point moves outward by half the
+    /// strength along the bisector of its neighbours, and the outline is then
+    /// shifted by half the strength so that it grows to the right and up.
+    /// Outer contours may run either way; the direction of the outline as a
+    /// whole determines which side is outward. Negative strengths thin the
+    /// outline.
+    fn embolden(&mut self, strength: f32) {
+        let contours = self.contours();
+        // Twice the signed area, which is positive for counter-clockwise
+        // outer contours as in CFF and negative for TrueType.
+        let area: f32 = contours
+            .iter()
+            .map(|contour| {
+                let points = &self.points[contour.clone()];
+                (0..points.len())
+                    .map(|i| {
+                        let (x0, y0) = points[i];
+                        let (x1, y1) = points[(i + 1) % points.len()];
+                        x0 * y1 - x1 * y0
+                    })
+                    .sum::<f32>()
+            })
+            .sum();
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        let clockwise = area < 0.0;
+        let half_strength = strength / 2.0;
+        for contour in contours {
+            let points = &self.points[contour.clone()];
+            let len = points.len();
+            // Coincident points, such as a contour's end repeating its start,
+            // are skipped when looking for neighbours.
+            let distinct_neighbour = |i: usize, step: usize| {
+                (1..len)
+                    .map(|n| (i + n * step) % len)
+                    .find(|j| points[*j] != points[i])
+                    .map(|j| points[j])
+            };
+            let shifted: Vec<(f32, f32)> = (0..len)
+                .map(|i| {
+                    let (x, y) = points[i];
+                    let shift = match (distinct_neighbour(i, len - 1), distinct_neighbour(i, 1)) {
+                        (Some(prev), Some(next)) => {
+                            embolden_shift(prev, points[i], next, half_strength, clockwise)
+                        }
+                        _ => (0.0, 0.0),
+                    };
+                    (x + half_strength + shift.0, y + half_strength + shift.1)
+                })
+                .collect();
+            self.points[contour].copy_from_slice(&shifted);
+        }
+    }
+}
+
+/// Offset of `current` along the lateral bisector of its neighbouring
+/// segments, following FreeType's FT_Outline_EmboldenXY. The shift is limited
+/// by the shorter segment so that small features do not collapse, and omitted
+/// at spikes turning back by more than about 160 degrees.
+fn embolden_shift(
+    prev: (f32, f32),
+    current: (f32, f32),
+    next: (f32, f32),
+    strength: f32,
+    clockwise: bool,
+) -> (f32, f32) {
+    let (in_x, in_y) = (current.0 - prev.0, current.1 - prev.1);
+    let (out_x, out_y) = (next.0 - current.0, next.1 - current.1);
+    let (len_in, len_out) = (in_x.hypot(in_y), out_x.hypot(out_y));
+    let (in_x, in_y) = (in_x / len_in, in_y / len_in);
+    let (out_x, out_y) = (out_x / len_out, out_y / len_out);
+    let cos = in_x * out_x + in_y * out_y;
+    if cos <= -0.9375 {
+        return (0.0, 0.0);
+    }
+    let d = cos + 1.0;
+    let (mut shift_x, mut shift_y) = (in_y + out_y, in_x + out_x);
+    let mut q = out_x * in_y - out_y * in_x;
+    if clockwise {
+        shift_x = -shift_x;
+        q = -q;
+    } else {
+        shift_y = -shift_y;
+    }
+    let l = len_in.min(len_out);
+    let scale = if strength * q <= l * d {
+        strength / d
+    } else {
+        l / q
+    };
+    (shift_x * scale, shift_y * scale)
+}
+
+/// Converts a size passed across the bridge. Zero requests unscaled font
+/// units, as in the FreeType port. Negative and non-finite sizes are
+/// rejected.
+fn checked_size(size: f32) -> Option<Size> {
+    if size == 0.0 {
+        Some(Size::unscaled())
+    } else if size.is_finite() && size > 0.0 {
+        Some(Size::new(size))
+    } else {
+        None
+    }
+}
+
+/// A requested size with a `SizeRounding` mode applied.
+#[derive(Clone, Copy)]
+struct RoundedSize {
+    size: Size,
+    /// Scale from font units to 26.6 pixels as a 16.16 value, as computed by
+    /// FreeType's `FT_Set_Char_Size`, for `SizeRounding::Fixed26Dot6`.
+    fixed_scale: Option<Fixed>,
+}
+
+impl RoundedSize {
+    /// Rounds `size` for `font`. Zero requests unscaled font units in every
+    /// mode, positive sizes round to at least the smallest representable
+    /// size. Invalid sizes are rejected as by `checked_size`.
+    fn new(font: &FontRef, size: f32, rounding: SizeRounding) -> Option<Self> {
+        let rounded = match rounding {
+            _ if size <= 0.0 || !size.is_finite() => size,
+            SizeRounding::Fixed26Dot6 => (size * 64.0).round().max(1.0) / 64.0,
+            SizeRounding::IntegerPpem => size.round().max(1.0),
+            _ => size,
+        };
+        let fixed_scale = match rounding {
+            SizeRounding::Fixed26Dot6 if rounded > 0.0 => {
+                let units_per_em = i32::from(font.head().ok()?.units_per_em());
+                Some(
+                    Fixed::from_bits((rounded * 64.0).min(i32::MAX as f32) as i32)
+                        / Fixed::from_bits(units_per_em),
+                )
+            }
+            _ => None,
+        };
+        Some(Self {
+            size: checked_size(rounded)?,
+            fixed_scale,
+        })
+    }
+
+    /// Size to pass to skrifa. Unscaled in the 26.6 mode, where lengths are
+    /// scaled afterwards by `scale`.
+    fn skrifa_size(&self) -> Size {
+        match self.fixed_scale {
+            Some(_) => Size::unscaled(),
+            None => self.size,
+        }
+    }
+
+    /// Scales a length returned for `skrifa_size`. In the 26.6 mode the
+    /// length is rounded to whole font units and scaled with FreeType's
+    /// fixed point arithmetic, otherwise it is returned unchanged.
+    fn scale(&self, length: f32) -> f32 {
+        match self.fixed_scale {
+            Some(scale) => {
+                (Fixed::from_bits(length.round() as i32) * scale).to_bits() as f32 / 64.0
+            }
+            None => length,
+        }
+    }
+
+    fn scale_metrics(&self, metrics: &mut ffi::Metrics) {
+        for length in [
+            &mut metrics.top,
+            &mut metrics.ascent,
+            &mut metrics.descent,
+            &mut metrics.bottom,
+            &mut metrics.leading,
+            &mut metrics.avg_char_width,
+            &mut metrics.max_char_width,
+            &mut metrics.x_min,
+            &mut metrics.x_max,
+            &mut metrics.x_height,
+            &mut metrics.cap_height,
+            &mut metrics.ideographic_bottom,
+            &mut metrics.underline_position,
+            &mut metrics.underline_thickness,
+            &mut metrics.strikeout_position,
+            &mut metrics.strikeout_thickness,
+        ] {
+            *length = self.scale(*length);
+        }
+        // FreeType grid-fits the ascender and descender of its size metrics
+        // to whole pixels, away from the baseline.
+        if self.fixed_scale.is_some() {
+            metrics.ascent = metrics.ascent.ceil();
+            metrics.descent = metrics.descent.floor();
+        }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    // Number of OutlineScalers created on this thread, for checking that
+    // glyph runs share one.
+    static NUM_OUTLINE_SCALERS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    // Number of GlyphMetricsScalers created on this thread, for checking
+    // that batches share one.
+    static NUM_GLYPH_METRICS_SCALERS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Draws outlines of a collection at one size, location, set of limits and
+/// hinting level. Setting up hinting is the expensive part for CFF fonts, so
+/// runs of glyphs share a scaler.
+struct OutlineScaler<'a> {
+    outlines: &'a BridgeOutlineCollection<'a>,
+    size: Size,
+    coords: &'a BridgeNormalizedCoords,
+    limits: &'a BridgeOutlineLimits,
+    hinting_instance: Option<EmbeddedHintingInstance>,
+}
+
+impl<'a> OutlineScaler<'a> {
+    /// Returns None for sizes rejected by `checked_size`.
+    fn new(
+        outlines: &'a BridgeOutlineCollection<'a>,
+        size: f32,
+        coords: &'a BridgeNormalizedCoords,
+        limits: &'a BridgeOutlineLimits,
+        hinting: HintingLevel,
+    ) -> Option<Self> {
+        #[cfg(test)]
+        NUM_OUTLINE_SCALERS.with(|count| count.set(count.get() + 1));
+        let size = checked_size(size)?;
+        Some(Self {
+            outlines,
+            size,
+            coords,
+            limits,
+            hinting_instance: outlines.hinting_instance(size, coords, hinting),
+        })
+    }
+
+    fn draw(
+        &self,
+        glyph_id: u16,
+        pen: &mut impl Pen,
+        scaler_metrics: &mut BridgeScalerMetrics,
+    ) -> PathStatus {
+        let outlines = self.outlines;
+        if glyph_id >= outlines.num_glyphs {
+            return PathStatus::GlyphOutOfRange;
+        }
+        if !outlines.within_limits(GlyphId::new(glyph_id), self.coords, self.limits) {
+            return PathStatus::LimitsExceeded;
+        }
+        let Some(glyph) = outlines
+            .outlines
+            .as_ref()
+            .and_then(|outlines| outlines.get(GlyphId::new(glyph_id)))
+        else {
+            return PathStatus::GlyphNotFound;
+        };
+        let draw_settings = match &self.hinting_instance {
+            Some(instance) => DrawSettings::embedded_hinting(instance),
+            None => DrawSettings::unhinted(self.size, &self.coords.normalized_coords),
+        };
+        let mut tracking_pen = SegmentTrackingPen {
+            pen,
+            num_segments: 0,
+            max_segments: self.limits.max_path_segments,
+        };
+        match glyph.draw(draw_settings, &mut tracking_pen) {
+            _ if tracking_pen.limit_exceeded() => PathStatus::LimitsExceeded,
+            Err(DrawError::GlyphNotFound(_)) => PathStatus::GlyphNotFound,
+            Err(_) => PathStatus::DrawError,
+            Ok(metrics) => {
+                scaler_metrics.has_overlaps = metrics.has_overlaps;
+                if tracking_pen.num_segments > 0 {
+                    PathStatus::Success
+                } else {
+                    PathStatus::EmptyOutline
+                }
+            }
+        }
+    }
+
+    /// Draws `glyph_id` emboldened by `embolden_strength` and then mapped
+    /// through `transform`, as specified for `get_path`, and sets the bounds
+    /// of `scaler_metrics` to those of the points passed to `pen`.
+    fn draw_styled(
+        &self,
+        glyph_id: u16,
+        transform: &BridgePathTransform,
+        embolden_strength: f32,
+        pen: &mut impl Pen,
+        scaler_metrics: &mut BridgeScalerMetrics,
+    ) -> PathStatus {
+        let mut bounds_pen = BoundsPen {
+            pen,
+            bounds: BridgePathBounds::default(),
+        };
+        let mut transform_pen = TransformPen {
+            pen: &mut bounds_pen,
+            transform: *transform,
+        };
+        let status = if embolden_strength != 0.0 && embolden_strength.is_finite() {
+            let mut recorder = OutlineRecorder::default();
+            let status = self.draw(glyph_id, &mut recorder, scaler_metrics);
+            if status == PathStatus::Success {
+                recorder.embolden(embolden_strength);
+                recorder.replay(&mut transform_pen);
+            }
+            status
+        } else {
+            self.draw(glyph_id, &mut transform_pen, scaler_metrics)
+        };
+        scaler_metrics.bounds = match status {
+            PathStatus::Success => bounds_pen.bounds,
+            _ => BridgePathBounds::default(),
+        };
+        status
+    }
+}
+
+/// Draws `glyph_id` with a scaler of its own.
+#[cfg(any(test, fuzzing))]
+#[allow(clippy::too_many_arguments)]
+fn draw_outline(
+    outlines: &BridgeOutlineCollection,
+    glyph_id: u16,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    limits: &BridgeOutlineLimits,
+    hinting: HintingLevel,
+    pen: &mut impl Pen,
+    scaler_metrics: &mut BridgeScalerMetrics,
+) -> PathStatus {
+    match OutlineScaler::new(outlines, size, coords, limits, hinting) {
+        Some(scaler) => scaler.draw(glyph_id, pen, scaler_metrics),
+        None => PathStatus::InvalidSize,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn get_path(
+    outlines: &BridgeOutlineCollection,
+    glyph_id: u16,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    limits: &BridgeOutlineLimits,
+    transform: &BridgePathTransform,
+    embolden_strength: f32,
+    hinting: HintingLevel,
+    clean_up_segments: bool,
+    path_wrapper: Pin<&mut PathWrapper>,
+    scaler_metrics: &mut BridgeScalerMetrics,
+) -> PathStatus {
+    ffi_guard(|| {
+        draw_path_wrapper(
+            outlines,
+            glyph_id,
+            size,
+            coords,
+            limits,
+            transform,
+            embolden_strength,
+            hinting,
+            clean_up_segments,
+            path_wrapper,
+            scaler_metrics,
+        )
+    })
+}
+
+/// Draws the outline for `get_path` to any `PathWrapperSink`.
+#[allow(clippy::too_many_arguments)]
+fn draw_path_wrapper<W: PathWrapperSink>(
+    outlines: &BridgeOutlineCollection,
+    glyph_id: u16,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    limits: &BridgeOutlineLimits,
+    transform: &BridgePathTransform,
+    embolden_strength: f32,
+    hinting: HintingLevel,
+    clean_up_segments: bool,
+    path_wrapper: Pin<&mut W>,
+    scaler_metrics: &mut BridgeScalerMetrics,
+) -> PathStatus {
+    *scaler_metrics = BridgeScalerMetrics::default();
+    let Some(scaler) = OutlineScaler::new(outlines, size, coords, limits, hinting) else {
+        return PathStatus::InvalidSize;
+    };
+    let transform = transform.then(&BridgePathTransform::Y_FLIP);
+    let mut pen_dump = PathWrapperPen { path_wrapper };
+    let status = if clean_up_segments {
+        scaler.draw_styled(
+            glyph_id,
+            &transform,
+            embolden_strength,
+            &mut SegmentCleanupPen::new(&mut pen_dump),
+            scaler_metrics,
+        )
+    } else {
+        scaler.draw_styled(
+            glyph_id,
+            &transform,
+            embolden_strength,
+            &mut pen_dump,
+            scaler_metrics,
+        )
+    };
+    if status == PathStatus::LimitsExceeded {
+        pen_dump.path_wrapper.as_mut().reset();
+    }
+    status
+}
+
+/// Receives the outlines of a run of glyphs, each drawn to the pen returned
+/// by `begin_glyph`.
+trait GlyphRunSink {
+    type Pen<'a>: Pen
+    where
+        Self: 'a;
+
+    fn begin_glyph(&mut self, glyph_id: u16) -> Self::Pen<'_>;
+    fn end_glyph(
+        &mut self,
+        glyph_id: u16,
+        status: PathStatus,
+        scaler_metrics: &BridgeScalerMetrics,
+    );
+}
+
+impl GlyphRunSink for Pin<&mut GlyphPathsWrapper> {
+    type Pen<'a>
+        = PathWrapperPen<'a>
+    where
+        Self: 'a;
+
+    fn begin_glyph(&mut self, glyph_id: u16) -> PathWrapperPen<'_> {
+        PathWrapperPen {
+            path_wrapper: self.as_mut().begin_glyph(glyph_id),
+        }
+    }
+
+    fn end_glyph(
+        &mut self,
+        glyph_id: u16,
+        status: PathStatus,
+        scaler_metrics: &BridgeScalerMetrics,
+    ) {
+        self.as_mut().end_glyph(glyph_id, status, scaler_metrics);
+    }
+}
+
+/// Draws each of `glyph_ids` to `sink` with one `OutlineScaler`, returning
+/// the number of glyphs drawn successfully. A glyph failing to draw does not
+/// stop the run.
+#[allow(clippy::too_many_arguments)]
+fn draw_glyph_run(
+    outlines: &BridgeOutlineCollection,
+    glyph_ids: &[u16],
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    limits: &BridgeOutlineLimits,
+    transform: &BridgePathTransform,
+    embolden_strength: f32,
+    hinting: HintingLevel,
+    sink: &mut impl GlyphRunSink,
+) -> usize {
+    let scaler = OutlineScaler::new(outlines, size, coords, limits, hinting);
+    let mut num_drawn = 0;
+    for glyph_id in glyph_ids.iter().copied() {
+        let mut scaler_metrics = BridgeScalerMetrics::default();
+        let mut pen = sink.begin_glyph(glyph_id);
+        let status = match &scaler {
+            Some(scaler) => scaler.draw_styled(
+                glyph_id,
+                transform,
+                embolden_strength,
+                &mut pen,
+                &mut scaler_metrics,
+            ),
+            None => PathStatus::InvalidSize,
+        };
+        drop(pen);
+        if matches!(status, PathStatus::Success | PathStatus::EmptyOutline) {
+            num_drawn += 1;
+        }
+        sink.end_glyph(glyph_id, status, &scaler_metrics);
+    }
+    num_drawn
+}
+
+#[allow(clippy::too_many_arguments)]
+fn get_path_verbs(
+    outlines: &BridgeOutlineCollection,
+    glyph_id: u16,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    limits: &BridgeOutlineLimits,
+    transform: &BridgePathTransform,
+    embolden_strength: f32,
+    hinting: HintingLevel,
+    verbs: &mut Vec<u8>,
+    points: &mut Vec<f32>,
+    scaler_metrics: &mut BridgeScalerMetrics,
+) -> PathStatus {
+    ffi_guard(|| {
+        verbs.clear();
+        points.clear();
+        *scaler_metrics = BridgeScalerMetrics::default();
+        let Some(scaler) = OutlineScaler::new(outlines, size, coords, limits, hinting) else {
+            return PathStatus::InvalidSize;
+        };
+        let mut pen = PackedPathPen { verbs, points };
+        let status = scaler.draw_styled(
+            glyph_id,
+            &transform.then(&BridgePathTransform::Y_FLIP),
+            embolden_strength,
+            &mut pen,
+            scaler_metrics,
+        );
+        if !matches!(status, PathStatus::Success | PathStatus::EmptyOutline) {
+            pen.verbs.clear();
+            pen.points.clear();
+        }
+        status
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn get_paths(
+    outlines: &BridgeOutlineCollection,
+    glyph_ids: &[u16],
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    limits: &BridgeOutlineLimits,
+    transform: &BridgePathTransform,
+    embolden_strength: f32,
+    hinting: HintingLevel,
+    mut paths_wrapper: Pin<&mut GlyphPathsWrapper>,
+) -> usize {
+    ffi_guard(|| {
+        draw_glyph_run(
+            outlines,
+            glyph_ids,
+            size,
+            coords,
+            limits,
+            &transform.then(&BridgePathTransform::Y_FLIP),
+            embolden_strength,
+            hinting,
+            &mut paths_wrapper,
+        )
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn get_path_unscaled(
+    outlines: &BridgeOutlineCollection,
+    glyph_id: u16,
+    coords: &BridgeNormalizedCoords,
+    limits: &BridgeOutlineLimits,
+    transform: &BridgePathTransform,
+    embolden_strength: f32,
+    path_wrapper: Pin<&mut PathWrapper>,
+    scaler_metrics: &mut BridgeScalerMetrics,
+) -> PathStatus {
+    get_path(
+        outlines,
+        glyph_id,
+        0.0,
+        coords,
+        limits,
+        transform,
+        embolden_strength,
+        HintingLevel::None,
+        false,
+        path_wrapper,
+        scaler_metrics,
+    )
+}
+
+fn outline_is_empty(
+    outlines: &BridgeOutlineCollection,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+) -> bool {
+    if glyph_id >= outlines.num_glyphs {
+        return false;
+    }
+    let Some(glyph) = outlines
+        .outlines
+        .as_ref()
+        .and_then(|outlines| outlines.get(GlyphId::new(glyph_id)))
+    else {
+        return false;
+    };
+    let draw_settings = DrawSettings::unhinted(Size::unscaled(), &coords.normalized_coords);
+    let mut pen = EmptyOutlinePen::default();
+    glyph.draw(draw_settings, &mut pen).is_ok() && !pen.has_segments
+}
+
+fn is_empty_glyph(
+    font_ref: &BridgeFontRef,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+) -> bool {
+    ffi_guard(|| outline_is_empty(&BridgeOutlineCollection::new(font_ref), coords, glyph_id))
+}
+
+fn are_empty_glyphs(
+    font_ref: &BridgeFontRef,
+    coords: &BridgeNormalizedCoords,
+    glyph_ids: &[u16],
+    out_is_empty: &mut [bool],
+) -> usize {
+    ffi_guard(|| {
+        let outlines = BridgeOutlineCollection::new(font_ref);
+        let mut num_empty = 0;
+        for (glyph_id, is_empty) in glyph_ids.iter().zip(out_is_empty.iter_mut()) {
+            *is_empty = outline_is_empty(&outlines, coords, *glyph_id);
+            if *is_empty {
+                num_empty += 1;
+            }
+        }
+        num_empty
+    })
+}
+
+/// Computes the control box of `glyph_id` at `size` and `coords` in the
+/// y-flipped space of `get_path`. For simple glyf outlines not varied by
+/// gvar, the bounding box stored in the glyph header is scaled instead of
+/// drawing the outline, rounded outwards to the 26.6 grid the scaled outline points
+/// are rounded to. Returns None for glyph IDs out of range and outlines that
+/// fail to draw.
+fn glyph_bounds_in(
+    font: &FontRef,
+    outlines: &BridgeOutlineCollection,
+    size: Size,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+) -> Option<BridgePathBounds> {
+    if glyph_id >= outlines.num_glyphs {
+        return None;
+    }
+    let glyph_id = GlyphId::new(glyph_id);
+    let mut bounds = BridgePathBounds::default();
+    let is_varied = font.gvar().is_ok() && !significant_normalized_coords(coords).is_empty();
+    let simple_glyph = match &outlines.glyf {
+        Some((loca, glyf)) if !is_varied => match loca.get_glyf(glyph_id, glyf).ok()? {
+            Some(Glyph::Simple(simple_glyph)) => Some(simple_glyph),
+            // Composite headers do not account for component offsets being
+            // rounded to the grid when scaled.
+            Some(Glyph::Composite(_)) => None,
+            None => return Some(bounds),
+        },
+        _ => None,
+    };
+    match simple_glyph {
+        Some(glyph) => {
+            let scale = size.linear_scale(font.head().ok()?.units_per_em()) * 64.0;
+            let floor = |value: i16| (f32::from(value) * scale).floor() / 64.0;
+            let ceil = |value: i16| (f32::from(value) * scale).ceil() / 64.0;
+            bounds.add(floor(glyph.x_min()), -ceil(glyph.y_max()));
+            bounds.add(ceil(glyph.x_max()), -floor(glyph.y_min()));
+        }
+        None => {
+            let glyph = outlines.outlines.as_ref()?.get(glyph_id)?;
+            let mut pen = BoundsPen {
+                pen: &mut EmptyOutlinePen::default(),
+                bounds: BridgePathBounds::default(),
+            };
+            glyph
+                .draw(
+                    DrawSettings::unhinted(size, &coords.normalized_coords),
+                    &mut pen,
+                )
+                .ok()?;
+            if !pen.bounds.is_empty {
+                bounds.add(pen.bounds.x_min, -pen.bounds.y_max);
+                bounds.add(pen.bounds.x_max, -pen.bounds.y_min);
+            }
+        }
+    }
+    Some(bounds)
+}
+
+fn glyph_bounds(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    out: &mut BridgePathBounds,
+) -> bool {
+    ffi_guard(|| {
+        let outlines = BridgeOutlineCollection::new(font_ref);
+        let bounds = font_ref
+            .with_font(|f| glyph_bounds_in(f, &outlines, checked_size(size)?, coords, glyph_id));
+        *out = bounds.unwrap_or_default();
+        bounds.is_some()
+    })
+}
+
+fn glyph_bounds_batch(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_ids: &[u16],
+    out_bounds: &mut [BridgePathBounds],
+) -> usize {
+    ffi_guard(|| {
+        let outlines = BridgeOutlineCollection::new(font_ref);
+        let size = checked_size(size);
+        let mut num_computed = 0;
+        for (glyph_id, out) in glyph_ids.iter().zip(out_bounds.iter_mut()) {
+            let bounds =
+                font_ref.with_font(|f| glyph_bounds_in(f, &outlines, size?, coords, *glyph_id));
+            *out = bounds.unwrap_or_default();
+            if bounds.is_some() {
+                num_computed += 1;
+            }
+        }
+        num_computed
+    })
+}
+
+/// Returns true if the glyf outline of `glyph_id` sets OVERLAP_SIMPLE, or
+/// if any component at any nesting level sets OVERLAP_COMPOUND or is a
+/// simple glyph setting OVERLAP_SIMPLE. Composites nested deeper than the
+/// default `BridgeOutlineLimits` fail to draw and are not followed further.
+fn glyf_has_overlaps(loca: &Loca, glyf: &Glyf, glyph_id: GlyphId, depth: u16) -> bool {
+    match loca.get_glyf(glyph_id, glyf) {
+        Ok(Some(Glyph::Simple(simple))) => simple.has_overlapping_contours(),
+        Ok(Some(Glyph::Composite(composite))) => {
+            depth < BridgeOutlineLimits::default().max_composite_depth
+                && composite
+                    .component_glyphs_and_flags()
+                    .any(|(component, flags)| {
+                        flags.contains(CompositeGlyphFlags::OVERLAP_COMPOUND)
+                            || glyf_has_overlaps(loca, glyf, component, depth + 1)
+                    })
+        }
+        Ok(None) | Err(_) => false,
+    }
+}
+
+fn glyph_has_overlaps(font_ref: &BridgeFontRef, glyph_id: u16) -> bool {
+    ffi_guard(|| {
+        if glyph_id >= font_ref.num_glyphs {
+            return false;
+        }
+        font_ref
+            .with_font(|f| match (f.loca(None), f.glyf()) {
+                (Ok(loca), Ok(glyf)) => {
+                    Some(glyf_has_overlaps(&loca, &glyf, GlyphId::new(glyph_id), 0))
+                }
+                // CFF has no overlap flags and overlapping contours are
+                // common, so assume they may overlap.
+                _ => Some(f.cff().is_ok() || f.cff2().is_ok()),
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Advance widths and left side bearings at one size and location. Deltas
+/// come from HVAR for the glyphs it covers. For glyf fonts varied by gvar
+/// whose HVAR table is missing, lacks side bearing mappings or has no delta
+/// for a glyph, they come from the phantom points after applying the gvar
+/// deltas, as in FreeType: the advance is the distance between them and the
+/// side bearing the distance from the first to the left edge of the varied
+/// outline.
+struct VariedHorizontalMetrics<'a> {
+    glyph_metrics: GlyphMetrics<'a>,
+    /// Present at non-default coordinates.
+    hvar: Option<Hvar<'a>>,
+    /// Present for glyf fonts with gvar at non-default coordinates.
+    phantom_points: Option<(GlyphMetrics<'a>, OutlineGlyphCollection<'a>)>,
+    /// Scale from font units to `size`.
+    scale: f32,
+    coords: &'a [NormalizedCoord],
+}
+
+impl<'a> VariedHorizontalMetrics<'a> {
+    fn new(font: &FontRef<'a>, size: Size, coords: &'a BridgeNormalizedCoords) -> Self {
+        let is_varied = !significant_normalized_coords(coords).is_empty();
+        let coords = coords.normalized_coords.coords();
+        let phantom_points = (font.glyf().is_ok() && font.gvar().is_ok() && is_varied).then(|| {
+            // Advances of a font without HVAR take their deltas from gvar.
+            let without_hvar = WithoutTable {
+                font,
+                tag: Tag::new(b"HVAR"),
+            };
+            (
+                GlyphMetrics::new(&without_hvar, size, coords),
+                font.outline_glyphs(),
+            )
+        });
+        let units_per_em = font
+            .head()
+            .map(|head| head.units_per_em())
+            .unwrap_or_default();
+        Self {
+            glyph_metrics: GlyphMetrics::new(font, size, coords),
+            hvar: font.hvar().ok().filter(|_| is_varied),
+            phantom_points,
+            scale: size.linear_scale(units_per_em),
+            coords,
+        }
+    }
+
+    fn advance_width(&self, glyph_id: GlyphId) -> Option<f32> {
+        let hvar_delta = self.hvar.as_ref().and_then(|hvar| {
+            hvar_item_delta(hvar, hvar.advance_width_mapping(), glyph_id, self.coords)
+        });
+        match &self.phantom_points {
+            Some((phantom_metrics, _)) if hvar_delta.is_none() => {
+                phantom_metrics.advance_width(glyph_id)
+            }
+            _ => self.glyph_metrics.advance_width(glyph_id),
+        }
+    }
+
+    fn left_side_bearing(&self, glyph_id: GlyphId) -> Option<f32> {
+        if let (Some((_, outlines)), SideBearingSource::PhantomPoints) = (
+            &self.phantom_points,
+            self.left_side_bearing_source(glyph_id),
+        ) {
+            // Outlines are drawn relative to the first phantom point.
+            let mut pen = BoundsPen {
+                pen: &mut EmptyOutlinePen::default(),
+                bounds: BridgePathBounds::default(),
+            };
+            outlines
+                .get(glyph_id)?
+                .draw(
+                    DrawSettings::unhinted(Size::unscaled(), self.coords),
+                    &mut pen,
+                )
+                .ok()?;
+            // Glyphs without points keep their hmtx side bearing.
+            if !pen.bounds.is_empty {
+                return Some(pen.bounds.x_min * self.scale);
+            }
+        }
+        self.glyph_metrics.left_side_bearing(glyph_id)
+    }
+
+    fn left_side_bearing_source(&self, glyph_id: GlyphId) -> SideBearingSource {
+        // Unlike advances, side bearings have no implicit mapping.
+        let hvar_delta = self.hvar.as_ref().and_then(|hvar| {
+            hvar_item_delta(hvar, Some(hvar.lsb_mapping()?), glyph_id, self.coords)
+        });
+        match (hvar_delta, &self.phantom_points) {
+            (Some(_), _) => SideBearingSource::Hvar,
+            (None, Some(_)) => SideBearingSource::PhantomPoints,
+            (None, None) => SideBearingSource::Hmtx,
+        }
+    }
+}
+
+/// Tables of `font` except `tag`.
+struct WithoutTable<'a, 'b> {
+    font: &'b FontRef<'a>,
+    tag: Tag,
+}
+
+impl<'a> TableProvider<'a> for WithoutTable<'a, '_> {
+    fn data_for_tag(&self, tag: Tag) -> Option<FontData<'a>> {
+        (tag != self.tag)
+            .then(|| self.font.data_for_tag(tag))
+            .flatten()
+    }
+}
+
+/// Returns the HVAR delta of `glyph_id` through `mapping`, or through the
+/// implicit glyph ID mapping if there is none. Returns None if the table has
+/// no delta set for the glyph: the variation store treats a missing item as
+/// a zero delta, which would leave the metric at its default rather than
+/// follow the outline.
+fn hvar_item_delta(
+    hvar: &Hvar,
+    mapping: Option<Result<DeltaSetIndexMap, ReadError>>,
+    glyph_id: GlyphId,
+    coords: &[NormalizedCoord],
+) -> Option<Fixed> {
+    let index = match mapping {
+        Some(mapping) => mapping.ok()?.get(glyph_id.to_u16().into()).ok()?,
+        None => DeltaSetIndex {
+            outer: 0,
+            inner: glyph_id.to_u16(),
+        },
+    };
+    let store = hvar.item_variation_store().ok()?;
+    let data = store.item_variation_data().get(index.outer.into())?.ok()?;
+    if index.inner >= data.item_count() {
+        return None;
+    }
+    store.compute_delta(index, coords).ok().map(Fixed::from_i32)
+}
+
+fn advance_width_or_zero(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    rounding: SizeRounding,
+) -> f32 {
+    ffi_guard(|| {
+        if glyph_id >= font_ref.num_glyphs {
+            return 0.0;
+        }
+        font_ref
+            .with_font(|f| {
+                let size = RoundedSize::new(f, size, rounding)?;
+                VariedHorizontalMetrics::new(f, size.skrifa_size(), coords)
+                    .advance_width(GlyphId::new(glyph_id))
+                    .map(|advance| size.scale(advance))
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn advance_widths(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_ids: &[u16],
+    out_advances: &mut [f32],
+    rounding: SizeRounding,
+) -> usize {
+    ffi_guard(|| {
+        let num_processed = glyph_ids.len().min(out_advances.len());
+        let glyph_metrics = font_ref.with_font(|f| {
+            let size = RoundedSize::new(f, size, rounding)?;
+            Some((
+                VariedHorizontalMetrics::new(f, size.skrifa_size(), coords),
+                size,
+            ))
+        });
+        for (glyph_id, advance) in glyph_ids.iter().zip(out_advances.iter_mut()) {
+            *advance = glyph_metrics
+                .as_ref()
+                .filter(|_| *glyph_id < font_ref.num_glyphs)
+                .and_then(|(glyph_metrics, size)| {
+                    glyph_metrics
+                        .advance_width(GlyphId::new(*glyph_id))
+                        .map(|advance| size.scale(advance))
+                })
+                .unwrap_or_default();
+        }
+        num_processed
+    })
+}
+
+fn advance_width_font_units(
+    font_ref: &BridgeFontRef,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+) -> i32 {
+    ffi_guard(|| {
+        advance_width_or_zero(font_ref, 0.0, coords, glyph_id, SizeRounding::Exact).round() as i32
+    })
+}
+
+fn advance_widths_font_units(
+    font_ref: &BridgeFontRef,
+    coords: &BridgeNormalizedCoords,
+    glyph_ids: &[u16],
+    out_advances: &mut [i32],
+) -> usize {
+    ffi_guard(|| {
+        let mut advances = vec![0.0; glyph_ids.len().min(out_advances.len())];
+        let num_processed = advance_widths(
+            font_ref,
+            0.0,
+            coords,
+            glyph_ids,
+            &mut advances,
+            SizeRounding::Exact,
+        );
+        for (out_advance, advance) in out_advances.iter_mut().zip(advances) {
+            *out_advance = advance.round() as i32;
+        }
+        num_processed
+    })
+}
+
+fn left_side_bearing_or_zero(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+) -> f32 {
+    ffi_guard(|| {
+        let Some(size) = checked_size(size) else {
+            return 0.0;
+        };
+        if glyph_id >= font_ref.num_glyphs {
+            return 0.0;
+        }
+        font_ref
+            .with_font(|f| {
+                VariedHorizontalMetrics::new(f, size, coords)
+                    .left_side_bearing(GlyphId::new(glyph_id))
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn left_side_bearing_source(
+    font_ref: &BridgeFontRef,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+) -> SideBearingSource {
+    ffi_guard(|| {
+        if glyph_id >= font_ref.num_glyphs {
+            return SideBearingSource::Hmtx;
+        }
+        font_ref
+            .with_font(|f| {
+                Some(
+                    VariedHorizontalMetrics::new(f, Size::unscaled(), coords)
+                        .left_side_bearing_source(GlyphId::new(glyph_id)),
+                )
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Returns the ascent and descent in font units that FreeType makes up
+/// vertical metrics from for fonts without vmtx: the typo metrics, or the
+/// hhea metrics if there is no OS/2 table.
+fn synthesized_vertical_metrics(
+    font: &FontRef,
+    coords: &BridgeNormalizedCoords,
+) -> Option<(f32, f32)> {
+    let (ascent, descent) = match font.os2() {
+        Ok(os2) => (os2.s_typo_ascender(), os2.s_typo_descender()),
+        Err(_) => {
+            let hhea = font.hhea().ok()?;
+            (hhea.ascender().to_i16(), hhea.descender().to_i16())
+        }
+    };
+    Some((
+        f32::from(ascent) + mvar_delta(font, b"hasc", coords),
+        f32::from(descent) + mvar_delta(font, b"hdsc", coords),
+    ))
+}
+
+fn advance_height_or_zero(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    out_is_synthesized: &mut bool,
+) -> f32 {
+    ffi_guard(|| {
+        *out_is_synthesized = false;
+        if glyph_id >= font_ref.num_glyphs {
+            return 0.0;
+        }
+        font_ref
+            .with_font(|f| {
+                let size = checked_size(size)?;
+                let units_per_em = f.head().ok()?.units_per_em();
+                let advance = match f.vmtx() {
+                    Ok(vmtx) => {
+                        // Glyphs beyond numOfLongVerMetrics repeat the last
+                        // advance.
+                        let v_metrics = vmtx.v_metrics();
+                        let advance = v_metrics
+                            .get(usize::from(glyph_id))
+                            .or(v_metrics.last())?
+                            .advance();
+                        // Deltas are truncated like FreeType and skrifa's
+                        // horizontal advances.
+                        let delta = f
+                            .vvar()
+                            .ok()
+                            .filter(|_| !coords.normalized_coords.coords().is_empty())
+                            .and_then(|vvar| {
+                                vvar.advance_height_delta(
+                                    GlyphId::new(glyph_id),
+                                    coords.normalized_coords.coords(),
+                                )
+                                .ok()
+                            })
+                            .map_or(0, |delta| delta.to_f64() as i32);
+                        (i32::from(advance) + delta) as f32
+                    }
+                    Err(_) => {
+                        *out_is_synthesized = true;
+                        let (ascent, descent) = synthesized_vertical_metrics(f, coords)?;
+                        ascent - descent
+                    }
+                };
+                Some(advance * size.linear_scale(units_per_em))
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Returns the top side bearing of `glyph_id` in font units from vmtx, with
+/// VVAR deltas for `coords` truncated like advances. If the font has no
+/// vertical metrics, returns the synthesized ascent minus the top of the
+/// outline, as FreeType does.
+fn top_side_bearing_in(
+    font: &FontRef,
+    outlines: &BridgeOutlineCollection,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+) -> Option<f32> {
+    if glyph_id >= outlines.num_glyphs {
+        return None;
+    }
+    match font.vmtx() {
+        Ok(vmtx) => {
+            // Glyphs beyond numOfLongVerMetrics read the trailing
+            // topSideBearings array.
+            let index = usize::from(glyph_id);
+            let v_metrics = vmtx.v_metrics();
+            let side_bearing = match v_metrics.get(index) {
+                Some(metric) => metric.side_bearing(),
+                None => vmtx.top_side_bearings().get(index - v_metrics.len())?.get(),
+            };
+            let delta = font
+                .vvar()
+                .ok()
+                .filter(|_| !coords.normalized_coords.coords().is_empty())
+                .and_then(|vvar| {
+                    vvar.tsb_delta(GlyphId::new(glyph_id), coords.normalized_coords.coords())
+                        .ok()
+                })
+                .map_or(0, |delta| delta.to_f64() as i32);
+            Some((i32::from(side_bearing) + delta) as f32)
+        }
+        Err(_) => {
+            let (ascent, _) = synthesized_vertical_metrics(font, coords)?;
+            // The bounds are y-flipped, y_min is the negated top.
+            let bounds = glyph_bounds_in(font, outlines, Size::unscaled(), coords, glyph_id)?;
+            Some(ascent + bounds.y_min)
+        }
+    }
+}
+
+fn top_side_bearing_or_zero(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+) -> f32 {
+    ffi_guard(|| {
+        let mut side_bearing = 0.0;
+        top_side_bearings(
+            font_ref,
+            size,
+            coords,
+            &[glyph_id],
+            std::slice::from_mut(&mut side_bearing),
+        );
+        side_bearing
+    })
+}
+
+fn top_side_bearings(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_ids: &[u16],
+    out_side_bearings: &mut [f32],
+) -> usize {
+    ffi_guard(|| {
+        let num_processed = glyph_ids.len().min(out_side_bearings.len());
+        let outlines = BridgeOutlineCollection::new(font_ref);
+        let scale = font_ref
+            .with_font(|f| Some(checked_size(size)?.linear_scale(f.head().ok()?.units_per_em())));
+        for (glyph_id, side_bearing) in glyph_ids.iter().zip(out_side_bearings.iter_mut()) {
+            *side_bearing = font_ref
+                .with_font(|f| Some(top_side_bearing_in(f, &outlines, coords, *glyph_id)? * scale?))
+                .unwrap_or_default();
+        }
+        num_processed
+    })
+}
+
+/// Finds the vertical origin of `glyph_id` in `vorg`, the raw VORG table
+/// data: its entry in vertOriginYMetrics, or defaultVertOriginY if it has
+/// none.
+fn find_vert_origin_y(vorg: FontData, glyph_id: u16) -> Option<i16> {
+    let default_origin = vorg.read_at::<i16>(4).ok()?;
+    let num_metrics = usize::from(vorg.read_at::<u16>(6).ok()?);
+    // Entries are glyphIndex and vertOriginY, sorted by glyph index.
+    let (mut low, mut high) = (0, num_metrics);
+    while low < high {
+        let mid = (low + high) / 2;
+        let offset = 8 + 4 * mid;
+        match vorg.read_at::<u16>(offset).ok()?.cmp(&glyph_id) {
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+            Ordering::Equal => return vorg.read_at::<i16>(offset + 2).ok(),
+        }
+    }
+    Some(default_origin)
+}
+
+fn vertical_origin_y(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    out: &mut f32,
+) -> bool {
+    ffi_guard(|| {
+        let origin = font_ref.with_font(|f| {
+            let size = checked_size(size)?;
+            if glyph_id >= font_ref.num_glyphs {
+                return None;
+            }
+            let origin = find_vert_origin_y(f.data_for_tag(Tag::new(b"VORG"))?, glyph_id)?;
+            let delta = f
+                .vvar()
+                .ok()
+                .filter(|_| !coords.normalized_coords.coords().is_empty())
+                .and_then(|vvar| {
+                    vvar.v_org_delta(GlyphId::new(glyph_id), coords.normalized_coords.coords())
+                        .ok()
+                })
+                .map_or(0, |delta| delta.to_f64() as i32);
+            let units_per_em = f.head().ok()?.units_per_em();
+            Some((i32::from(origin) + delta) as f32 * size.linear_scale(units_per_em))
+        });
+        *out = origin.unwrap_or_default();
+        origin.is_some()
+    })
+}
+
+fn vertical_glyph_metrics(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    out: &mut BridgeVerticalGlyphMetrics,
+) -> bool {
+    ffi_guard(|| {
+        *out = BridgeVerticalGlyphMetrics::default();
+        let outlines = BridgeOutlineCollection::new(font_ref);
+        font_ref
+            .with_font(|f| {
+                let scale = checked_size(size)?.linear_scale(f.head().ok()?.units_per_em());
+                if glyph_id >= font_ref.num_glyphs {
+                    return None;
+                }
+                // Work in font units and scale at the end, so that the origin
+                // derived from the outline is not rounded at small sizes.
+                let mut is_synthesized = false;
+                let advance =
+                    advance_height_or_zero(font_ref, 0.0, coords, glyph_id, &mut is_synthesized);
+                let mut origin_y = 0.0;
+                if !vertical_origin_y(font_ref, 0.0, coords, glyph_id, &mut origin_y) {
+                    origin_y = if is_synthesized {
+                        synthesized_vertical_metrics(f, coords)?.0
+                    } else {
+                        // The bounds are y-flipped, y_min is the negated top.
+                        let bounds =
+                            glyph_bounds_in(f, &outlines, Size::unscaled(), coords, glyph_id)?;
+                        top_side_bearing_in(f, &outlines, coords, glyph_id)? - bounds.y_min
+                    };
+                }
+                let origin_x =
+                    advance_width_or_zero(font_ref, 0.0, coords, glyph_id, SizeRounding::Exact)
+                        / 2.0;
+                *out = BridgeVerticalGlyphMetrics {
+                    origin_x: origin_x * scale,
+                    origin_y: origin_y * scale,
+                    advance: advance * scale,
+                    is_synthesized,
+                };
+                Some(true)
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Computes `BridgeGlyphMetrics` at one size and location, sharing the
+/// metrics tables and outlines across a batch of glyphs.
+struct GlyphMetricsScaler<'a> {
+    font: &'a FontRef<'a>,
+    outlines: BridgeOutlineCollection<'a>,
+    horizontal_metrics: VariedHorizontalMetrics<'a>,
+    size: Size,
+    coords: &'a BridgeNormalizedCoords,
+}
+
+impl<'a> GlyphMetricsScaler<'a> {
+    /// Returns None for negative and non-finite sizes and invalid fonts.
+    fn new(
+        font_ref: &'a BridgeFontRef<'a>,
+        size: f32,
+        coords: &'a BridgeNormalizedCoords,
+    ) -> Option<Self> {
+        #[cfg(test)]
+        NUM_GLYPH_METRICS_SCALERS.with(|count| count.set(count.get() + 1));
+        let size = checked_size(size)?;
+        let font = font_ref.font.as_ref()?;
+        Some(Self {
+            font,
+            outlines: BridgeOutlineCollection::new(font_ref),
+            horizontal_metrics: VariedHorizontalMetrics::new(font, size, coords),
+            size,
+            coords,
+        })
+    }
+
+    fn metrics(&self, glyph_id: u16) -> Option<BridgeGlyphMetrics> {
+        let bounds = glyph_bounds_in(self.font, &self.outlines, self.size, self.coords, glyph_id)?;
+        let glyph_id = GlyphId::new(glyph_id);
+        Some(BridgeGlyphMetrics {
+            advance_width: self
+                .horizontal_metrics
+                .advance_width(glyph_id)
+                .unwrap_or_default(),
+            left_side_bearing: self
+                .horizontal_metrics
+                .left_side_bearing(glyph_id)
+                .unwrap_or_default(),
+            bounds,
+            is_empty: bounds.is_empty,
+        })
+    }
+}
+
+fn glyph_metrics(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    out: &mut BridgeGlyphMetrics,
+) -> bool {
+    ffi_guard(|| {
+        let metrics = GlyphMetricsScaler::new(font_ref, size, coords)
+            .and_then(|scaler| scaler.metrics(glyph_id));
+        *out = metrics.unwrap_or_default();
+        metrics.is_some()
+    })
+}
+
+fn glyph_metrics_batch(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_ids: &[u16],
+    out_metrics: &mut [BridgeGlyphMetrics],
+) -> usize {
+    ffi_guard(|| {
+        let scaler = GlyphMetricsScaler::new(font_ref, size, coords);
+        let mut num_computed = 0;
+        for (glyph_id, out) in glyph_ids.iter().zip(out_metrics.iter_mut()) {
+            let metrics = scaler.as_ref().and_then(|scaler| scaler.metrics(*glyph_id));
+            *out = metrics.unwrap_or_default();
+            if metrics.is_some() {
+                num_computed += 1;
+            }
+        }
+        num_computed
+    })
+}
+
+fn hinted_advance_width_or_zero(
+    font_ref: &BridgeFontRef,
+    outlines: &BridgeOutlineCollection,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    hinting: HintingLevel,
+) -> f32 {
+    ffi_guard(|| {
+        linear_and_hinted_advances(font_ref, outlines, size, coords, glyph_id, hinting).hinted
+    })
+}
+
+fn hdmx_advance(font_ref: &BridgeFontRef, ppem: u8, glyph_id: u16, out: &mut u8) -> bool {
+    ffi_guard(|| {
+        let advance = font_ref.with_font(|f| {
+            // Device metrics only describe the default instance, the spec
+            // excludes them from variable fonts.
+            if f.fvar().is_ok() {
+                return None;
+            }
+            let num_glyphs = usize::from(f.maxp().ok()?.num_glyphs());
+            if usize::from(glyph_id) >= num_glyphs {
+                return None;
+            }
+            let hdmx = f.data_for_tag(Tag::new(b"hdmx"))?;
+            let num_records = usize::try_from(hdmx.read_at::<i16>(2).ok()?).ok()?;
+            let record_size = usize::try_from(hdmx.read_at::<i32>(4).ok()?).ok()?;
+            // Each device record holds the pixel size, the maximum width and
+            // a width per glyph.
+            if record_size < num_glyphs + 2 {
+                return None;
+            }
+            (0..num_records)
+                .map(|index| 8 + index * record_size)
+                .find(|offset| hdmx.read_at::<u8>(*offset).ok() == Some(ppem))
+                .and_then(|offset| hdmx.read_at::<u8>(offset + 2 + usize::from(glyph_id)).ok())
+        });
+        *out = advance.unwrap_or_default();
+        advance.is_some()
+    })
+}
+
+// Bits of the gasp range behavior, as in the OpenType specification.
+const GASP_GRIDFIT: u16 = 0x0001;
+const GASP_DOGRAY: u16 = 0x0002;
+const GASP_SYMMETRIC_GRIDFIT: u16 = 0x0004;
+const GASP_SYMMETRIC_SMOOTHING: u16 = 0x0008;
+
+/// Behavior for sizes not covered by a gasp table: grid-fitted and
+/// antialiased, as rasterizers do when the font expresses no preference.
+const GASP_DEFAULT_BEHAVIOR: u16 = GASP_GRIDFIT | GASP_DOGRAY;
+
+fn gasp_behavior(font_ref: &BridgeFontRef, ppem: u16) -> u16 {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let gasp = f.data_for_tag(Tag::new(b"gasp"))?;
+                let version = gasp.read_at::<u16>(0).ok()?;
+                let num_ranges = usize::from(gasp.read_at::<u16>(2).ok()?);
+                // The symmetric bits were added in version 1.
+                let defined_bits = match version {
+                    0 => GASP_GRIDFIT | GASP_DOGRAY,
+                    _ => {
+                        GASP_GRIDFIT
+                            | GASP_DOGRAY
+                            | GASP_SYMMETRIC_GRIDFIT
+                            | GASP_SYMMETRIC_SMOOTHING
+                    }
+                };
+                // Ranges are rangeMaxPPEM and rangeGaspBehavior, sorted by
+                // rangeMaxPPEM.
+                (0..num_ranges)
+                    .map(|index| 4 + 4 * index)
+                    .map_while(|offset| Some((offset, gasp.read_at::<u16>(offset).ok()?)))
+                    .find(|(_, range_max_ppem)| *range_max_ppem >= ppem)
+                    .and_then(|(offset, _)| gasp.read_at::<u16>(offset + 2).ok())
+                    .map(|behavior| behavior & defined_bits)
+            })
+            .unwrap_or(GASP_DEFAULT_BEHAVIOR)
+    })
+}
+
+fn linear_and_hinted_advances(
+    font_ref: &BridgeFontRef,
+    outlines: &BridgeOutlineCollection,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    hinting: HintingLevel,
+) -> BridgeAdvances {
+    ffi_guard(|| {
+        let advance = advance_width_or_zero(font_ref, size, coords, glyph_id, SizeRounding::Exact);
+        let linear = BridgeAdvances {
+            linear: advance,
+            hinted: advance,
+        };
+        if hinting != HintingLevel::Full || advance == 0.0 {
+            return linear;
+        }
+        let Some(size) = checked_size(size) else {
+            return BridgeAdvances::default();
+        };
+        let Some(instance) = outlines.hinting_instance(size, coords, hinting) else {
+            return linear;
+        };
+        let glyph_id = GlyphId::new(glyph_id);
+        // Hinted advances are rounded to whole pixels.
+        let hinted_advance = outlines
+            .outlines
+            .as_ref()
+            .filter(|_| outlines.within_limits(glyph_id, coords, &BridgeOutlineLimits::default()))
+            .and_then(|collection| collection.get(glyph_id))
+            .and_then(|glyph| {
+                glyph
+                    .draw(
+                        DrawSettings::embedded_hinting(&instance),
+                        &mut EmptyOutlinePen::default(),
+                    )
+                    .ok()?
+                    .advance_width
+            });
+        BridgeAdvances {
+            linear: advance,
+            hinted: hinted_advance.unwrap_or(advance).round(),
+        }
+    })
+}
+
+fn emboldened_advance_width_or_zero(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    embolden_strength: f32,
+) -> f32 {
+    ffi_guard(|| {
+        let advance = advance_width_or_zero(font_ref, size, coords, glyph_id, SizeRounding::Exact);
+        if advance != 0.0 && embolden_strength.is_finite() {
+            advance + embolden_strength
+        } else {
+            advance
+        }
+    })
+}
+
+fn units_per_em_or_zero(font_ref: &BridgeFontRef) -> u16 {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| Some(f.head().ok()?.units_per_em()))
+            .unwrap_or_default()
+    })
+}
+
+/// Returns the raw macStyle and flags of the head table of `font`.
+fn head_style_and_flags_in(font: &FontRef) -> Option<(u16, u16)> {
+    let head = font.head().ok()?;
+    Some((head.mac_style().bits(), head.flags()))
+}
+
+fn head_style_and_flags(
+    font_ref: &BridgeFontRef,
+    out_mac_style: &mut u16,
+    out_flags: &mut u16,
+) -> bool {
+    ffi_guard(|| {
+        let style_and_flags = font_ref.with_font(head_style_and_flags_in);
+        (*out_mac_style, *out_flags) = style_and_flags.unwrap_or_default();
+        style_and_flags.is_some()
+    })
+}
+
+fn lowest_rec_ppem_or_zero(font_ref: &BridgeFontRef) -> u16 {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| Some(f.head().ok()?.lowest_rec_ppem()))
+            .unwrap_or_default()
+    })
+}
+
+fn convert_metrics(
+    skrifa_metrics: &Metrics,
+    line_metrics_source: LineMetricsSource,
+) -> ffi::Metrics {
+    ffi::Metrics {
+        top: skrifa_metrics.bounds.map_or_else(|| 0.0, |b| b.y_max),
+        bottom: skrifa_metrics.bounds.map_or_else(|| 0.0, |b| b.y_min),
+        x_min: skrifa_metrics.bounds.map_or_else(|| 0.0, |b| b.x_min),
+        x_max: skrifa_metrics.bounds.map_or_else(|| 0.0, |b| b.x_max),
+        ascent: skrifa_metrics.ascent,
+        descent: skrifa_metrics.descent,
+        leading: skrifa_metrics.leading,
+        avg_char_width: skrifa_metrics.average_width.unwrap_or(0.0),
+        max_char_width: skrifa_metrics.max_width.unwrap_or(0.0),
+        x_height: skrifa_metrics.x_height.unwrap_or(0.0),
+        cap_height: skrifa_metrics.cap_height.unwrap_or(0.0),
+        x_height_is_synthesized: false,
+        cap_height_is_synthesized: false,
+        ideographic_bottom: 0.0,
+        ideographic_bottom_is_synthesized: false,
+        underline_position: skrifa_metrics.underline.map_or(0.0, |u| u.offset),
+        underline_thickness: skrifa_metrics.underline.map_or(0.0, |u| u.thickness),
+        underline_position_is_valid: skrifa_metrics.underline.is_some(),
+        underline_thickness_is_valid: skrifa_metrics.underline.is_some_and(|u| u.thickness > 0.0),
+        strikeout_position: skrifa_metrics.strikeout.map_or(0.0, |s| s.offset),
+        strikeout_thickness: skrifa_metrics.strikeout.map_or(0.0, |s| s.thickness),
+        strikeout_position_is_valid: skrifa_metrics.strikeout.is_some(),
+        strikeout_thickness_is_valid: skrifa_metrics.strikeout.is_some_and(|s| s.thickness > 0.0),
+        line_metrics_source,
+        bounds_is_valid: skrifa_metrics.bounds.is_some(),
+    }
+}
+
+/// Returns the table skrifa's `Metrics` takes the line metrics from, which
+/// follows FreeType: OS/2 typo metrics if USE_TYPO_METRICS is set, otherwise
+/// hhea, unless both its ascent and descent are zero, in which case the typo
+/// metrics, or if those are zero as well, the win metrics. Returns
+/// `UnitsPerEm` if the chosen metrics have neither ascent nor descent.
+fn line_metrics_source(font: &FontRef) -> LineMetricsSource {
+    let os2 = font.os2().ok();
+    let typo = os2
+        .as_ref()
+        .map(|os2| (os2.s_typo_ascender(), os2.s_typo_descender()));
+    if let Some(os2) = &os2 {
+        if os2
+            .fs_selection()
+            .contains(SelectionFlags::USE_TYPO_METRICS)
+        {
+            return match typo {
+                Some((0, 0)) => LineMetricsSource::UnitsPerEm,
+                _ => LineMetricsSource::Typo,
+            };
+        }
+    }
+    if let Ok(hhea) = font.hhea() {
+        if hhea.ascender().to_i16() != 0 || hhea.descender().to_i16() != 0 {
+            return LineMetricsSource::Hhea;
+        }
+    }
+    match (&os2, typo) {
+        (_, Some((ascent, descent))) if ascent != 0 || descent != 0 => LineMetricsSource::Typo,
+        (Some(os2), _) if os2.us_win_ascent() != 0 || os2.us_win_descent() != 0 => {
+            LineMetricsSource::Win
+        }
+        _ => LineMetricsSource::UnitsPerEm,
+    }
+}
+
+/// Returns the top of the outline of the glyph mapped to `codepoint` at
+/// `size` and `coords`, or None if the glyph is unmapped, has no outline or
+/// draws nothing.
+fn outline_top(
+    font: &FontRef,
+    codepoint: char,
+    size: Size,
+    coords: &BridgeNormalizedCoords,
+) -> Option<f32> {
+    let glyph_id = NominalCharmap::new(font).map(codepoint as u32)?;
+    let glyph = font.outline_glyphs().get(glyph_id)?;
+    let mut pen = BoundsPen {
+        pen: &mut EmptyOutlinePen::default(),
+        bounds: BridgePathBounds::default(),
+    };
+    glyph
+        .draw(
+            DrawSettings::unhinted(size, &coords.normalized_coords),
+            &mut pen,
+        )
+        .ok()?;
+    (!pen.bounds.is_empty).then_some(pen.bounds.y_max)
+}
+
+/// Proportions of the em the line metrics are synthesized from for
+/// `LineMetricsSource::UnitsPerEm`, as in HarfBuzz's metrics fallback.
+const SYNTHESIZED_ASCENT_PER_EM: f32 = 0.8;
+const SYNTHESIZED_DESCENT_PER_EM: f32 = -0.2;
+
+/// Finds the VDMX ascent and descent for `ppem` in `vdmx`, the raw table
+/// data. Uses the group of the first ratio record covering a 1:1 aspect ratio,
+/// or failing that the first record matching all ratios, and within the group
+/// the entry for exactly `ppem`.
+fn find_vdmx_values(vdmx: FontData, ppem: u16) -> Option<BridgeVdmxValues> {
+    let num_ratios = usize::from(vdmx.read_at::<u16>(4).ok()?);
+    let ratio_at = |index: usize| -> Option<[u8; 4]> {
+        let offset = 6 + 4 * index;
+        Some([
+            vdmx.read_at::<u8>(offset).ok()?,
+            vdmx.read_at::<u8>(offset + 1).ok()?,
+            vdmx.read_at::<u8>(offset + 2).ok()?,
+            vdmx.read_at::<u8>(offset + 3).ok()?,
+        ])
+    };
+    let mut ratios = (0..num_ratios).filter_map(|index| Some((index, ratio_at(index)?)));
+    // Ratio records are bCharSet, xRatio, yStartRatio and yEndRatio.
+    let square = ratios
+        .clone()
+        .find(|(_, [_, x, y_start, y_end])| *x == 1 && *y_start <= 1 && *y_end >= 1);
+    let (ratio_index, _) = square.or_else(|| {
+        ratios.find(|(_, [_, x, y_start, y_end])| *x == 0 && *y_start == 0 && *y_end == 0)
+    })?;
+    let group_offset = usize::from(
+        vdmx.read_at::<u16>(6 + 4 * num_ratios + 2 * ratio_index)
+            .ok()?,
+    );
+    let num_entries = usize::from(vdmx.read_at::<u16>(group_offset).ok()?);
+    let start_size = vdmx.read_at::<u8>(group_offset + 2).ok()?;
+    let end_size = vdmx.read_at::<u8>(group_offset + 3).ok()?;
+    if ppem < u16::from(start_size) || ppem > u16::from(end_size) {
+        return None;
+    }
+    // Entries are yPelHeight, yMax and yMin, sorted by yPelHeight.
+    (0..num_entries)
+        .map(|index| group_offset + 4 + 6 * index)
+        .map_while(|offset| Some((offset, vdmx.read_at::<u16>(offset).ok()?)))
+        .take_while(|(_, pel_height)| *pel_height <= ppem)
+        .find(|(_, pel_height)| *pel_height == ppem)
+        .and_then(|(offset, _)| {
+            Some(BridgeVdmxValues {
+                y_max: vdmx.read_at::<i16>(offset + 2).ok()?,
+                y_min: vdmx.read_at::<i16>(offset + 4).ok()?,
+            })
+        })
+}
+
+fn vdmx_metrics(font_ref: &BridgeFontRef, ppem: u16, out: &mut BridgeVdmxValues) -> bool {
+    ffi_guard(|| {
+        let values =
+            font_ref.with_font(|f| find_vdmx_values(f.data_for_tag(Tag::new(b"VDMX"))?, ppem));
+        *out = values.unwrap_or_default();
+        values.is_some()
+    })
+}
+
+fn get_skia_metrics(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    hinting: HintingLevel,
+    use_vdmx: bool,
+    rounding: SizeRounding,
+) -> ffi::Metrics {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let rounded_size = RoundedSize::new(f, size, rounding)?;
+                let size = rounded_size.skrifa_size();
+                let fontations_metrics = Metrics::new(f, size, coords.normalized_coords.coords());
+                let source = line_metrics_source(f);
+                let mut metrics = convert_metrics(&fontations_metrics, source);
+                if source == LineMetricsSource::Win {
+                    // skrifa applies the hhea and typo metric deltas, the win
+                    // metrics have their own. The win descent points down.
+                    let scale = size.linear_scale(fontations_metrics.units_per_em);
+                    metrics.ascent +=
+                        (mvar_delta(f, b"hcla", coords) - mvar_delta(f, b"hasc", coords)) * scale;
+                    metrics.descent -=
+                        (mvar_delta(f, b"hcld", coords) + mvar_delta(f, b"hdsc", coords)) * scale;
+                }
+                if source == LineMetricsSource::UnitsPerEm {
+                    let units_per_em = fontations_metrics.units_per_em;
+                    let em = f32::from(units_per_em) * size.linear_scale(units_per_em);
+                    metrics.ascent = em * SYNTHESIZED_ASCENT_PER_EM;
+                    metrics.descent = em * SYNTHESIZED_DESCENT_PER_EM;
+                    metrics.leading = 0.0;
+                }
+                // Fonts with OS/2 versions before 2 have no x-height and cap
+                // height.
+                if metrics.x_height == 0.0 {
+                    if let Some(top) = outline_top(f, 'x', size, coords) {
+                        metrics.x_height = top;
+                        metrics.x_height_is_synthesized = true;
+                    }
+                }
+                if metrics.cap_height == 0.0 {
+                    if let Some(top) = outline_top(f, 'H', size, coords) {
+                        metrics.cap_height = top;
+                        metrics.cap_height_is_synthesized = true;
+                    }
+                }
+                match ideographic_baseline(f) {
+                    Some(baseline) => {
+                        let units_per_em = fontations_metrics.units_per_em;
+                        metrics.ideographic_bottom =
+                            f32::from(baseline) * size.linear_scale(units_per_em);
+                    }
+                    None => {
+                        metrics.ideographic_bottom = metrics.descent;
+                        metrics.ideographic_bottom_is_synthesized = true;
+                    }
+                }
+                rounded_size.scale_metrics(&mut metrics);
+                // VDMX describes the extent of hinted outlines at whole
+                // pixel sizes.
+                let ppem = rounded_size
+                    .size
+                    .ppem()
+                    .filter(|ppem| ppem.fract() == 0.0 && *ppem <= f32::from(u16::MAX));
+                if let Some(ppem) = ppem.filter(|_| use_vdmx && hinting != HintingLevel::None) {
+                    let vdmx = f.data_for_tag(Tag::new(b"VDMX"));
+                    if let Some(values) = vdmx.and_then(|vdmx| find_vdmx_values(vdmx, ppem as u16))
+                    {
+                        metrics.ascent = f32::from(values.y_max);
+                        metrics.descent = f32::from(values.y_min);
+                    }
+                }
+                Some(metrics)
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Scripts whose BASE records are preferred for the ideographic baseline,
+/// most preferred first. Other scripts are used in table order after these.
+const IDEOGRAPHIC_BASELINE_SCRIPTS: [&[u8; 4]; 4] = [b"hani", b"kana", b"hang", b"DFLT"];
+
+/// Returns the ideographic em-box bottom ('ideo') of the horizontal BASE
+/// axis in font units, from the first script in
+/// `IDEOGRAPHIC_BASELINE_SCRIPTS` order that has a value for it. Variation
+/// deltas of format 3 coordinates are not applied, to keep this cheap enough
+/// for every metrics request.
+fn ideographic_baseline(font: &FontRef) -> Option<i16> {
+    let axis = font.expect_table::<Base>().ok()?.horiz_axis()?.ok()?;
+    let ideo = Tag::new(b"ideo");
+    let tag_index = axis
+        .base_tag_list()?
+        .ok()?
+        .baseline_tags()
+        .iter()
+        .position(|tag| tag.get() == ideo)?;
+    let script_list = axis.base_script_list().ok()?;
+    let records = script_list.base_script_records();
+    let preference = |tag: Tag| {
+        IDEOGRAPHIC_BASELINE_SCRIPTS
+            .iter()
+            .position(|preferred| Tag::new(preferred) == tag)
+            .unwrap_or(IDEOGRAPHIC_BASELINE_SCRIPTS.len())
+    };
+    let mut order: Vec<usize> = (0..records.len()).collect();
+    order.sort_by_key(|index| preference(records[*index].base_script_tag()));
+    order.into_iter().find_map(|index| {
+        let script = records[index].base_script(script_list.offset_data()).ok()?;
+        let coord = script
+            .base_values()?
+            .ok()?
+            .base_coords()
+            .get(tag_index)
+            .ok()?;
+        Some(coord.coordinate())
+    })
+}
+
+/// Returns the MVAR delta for the metric `tag` at `coords` in font units, 0.0
+/// if the font has no MVAR table or no record for `tag`.
+fn mvar_delta(font: &FontRef, tag: &[u8; 4], coords: &BridgeNormalizedCoords) -> f32 {
+    font.mvar()
+        .ok()
+        .and_then(|mvar| {
+            mvar.metric_delta(Tag::new(tag), coords.normalized_coords.coords())
+                .ok()
+        })
+        .map(|delta| delta.to_f32())
+        .unwrap_or_default()
+}
+
+fn caret_info(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    out: &mut BridgeCaretInfo,
+) -> bool {
+    ffi_guard(|| {
+        *out = BridgeCaretInfo::default();
+        font_ref
+            .with_font(|f| {
+                let size = checked_size(size)?;
+                let hhea = f.hhea().ok()?;
+                let units_per_em = f.head().map(|head| head.units_per_em()).unwrap_or_default();
+                let scale = size.linear_scale(units_per_em);
+                let scaled =
+                    |value: i16, tag| (f32::from(value) + mvar_delta(f, tag, coords)) * scale;
+                *out = BridgeCaretInfo {
+                    slope_rise: scaled(hhea.caret_slope_rise(), b"hcrs"),
+                    slope_run: scaled(hhea.caret_slope_run(), b"hcrn"),
+                    offset: scaled(hhea.caret_offset(), b"hcof"),
+                };
+                Some(true)
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn has_vertical_metrics(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let num_long_metrics = usize::from(f.vhea().ok()?.number_of_long_ver_metrics());
+                let num_glyphs = usize::from(f.maxp().ok()?.num_glyphs());
+                let vmtx = f.data_for_tag(Tag::new(b"vmtx"))?;
+                // Long metrics are followed by a top side bearing for each
+                // remaining glyph.
+                let expected_len = num_long_metrics
+                    .checked_mul(4)?
+                    .checked_add(num_glyphs.checked_sub(num_long_metrics)? * 2)?;
+                Some(num_long_metrics > 0 && vmtx.len() >= expected_len)
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn vertical_line_metrics(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    out: &mut BridgeVerticalLineMetrics,
+) -> bool {
+    ffi_guard(|| {
+        *out = BridgeVerticalLineMetrics::default();
+        font_ref
+            .with_font(|f| {
+                let size = checked_size(size)?;
+                let vhea = f.vhea().ok()?;
+                let units_per_em = f.head().map(|head| head.units_per_em()).unwrap_or_default();
+                let scale = size.linear_scale(units_per_em);
+                let scaled =
+                    |value: i16, tag| (f32::from(value) + mvar_delta(f, tag, coords)) * scale;
+                *out = BridgeVerticalLineMetrics {
+                    ascender: scaled(vhea.ascender().to_i16(), b"vasc"),
+                    descender: scaled(vhea.descender().to_i16(), b"vdsc"),
+                    line_gap: scaled(vhea.line_gap().to_i16(), b"vlgp"),
+                };
+                Some(true)
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn get_superscript_subscript_metrics(
+    font_ref: &BridgeFontRef,
+    size: f32,
+    coords: &BridgeNormalizedCoords,
+    out: &mut BridgeScriptMetrics,
+) -> bool {
+    ffi_guard(|| {
+        *out = BridgeScriptMetrics::default();
+        font_ref
+            .with_font(|f| {
+                let size = checked_size(size)?;
+                let os2 = f.os2().ok()?;
+                let units_per_em = f.head().map(|head| head.units_per_em()).unwrap_or_default();
+                let scale = size.linear_scale(units_per_em);
+                let scaled =
+                    |value: i16, tag| (f32::from(value) + mvar_delta(f, tag, coords)) * scale;
+                *out = BridgeScriptMetrics {
+                    subscript_x_size: scaled(os2.y_subscript_x_size(), b"sbxs"),
+                    subscript_y_size: scaled(os2.y_subscript_y_size(), b"sbys"),
+                    subscript_x_offset: scaled(os2.y_subscript_x_offset(), b"sbxo"),
+                    subscript_y_offset: scaled(os2.y_subscript_y_offset(), b"sbyo"),
+                    superscript_x_size: scaled(os2.y_superscript_x_size(), b"spxs"),
+                    superscript_y_size: scaled(os2.y_superscript_y_size(), b"spys"),
+                    superscript_x_offset: scaled(os2.y_superscript_x_offset(), b"spxo"),
+                    superscript_y_offset: scaled(os2.y_superscript_y_offset(), b"spyo"),
+                };
+                Some(true)
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn get_localized_strings<'a>(
+    font_ref: &'a BridgeFontRef<'a>,
+    name_id: u16,
+) -> Box<BridgeLocalizedStrings<'a>> {
+    ffi_guard(|| {
+        let mut bridge_localized_strings = Box::<BridgeLocalizedStrings>::default();
+        reset_localized_strings(font_ref, &mut bridge_localized_strings, name_id);
+        bridge_localized_strings
+    })
+}
+
+fn reset_localized_strings<'a>(
+    font_ref: &'a BridgeFontRef<'a>,
+    bridge_localized_strings: &mut BridgeLocalizedStrings<'a>,
+    name_id: u16,
+) {
+    ffi_guard(|| {
+        let name_id = StringId::new(name_id);
+        let mut names: Vec<_> = font_ref
+            .with_font(|f| Some(localized_strings_with_records(f, name_id).collect()))
+            .unwrap_or_default();
+        // Stable, so that records of the same rank stay in table order.
+        names.sort_by_key(|(localized_string, record, _)| {
+            name_preference(language_rank(localized_string), record)
+        });
+        bridge_localized_strings.name_id = name_id;
+        bridge_localized_strings.names = names;
+        restart_localized_strings(bridge_localized_strings);
+    })
+}
+
+fn restart_localized_strings(bridge_localized_strings: &mut BridgeLocalizedStrings) {
+    ffi_guard(|| {
+        bridge_localized_strings.next = 0;
+        bridge_localized_strings.seen.clear();
+    })
+}
+
+fn localized_strings_count(bridge_localized_strings: &BridgeLocalizedStrings) -> usize {
+    ffi_guard(|| {
+        let (mut next, mut seen) = (0, Vec::new());
+        std::iter::from_fn(|| {
+            next_unique_localized_name(&bridge_localized_strings.names, &mut next, &mut seen)
+        })
+        .count()
+    })
+}
+
+/// Returns the first of `names` from `next` on whose string and language are
+/// not in `seen` yet, adds them and moves `next` past it. Fonts repeat
+/// strings for several platforms and encodings.
+fn next_unique_localized_name<'a, 'b>(
+    names: &'b [(LocalizedString<'a>, &'a NameRecord, String)],
+    next: &mut usize,
+    seen: &mut Vec<(String, String)>,
+) -> Option<&'b (LocalizedString<'a>, &'a NameRecord, String)> {
+    loop {
+        let name = names.get(*next)?;
+        *next += 1;
+        let (localized_string, _, string) = name;
+        let string_and_language = (
+            string.clone(),
+            localized_string
+                .language()
+                .map(canonical_language_tag)
+                .unwrap_or_default(),
+        );
+        if !seen.contains(&string_and_language) {
+            seen.push(string_and_language);
+            return Some(name);
+        }
+    }
+}
+
+/// Returns the next record of `name_id` in `records`. Going through the name
+/// records like `LocalizedStrings` does pairs its strings with their records.
+fn next_name_record<'a>(
+    records: &mut std::slice::Iter<'a, NameRecord>,
+    name_id: StringId,
+) -> Option<&'a NameRecord> {
+    records.find(|record| record.name_id() == name_id)
+}
+
+/// Ranks the platform and encoding of a name record, best first: Windows
+/// Unicode, the Unicode platform, then Macintosh, whose strings are more often
+/// transliterated or corrupt, and then others such as Windows symbol.
+fn name_platform_rank(record: &NameRecord) -> u8 {
+    match (record.platform_id(), record.encoding_id()) {
+        (3, 1 | 10) => 0,
+        (0, _) => 1,
+        (1, _) => 2,
+        _ => 3,
+    }
+}
+
+/// Ranks a language the way `LocalizedStrings::english_or_first` does: 3 for
+/// "en-US", 2 for "en", 1 for no language and 0 for others.
+fn language_rank(localized_string: &LocalizedString) -> u8 {
+    match localized_string.language() {
+        Some(language) if language.eq_ignore_ascii_case("en-US") => 3,
+        Some(language) if language.eq_ignore_ascii_case("en") => 2,
+        Some(_) => 0,
+        None => 1,
+    }
+}
+
+/// Orders name records by preference, smallest first, given the
+/// `language_rank` of their string: by `name_platform_rank`, then within a
+/// platform "en-US" before "en" before no language before others.
+fn name_preference(language_rank: u8, record: &NameRecord) -> (u8, u8) {
+    (name_platform_rank(record), 3 - language_rank)
+}
+
+/// Returns the string of `name_id` that `name_preference` prefers, the first
+/// one of those ranked the same, with its record.
+fn preferred_name<'a>(
+    font: &FontRef<'a>,
+    name_id: StringId,
+) -> Option<(LocalizedString<'a>, &'a NameRecord, String)> {
+    localized_strings_with_records(font, name_id).min_by_key(|(localized_string, record, _)| {
+        name_preference(language_rank(localized_string), record)
+    })
+}
+
+/// Returns the strings of `name_id` with their name records and their text
+/// as decoded by `decode_name_string`.
+fn localized_strings_with_records<'a>(
+    font: &FontRef<'a>,
+    name_id: StringId,
+) -> impl Iterator<Item = (LocalizedString<'a>, &'a NameRecord, String)> {
+    let name = font.name().ok();
+    let string_data = name
+        .as_ref()
+        .map(|name| name.string_data())
+        .unwrap_or_default();
+    let mut records = name
+        .map(|name| name.name_record().iter())
+        .unwrap_or_default();
+    font.localized_strings(name_id)
+        .map_while(move |localized_string| {
+            let record = next_name_record(&mut records, name_id)?;
+            let string = decode_name_string(string_data, record).unwrap_or_default();
+            Some((localized_string, record, string))
+        })
+}
+
+/// Decodes the string of a name `record` from `string_data`, the storage area
+/// of its name table, or returns `None` if it is out of bounds. Unicode
+/// strings are UTF-16BE and Macintosh Roman ones use `MAC_ROMAN_TO_UNICODE`.
+/// Other encodings, such as the Macintosh CJK ones, keep their ASCII
+/// characters and decode anything else to U+FFFD instead of dropping the
+/// string.
+fn decode_name_string(string_data: FontData, record: &NameRecord) -> Option<String> {
+    const REPLACEMENT: char = char::REPLACEMENT_CHARACTER;
+    let start = record.string_offset().to_u32() as usize;
+    let bytes = string_data
+        .slice(start..start + record.length() as usize)?
+        .as_bytes();
+    let ascii_or_replacement = |unit: u16| match u8::try_from(unit) {
+        Ok(byte) if byte.is_ascii() => byte as char,
+        _ => REPLACEMENT,
+    };
+    let string = match (record.platform_id(), record.encoding_id()) {
+        // Unicode, ISO 10646 and Windows Unicode.
+        (0, _) | (2, 1) | (3, 0 | 1 | 10) => {
+            let units = bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_be_bytes([unit[0], unit[1]]));
+            let mut string: String = char::decode_utf16(units)
+                .map(|c| c.unwrap_or(REPLACEMENT))
+                .collect();
+            if bytes.len() % 2 != 0 {
+                string.push(REPLACEMENT);
+            }
+            string
+        }
+        // Macintosh Roman.
+        (1, 0) => bytes
+            .iter()
+            .map(|&byte| match byte.checked_sub(0x80) {
+                Some(index) => char::from_u32(MAC_ROMAN_TO_UNICODE[index as usize].into())
+                    .unwrap_or(REPLACEMENT),
+                None => byte as char,
+            })
+            .collect(),
+        // Macintosh Japanese, Traditional Chinese, Korean and Simplified
+        // Chinese, whose non-ASCII characters take two bytes except for the
+        // Japanese half-width katakana.
+        (1, encoding_id @ (1 | 2 | 3 | 25)) => {
+            let mut string = String::with_capacity(bytes.len());
+            let mut bytes = bytes.iter();
+            while let Some(&byte) = bytes.next() {
+                if byte.is_ascii() {
+                    string.push(byte as char);
+                    continue;
+                }
+                let is_single_byte = encoding_id == 1 && (0xA0..=0xDF).contains(&byte);
+                if !is_single_byte {
+                    bytes.next();
+                }
+                string.push(REPLACEMENT);
+            }
+            string
+        }
+        // ISO 8859-1, whose bytes are the first 256 Unicode codepoints.
+        (2, 2) => bytes.iter().map(|&byte| byte as char).collect(),
+        // The other Windows encodings store 16 bit code units.
+        (3, _) => bytes
+            .chunks(2)
+            .map(|unit| match unit {
+                [high, low] => ascii_or_replacement(u16::from_be_bytes([*high, *low])),
+                _ => REPLACEMENT,
+            })
+            .collect(),
+        _ => bytes
+            .iter()
+            .map(|&byte| ascii_or_replacement(byte.into()))
+            .collect(),
+    };
+    Some(string)
+}
+
+/// Brings a BCP 47 language tag into its canonical case: lowercase language
+/// and other subtags, titlecase four letter script subtags and uppercase
+/// region subtags, e.g. "zh-Hant-TW" for "ZH-HANT-tw". Name tables of version
+/// 1 may give tags in any case.
+fn canonical_language_tag(language: &str) -> String {
+    let mut canonical = String::with_capacity(language.len());
+    for (i, subtag) in language.split(['-', '_']).enumerate() {
+        if i > 0 {
+            canonical.push('-');
+            let is_script = subtag.len() == 4 && subtag.bytes().all(|b| b.is_ascii_alphabetic());
+            let is_region = subtag.len() == 2 && subtag.bytes().all(|b| b.is_ascii_alphabetic());
+            if is_script {
+                canonical.push_str(&subtag[..1].to_ascii_uppercase());
+                canonical.push_str(&subtag[1..].to_ascii_lowercase());
+                continue;
+            }
+            if is_region {
+                canonical.push_str(&subtag.to_ascii_uppercase());
+                continue;
+            }
+        }
+        canonical.push_str(&subtag.to_ascii_lowercase());
+    }
+    canonical
+}
+
+/// Fills `out_localized_name` with the decoded `string` of `localized_string`
+/// and the raw IDs of its `record`.
+fn fill_localized_name(
+    out_localized_name: &mut BridgeLocalizedName,
+    localized_string: &LocalizedString,
+    record: &NameRecord,
+    string: &str,
+) {
+    out_localized_name.string = string.to_string();
+    out_localized_name.language = localized_string
+        .language()
+        .map(canonical_language_tag)
+        .unwrap_or_default();
+    out_localized_name.platform_id = record.platform_id();
+    out_localized_name.language_id = record.language_id();
+}
+
+use crate::ffi::{BridgeFontNames, BridgeLocalizedName};
+
+fn localized_name_next(
+    bridge_localized_strings: &mut BridgeLocalizedStrings,
+    out_localized_name: &mut BridgeLocalizedName,
+) -> bool {
+    ffi_guard(|| {
+        let name_id = bridge_localized_strings.name_id;
+        let name = next_unique_localized_name(
+            &bridge_localized_strings.names,
+            &mut bridge_localized_strings.next,
+            &mut bridge_localized_strings.seen,
+        );
+        match name {
+            Some((localized_string, record, string)) => {
+                fill_localized_name(out_localized_name, localized_string, record, string);
+                // TODO(b/307906051): Remove the suffix before shipping.
+                if name_id == StringId::FAMILY_NAME {
+                    out_localized_name.string.push_str(" (Fontations)");
+                }
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+fn get_localized_strings_vec(font_ref: &BridgeFontRef, name_id: u16) -> Vec<BridgeLocalizedName> {
+    ffi_guard(|| {
+        let mut bridge_localized_strings = BridgeLocalizedStrings::default();
+        reset_localized_strings(font_ref, &mut bridge_localized_strings, name_id);
+        let mut names = Vec::new();
+        let mut name = BridgeLocalizedName::default();
+        while localized_name_next(&mut bridge_localized_strings, &mut name) {
+            names.push(std::mem::take(&mut name));
+        }
+        names
+    })
+}
+
+/// Returns the name `name_id` in the language of `language_preference`, a
+/// BCP-47 tag matched exactly or else by its primary language subtag, and
+/// otherwise the `preferred_name`, with its name record. Of several strings in
+/// the language, the one of the best `name_platform_rank` is taken.
+fn preferred_language_name<'a>(
+    font: &FontRef<'a>,
+    name_id: StringId,
+    language_preference: &str,
+) -> Option<(LocalizedString<'a>, &'a NameRecord, String)> {
+    fn primary_subtag(language: &str) -> &str {
+        language.split('-').next().unwrap_or_default()
+    }
+    let best_match = |matches: &dyn Fn(&str) -> bool| {
+        localized_strings_with_records(font, name_id)
+            .filter(|(localized_string, ..)| localized_string.language().is_some_and(matches))
+            .min_by_key(|(_, record, _)| name_platform_rank(record))
+    };
+    best_match(&|language| language.eq_ignore_ascii_case(language_preference))
+        .or_else(|| {
+            best_match(&|language| {
+                primary_subtag(language).eq_ignore_ascii_case(primary_subtag(language_preference))
+            })
+        })
+        .or_else(|| preferred_name(font, name_id))
+}
+
+fn axis_name(
+    font_ref: &BridgeFontRef,
+    axis_index: usize,
+    language_preference: &str,
+    out_localized_name: &mut BridgeLocalizedName,
+) -> bool {
+    ffi_guard(|| {
+        let name = font_ref.with_font(|f| {
+            let name_id = f.axes().get(axis_index)?.name_id();
+            preferred_language_name(f, name_id, language_preference)
+        });
+        match name {
+            Some((localized_string, record, string)) => {
+                fill_localized_name(out_localized_name, &localized_string, record, &string);
+                true
+            }
+            None => {
+                *out_localized_name = BridgeLocalizedName::default();
+                false
+            }
+        }
+    })
+}
+
+use crate::ffi::BridgeAxisValueName;
+
+fn get_axis_value_names(
+    font_ref: &BridgeFontRef,
+    language_preference: &str,
+) -> Box<BridgeAxisValueNames> {
+    ffi_guard(|| {
+        let names = font_ref
+            .with_font(|f| {
+                let stat = f.stat().ok()?;
+                let design_axes = stat.design_axes().ok()?;
+                let axis_values = stat.offset_to_axis_values()?.ok()?;
+                let names: Vec<BridgeAxisValueName> = axis_values
+                    .axis_values()
+                    .iter()
+                    .filter_map(Result::ok)
+                    .map(|axis_value| {
+                        let (axis, value) = single_axis_value(&axis_value)
+                            .and_then(|(axis_index, value)| {
+                                let tag = design_axes.get(axis_index)?.axis_tag();
+                                Some((u32::from_be_bytes(tag.into_bytes()), value.to_f32()))
+                            })
+                            .unwrap_or_default();
+                        let name = preferred_language_name(
+                            f,
+                            axis_value.value_name_id(),
+                            language_preference,
+                        );
+                        BridgeAxisValueName {
+                            axis,
+                            value,
+                            is_elidable: axis_value
+                                .flags()
+                                .contains(AxisValueTableFlags::ELIDABLE_AXIS_VALUE_NAME),
+                            string: name
+                                .as_ref()
+                                .map(|(_, _, string)| string.clone())
+                                .unwrap_or_default(),
+                            language: name
+                                .as_ref()
+                                .and_then(|(localized_string, ..)| localized_string.language())
+                                .map(canonical_language_tag)
+                                .unwrap_or_default(),
+                        }
+                    })
+                    .collect();
+                Some(names)
+            })
+            .unwrap_or_default();
+        Box::new(BridgeAxisValueNames {
+            names: names.into_iter(),
+        })
+    })
+}
+
+fn axis_value_name_next(
+    bridge_axis_value_names: &mut BridgeAxisValueNames,
+    out_axis_value_name: &mut BridgeAxisValueName,
+) -> bool {
+    ffi_guard(|| match bridge_axis_value_names.names.next() {
+        Some(axis_value_name) => {
+            *out_axis_value_name = axis_value_name;
+            true
+        }
+        _ => false,
+    })
+}
+
+/// Returns the `preferred_name` of `name_id`: from Windows Unicode records
+/// over Unicode platform records over Macintosh records, and within those in
+/// English or else the first language.
+fn english_or_first_font_name(font_ref: &BridgeFontRef, name_id: StringId) -> Option<String> {
+    font_ref.with_font(|f| preferred_name(f, name_id).map(|(_, _, string)| string))
+}
+
+fn family_name(font_ref: &BridgeFontRef) -> String {
+    ffi_guard(|| english_or_first_font_name(font_ref, StringId::FAMILY_NAME).unwrap_or_default())
+}
+
+fn typographic_family_name(font_ref: &BridgeFontRef) -> String {
+    ffi_guard(|| {
+        english_or_first_font_name(font_ref, StringId::TYPOGRAPHIC_FAMILY_NAME)
+            .or_else(|| english_or_first_font_name(font_ref, StringId::FAMILY_NAME))
+            .unwrap_or_default()
+    })
+}
+
+fn typographic_subfamily_name(font_ref: &BridgeFontRef) -> String {
+    ffi_guard(|| {
+        english_or_first_font_name(font_ref, StringId::TYPOGRAPHIC_SUBFAMILY_NAME)
+            .or_else(|| english_or_first_font_name(font_ref, StringId::SUBFAMILY_NAME))
+            .unwrap_or_default()
+    })
+}
+
+fn wws_family_name(font_ref: &BridgeFontRef, out_string: &mut String) -> bool {
+    ffi_guard(|| {
+        let name = english_or_first_font_name(font_ref, StringId::WWS_FAMILY_NAME);
+        *out_string = name.clone().unwrap_or_default();
+        name.is_some()
+    })
+}
+
+fn wws_subfamily_name(font_ref: &BridgeFontRef, out_string: &mut String) -> bool {
+    ffi_guard(|| {
+        let name = english_or_first_font_name(font_ref, StringId::WWS_SUBFAMILY_NAME);
+        *out_string = name.clone().unwrap_or_default();
+        name.is_some()
+    })
+}
+
+fn is_wws_conformant(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| Some(f.os2().ok()?.fs_selection().contains(SelectionFlags::WWS)))
+            .unwrap_or_default()
+    })
+}
+
+fn font_names(font_ref: &BridgeFontRef, out: &mut BridgeFontNames) -> bool {
+    ffi_guard(|| {
+        let font_names = font_ref.with_font(|f| {
+            f.name().ok()?;
+            let name_ids = [
+                StringId::COPYRIGHT_NOTICE,
+                StringId::UNIQUE_ID,
+                StringId::FULL_NAME,
+                StringId::VERSION_STRING,
+                StringId::MANUFACTURER,
+                StringId::DESIGNER,
+                StringId::DESCRIPTION,
+                StringId::LICENSE_DESCRIPTION,
+            ];
+            // Picks the string of each ID that `english_or_first_font_name`
+            // would, resolving languages the same way.
+            let mut present = 0;
+            let strings = name_ids.map(|name_id| {
+                let (_, _, string) = preferred_name(f, name_id)?;
+                present |= 1 << name_id.to_u16();
+                Some(string)
+            });
+            let [copyright, unique_id, full_name, version, manufacturer, designer, description, license] =
+                strings.map(Option::unwrap_or_default);
+            Some(BridgeFontNames {
+                copyright,
+                unique_id,
+                full_name,
+                version,
+                manufacturer,
+                designer,
+                description,
+                license,
+                present,
+            })
+        });
+        let is_valid = font_names.is_some();
+        *out = font_names.unwrap_or_default();
+        is_valid
+    })
+}
+
+fn postscript_name(font_ref: &BridgeFontRef, out_string: &mut String) -> bool {
+    ffi_guard(|| {
+        let postscript_name = english_or_first_font_name(font_ref, StringId::POSTSCRIPT_NAME);
+        match postscript_name {
+            Some(name) => {
+                *out_string = name;
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+fn postscript_name_or_synthesized(
+    font_ref: &BridgeFontRef,
+    out_string: &mut String,
+) -> PostScriptNameSource {
+    ffi_guard(|| {
+        let name_table_name = english_or_first_font_name(font_ref, StringId::POSTSCRIPT_NAME)
+            .map(|name| {
+                let sanitized =
+                    sanitize_postscript_name(&name, MAX_NAME_TABLE_POSTSCRIPT_NAME_LENGTH);
+                let source = match sanitized == name {
+                    true => PostScriptNameSource::NameTable,
+                    false => PostScriptNameSource::Sanitized,
+                };
+                (sanitized, source)
+            })
+            .filter(|(name, _)| !name.is_empty());
+        let (name, source) = name_table_name
+            .or_else(|| {
+                let name = synthesize_postscript_name(font_ref)?;
+                Some((name, PostScriptNameSource::Synthesized))
+            })
+            .unwrap_or_default();
+        *out_string = name;
+        source
+    })
+}
+
+fn version_string(font_ref: &BridgeFontRef, out_string: &mut String) -> bool {
+    ffi_guard(|| {
+        let version = english_or_first_font_name(font_ref, StringId::VERSION_STRING);
+        *out_string = version.clone().unwrap_or_default();
+        version.is_some()
+    })
+}
+
+fn font_revision(font_ref: &BridgeFontRef) -> f32 {
+    ffi_guard(|| {
+        // Divides in f64 so that the result is rounded once, which leaves
+        // revisions below 256 exact in the 24 bit mantissa of an f32.
+        font_ref
+            .with_font(|f| {
+                let revision = f.head().ok()?.font_revision().to_bits();
+                Some((f64::from(revision) / 65536.0) as f32)
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// PostScript names are limited to 127 bytes, see Adobe Technical Note #5902.
+const MAX_POSTSCRIPT_NAME_LENGTH: usize = 127;
+
+/// The OpenType name table limits the PostScript name of name ID 6 to 63
+/// bytes.
+const MAX_NAME_TABLE_POSTSCRIPT_NAME_LENGTH: usize = 63;
+
+/// Restricts `name` to printable ASCII without the PostScript delimiter
+/// characters and truncates it to `max_length` bytes.
+fn sanitize_postscript_name(name: &str, max_length: usize) -> String {
+    name.chars()
+        .filter(|c| matches!(c, '!'..='~') && !"[](){}<>/%".contains(*c))
+        .take(max_length)
+        .collect()
+}
+
+/// Synthesizes a PostScript name like FreeType does for fonts without one: the
+/// typographic or else legacy family and subfamily names, each sanitized,
+/// joined by a hyphen. Just the family if there is no subfamily name.
+fn synthesize_postscript_name(font_ref: &BridgeFontRef) -> Option<String> {
+    let sanitized_name = |name_ids: [StringId; 2]| {
+        name_ids
+            .into_iter()
+            .find_map(|name_id| english_or_first_font_name(font_ref, name_id))
+            .map(|name| sanitize_postscript_name(&name, MAX_NAME_TABLE_POSTSCRIPT_NAME_LENGTH))
+            .filter(|name| !name.is_empty())
+    };
+    let family = sanitized_name([StringId::TYPOGRAPHIC_FAMILY_NAME, StringId::FAMILY_NAME])?;
+    let subfamily = sanitized_name([
+        StringId::TYPOGRAPHIC_SUBFAMILY_NAME,
+        StringId::SUBFAMILY_NAME,
+    ]);
+    Some(match subfamily {
+        Some(subfamily) => sanitize_postscript_name(
+            &format!("{family}-{subfamily}"),
+            MAX_NAME_TABLE_POSTSCRIPT_NAME_LENGTH,
+        ),
+        None => family,
+    })
+}
+
+/// Synthesizes a named instance PostScript name following the Adobe Technical
+/// Note #5902 convention: the family prefix and the instance's subfamily name,
+/// each reduced to ASCII alphanumerics, joined by a hyphen.
+fn synthesize_named_instance_postscript_name(
+    font_ref: &BridgeFontRef,
+    subfamily_name_id: StringId,
+) -> Option<String> {
+    let alphanumeric_only =
+        |name: String| -> String { name.chars().filter(char::is_ascii_alphanumeric).collect() };
+    let family_prefix = [
+        StringId::VARIATIONS_POSTSCRIPT_NAME_PREFIX,
+        StringId::TYPOGRAPHIC_FAMILY_NAME,
+        StringId::FAMILY_NAME,
+    ]
+    .into_iter()
+    .find_map(|name_id| english_or_first_font_name(font_ref, name_id))
+    .map(alphanumeric_only)?;
+    let subfamily =
+        english_or_first_font_name(font_ref, subfamily_name_id).map(alphanumeric_only)?;
+    Some(sanitize_postscript_name(
+        &format!("{family_prefix}-{subfamily}"),
+        MAX_POSTSCRIPT_NAME_LENGTH,
+    ))
+}
+
+fn named_instance_postscript_name(
+    font_ref: &BridgeFontRef,
+    instance_index: usize,
+    out_string: &mut String,
+) -> bool {
+    ffi_guard(|| {
+        let instance = match font_ref.with_font(|f| f.named_instances().get(instance_index)) {
+            Some(instance) => instance,
+            _ => return false,
+        };
+        let postscript_name = instance
+            .postscript_name_id()
+            .and_then(|name_id| english_or_first_font_name(font_ref, name_id))
+            .or_else(|| {
+                synthesize_named_instance_postscript_name(font_ref, instance.subfamily_name_id())
+            });
+        match postscript_name {
+            Some(name) => {
+                *out_string = name;
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+fn has_glyph_names(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| Some(f.post().ok()?.num_names() > 0))
+            .unwrap_or_default()
+    })
+}
+
+fn glyph_name(font_ref: &BridgeFontRef, glyph_id: u16, out_string: &mut String) -> bool {
+    ffi_guard(|| {
+        // Post::glyph_name covers the standard Macintosh names of format 1.0 and
+        // format 2.0 indices below 258, and returns None for format 3.0 as well as
+        // for out of bounds name indices or unreadable string data.
+        let name = font_ref.with_font(|f| {
+            let post = f.post().ok()?;
+            if usize::from(glyph_id) >= post.num_names() {
+                return None;
+            }
+            post.glyph_name(GlyphId::new(glyph_id))
+                .map(|name| name.to_string())
+        });
+        match name {
+            Some(name) => {
+                *out_string = name;
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+fn resolve_palette(
+    font_ref: &BridgeFontRef,
+    base_palette: u16,
+    palette_overrides: &[PaletteOverride],
+) -> Vec<u32> {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let cpal = f.cpal().ok()?;
+
+                let start_index: usize = cpal
+                    .color_record_indices()
+                    .get(usize::from(base_palette))?
+                    .get()
+                    .into();
+                let num_entries: usize = cpal.num_palette_entries().into();
+
+                let color_records = cpal.color_records_array()?.ok()?;
+                let mut palette: Vec<u32> = color_records
+                    .get(start_index..start_index + num_entries)?
+                    .iter()
+                    .map(|record| {
+                        u32::from_be_bytes([record.alpha, record.red, record.green, record.blue])
+                    })
+                    .collect();
+
+                for override_entry in palette_overrides {
+                    let index = override_entry.index as usize;
+                    if index < palette.len() {
+                        palette[index] = override_entry.color_8888;
+                    }
+                }
+                Some(palette)
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn has_colr_glyph(font_ref: &BridgeFontRef, format: ColorGlyphFormat, glyph_id: u16) -> bool {
+    font_ref
+        .with_font(|f| {
+            let colrv1_paintable = f
+                .color_glyphs()
+                .get_with_format(GlyphId::new(glyph_id), format);
+            Some(colrv1_paintable.is_some())
+        })
+        .unwrap_or_default()
+}
+
+fn has_colrv1_glyph(font_ref: &BridgeFontRef, glyph_id: u16) -> bool {
+    ffi_guard(|| has_colr_glyph(font_ref, ColorGlyphFormat::ColrV1, glyph_id))
+}
+
+fn has_colrv0_glyph(font_ref: &BridgeFontRef, glyph_id: u16) -> bool {
+    ffi_guard(|| has_colr_glyph(font_ref, ColorGlyphFormat::ColrV0, glyph_id))
+}
+
+fn has_colrv0_glyphs(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let colr = f.colr().ok()?;
+                let base_glyph_records = colr.base_glyph_records()?.ok()?;
+                let layer_records = colr.layer_records()?.ok()?;
+                Some(!base_glyph_records.is_empty() && !layer_records.is_empty())
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn has_colrv1_glyphs(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                // Version 0 tables have no base glyph list.
+                let base_glyph_list = f.colr().ok()?.base_glyph_list()?.ok()?;
+                Some(!base_glyph_list.base_glyph_paint_records().is_empty())
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn has_bitmap_glyphs(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let has_cbdt_glyphs = f.cbdt().is_ok()
+                    && f.cblc().is_ok_and(|cblc| {
+                        cblc.bitmap_sizes()
+                            .iter()
+                            .any(|size| size.number_of_index_subtables() > 0)
+                    });
+                // Glyphs without an image repeat the next glyph's offset.
+                let has_sbix_glyphs = f.sbix().is_ok_and(|sbix| {
+                    sbix.strikes().iter().filter_map(Result::ok).any(|strike| {
+                        strike
+                            .glyph_data_offsets()
+                            .windows(2)
+                            .any(|offsets| offsets[1].get() > offsets[0].get())
+                    })
+                });
+                Some(has_cbdt_glyphs || has_sbix_glyphs)
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Returns whether `svg`, the raw SVG table data, is of version 0 and lists
+/// at least one document, all of whose index entries are in bounds.
+fn has_svg_documents(svg: FontData) -> Option<bool> {
+    const DOCUMENT_RECORD_SIZE: usize = 12;
+    if svg.read_at::<u16>(0).ok()? != 0 {
+        return Some(false);
+    }
+    let document_list_offset = usize::try_from(svg.read_at::<u32>(2).ok()?).ok()?;
+    let num_entries = usize::from(svg.read_at::<u16>(document_list_offset).ok()?);
+    let document_list_end = document_list_offset + 2 + num_entries * DOCUMENT_RECORD_SIZE;
+    Some(num_entries > 0 && document_list_end <= svg.len())
+}
+
+fn has_svg_glyphs(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| has_svg_documents(f.data_for_tag(Tag::new(b"SVG "))?))
+            .unwrap_or_default()
+    })
+}
+
+use crate::ffi::BridgeColorLayer;
+
+/// The palette index of COLR layers and paints that use the text foreground
+/// color rather than a CPAL entry.
+const FOREGROUND_PALETTE_INDEX: u16 = 0xFFFF;
+
+fn get_colrv0_layers(
+    font_ref: &BridgeFontRef,
+    glyph_id: u16,
+    out: &mut Vec<BridgeColorLayer>,
+) -> bool {
+    ffi_guard(|| {
+        out.clear();
+        let layers = font_ref.with_font(|f| {
+            let colr = f.colr().ok()?;
+            let layer_range = colr.v0_base_glyph(GlyphId::new(glyph_id)).ok()??;
+            layer_range
+                .map(|index| {
+                    let (layer_glyph_id, palette_index) = colr.v0_layer(index).ok()?;
+                    Some(BridgeColorLayer {
+                        glyph_id: layer_glyph_id.to_u16(),
+                        palette_index,
+                        uses_foreground_color: palette_index == FOREGROUND_PALETTE_INDEX,
+                    })
+                })
+                .collect::<Option<Vec<_>>>()
+        });
+        match layers {
+            Some(layers) => {
+                *out = layers;
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+use crate::ffi::ClipBox;
+
+fn get_colrv1_clip_box(
+    font_ref: &BridgeFontRef,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    size: f32,
+    clip_box: &mut ClipBox,
+) -> bool {
+    ffi_guard(|| {
+        let size = match size {
+            x if x == 0.0 => {
+                return false;
+            }
+            _ => Size::new(size),
+        };
+        font_ref
+            .with_font(|f| {
+                match f
+                    .color_glyphs()
+                    .get_with_format(GlyphId::new(glyph_id), ColorGlyphFormat::ColrV1)?
+                    .bounding_box(coords.normalized_coords.coords(), size)
+                {
+                    Some(bounding_box) => {
+                        *clip_box = ClipBox {
+                            x_min: bounding_box.x_min,
+                            y_min: bounding_box.y_min,
+                            x_max: bounding_box.x_max,
+                            y_max: bounding_box.y_max,
+                        };
+                        Some(true)
+                    }
+                    _ => None,
+                }
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Implements the behavior expected for `SkTypeface::getTableData`, compare
+/// documentation for this method and the FreeType implementation in Skia.
+/// * If the target data array is empty, do not copy any data into it, but
+///   return the size of the table.
+/// * If the target data buffer is shorted than from offset to the end of the
+///   table, truncate the data.
+/// * If offset is longer than the table's length, return 0.
+fn table_data(font_ref: &BridgeFontRef, tag: u32, offset: usize, data: &mut [u8]) -> usize {
+    ffi_guard(|| {
+        let table_data = font_ref
+            .with_font(|f| f.table_data(Tag::from_be_bytes(tag.to_be_bytes())))
+            .unwrap_or_default();
+        let table_data = table_data.as_ref();
+        // Remaining table data size measured from offset to end, or 0 if offset is
+        // too large.
+        let Some(remaining_length) = table_data.len().checked_sub(offset) else {
+            return 0;
+        };
+        if data.is_empty() {
+            return remaining_length;
+        }
+        let to_copy_length = remaining_length.min(data.len());
+        let Some(table_offset_data) = offset
+            .checked_add(to_copy_length)
+            .and_then(|end| table_data.get(offset..end))
+        else {
+            return 0;
+        };
+        data[..to_copy_length].copy_from_slice(table_offset_data);
+        to_copy_length
+    })
+}
+
+fn table_data_slice<'a>(font_ref: &'a BridgeFontRef<'a>, tag: u32) -> &'a [u8] {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| f.table_data(Tag::from_be_bytes(tag.to_be_bytes())))
+            .map(|table_data| table_data.as_bytes())
+            .unwrap_or_default()
+    })
+}
+
+fn table_tags(font_ref: &BridgeFontRef, out_tags: &mut [u32]) -> usize {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let table_records = f.table_directory.table_records();
+                if out_tags.is_empty() {
+                    return Some(table_records.len());
+                }
+                let table_tags_iter = table_records
+                    .iter()
+                    .map(|table| u32::from_be_bytes(table.tag.get().into_bytes()));
+                Some(
+                    out_tags
+                        .iter_mut()
+                        .zip(table_tags_iter)
+                        .map(|(out_tag, table_tag)| *out_tag = table_tag)
+                        .count(),
+                )
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn has_table(font_ref: &BridgeFontRef, tag: u32) -> bool {
+    ffi_guard(|| {
+        let tag = Tag::from_be_bytes(tag.to_be_bytes());
+        font_ref
+            .with_font(|f| {
+                Some(
+                    f.table_directory
+                        .table_records()
+                        .iter()
+                        .any(|table| table.tag() == tag),
+                )
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn has_tables(font_ref: &BridgeFontRef, tags: &[u32], out_has_table: &mut [bool]) -> usize {
+    ffi_guard(|| {
+        let table_records = font_ref
+            .with_font(|f| Some(f.table_directory.table_records()))
+            .unwrap_or_default();
+        let mut num_present = 0;
+        for (tag, has_table) in tags.iter().zip(out_has_table.iter_mut()) {
+            let tag = Tag::from_be_bytes(tag.to_be_bytes());
+            *has_table = table_records.iter().any(|table| table.tag() == tag);
+            if *has_table {
+                num_present += 1;
+            }
+        }
+        num_present
+    })
+}
+
+fn variation_position(
+    coords: &BridgeNormalizedCoords,
+    coordinates: &mut [SkiaDesignCoordinate],
+) -> isize {
+    ffi_guard(|| {
+        if !coordinates.is_empty() {
+            if coords.filtered_user_coords.len() > coordinates.len() {
+                return -1;
+            }
+            let skia_design_coordinates =
+                coords
+                    .filtered_user_coords
+                    .iter()
+                    .map(|setting| SkiaDesignCoordinate {
+                        axis: u32::from_be_bytes(setting.selector.into_bytes()),
+                        value: setting.value,
+                    });
+            for (i, coord) in skia_design_coordinates.enumerate() {
+                coordinates[i] = coord;
+            }
+        }
+        coords.filtered_user_coords.len().try_into().unwrap()
+    })
+}
+
+fn normalized_coords_to_f2dot14(coords: &BridgeNormalizedCoords, out_coords: &mut [i16]) -> usize {
+    ffi_guard(|| {
+        let normalized_coords = coords.normalized_coords.coords();
+        out_coords
+            .iter_mut()
+            .zip(normalized_coords)
+            .for_each(|(out_coord, coord)| *out_coord = coord.to_bits());
+        normalized_coords.len()
+    })
+}
+
+/// Returns the normalized coordinates without trailing default (zero)
+/// coordinates, so that an empty location and a location explicitly set to
+/// the default position compare and hash identically.
+fn significant_normalized_coords(coords: &BridgeNormalizedCoords) -> &[NormalizedCoord] {
+    let normalized_coords = coords.normalized_coords.coords();
+    let significant_len = normalized_coords
+        .iter()
+        .rposition(|coord| *coord != NormalizedCoord::default())
+        .map_or(0, |last_non_default| last_non_default + 1);
+    &normalized_coords[..significant_len]
+}
+
+fn normalized_coords_equal(a: &BridgeNormalizedCoords, b: &BridgeNormalizedCoords) -> bool {
+    ffi_guard(|| significant_normalized_coords(a) == significant_normalized_coords(b))
+}
+
+/// 64-bit FNV-1a over the big-endian F2Dot14 bytes of the significant
+/// coordinates, stable across process runs.
+fn normalized_coords_hash(coords: &BridgeNormalizedCoords) -> u64 {
+    ffi_guard(|| {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+        significant_normalized_coords(coords)
+            .iter()
+            .flat_map(|coord| coord.to_be_bytes())
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    })
+}
+
+/// Inverts the piecewise linear avar mapping of `segment_maps`, mapping a
+/// coordinate from avar output space back to fvar normalized space. Mirrors
+/// `SegmentMaps::apply`: coordinates exactly at a segment boundary map to the
+/// boundary's `fromCoordinate`, coordinates outside the mapped range and
+/// empty segment maps are passed through unchanged.
+fn unapply_segment_maps(segment_maps: &SegmentMaps, coord: f32) -> f32 {
+    let mut prev: Option<(f32, f32)> = None;
+    for axis_value_map in segment_maps.axis_value_maps() {
+        let from = axis_value_map.from_coordinate().to_f32();
+        let to = axis_value_map.to_coordinate().to_f32();
+        if to == coord {
+            return from;
+        }
+        if to > coord {
+            return match prev {
+                Some((prev_from, prev_to)) => {
+                    prev_from + (from - prev_from) * (coord - prev_to) / (to - prev_to)
+                }
+                _ => coord,
+            };
+        }
+        prev = Some((from, to));
+    }
+    coord
+}
+
+/// Maps a normalized fvar coordinate back to the design space of `axis`.
+fn denormalize_coord(axis: &Axis, normalized: f32) -> f32 {
+    let default_value = axis.default_value();
+    if normalized < 0.0 {
+        default_value + (default_value - axis.min_value()) * normalized
+    } else {
+        default_value + (axis.max_value() - default_value) * normalized
+    }
+}
+
+/// Returns the design space value of `axis`, the `axis_index`th axis of
+/// `font`, at `coords`, inverting the avar and fvar normalization.
+fn design_coord(
+    font: &FontRef,
+    coords: &BridgeNormalizedCoords,
+    axis_index: usize,
+    axis: &Axis,
+) -> f32 {
+    let normalized = coords
+        .normalized_coords
+        .coords()
+        .get(axis_index)
+        .map(|coord| coord.to_f32())
+        .unwrap_or_default();
+    let unmapped = font
+        .avar()
+        .ok()
+        .and_then(|avar| {
+            avar.axis_segment_maps()
+                .get(axis_index)
+                .transpose()
+                .ok()
+                .flatten()
+        })
+        .map_or(normalized, |segment_maps| {
+            unapply_segment_maps(&segment_maps, normalized)
+        });
+    denormalize_coord(axis, unmapped)
+}
+
+fn design_position(
+    font_ref: &BridgeFontRef,
+    coords: &BridgeNormalizedCoords,
+    out_coordinates: &mut [SkiaDesignCoordinate],
+) -> usize {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let axes = f.axes();
+                for ((i, axis), out_coordinate) in axes.iter().enumerate().zip(out_coordinates) {
+                    *out_coordinate = SkiaDesignCoordinate {
+                        axis: u32::from_be_bytes(axis.tag().into_bytes()),
+                        value: design_coord(f, coords, i, &axis),
+                    };
+                }
+                Some(axes.len())
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn populate_axes(font_ref: &BridgeFontRef, mut axis_wrapper: Pin<&mut AxisWrapper>) -> isize {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let axes = f.axes();
+                // Populate incoming allocated SkFontParameters::Variation::Axis[] only when a
+                // buffer is passed.
+                if axis_wrapper.as_ref().size() > 0 {
+                    for (i, axis) in axes.iter().enumerate() {
+                        if !axis_wrapper.as_mut().populate_axis(
+                            i,
+                            u32::from_be_bytes(axis.tag().into_bytes()),
+                            axis.min_value(),
+                            axis.default_value(),
+                            axis.max_value(),
+                            axis.is_hidden(),
+                        ) {
+                            return None;
+                        }
+                    }
+                }
+                isize::try_from(axes.len()).ok()
+            })
+            .unwrap_or(-1)
+    })
+}
+
+fn num_axes(font_ref: &BridgeFontRef) -> usize {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| Some(f.axes().len()))
+            .unwrap_or_default()
+    })
+}
+
+fn populate_axis_info(font_ref: &BridgeFontRef, axis_info: &mut [BridgeAxisInfo]) -> usize {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let axes = f.axes();
+                let mut num_populated = 0;
+                for (out_axis, axis) in axis_info.iter_mut().zip(axes.iter()) {
+                    *out_axis = BridgeAxisInfo {
+                        tag: u32::from_be_bytes(axis.tag().into_bytes()),
+                        min: axis.min_value(),
+                        def: axis.default_value(),
+                        max: axis.max_value(),
+                        hidden: axis.is_hidden(),
+                    };
+                    num_populated += 1;
+                }
+                Some(num_populated)
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn make_font_ref_internal<'a>(font_data: &'a [u8], index: u32) -> Result<FontRef<'a>, ReadError> {
+    let file_ref = FileRef::new(font_data)?;
+    if index >= file_ref_font_count(&file_ref) {
+        return Err(ReadError::InvalidCollectionIndex(index));
+    }
+    match file_ref {
+        FileRef::Font(font_ref) => Ok(font_ref),
+        FileRef::Collection(collection) => collection.get(index),
+    }
+}
+
+fn file_ref_font_count(file_ref: &FileRef) -> u32 {
+    match file_ref {
+        FileRef::Font(_) => 1,
+        FileRef::Collection(collection) => collection.len(),
+    }
+}
+
+fn font_count(font_data: &[u8]) -> u32 {
+    ffi_guard(|| {
+        FileRef::new(font_data)
+            .map(|file_ref| file_ref_font_count(&file_ref))
+            .unwrap_or_default()
+    })
+}
+
+fn make_font_ref<'a>(font_data: &'a [u8], index: u32) -> Box<BridgeFontRef<'a>> {
+    ffi_guard(|| {
+        Box::new(BridgeFontRef::new(
+            make_font_ref_internal(font_data, index).ok(),
+            index,
+            None,
+        ))
+    })
+}
+
+fn make_font_ref_owned<'a>(font_data: Vec<u8>, index: u32) -> Box<BridgeFontRef<'a>> {
+    ffi_guard(|| {
+        // The data is held as a raw pointer rather than a Box, since moving
+        // a Box asserts unique access to its allocation, which `font` aliases.
+        let owned_data = Box::into_raw(font_data.into_boxed_slice());
+        // SAFETY: The allocation is valid until the BridgeFontRef frees it on
+        // drop, after releasing `font`. `font` cannot outlive it, as it is only
+        // handed out through `with_font`, bounded by a borrow of the
+        // BridgeFontRef.
+        let font_data: &'a [u8] = unsafe { &*owned_data };
+        Box::new(BridgeFontRef::new(
+            make_font_ref_internal(font_data, index).ok(),
+            index,
+            Some(owned_data),
+        ))
+    })
+}
+
+fn font_ref_is_valid(bridge_font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| bridge_font_ref.font.is_some())
+}
+
+fn render_prerequisites(font_ref: &BridgeFontRef) -> BridgeRenderPrerequisites {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let num_glyphs = font_ref.num_glyphs;
+                let has_glyf_outlines = f
+                    .loca(None)
+                    .is_ok_and(|loca| loca.len() >= usize::from(num_glyphs))
+                    && f.glyf().is_ok();
+                let has_cff_outlines = || {
+                    CffCharstrings::new(f)
+                        .is_some_and(|cff| cff.charstrings.count() >= u32::from(num_glyphs))
+                };
+                Some(BridgeRenderPrerequisites {
+                    has_head: f.head().is_ok_and(|head| head.units_per_em() != 0),
+                    has_hhea: f.hhea().is_ok(),
+                    has_maxp: f.maxp().is_ok() && num_glyphs != 0,
+                    has_cmap: f
+                        .cmap()
+                        .is_ok_and(|cmap| selected_cmap_subtable_index(&cmap).is_some()),
+                    has_outlines: has_glyf_outlines || has_cff_outlines(),
+                })
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn font_ref_is_renderable(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        let prerequisites = render_prerequisites(font_ref);
+        prerequisites.has_head
+            && prerequisites.has_hhea
+            && prerequisites.has_maxp
+            && prerequisites.has_cmap
+            && prerequisites.has_outlines
+    })
+}
+
+fn font_ref_collection_index(font_ref: &BridgeFontRef) -> u32 {
+    ffi_guard(|| font_ref.collection_index)
+}
+
+fn font_ref_data_size(font_ref: &BridgeFontRef) -> usize {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let table_records = f.table_directory.table_records();
+                Some(
+                    table_records
+                        .iter()
+                        .map(|record| record.length() as usize)
+                        .sum::<usize>()
+                        + 12
+                        + 16 * table_records.len(),
+                )
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn get_outline_collection<'a>(font_ref: &'a BridgeFontRef<'a>) -> Box<BridgeOutlineCollection<'a>> {
+    ffi_guard(|| Box::new(BridgeOutlineCollection::new(font_ref)))
+}
+
+fn outlines_support_hinting(outlines: &BridgeOutlineCollection) -> bool {
+    ffi_guard(|| outlines.supports_hinting())
+}
+
+/// Sums `data` as big-endian u32 values, zero-padding the last one, as
+/// specified for OpenType table checksums.
+fn table_checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+const HEAD_CHECKSUM_ADJUSTMENT_OFFSET: usize = 8;
+const CHECKSUM_ADJUSTMENT_MAGIC: u32 = 0xB1B0AFBA;
+
+fn extract_font_from_collection(font_data: &[u8], index: u32, out: &mut Vec<u8>) -> bool {
+    ffi_guard(|| {
+        out.clear();
+        let Ok(font) = make_font_ref_internal(font_data, index) else {
+            return false;
+        };
+        let table_records = font.table_directory.table_records();
+        let num_tables = table_records.len();
+        let Ok(num_tables_u16) = u16::try_from(num_tables) else {
+            return false;
+        };
+
+        // Lay out table data after the directory, storing tables that share their
+        // source range only once.
+        let mut new_offsets: Vec<u32> = Vec::with_capacity(num_tables);
+        let mut unique_ranges: Vec<(usize, usize, u32)> = Vec::new();
+        let mut table_data = Vec::new();
+        let directory_size = 12 + 16 * num_tables;
+        for record in table_records {
+            let start = record.offset() as usize;
+            let length = record.length() as usize;
+            let new_offset = match unique_ranges.iter().find(|(other_start, other_length, _)| {
+                (*other_start, *other_length) == (start, length)
+            }) {
+                Some((_, _, new_offset)) => *new_offset,
+                None => {
+                    let Some(data) = start
+                        .checked_add(length)
+                        .and_then(|end| font_data.get(start..end))
+                    else {
+                        return false;
+                    };
+                    let Ok(new_offset) = u32::try_from(directory_size + table_data.len()) else {
+                        return false;
+                    };
+                    table_data.extend_from_slice(data);
+                    table_data.resize((table_data.len() + 3) & !3, 0);
+                    unique_ranges.push((start, length, new_offset));
+                    new_offset
+                }
+            };
+            new_offsets.push(new_offset);
+        }
+
+        let entry_selector = num_tables_u16.checked_ilog2().unwrap_or_default() as u16;
+        let search_range = (1u16 << entry_selector).wrapping_mul(16);
+        out.reserve(directory_size + table_data.len());
+        out.extend(font.table_directory.sfnt_version().to_be_bytes());
+        out.extend(num_tables_u16.to_be_bytes());
+        out.extend(search_range.to_be_bytes());
+        out.extend(entry_selector.to_be_bytes());
+        out.extend((num_tables_u16.wrapping_mul(16).wrapping_sub(search_range)).to_be_bytes());
+        let mut head_offset = None;
+        for (record, new_offset) in table_records.iter().zip(&new_offsets) {
+            let tag = record.tag();
+            if tag == Tag::new(b"head") {
+                head_offset = Some(*new_offset as usize);
+            }
+            out.extend(tag.into_bytes());
+            out.extend(record.checksum().to_be_bytes());
+            out.extend(new_offset.to_be_bytes());
+            out.extend(record.length().to_be_bytes());
+        }
+        out.extend(table_data);
+
+        // Recompute checksumAdjustment over the rebuilt font with it set to zero.
+        if let Some(adjustment_range) = head_offset
+            .map(|head_offset| head_offset + HEAD_CHECKSUM_ADJUSTMENT_OFFSET)
+            .map(|start| start..start + 4)
+            .filter(|range| range.end <= out.len())
+        {
+            out[adjustment_range.clone()].fill(0);
+            let adjustment = CHECKSUM_ADJUSTMENT_MAGIC.wrapping_sub(table_checksum(out));
+            out[adjustment_range].copy_from_slice(&adjustment.to_be_bytes());
+        }
+        true
+    })
+}
+
+fn verify_checksums(font_ref: &BridgeFontRef) -> BridgeChecksumResult {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let table_directory = &f.table_directory;
+                let table_records = table_directory.table_records();
+                let mut font_checksum = table_directory
+                    .sfnt_version()
+                    .wrapping_add((table_directory.num_tables() as u32) << 16)
+                    .wrapping_add(u32::from(table_directory.search_range()))
+                    .wrapping_add((table_directory.entry_selector() as u32) << 16)
+                    .wrapping_add(u32::from(table_directory.range_shift()));
+                let mut num_mismatched_tables = 0;
+                let mut checksum_adjustment = None;
+                for record in table_records {
+                    let tag = record.tag();
+                    let data = f.table_data(tag)?;
+                    let data = data.as_bytes();
+                    let mut checksum = table_checksum(data);
+                    if tag == Tag::new(b"head") {
+                        // The checksum of head is computed with checksumAdjustment
+                        // set to zero.
+                        let adjustment = u32::from_be_bytes(
+                            data.get(
+                                HEAD_CHECKSUM_ADJUSTMENT_OFFSET
+                                    ..HEAD_CHECKSUM_ADJUSTMENT_OFFSET + 4,
+                            )?
+                            .try_into()
+                            .ok()?,
+                        );
+                        checksum = checksum.wrapping_sub(adjustment);
+                        checksum_adjustment = Some(adjustment);
+                    }
+                    if checksum != record.checksum() {
+                        num_mismatched_tables += 1;
+                    }
+                    font_checksum = font_checksum
+                        .wrapping_add(u32::from_be_bytes(tag.into_bytes()))
+                        .wrapping_add(record.checksum())
+                        .wrapping_add(record.offset())
+                        .wrapping_add(record.length())
+                        .wrapping_add(checksum);
+                }
+                Some(BridgeChecksumResult {
+                    verifiable: true,
+                    num_mismatched_tables,
+                    checksum_adjustment_valid: checksum_adjustment.is_some_and(|adjustment| {
+                        adjustment == CHECKSUM_ADJUSTMENT_MAGIC.wrapping_sub(font_checksum)
+                    }),
+                })
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Streaming 64-bit xxHash, used for digests of font data that are stable
+/// across process runs.
+struct XxHash64 {
+    accumulators: [u64; 4],
+    buffer: [u8; 32],
+    buffer_len: usize,
+    total_len: u64,
+}
+
+impl XxHash64 {
+    const PRIME_1: u64 = 0x9E3779B185EBCA87;
+    const PRIME_2: u64 = 0xC2B2AE3D27D4EB4F;
+    const PRIME_3: u64 = 0x165667B19E3779F9;
+    const PRIME_4: u64 = 0x85EBCA77C2B2AE63;
+    const PRIME_5: u64 = 0x27D4EB2F165667C5;
+
+    fn new() -> Self {
+        Self {
+            accumulators: [
+                Self::PRIME_1.wrapping_add(Self::PRIME_2),
+                Self::PRIME_2,
+                0,
+                0u64.wrapping_sub(Self::PRIME_1),
+            ],
+            buffer: [0; 32],
+            buffer_len: 0,
+            total_len: 0,
+        }
+    }
+
+    fn round(accumulator: u64, input: u64) -> u64 {
+        accumulator
+            .wrapping_add(input.wrapping_mul(Self::PRIME_2))
+            .rotate_left(31)
+            .wrapping_mul(Self::PRIME_1)
+    }
+
+    fn merge_round(hash: u64, accumulator: u64) -> u64 {
+        (hash ^ Self::round(0, accumulator))
+            .wrapping_mul(Self::PRIME_1)
+            .wrapping_add(Self::PRIME_4)
+    }
+
+    fn read_u64(bytes: &[u8]) -> u64 {
+        u64::from_le_bytes(bytes[..8].try_into().unwrap())
+    }
+
+    fn consume_stripe(&mut self, stripe: &[u8]) {
+        for (accumulator, lane) in self.accumulators.iter_mut().zip(stripe.chunks_exact(8)) {
+            *accumulator = Self::round(*accumulator, Self::read_u64(lane));
+        }
+    }
+
+    fn write(&mut self, mut bytes: &[u8]) {
+        self.total_len += bytes.len() as u64;
+        if self.buffer_len > 0 {
+            let to_fill = (32 - self.buffer_len).min(bytes.len());
+            self.buffer[self.buffer_len..self.buffer_len + to_fill]
+                .copy_from_slice(&bytes[..to_fill]);
+            self.buffer_len += to_fill;
+            bytes = &bytes[to_fill..];
+            if self.buffer_len < 32 {
+                return;
+            }
+            let stripe = self.buffer;
+            self.consume_stripe(&stripe);
+            self.buffer_len = 0;
+        }
+        let mut stripes = bytes.chunks_exact(32);
+        for stripe in &mut stripes {
+            self.consume_stripe(stripe);
+        }
+        let remainder = stripes.remainder();
+        self.buffer[..remainder.len()].copy_from_slice(remainder);
+        self.buffer_len = remainder.len();
+    }
+
+    fn finish(&self) -> u64 {
+        let mut hash = if self.total_len >= 32 {
+            let [v1, v2, v3, v4] = self.accumulators;
+            let hash = v1
+                .rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18));
+            self.accumulators.iter().fold(hash, |hash, accumulator| {
+                Self::merge_round(hash, *accumulator)
+            })
+        } else {
+            Self::PRIME_5
+        };
+        hash = hash.wrapping_add(self.total_len);
+        let mut remainder = &self.buffer[..self.buffer_len];
+        while remainder.len() >= 8 {
+            hash ^= Self::round(0, Self::read_u64(remainder));
+            hash = hash
+                .rotate_left(27)
+                .wrapping_mul(Self::PRIME_1)
+                .wrapping_add(Self::PRIME_4);
+            remainder = &remainder[8..];
+        }
+        if remainder.len() >= 4 {
+            let word = u32::from_le_bytes(remainder[..4].try_into().unwrap());
+            hash ^= u64::from(word).wrapping_mul(Self::PRIME_1);
+            hash = hash
+                .rotate_left(23)
+                .wrapping_mul(Self::PRIME_2)
+                .wrapping_add(Self::PRIME_3);
+            remainder = &remainder[4..];
+        }
+        for byte in remainder {
+            hash ^= u64::from(*byte).wrapping_mul(Self::PRIME_5);
+            hash = hash.rotate_left(11).wrapping_mul(Self::PRIME_1);
+        }
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(Self::PRIME_2);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(Self::PRIME_3);
+        hash ^ (hash >> 32)
+    }
+}
+
+fn font_digest(font_ref: &BridgeFontRef) -> u64 {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let mut table_records = f.table_directory.table_records().to_vec();
+                table_records.sort_by_key(|record| record.tag());
+                let mut hasher = XxHash64::new();
+                for record in table_records {
+                    let tag = record.tag();
+                    let table_data = f.table_data(tag).unwrap_or_default();
+                    hasher.write(&tag.into_bytes());
+                    hasher.write(&(table_data.len() as u64).to_be_bytes());
+                    hasher.write(table_data.as_bytes());
+                }
+                Some(hasher.finish())
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn font_or_collection<'a>(font_data: &'a [u8], num_fonts: &mut u32) -> bool {
+    ffi_guard(|| match FileRef::new(font_data) {
+        Ok(FileRef::Collection(collection)) => {
+            *num_fonts = collection.len();
+            true
+        }
+        Ok(FileRef::Font(_)) => {
+            *num_fonts = 0u32;
+            true
+        }
+        _ => false,
+    })
+}
+
+fn resolve_into_normalized_coords(
+    font_ref: &BridgeFontRef,
+    design_coords: &[SkiaDesignCoordinate],
+) -> Box<BridgeNormalizedCoords> {
+    ffi_guard(|| {
+        let variation_tuples = design_coords
+            .iter()
+            .map(|coord| (Tag::from_be_bytes(coord.axis.to_be_bytes()), coord.value));
+        let bridge_normalized_coords = font_ref
+            .with_font(|f| {
+                Some(BridgeNormalizedCoords {
+                    filtered_user_coords: f.axes().filter(variation_tuples.clone()).collect(),
+                    normalized_coords: f.axes().location(variation_tuples),
+                })
+            })
+            .unwrap_or_default();
+        Box::new(bridge_normalized_coords)
+    })
+}
+
+fn resolve_into_normalized_coords_with_report(
+    font_ref: &BridgeFontRef,
+    design_coords: &[SkiaDesignCoordinate],
+    report: &mut CoordinateResolutionReport,
+) -> Box<BridgeNormalizedCoords> {
+    ffi_guard(|| {
+        *report = CoordinateResolutionReport::default();
+        font_ref.with_font(|f| {
+            let axes = f.axes();
+            for coord in design_coords {
+                match axes.get_by_tag(Tag::from_be_bytes(coord.axis.to_be_bytes())) {
+                    Some(axis) => {
+                        if coord.value < axis.min_value() || coord.value > axis.max_value() {
+                            report.num_clamped += 1;
+                        }
+                    }
+                    _ => {
+                        if !report.unknown_axes.contains(&coord.axis) {
+                            report.unknown_axes.push(coord.axis);
+                        }
+                    }
+                }
+            }
+            Some(())
+        });
+        resolve_into_normalized_coords(font_ref, design_coords)
+    })
+}
+
+fn resolve_named_instance_coords(
+    font_ref: &BridgeFontRef,
+    instance_index: usize,
+) -> Box<BridgeNormalizedCoords> {
+    ffi_guard(|| {
+        let bridge_normalized_coords = font_ref
+            .with_font(|f| {
+                let instance = f.named_instances().get(instance_index)?;
+                Some(BridgeNormalizedCoords {
+                    filtered_user_coords: f
+                        .axes()
+                        .iter()
+                        .zip(instance.user_coords())
+                        .map(|(axis, value)| VariationSetting::new(axis.tag(), value))
+                        .collect(),
+                    normalized_coords: instance.location(),
+                })
+            })
+            .unwrap_or_default();
+        Box::new(bridge_normalized_coords)
+    })
+}
+
+/// Traverses the COLR paint graph of `glyph_id` at `coords`, forwarding its
+/// operations to `painter` in font units with y pointing up. The painters
+/// scale to the size and flip y like for outlines. Returns false if the glyph
+/// has no COLR record or its paint graph is malformed.
+fn paint_colr_glyph(
+    font_ref: &BridgeFontRef,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    painter: &mut impl ColorPainter,
+) -> bool {
+    font_ref
+        .with_font(|f| {
+            let paintable = f.color_glyphs().get(GlyphId::new(glyph_id))?;
+            paintable
+                .paint(coords.normalized_coords.coords(), painter)
+                .ok()
+        })
+        .is_some()
+}
+
+fn draw_colr_glyph(
+    font_ref: &BridgeFontRef,
+    coords: &BridgeNormalizedCoords,
+    glyph_id: u16,
+    color_painter: Pin<&mut ColorPainterWrapper>,
+) -> bool {
+    ffi_guard(|| {
+        let mut color_painter_impl = ColorPainterImpl {
+            color_painter_wrapper: color_painter,
+        };
+        paint_colr_glyph(font_ref, coords, glyph_id, &mut color_painter_impl)
+    })
+}
+
+fn next_color_stop(color_stops: &mut BridgeColorStops, out_stop: &mut ColorStop) -> bool {
+    ffi_guard(|| {
+        if let Some(color_stop) = color_stops.stops_iterator.next() {
+            out_stop.alpha = color_stop.alpha;
+            out_stop.stop = color_stop.offset;
+            out_stop.palette_index = color_stop.palette_index;
+            return true;
+        } else {
+            return false;
+        }
+    })
+}
+
+fn num_color_stops(color_stops: &BridgeColorStops) -> usize {
+    ffi_guard(|| {
+        return color_stops.num_stops;
+    })
+}
+
+use crate::ffi::BridgeFontStyle;
+
+/// Values of `SkFontStyle::Slant`.
+const SLANT_UPRIGHT: i32 = 0;
+const SLANT_ITALIC: i32 = 1;
+const SLANT_OBLIQUE: i32 = 2;
+
+/// Returns the style of the default instance of `font`, or None if it has
+/// neither an OS/2 nor a head table.
+fn default_font_style(font: &FontRef) -> Option<BridgeFontStyle> {
+    match font.os2() {
+        Ok(os2) => {
+            // Some legacy fonts use the classes 1 to 9 of the width
+            // scale for their weight.
+            let weight = match os2.us_weight_class() {
+                weight @ 1..=9 => weight * 100,
+                weight => weight.min(1000),
+            };
+            let fs_selection = os2.fs_selection();
+            // OBLIQUE is only defined from version 4 on, and takes
+            // precedence over ITALIC as it is the more specific.
+            let slant = if os2.version() >= 4 && fs_selection.contains(SelectionFlags::OBLIQUE) {
+                SLANT_OBLIQUE
+            } else if fs_selection.contains(SelectionFlags::ITALIC) {
+                SLANT_ITALIC
+            } else {
+                SLANT_UPRIGHT
+            };
+            Some(BridgeFontStyle {
+                weight: weight.into(),
+                slant,
+                // Width classes match SkFontStyle::Width. 0 is invalid.
+                width: match os2.us_width_class() {
+                    0 => 5,
+                    width => width.min(9).into(),
+                },
+            })
+        }
+        Err(_) => {
+            let (mac_style, _) = head_style_and_flags_in(font)?;
+            let mac_style = MacStyle::from_bits_truncate(mac_style);
+            Some(BridgeFontStyle {
+                weight: if mac_style.contains(MacStyle::BOLD) {
+                    700
+                } else {
+                    400
+                },
+                slant: if mac_style.contains(MacStyle::ITALIC) {
+                    SLANT_ITALIC
+                } else {
+                    SLANT_UPRIGHT
+                },
+                width: 5,
+            })
+        }
+    }
+}
+
+fn font_style(font_ref: &BridgeFontRef, style: &mut BridgeFontStyle) -> bool {
+    ffi_guard(|| {
+        let font_style = font_ref.with_font(default_font_style);
+        let is_valid = font_style.is_some();
+        *style = font_style.unwrap_or_default();
+        is_valid
+    })
+}
+
+/// wdth percentages of the `SkFontStyle::Width` values from 1 to 9, the
+/// same as of the OS/2 width classes.
+const WIDTH_PERCENTAGES: [f32; 9] = [50.0, 62.5, 75.0, 87.5, 100.0, 112.5, 125.0, 150.0, 200.0];
+
+/// Maps a wdth percentage to the nearest `SkFontStyle::Width`, interpolating
+/// linearly between the percentages of adjacent widths.
+fn width_from_percentage(percentage: f32) -> i32 {
+    let Some(upper) = WIDTH_PERCENTAGES
+        .iter()
+        .position(|value| *value >= percentage)
+    else {
+        return 9;
+    };
+    if upper == 0 {
+        return 1;
+    }
+    let (lower_value, upper_value) = (WIDTH_PERCENTAGES[upper - 1], WIDTH_PERCENTAGES[upper]);
+    let fraction = (percentage - lower_value) / (upper_value - lower_value);
+    upper as i32 + fraction.round() as i32
+}
+
+fn variable_font_style(
+    font_ref: &BridgeFontRef,
+    coords: &BridgeNormalizedCoords,
+    style: &mut BridgeFontStyle,
+) -> bool {
+    ffi_guard(|| {
+        let font_style = font_ref.with_font(|f| {
+            let mut font_style = default_font_style(f)?;
+            let (mut italic, mut slant) = (None, None);
+            for (i, axis) in f.axes().iter().enumerate() {
+                let value = design_coord(f, coords, i, &axis);
+                match &axis.tag().into_bytes() {
+                    b"wght" => font_style.weight = value.round().clamp(1.0, 1000.0) as i32,
+                    b"wdth" => font_style.width = width_from_percentage(value),
+                    b"ital" => italic = Some(value >= 0.5),
+                    // Fonts disagree on the direction of slnt, so any angle
+                    // is oblique.
+                    b"slnt" => slant = Some(value.abs() >= 0.5),
+                    _ => {}
+                }
+            }
+            if italic.is_some() || slant.is_some() {
+                font_style.slant = match (italic, slant) {
+                    (Some(true), _) => SLANT_ITALIC,
+                    (_, Some(true)) => SLANT_OBLIQUE,
+                    _ => SLANT_UPRIGHT,
+                };
+            }
+            Some(font_style)
+        });
+        let is_valid = font_style.is_some();
+        *style = font_style.unwrap_or_default();
+        is_valid
+    })
+}
+
+/// A name in a STAT style name, from an axis value table that matched.
+struct StyleNameFragment {
+    /// The lowest axisOrdering of the axes named, then the lowest design axis
+    /// index, which orders the fragments in the name.
+    ordering: (u16, usize),
+    name_id: StringId,
+    is_elidable: bool,
+}
+
+/// Returns the design axis index and (nominal) value of an axis value table
+/// for a single axis.
+fn single_axis_value(axis_value: &AxisValue) -> Option<(usize, Fixed)> {
+    match axis_value {
+        AxisValue::Format1(value) => Some((value.axis_index() as usize, value.value())),
+        AxisValue::Format2(value) => Some((value.axis_index() as usize, value.nominal_value())),
+        AxisValue::Format3(value) => Some((value.axis_index() as usize, value.value())),
+        AxisValue::Format4(_) => None,
+    }
+}
+
+/// Assembles the name of the instance of `font` at `coords` from the STAT
+/// axis value tables, following the style name algorithm of the STAT
+/// specification. Returns None if the font has no axis value tables.
+fn stat_style_name(font: &FontRef, coords: &BridgeNormalizedCoords) -> Option<String> {
+    let stat = font.stat().ok()?;
+    let design_axes = stat.design_axes().ok()?;
+    let axis_values: Vec<AxisValue> = stat
+        .offset_to_axis_values()?
+        .ok()?
+        .axis_values()
+        .iter()
+        .filter_map(Result::ok)
+        .collect();
+    if axis_values.is_empty() {
+        return None;
+    }
+
+    // Positions on axes of fvar are compared as normalized coordinates, so
+    // that axis values match the coordinates that they resolve to. Other axes
+    // cannot vary; the font is at the first value listed for them.
+    let fvar_axes = font.axes();
+    let fvar_index = |axis_index: usize| {
+        let tag = design_axes.get(axis_index)?.axis_tag();
+        fvar_axes.iter().position(|axis| axis.tag() == tag)
+    };
+    // Values beyond the fvar range match no position, unless `clamp`ed as
+    // for the bounds of ranges.
+    let position_of = |axis_index: usize, value: Fixed, clamp: bool| -> Option<i32> {
+        let Some(fvar_index) = fvar_index(axis_index) else {
+            return Some(value.to_bits());
+        };
+        let axis = fvar_axes.get(fvar_index)?;
+        let value = value.to_f32();
+        if !clamp && !(axis.min_value()..=axis.max_value()).contains(&value) {
+            return None;
+        }
+        let location = fvar_axes.location([(axis.tag(), value)]);
+        Some(location.coords().get(fvar_index)?.to_bits().into())
+    };
+    let positions: Vec<Option<i32>> = (0..design_axes.len())
+        .map(|axis_index| match fvar_index(axis_index) {
+            Some(fvar_index) => Some(
+                coords
+                    .normalized_coords
+                    .coords()
+                    .get(fvar_index)
+                    .copied()
+                    .unwrap_or_default()
+                    .to_bits()
+                    .into(),
+            ),
+            None => axis_values
+                .iter()
+                .filter_map(single_axis_value)
+                .find(|(index, _)| *index == axis_index)
+                .map(|(_, value)| value.to_bits()),
+        })
+        .collect();
+    let is_at = |axis_index: usize, value: Fixed| {
+        positions
+            .get(axis_index)
+            .copied()
+            .flatten()
+            .is_some_and(|position| position_of(axis_index, value, false) == Some(position))
+    };
+    let ordering = |axis_index: usize| {
+        let axis_ordering = design_axes
+            .get(axis_index)
+            .map(|axis| axis.axis_ordering())
+            .unwrap_or(u16::MAX);
+        (axis_ordering, axis_index)
+    };
+
+    let mut fragments = Vec::new();
+    let mut is_named = vec![false; design_axes.len()];
+    // Combinations name several axes at once and take precedence, the ones
+    // of the most axes first.
+    let mut combinations: Vec<_> = axis_values
+        .iter()
+        .filter_map(|axis_value| match axis_value {
+            AxisValue::Format4(combination) => Some(combination),
+            _ => None,
+        })
+        .collect();
+    combinations.sort_by_key(|combination| std::cmp::Reverse(combination.axis_count()));
+    for combination in combinations {
+        let records = combination.axis_values();
+        let is_match = !records.is_empty()
+            && records.iter().all(|record| {
+                let axis_index = record.axis_index() as usize;
+                is_at(axis_index, record.value()) && !is_named[axis_index]
+            });
+        if !is_match {
+            continue;
+        }
+        for record in records {
+            is_named[record.axis_index() as usize] = true;
+        }
+        fragments.push(StyleNameFragment {
+            ordering: records
+                .iter()
+                .map(|record| ordering(record.axis_index() as usize))
+                .min()
+                .unwrap_or_default(),
+            name_id: combination.value_name_id(),
+            is_elidable: combination
+                .flags()
+                .contains(AxisValueTableFlags::ELIDABLE_AXIS_VALUE_NAME),
+        });
+    }
+
+    for (axis_index, position) in positions.iter().enumerate() {
+        let Some(position) = *position else {
+            continue;
+        };
+        if is_named[axis_index] {
+            continue;
+        }
+        // Exact values take precedence over ranges, and ranges with the
+        // nearest nominal value over other ranges. The linked value of format
+        // 3 is the position of the bold counterpart for style linking and
+        // does not name this one.
+        let best_match = axis_values
+            .iter()
+            .filter_map(|axis_value| {
+                let distance = match axis_value {
+                    AxisValue::Format1(value) if value.axis_index() as usize == axis_index => {
+                        is_at(axis_index, value.value()).then_some(0)
+                    }
+                    AxisValue::Format3(value) if value.axis_index() as usize == axis_index => {
+                        is_at(axis_index, value.value()).then_some(0)
+                    }
+                    AxisValue::Format2(range) if range.axis_index() as usize == axis_index => {
+                        let min = position_of(axis_index, range.range_min_value(), true)?;
+                        let max = position_of(axis_index, range.range_max_value(), true)?;
+                        let nominal = position_of(axis_index, range.nominal_value(), true)?;
+                        (min..=max)
+                            .contains(&position)
+                            .then(|| 1 + u64::from(nominal.abs_diff(position)))
+                    }
+                    _ => None,
+                }?;
+                Some((distance, axis_value))
+            })
+            .min_by_key(|(distance, _)| *distance);
+        if let Some((_, axis_value)) = best_match {
+            fragments.push(StyleNameFragment {
+                ordering: ordering(axis_index),
+                name_id: axis_value.value_name_id(),
+                is_elidable: axis_value
+                    .flags()
+                    .contains(AxisValueTableFlags::ELIDABLE_AXIS_VALUE_NAME),
+            });
+        }
+    }
+
+    let name = |name_id| preferred_name(font, name_id).map(|(_, _, string)| string);
+    fragments.sort_by_key(|fragment| fragment.ordering);
+    let names: Vec<String> = fragments
+        .iter()
+        .filter(|fragment| !fragment.is_elidable)
+        .filter_map(|fragment| name(fragment.name_id))
+        .collect();
+    if names.is_empty() {
+        // STAT versions before 1.1 have no elided fallback name.
+        return name(
+            stat.elided_fallback_name_id()
+                .unwrap_or(StringId::SUBFAMILY_NAME),
+        );
+    }
+    Some(names.join(" "))
+}
+
+fn derive_style_name(
+    font_ref: &BridgeFontRef,
+    coords: &BridgeNormalizedCoords,
+    out_string: &mut String,
+) -> bool {
+    ffi_guard(|| {
+        let style_name = font_ref
+            .with_font(|f| stat_style_name(f, coords))
+            .or_else(|| english_or_first_font_name(font_ref, StringId::SUBFAMILY_NAME));
+        *out_string = style_name.clone().unwrap_or_default();
+        style_name.is_some()
+    })
+}
+
+/// Bits of the OS/2 fsType embedding permissions.
+const FS_TYPE_RESTRICTED_LICENSE: u16 = 0x0002;
+const FS_TYPE_PREVIEW_AND_PRINT: u16 = 0x0004;
+const FS_TYPE_EDITABLE: u16 = 0x0008;
+const FS_TYPE_NO_SUBSETTING: u16 = 0x0100;
+const FS_TYPE_BITMAP_ONLY: u16 = 0x0200;
+
+fn embedding_flags(font_ref: &BridgeFontRef) -> u16 {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| Some(f.os2().ok()?.fs_type()))
+            .unwrap_or_default()
+    })
+}
+
+fn can_embed(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        let fs_type = embedding_flags(font_ref);
+        // Of several usage permissions, the least restrictive applies, so a
+        // restricted license only forbids embedding on its own.
+        let is_restricted = fs_type & FS_TYPE_RESTRICTED_LICENSE != 0
+            && fs_type & (FS_TYPE_PREVIEW_AND_PRINT | FS_TYPE_EDITABLE) == 0;
+        !is_restricted && fs_type & FS_TYPE_BITMAP_ONLY == 0
+    })
+}
+
+fn can_subset(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| embedding_flags(font_ref) & FS_TYPE_NO_SUBSETTING == 0)
+}
+
+fn panose(font_ref: &BridgeFontRef, out: &mut [u8; 10]) -> bool {
+    ffi_guard(|| {
+        let panose = font_ref.with_font(|f| f.os2().ok()?.panose_10().try_into().ok());
+        *out = panose.unwrap_or_default();
+        panose.is_some()
+    })
+}
+
+fn unicode_range_bits(font_ref: &BridgeFontRef, out: &mut [u32; 4]) -> bool {
+    ffi_guard(|| {
+        let bits = font_ref.with_font(|f| {
+            let os2 = f.os2().ok()?;
+            Some([
+                os2.ul_unicode_range_1(),
+                os2.ul_unicode_range_2(),
+                os2.ul_unicode_range_3(),
+                os2.ul_unicode_range_4(),
+            ])
+        });
+        *out = bits.unwrap_or_default();
+        bits.is_some()
+    })
+}
+
+fn codepage_range_bits(font_ref: &BridgeFontRef, out: &mut [u32; 2]) -> bool {
+    ffi_guard(|| {
+        // The code page ranges were added in version 1.
+        let bits = font_ref.with_font(|f| {
+            let os2 = f.os2().ok()?;
+            Some([os2.ul_code_page_range_1()?, os2.ul_code_page_range_2()?])
+        });
+        *out = bits.unwrap_or_default();
+        bits.is_some()
+    })
+}
+
+/// Returns whether bit `index` is set in `ranges`, whose first element holds
+/// bits 0 to 31 like ulUnicodeRange1 and ulCodePageRange1.
+fn range_bit_is_set(ranges: &[u32], index: u32) -> bool {
+    ranges
+        .get((index / 32) as usize)
+        .is_some_and(|range| range & (1 << (index % 32)) != 0)
+}
+
+fn has_unicode_range(font_ref: &BridgeFontRef, index: u32) -> bool {
+    ffi_guard(|| {
+        let mut bits = [0; 4];
+        unicode_range_bits(font_ref, &mut bits) && range_bit_is_set(&bits, index)
+    })
+}
+
+fn has_codepage_range(font_ref: &BridgeFontRef, index: u32) -> bool {
+    ffi_guard(|| {
+        let mut bits = [0; 2];
+        codepage_range_bits(font_ref, &mut bits) && range_bit_is_set(&bits, index)
+    })
+}
+
+fn vendor_id(font_ref: &BridgeFontRef, out: &mut [u8; 4]) -> bool {
+    ffi_guard(|| {
+        let vendor_id = font_ref.with_font(|f| Some(f.os2().ok()?.ach_vend_id().into_bytes()));
+        *out = vendor_id.unwrap_or_default();
+        vendor_id.is_some()
+    })
+}
+
+fn vendor_id_string(font_ref: &BridgeFontRef) -> String {
+    ffi_guard(|| {
+        let mut id = [0; 4];
+        if !vendor_id(font_ref, &mut id) {
+            return String::new();
+        }
+        // IDs shorter than four characters are padded with spaces.
+        String::from_utf8_lossy(&id)
+            .trim_end_matches(' ')
+            .to_string()
+    })
+}
+
+/// PANOSE family kinds, the first of the ten bytes.
+const PANOSE_FAMILY_TEXT_AND_DISPLAY: u8 = 2;
+const PANOSE_FAMILY_SCRIPT: u8 = 3;
+
+/// Serif and script classification of a font for the flags of PDF font
+/// descriptors.
+#[derive(Debug, PartialEq)]
+enum FontClass {
+    Serif,
+    Script,
+    Other,
+}
+
+/// Classifies `font` for PDF by the PCLT serifStyle if the font has a PCLT
+/// table, as the FreeType port does. Otherwise, where the FreeType port leaves
+/// the font unclassified, by the PANOSE family kind and, for text fonts, serif
+/// style, as the DirectWrite port does.
+fn font_class(font: &FontRef) -> FontClass {
+    if let Some(pclt) = font.data_for_tag(Tag::new(b"PCLT")) {
+        return match pclt.read_at::<u8>(52).map(|serif_style| serif_style & 0x3F) {
+            Ok(2..=6) => FontClass::Serif,
+            Ok(9..=12) => FontClass::Script,
+            _ => FontClass::Other,
+        };
+    }
+    let Ok(os2) = font.os2() else {
+        return FontClass::Other;
+    };
+    match os2.panose_10() {
+        // Serif styles from cove to triangle, leaving out the sans styles.
+        [PANOSE_FAMILY_TEXT_AND_DISPLAY, 2..=10, ..] => FontClass::Serif,
+        [PANOSE_FAMILY_SCRIPT, ..] => FontClass::Script,
+        _ => FontClass::Other,
+    }
+}
+
+fn is_serif(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| Some(font_class(f) == FontClass::Serif))
+            .unwrap_or_default()
+    })
+}
+
+fn is_script(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| Some(font_class(f) == FontClass::Script))
+            .unwrap_or_default()
+    })
+}
+
+/// Returns post's italicAngle in counter-clockwise degrees from the
+/// vertical, 0.0 if the font has no post table.
+fn post_italic_angle(font: &FontRef) -> f32 {
+    font.post()
+        .map(|post| post.italic_angle().to_f32())
+        .unwrap_or_default()
+}
+
+fn italic_angle(font_ref: &BridgeFontRef) -> f32 {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| Some(post_italic_angle(f)))
+            .unwrap_or_default()
+    })
+}
+
+fn italic_angle_is_nonzero(font_ref: &BridgeFontRef) -> bool {
+    italic_angle(font_ref) != 0.0
+}
+
+/// The italicAngle in post describes the default instance only; no MVAR tag
+/// varies it. For fonts with a slnt axis the angle at `coords` is derived by
+/// offsetting it by the slnt axis' displacement from its default, which is
+/// expressed in the same unit and sign convention.
+fn italic_angle_at(font_ref: &BridgeFontRef, coords: &BridgeNormalizedCoords) -> f32 {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let slnt_offset = f
+                    .axes()
+                    .iter()
+                    .enumerate()
+                    .find(|(_, axis)| axis.tag() == Tag::new(b"slnt"))
+                    .map(|(i, axis)| design_coord(f, coords, i, &axis) - axis.default_value())
+                    .unwrap_or_default();
+                Some(post_italic_angle(f) + slnt_offset)
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Codepoints whose glyphs are sampled when checking for uniform advances:
+/// printable ASCII, excluding the space which some monospace fonts size
+/// differently.
+const FIXED_PITCH_SAMPLE_CODEPOINTS: RangeInclusive<u32> = 0x21..=0x7E;
+
+/// Returns whether the nonzero default advances of the glyphs mapped from
+/// `FIXED_PITCH_SAMPLE_CODEPOINTS` are all equal. Zero advances, as used by
+/// combining marks, are skipped. At least two glyphs need to be sampled for
+/// the advances to count as uniform.
+fn has_uniform_sample_advances(font: &FontRef) -> bool {
+    let charmap = font.charmap();
+    let glyph_metrics = GlyphMetrics::new(font, Size::unscaled(), LocationRef::default());
+    let mut advances = FIXED_PITCH_SAMPLE_CODEPOINTS
+        .filter_map(|codepoint| charmap.map(codepoint))
+        .filter_map(|glyph_id| glyph_metrics.advance_width(glyph_id))
+        .filter(|advance| *advance != 0.0);
+    let Some(first) = advances.next() else {
+        return false;
+    };
+    let mut num_sampled = 1;
+    for advance in advances {
+        if advance != first {
+            return false;
+        }
+        num_sampled += 1;
+    }
+    num_sampled >= 2
+}
+
+fn is_fixed_pitch(font_ref: &BridgeFontRef) -> bool {
+    ffi_guard(|| {
+        font_ref
+            .with_font(|f| {
+                let post_is_fixed_pitch = f.post().is_ok_and(|post| post.is_fixed_pitch() != 0);
+                Some(post_is_fixed_pitch || has_uniform_sample_advances(f))
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Returns the number of glyphs in `font`.
+///
+/// The maxp count is absent or too small in some broken fonts, so it is
+/// cross-checked against the number of glyphs with outlines: the loca entry
+/// count when both glyf and loca are present, otherwise the CharStrings INDEX
+/// count of CFF2 or CFF, matching the scaler's preference. The larger of the
+/// two is returned: glyph IDs without outline data merely fail to load,
+/// whereas an undercount would make valid glyphs inaccessible. A font with
+/// outlines thus never reports zero glyphs.
+fn glyph_count(font: &FontRef) -> u16 {
+    let maxp_count = font
+        .maxp()
+        .map(|maxp| maxp.num_glyphs())
+        .unwrap_or_default();
+    let outline_count = match (font.glyf(), font.loca(None)) {
+        (Ok(_), Ok(loca)) => loca.len(),
+        _ => CffCharstrings::new(font).map_or(0, |cff| cff.charstrings.count() as usize),
+    };
+    maxp_count.max(u16::try_from(outline_count).unwrap_or(u16::MAX))
+}
+
+struct BridgeFontRef<'a> {
+    font: Option<FontRef<'a>>,
+    // Collection index requested at construction, 0 for single fonts.
+    collection_index: u32,
+    // Glyph count from glyph_count(), cached for bounds checks of glyph IDs.
+    num_glyphs: u16,
+    // Font data owned by references created through make_font_ref_owned,
+    // which `font` borrows from. Allocated by Box::into_raw and freed on
+    // drop.
+    owned_data: Option<*mut [u8]>,
+}
+
+impl Drop for BridgeFontRef<'_> {
+    fn drop(&mut self) {
+        // Release the borrow of the owned data before freeing it.
+        self.font = None;
+        if let Some(owned_data) = self.owned_data.take() {
+            // SAFETY: `owned_data` comes from Box::into_raw in
+            // make_font_ref_owned and is freed only here, once.
+            drop(unsafe { Box::from_raw(owned_data) });
+        }
+    }
+}
+
+impl Default for BridgeFontRef<'_> {
+    fn default() -> Self {
+        Self::new(None, 0, None)
+    }
+}
+
+impl<'a> BridgeFontRef<'a> {
+    fn new(
+        font: Option<FontRef<'a>>,
+        collection_index: u32,
+        owned_data: Option<*mut [u8]>,
+    ) -> Self {
+        let num_glyphs = font.as_ref().map(glyph_count).unwrap_or_default();
+        Self {
+            font,
+            collection_index,
+            num_glyphs,
+            owned_data,
+        }
+    }
+
+    fn with_font<T>(&'a self, f: impl FnOnce(&'a FontRef) -> Option<T>) -> Option<T> {
+        f(self.font.as_ref()?)
+    }
+}
+
+#[derive(Default)]
+struct BridgeOutlineCollection<'a> {
+    outlines: Option<OutlineGlyphCollection<'a>>,
+    // Glyph count of the font the outlines belong to.
+    num_glyphs: u16,
+    // Outline tables for checking outlines against `BridgeOutlineLimits`
+    // before drawing them.
+    glyf: Option<(Loca<'a>, Glyf<'a>)>,
+    cff: Option<CffCharstrings<'a>>,
+    // Results of checking CFF charstrings against the limits, which takes
+    // about as long as drawing them, so that each outline is checked once.
+    cff_verdicts: Mutex<HashMap<CffVerdictKey, bool>>,
+}
+
+/// Glyph, location and the limits checked by `CffCharstrings::within_limits`
+/// a cached result applies to.
+#[derive(PartialEq, Eq, Hash)]
+struct CffVerdictKey {
+    glyph_id: GlyphId,
+    coords: Vec<F2Dot14>,
+    max_subroutine_depth: u16,
+    max_stem_hints: u32,
+    max_charstring_operations: u32,
+}
+
+/// Number of cached CFF results after which the cache is cleared, bounding
+/// its size for fonts drawn at many locations.
+const MAX_CFF_VERDICTS: usize = 4096;
+
+impl<'a> BridgeOutlineCollection<'a> {
+    fn new(font_ref: &'a BridgeFontRef<'a>) -> Self {
+        Self {
+            outlines: font_ref.with_font(|f| Some(f.outline_glyphs())),
+            num_glyphs: font_ref.num_glyphs,
+            glyf: font_ref.with_font(|f| Some((f.loca(None).ok()?, f.glyf().ok()?))),
+            cff: font_ref.with_font(CffCharstrings::new),
+            cff_verdicts: Mutex::default(),
+        }
+    }
+
+    /// Whether the outlines can be hinted. The skrifa version in use applies
+    /// CFF and CFF2 hints but does not execute TrueType instructions, so glyf
+    /// outlines are always drawn unhinted rather than merely rounded.
+    // TODO(AfterlifeOS/external_skia#synth-44): Hint glyf outlines once skrifa
+    // is rolled to a version with the TrueType interpreter.
+    fn supports_hinting(&self) -> bool {
+        matches!(
+            self.outlines
+                .as_ref()
+                .and_then(|outlines| outlines.format()),
+            Some(OutlineGlyphFormat::Cff | OutlineGlyphFormat::Cff2)
+        )
+    }
+
+    fn within_limits(
+        &self,
+        glyph_id: GlyphId,
+        coords: &BridgeNormalizedCoords,
+        limits: &BridgeOutlineLimits,
+    ) -> bool {
+        if let Some((loca, glyf)) = &self.glyf {
+            GlyfOutlineExtent::default().add_glyph(loca, glyf, glyph_id, 0, limits)
+        } else if let Some(cff) = &self.cff {
+            let key = CffVerdictKey {
+                glyph_id,
+                coords: coords.normalized_coords.coords().to_vec(),
+                max_subroutine_depth: limits.max_subroutine_depth,
+                max_stem_hints: limits.max_stem_hints,
+                max_charstring_operations: limits.max_charstring_operations,
+            };
+            let cached = self
+                .cff_verdicts
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(&key)
+                .copied();
+            // The lock is not held while checking, so that other threads can
+            // draw meanwhile.
+            cached.unwrap_or_else(|| {
+                let verdict = cff.within_limits(glyph_id, &key.coords, limits);
+                let mut verdicts = self
+                    .cff_verdicts
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                if verdicts.len() >= MAX_CFF_VERDICTS {
+                    verdicts.clear();
+                }
+                verdicts.insert(key, verdict);
+                verdict
+            })
+        } else {
+            true
+        }
+    }
+
+    /// Returns the hinting instance for `hinting` at the pixels per em of
+    /// `size`, or None for unhinted drawing: if no hinting is requested, the
+    /// size is unscaled, the outlines do not support hinting, or the instance
+    /// cannot be set up.
+    fn hinting_instance(
+        &self,
+        size: Size,
+        coords: &BridgeNormalizedCoords,
+        hinting: HintingLevel,
+    ) -> Option<EmbeddedHintingInstance> {
+        let mode = match hinting {
+            HintingLevel::Slight => EmbeddedHinting::Light,
+            HintingLevel::Full => EmbeddedHinting::Full,
+            _ => return None,
+        };
+        size.ppem()?;
+        if !self.supports_hinting() {
+            return None;
+        }
+        EmbeddedHintingInstance::new(
+            self.outlines.as_ref()?,
+            size,
+            &coords.normalized_coords,
+            mode,
+        )
+        .ok()
+    }
+}
+
+#[derive(Default)]
+struct BridgeNormalizedCoords {
+    normalized_coords: Location,
+    filtered_user_coords: Vec<VariationSetting>,
+}
+
+#[derive(Default)]
+struct BridgeLocalizedStrings<'a> {
+    name_id: StringId,
+    /// The strings of `name_id` with their records, from the best
+    /// `name_platform_rank` on and English first within a platform.
+    names: Vec<(LocalizedString<'a>, &'a NameRecord, String)>,
+    /// The index of the next name to return.
+    next: usize,
+    /// The strings and languages returned so far.
+    seen: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct BridgeAxisValueNames {
+    names: std::vec::IntoIter<BridgeAxisValueName>,
+}
+
+pub struct BridgeColorStops<'a> {
+    pub stops_iterator: Box<dyn Iterator<Item = &'a skrifa::color::ColorStop> + 'a>,
+    pub num_stops: usize,
+}
+
+#[cxx::bridge(namespace = "fontations_ffi")]
+mod ffi {
+    struct ColorStop {
+        stop: f32,
+        palette_index: u16,
+        alpha: f32,
+    }
+
+    #[derive(Default, Debug, PartialEq)]
+    struct Metrics {
+        /// Font bounding box from head: `top`, `bottom`, `x_min` and `x_max`
+        /// are 0 unless `bounds_is_valid` is set.
+        top: f32,
+        ascent: f32,
         descent: f32,
         bottom: f32,
         leading: f32,
@@ -797,425 +6104,9880 @@ mod ffi {
         max_char_width: f32,
         x_min: f32,
         x_max: f32,
-        x_height: f32,
-        cap_height: f32,
+        /// OS/2 sxHeight, or if that is missing or zero, the top of the
+        /// outline of 'x'.
+        x_height: f32,
+        /// OS/2 sCapHeight, or if that is missing or zero, the top of the
+        /// outline of 'H'.
+        cap_height: f32,
+        /// Set if `x_height` was measured from the outline of 'x'.
+        x_height_is_synthesized: bool,
+        /// Set if `cap_height` was measured from the outline of 'H'.
+        cap_height_is_synthesized: bool,
+        /// Bottom of the ideographic em box, positive above the baseline:
+        /// the 'ideo' baseline from BASE, or if that is missing, `descent`.
+        ideographic_bottom: f32,
+        /// Set if `ideographic_bottom` is `descent` rather than from BASE.
+        ideographic_bottom_is_synthesized: bool,
+        /// Top of the underline from post, positive above the baseline, with
+        /// the MVAR undo delta applied.
+        underline_position: f32,
+        /// Underline thickness from post, with the MVAR unds delta applied.
+        underline_thickness: f32,
+        /// Set if the font has a post table.
+        underline_position_is_valid: bool,
+        /// Set if the font has a post table with a positive underline
+        /// thickness.
+        underline_thickness_is_valid: bool,
+        /// Top of the strikeout from OS/2, positive above the baseline, with
+        /// the MVAR stro delta applied.
+        strikeout_position: f32,
+        /// Strikeout thickness from OS/2, with the MVAR strs delta applied.
+        strikeout_thickness: f32,
+        /// Set if the font has an OS/2 table.
+        strikeout_position_is_valid: bool,
+        /// Set if the font has an OS/2 table with a positive strikeout
+        /// thickness.
+        strikeout_thickness_is_valid: bool,
+        line_metrics_source: LineMetricsSource,
+        /// Set if the font has a head table providing the bounding box.
+        bounds_is_valid: bool,
+    }
+
+    /// Caret slope and offset from hhea, with MVAR deltas applied and scaled
+    /// to the requested size.
+    #[derive(Default, Debug, PartialEq)]
+    struct BridgeCaretInfo {
+        /// Rise and run of the caret slope, a vertical caret has a run of 0.
+        /// Only their ratio is meaningful.
+        slope_rise: f32,
+        slope_run: f32,
+        /// Horizontal shift of the caret from the advance position, nonzero
+        /// for slanted fonts to center the caret on the glyph's ink.
+        offset: f32,
+    }
+
+    /// Placement of a glyph in vertical text.
+    #[derive(Default, Debug, PartialEq)]
+    struct BridgeVerticalGlyphMetrics {
+        /// Position of the vertical origin relative to the horizontal
+        /// origin, y up: half the horizontal advance, and the top of the
+        /// vertical em box.
+        origin_x: f32,
+        origin_y: f32,
+        advance: f32,
+        /// Set if the font has no vmtx and the advance was made up from the
+        /// ascent and descent.
+        is_synthesized: bool,
+    }
+
+    /// Line metrics for vertical text from vhea, with MVAR deltas applied
+    /// and scaled to the requested size.
+    #[derive(Default, Debug, PartialEq)]
+    struct BridgeVerticalLineMetrics {
+        /// Distance from the vertical baseline to the right of the line,
+        /// positive for lines extending right.
+        ascender: f32,
+        /// Distance from the vertical baseline to the left of the line,
+        /// negative for lines extending left.
+        descender: f32,
+        line_gap: f32,
+    }
+
+    /// Subscript and superscript em box sizes and offsets from OS/2, with
+    /// MVAR deltas applied and scaled to the requested size.
+    #[derive(Default, Debug, PartialEq)]
+    struct BridgeScriptMetrics {
+        subscript_x_size: f32,
+        subscript_y_size: f32,
+        subscript_x_offset: f32,
+        /// Positive values move subscripts below the baseline.
+        subscript_y_offset: f32,
+        superscript_x_size: f32,
+        superscript_y_size: f32,
+        superscript_x_offset: f32,
+        /// Positive values move superscripts above the baseline.
+        superscript_y_offset: f32,
+    }
+
+    #[derive(Default, Debug, PartialEq)]
+    struct BridgeLocalizedName {
+        string: String,
+        /// The BCP 47 tag of the language in canonical case, mapped from
+        /// Macintosh language codes and Windows LCIDs as `SkOTTableName`
+        /// does, e.g. "de" for Macintosh code 2 and "pt-BR" for LCID 0x0416.
+        /// Empty if the language is unknown.
+        language: String,
+        /// The raw platformID of the name record, for debugging.
+        platform_id: u16,
+        /// The raw languageID of the name record, a language tag index from
+        /// 0x8000 on.
+        language_id: u16,
+    }
+
+    /// Name table strings of a font, as filled by `font_names`. Strings the
+    /// font lacks are empty.
+    #[derive(Default)]
+    struct BridgeFontNames {
+        /// Name ID 0.
+        copyright: String,
+        /// Name ID 3.
+        unique_id: String,
+        /// Name ID 4.
+        full_name: String,
+        /// Name ID 5.
+        version: String,
+        /// Name ID 8.
+        manufacturer: String,
+        /// Name ID 9.
+        designer: String,
+        /// Name ID 10.
+        description: String,
+        /// Name ID 13.
+        license: String,
+        /// Bit `1 << n` is set if the font has name ID `n`, to tell strings
+        /// the font lacks from empty ones.
+        present: u32,
+    }
+
+    /// The name of a STAT axis value table, as returned by
+    /// `axis_value_name_next`.
+    #[derive(Default)]
+    struct BridgeAxisValueName {
+        /// The tag of the axis named, 0 for combinations of several axes.
+        axis: u32,
+        /// The (nominal) value named, 0.0 for combinations of several axes.
+        value: f32,
+        /// Whether the name is left out of the names of instances.
+        is_elidable: bool,
+        string: String,
+        language: String,
+    }
+
+    struct SkiaDesignCoordinate {
+        axis: u32,
+        value: f32,
+    }
+
+    #[derive(Default, Clone, Copy)]
+    struct BridgeAxisInfo {
+        tag: u32,
+        min: f32,
+        def: f32,
+        max: f32,
+        hidden: bool,
+    }
+
+    /// Tables a face needs to render text, as found by
+    /// `render_prerequisites`.
+    #[derive(Default, Debug, PartialEq)]
+    struct BridgeRenderPrerequisites {
+        /// head is readable and has a nonzero unitsPerEm.
+        has_head: bool,
+        has_hhea: bool,
+        /// maxp is readable and declares at least one glyph.
+        has_maxp: bool,
+        /// cmap has a subtable that `lookup_glyph_or_zero` can map with.
+        has_cmap: bool,
+        /// glyf and loca, or else CFF2 or CFF, are readable and cover every
+        /// glyph.
+        has_outlines: bool,
+    }
+
+    /// Outcome of verifying table checksums against the table directory.
+    #[derive(Default)]
+    struct BridgeChecksumResult {
+        /// False if the table directory or table data could not be read. The
+        /// remaining fields are only meaningful if true.
+        verifiable: bool,
+        /// Number of tables whose data does not match its directory checksum.
+        num_mismatched_tables: usize,
+        /// Whether head's checksumAdjustment matches the font's checksum.
+        /// False if there is no head table.
+        checksum_adjustment_valid: bool,
+    }
+
+    /// Description of a cmap subtable for diagnostics.
+    #[derive(Default, Clone, Copy)]
+    struct BridgeCmapSubtableInfo {
+        platform_id: u16,
+        encoding_id: u16,
+        format: u16,
+        /// Number of mapped codepoints, or of variation sequences for format
+        /// 14. Zero for formats that are not read by the bridge.
+        num_entries: u32,
+        /// Whether this subtable backs `lookup_glyph_or_zero`.
+        selected: bool,
+    }
+
+    /// Diagnostics collected while resolving design coordinates.
+    #[derive(Default)]
+    struct CoordinateResolutionReport {
+        /// Number of design coordinates that were clamped to their axis range.
+        num_clamped: usize,
+        /// Axis tags, in order of first appearance, that are not in fvar and
+        /// were ignored.
+        unknown_axes: Vec<u32>,
+    }
+
+    /// Conservative bounds of an outline drawn by `get_path` and its
+    /// variants, covering all on-curve and control points in the coordinates
+    /// of the emitted path, i.e. transformed and y-flipped.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct BridgePathBounds {
+        /// True if no point was emitted, in which case the coordinates are 0.
+        is_empty: bool,
+        x_min: f32,
+        y_min: f32,
+        x_max: f32,
+        y_max: f32,
+    }
+
+    #[derive(Default)]
+    struct BridgeScalerMetrics {
+        /// True if the drawn glyf outline is flagged as overlapping, like
+        /// `glyph_has_overlaps`. False for CFF and CFF2 outlines.
+        has_overlaps: bool,
+        /// Bounds of the emitted path, empty unless the outline was drawn
+        /// with `PathStatus::Success`.
+        bounds: BridgePathBounds,
+    }
+
+    /// Maximum extent of hinted outlines at a pixel size from VDMX.
+    #[derive(Default, Clone, Copy, Debug, PartialEq)]
+    struct BridgeVdmxValues {
+        /// Highest pixel of any glyph, positive above the baseline.
+        y_max: i16,
+        /// Lowest pixel of any glyph, negative below the baseline.
+        y_min: i16,
+    }
+
+    /// Advance widths of a glyph for measuring and for rasterizing text.
+    #[derive(Default, Clone, Copy, Debug, PartialEq)]
+    struct BridgeAdvances {
+        /// As returned by `advance_width_or_zero`, independent of hinting.
+        linear: f32,
+        /// As returned by `hinted_advance_width_or_zero`.
+        hinted: f32,
+    }
+
+    /// Unhinted metrics of a glyph, as returned by `glyph_metrics`.
+    #[derive(Default, Clone, Copy, Debug, PartialEq)]
+    struct BridgeGlyphMetrics {
+        /// As returned by `advance_width_or_zero`.
+        advance_width: f32,
+        /// As returned by `left_side_bearing_or_zero`.
+        left_side_bearing: f32,
+        /// As returned by `glyph_bounds`, in the y-down space of `get_path`.
+        bounds: BridgePathBounds,
+        /// True if the outline has no points, same as `bounds.is_empty`.
+        is_empty: bool,
+    }
+
+    /// Bounds on the work `get_path` spends on a single glyph. Points,
+    /// contours and composite depth are checked for TrueType outlines, and
+    /// subroutine depth, stem hints and charstring operations for CFF and
+    /// CFF2 outlines, before drawing. The segment limit applies to all
+    /// outline formats.
+    #[derive(Clone, Copy)]
+    struct BridgeOutlineLimits {
+        /// Maximum number of points across a glyph and its components.
+        max_points: u32,
+        /// Maximum number of contours across a glyph and its components.
+        max_contours: u32,
+        /// Maximum nesting of composite glyphs. Zero rejects composites.
+        max_composite_depth: u16,
+        /// Maximum number of path segments drawn for a glyph.
+        max_path_segments: u32,
+        /// Maximum nesting of charstring subroutine calls. The evaluator
+        /// fails charstrings nested deeper than 10 regardless.
+        max_subroutine_depth: u16,
+        /// Maximum number of stem hints declared by a charstring, which
+        /// bounds the size of hint masks.
+        max_stem_hints: u32,
+        /// Maximum number of charstring operators evaluated for a glyph,
+        /// including those in subroutines.
+        max_charstring_operations: u32,
+    }
+
+    /// Affine transform applied by `get_path` to outline points in font
+    /// coordinates, before the y-axis is flipped for Skia:
+    /// x' = xx * x + xy * y + dx, y' = yx * x + yy * y + dy.
+    /// A synthetic oblique of slant s is { xx: 1, xy: s, yx: 0, yy: 1 }.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct BridgePathTransform {
+        xx: f32,
+        xy: f32,
+        yx: f32,
+        yy: f32,
+        dx: f32,
+        dy: f32,
+    }
+
+    /// Table the ascent, descent and leading of `Metrics` are read from.
+    #[derive(Debug)]
+    enum LineMetricsSource {
+        /// hhea ascender, descender and lineGap.
+        Hhea,
+        /// OS/2 sTypoAscender, sTypoDescender and sTypoLineGap, used if
+        /// fsSelection sets USE_TYPO_METRICS, or if hhea has neither ascent
+        /// nor descent.
+        Typo,
+        /// OS/2 usWinAscent and usWinDescent, with the descent negated to
+        /// point down like the others, used if hhea and the typo metrics
+        /// have neither ascent nor descent. The leading is hhea's lineGap.
+        /// Varied by the MVAR hcla and hcld deltas rather than hasc and hdsc.
+        Win,
+        /// Synthesized as 0.8 em ascent and 0.2 em descent without leading,
+        /// used if the metrics chosen otherwise have neither ascent nor
+        /// descent, or the font has neither hhea nor OS/2.
+        UnitsPerEm,
+    }
+
+    /// Grid fitting applied to CFF and CFF2 outlines by `get_path` and
+    /// `hinted_advance_width_or_zero`, mirroring `SkFontHinting`. TrueType
+    /// outlines are not hinted yet, see `outlines_support_hinting`.
+    #[derive(Debug)]
+    enum HintingLevel {
+        /// Unhinted outlines and linear advances.
+        None,
+        /// Hinting that avoids horizontal changes, with linear advances.
+        Slight,
+        /// Hinting in both directions, with advances rounded to whole pixels.
+        Full,
+    }
+
+    /// Where `left_side_bearing_or_zero` takes the variation of a glyph's
+    /// side bearing from.
+    #[derive(Debug)]
+    enum SideBearingSource {
+        /// hmtx as is, at the default location or for fonts that vary
+        /// neither by HVAR side bearing deltas nor by gvar.
+        Hmtx,
+        /// The HVAR side bearing delta of the glyph.
+        Hvar,
+        /// The gvar deltas of the glyph's phantom points, for glyf fonts
+        /// whose HVAR table is missing or has no side bearing delta for it.
+        PhantomPoints,
+    }
+
+    /// How the advance and metrics functions treat fractional sizes.
+    #[derive(Debug)]
+    enum SizeRounding {
+        /// Scale by the requested size as is.
+        Exact,
+        /// Quantize the size to 1/64 pixel and scale with 16.16 fixed point
+        /// arithmetic like FreeType, giving lengths in 1/64 pixels that
+        /// match the FreeType port. The ascent and descent are grid-fitted
+        /// to whole pixels as in FreeType's size metrics.
+        Fixed26Dot6,
+        /// Round the size to whole pixels per em.
+        IntegerPpem,
+    }
+
+    /// Where `postscript_name_or_synthesized` took the PostScript name from.
+    #[derive(Debug)]
+    enum PostScriptNameSource {
+        /// Name ID 6 as is.
+        NameTable,
+        /// Name ID 6 with the characters PostScript names disallow removed
+        /// or truncated to their maximum length.
+        Sanitized,
+        /// Synthesized from the family and subfamily names, because name ID 6
+        /// is missing or has no allowed characters.
+        Synthesized,
+        /// There is neither name ID 6 nor a family name.
+        Missing,
+    }
+
+    /// Drawing commands of the packed outlines filled by `get_path_verbs`,
+    /// stored as one byte each.
+    #[derive(Debug)]
+    #[repr(u8)]
+    enum PathVerb {
+        /// Starts a contour at one point.
+        MoveTo = 0,
+        /// Adds a line to one point.
+        LineTo = 1,
+        /// Adds a quadratic curve through a control point to an end point.
+        QuadTo = 2,
+        /// Adds a cubic curve through two control points to an end point.
+        CurveTo = 3,
+        /// Closes the current contour, consuming no points.
+        Close = 4,
+    }
+
+    /// Result of drawing a glyph outline with `get_path`.
+    #[derive(Debug)]
+    enum PathStatus {
+        /// The outline was drawn.
+        Success,
+        /// The glyph exists but its outline draws nothing, e.g. a space.
+        EmptyOutline,
+        /// The glyph ID is not below the font's glyph count.
+        GlyphOutOfRange,
+        /// The glyph is not present in the font's outline tables.
+        GlyphNotFound,
+        /// The glyph's outline data is malformed or could not be scaled.
+        DrawError,
+        /// The outline exceeds the `BridgeOutlineLimits` passed to `get_path`.
+        /// Any partially drawn path has been reset.
+        LimitsExceeded,
+        /// The size is negative or not finite.
+        InvalidSize,
+    }
+
+    struct PaletteOverride {
+        index: u16,
+        color_8888: u32,
+    }
+
+    /// A layer of a COLRv0 glyph, listed bottom up.
+    #[derive(Default, Clone, Copy, Debug, PartialEq)]
+    struct BridgeColorLayer {
+        /// The glyph whose outline, as drawn by `get_path`, the layer fills.
+        glyph_id: u16,
+        /// The CPAL palette entry to fill it with, 0xFFFF if
+        /// `uses_foreground_color`.
+        palette_index: u16,
+        /// Whether the layer is filled with the text foreground color.
+        uses_foreground_color: bool,
+    }
+
+    struct ClipBox {
+        x_min: f32,
+        y_min: f32,
+        x_max: f32,
+        y_max: f32,
+    }
+
+    struct Transform {
+        xx: f32,
+        xy: f32,
+        yx: f32,
+        yy: f32,
+        dx: f32,
+        dy: f32,
+    }
+
+    struct FillLinearParams {
+        x0: f32,
+        y0: f32,
+        x1: f32,
+        y1: f32,
+    }
+
+    struct FillRadialParams {
+        x0: f32,
+        y0: f32,
+        r0: f32,
+        x1: f32,
+        y1: f32,
+        r1: f32,
+    }
+
+    struct FillSweepParams {
+        x0: f32,
+        y0: f32,
+        start_angle: f32,
+        end_angle: f32,
+    }
+
+    // This type is used to mirror SkFontStyle values for Weight, Slant and Width
+    pub struct BridgeFontStyle {
+        pub weight: i32,
+        pub slant: i32,
+        pub width: i32,
+    }
+
+    extern "Rust" {
+        type BridgeFontRef<'a>;
+        unsafe fn make_font_ref<'a>(font_data: &'a [u8], index: u32) -> Box<BridgeFontRef<'a>>;
+        /// Like `make_font_ref`, but takes ownership of `font_data` so that
+        /// the returned reference does not depend on the caller keeping the
+        /// data alive. All functions taking a BridgeFontRef accept either.
+        unsafe fn make_font_ref_owned<'a>(font_data: Vec<u8>, index: u32)
+            -> Box<BridgeFontRef<'a>>;
+        // Returns whether BridgeFontRef is a valid font containing at
+        // least a valid sfnt structure from which tables can be
+        // accessed. This is what instantiation in make_font_ref checks
+        // for. (see FontRef::new in read_fonts's lib.rs). Implemented
+        // by returning whether the option is Some() and thus whether a
+        // FontRef instantiation succeeded and a table directory was
+        // accessible.
+        fn font_ref_is_valid(bridge_font_ref: &BridgeFontRef) -> bool;
+        /// Checks the tables a face needs to render text without scaling any
+        /// glyphs. Everything is missing for an invalid font reference.
+        fn render_prerequisites(font_ref: &BridgeFontRef) -> BridgeRenderPrerequisites;
+        /// Returns whether all of `render_prerequisites` are present, a
+        /// stronger check than `font_ref_is_valid`.
+        fn font_ref_is_renderable(font_ref: &BridgeFontRef) -> bool;
+        /// Returns the collection index `font_ref` was created with.
+        fn font_ref_collection_index(font_ref: &BridgeFontRef) -> u32;
+        /// Returns the size of the face's table directory plus the lengths
+        /// of its tables, or 0 for an invalid font reference.
+        fn font_ref_data_size(font_ref: &BridgeFontRef) -> usize;
+
+        type BridgeOutlineCollection<'a>;
+        unsafe fn get_outline_collection<'a>(
+            font_ref: &'a BridgeFontRef<'a>,
+        ) -> Box<BridgeOutlineCollection<'a>>;
+        /// Returns true if `get_path` and the hinted advance functions hint
+        /// these outlines. Only CFF and CFF2 outlines are hinted; TrueType
+        /// outlines are drawn unhinted at every hinting level.
+        fn outlines_support_hinting(outlines: &BridgeOutlineCollection) -> bool;
+
+        /// Returns true on a font or collection, sets `num_fonts``
+        /// to 0 if single font file, and to > 0 for a TrueType collection.
+        /// Returns false if the data cannot be interpreted as a font or collection.
+        unsafe fn font_or_collection<'a>(font_data: &'a [u8], num_fonts: &mut u32) -> bool;
+        /// Returns the number of fonts in `font_data`: 1 for a single font,
+        /// the number of faces for a collection and 0 if the data cannot be
+        /// interpreted as either. `make_font_ref` accepts indices below this
+        /// count.
+        fn font_count(font_data: &[u8]) -> u32;
+        /// Writes a standalone font containing the tables of face `index` of
+        /// `font_data` to `out`. Tables shared within the face are stored
+        /// once and head's checksumAdjustment is recomputed. Returns false
+        /// and leaves `out` empty if the face or its tables cannot be read.
+        fn extract_font_from_collection(font_data: &[u8], index: u32, out: &mut Vec<u8>) -> bool;
+        /// Verifies each table's data against its directory checksum and
+        /// head's checksumAdjustment against the checksum of the face's
+        /// table directory and tables, following the OpenType algorithm.
+        fn verify_checksums(font_ref: &BridgeFontRef) -> BridgeChecksumResult;
+        /// Returns a 64-bit xxHash digest of the selected face's tables in
+        /// tag order, covering each tag, length and table data but not the
+        /// directory layout or padding. Stable across process runs, 0 for an
+        /// invalid font reference.
+        fn font_digest(font_ref: &BridgeFontRef) -> u64;
+
+        fn lookup_glyph_or_zero(font_ref: &BridgeFontRef, codepoint: u32) -> u16;
+        /// Returns whether the selected charmap is a Windows Symbol (3,0)
+        /// subtable. Lookups against such a charmap retry codepoints in the
+        /// range 0x00-0xFF at U+F000-U+F0FF, matching FreeType.
+        fn has_symbol_charmap(font_ref: &BridgeFontRef) -> bool;
+        /// Replaces the contents of `out` with a description of each cmap
+        /// subtable in encoding record order.
+        fn cmap_subtable_info(font_ref: &BridgeFontRef, out: &mut Vec<BridgeCmapSubtableInfo>);
+        /// Maps `codepoints` to nominal glyph IDs in `glyphs`, using 0 for
+        /// unmapped codepoints. Maps as many entries as the shorter of the two
+        /// slices holds and returns the number of non-zero glyph IDs.
+        fn lookup_glyphs(font_ref: &BridgeFontRef, codepoints: &[u32], glyphs: &mut [u16])
+            -> usize;
+        /// Returns the number of codepoints mapped by the font's charmap, for
+        /// sizing the buffers passed to `fill_unichar_map`.
+        fn count_mapped_unichars(font_ref: &BridgeFontRef) -> usize;
+        /// Fills `out_unichars` and `out_glyphs` with the charmap's codepoint
+        /// to glyph mappings in ascending codepoint order, including
+        /// supplementary planes. Fills as many entries as the shorter slice
+        /// holds and returns the number of entries written.
+        fn fill_unichar_map(
+            font_ref: &BridgeFontRef,
+            out_unichars: &mut [u32],
+            out_glyphs: &mut [u16],
+        ) -> usize;
+        /// Fills `out_codepoints`, indexed by glyph ID, with the lowest
+        /// codepoint mapping to each glyph, or 0 for unmapped glyphs.
+        fn glyphs_to_unicode(font_ref: &BridgeFontRef, out_codepoints: &mut [u32]);
+        /// Returns whether the font's charmap contains an explicit mapping
+        /// for `codepoint`, including mappings to glyph 0.
+        fn has_codepoint(font_ref: &BridgeFontRef, codepoint: u32) -> bool;
+        /// Maps a Unicode variation sequence to a glyph ID using the cmap
+        /// format 14 subtable. Sequences listed as default UVS resolve through
+        /// the regular codepoint mapping. Returns 0 if the font has no mapping
+        /// for the sequence, in which case callers should fall back to
+        /// `lookup_glyph_or_zero`.
+        fn lookup_glyph_variant(
+            font_ref: &BridgeFontRef,
+            codepoint: u32,
+            variation_selector: u32,
+        ) -> u16;
+        /// Returns the default `BridgeOutlineLimits`, which are generous
+        /// enough for real fonts.
+        fn default_outline_limits() -> BridgeOutlineLimits;
+        /// Returns the identity `BridgePathTransform`.
+        fn identity_path_transform() -> BridgePathTransform;
+        /// Draws the outline of `glyph_id` at `size` pixels per em, or in
+        /// font units if `size` is 0, with points mapped through `transform`.
+        /// A nonzero `embolden_strength` grows the outline by that many pixels,
+        /// or font units, horizontally and vertically before it is
+        /// transformed, see `emboldened_advance_width_or_zero`. Outlines are
+        /// hinted at the pixels per em of `size` according to `hinting`, and
+        /// drawn unhinted if the font has no hinting data. With
+        /// `clean_up_segments`, segments not moving the pen and moves not
+        /// followed by a segment are dropped, and open contours are closed
+        /// before the next starts; otherwise the outline is emitted as drawn.
+        #[allow(clippy::too_many_arguments)]
+        fn get_path(
+            outlines: &BridgeOutlineCollection,
+            glyph_id: u16,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            limits: &BridgeOutlineLimits,
+            transform: &BridgePathTransform,
+            embolden_strength: f32,
+            hinting: HintingLevel,
+            clean_up_segments: bool,
+            path_wrapper: Pin<&mut PathWrapper>,
+            scaler_metrics: &mut BridgeScalerMetrics,
+        ) -> PathStatus;
+        /// Same as `get_path`, but packs the outline into `verbs`, one
+        /// `PathVerb` per byte, and `points`, with x and y of each point
+        /// consecutive and y already flipped. Both buffers are cleared first
+        /// and left empty unless the outline is drawn.
+        #[allow(clippy::too_many_arguments)]
+        fn get_path_verbs(
+            outlines: &BridgeOutlineCollection,
+            glyph_id: u16,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            limits: &BridgeOutlineLimits,
+            transform: &BridgePathTransform,
+            embolden_strength: f32,
+            hinting: HintingLevel,
+            verbs: &mut Vec<u8>,
+            points: &mut Vec<f32>,
+            scaler_metrics: &mut BridgeScalerMetrics,
+        ) -> PathStatus;
+        /// Draws the outlines of `glyph_ids` like `get_path`, sharing the
+        /// scaling and hinting setup across the run. Each glyph is drawn to
+        /// the path wrapper returned by `begin_glyph` and finished with
+        /// `end_glyph`, which receives its status; paths of glyphs failing to
+        /// draw are to be discarded. Failures do not stop the run. Returns the
+        /// number of glyphs drawn with `Success` or `EmptyOutline`.
+        #[allow(clippy::too_many_arguments)]
+        fn get_paths(
+            outlines: &BridgeOutlineCollection,
+            glyph_ids: &[u16],
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            limits: &BridgeOutlineLimits,
+            transform: &BridgePathTransform,
+            embolden_strength: f32,
+            hinting: HintingLevel,
+            paths_wrapper: Pin<&mut GlyphPathsWrapper>,
+        ) -> usize;
+        /// Same as `get_path` at size 0 and without hinting: emits the outline in font units,
+        /// y-flipped like `get_path`, so that one path serves all sizes when
+        /// scaled by size / units per em. The matching advance is
+        /// `advance_width_or_zero` at size 0.
+        #[allow(clippy::too_many_arguments)]
+        fn get_path_unscaled(
+            outlines: &BridgeOutlineCollection,
+            glyph_id: u16,
+            coords: &BridgeNormalizedCoords,
+            limits: &BridgeOutlineLimits,
+            transform: &BridgePathTransform,
+            embolden_strength: f32,
+            path_wrapper: Pin<&mut PathWrapper>,
+            scaler_metrics: &mut BridgeScalerMetrics,
+        ) -> PathStatus;
+        /// Returns true if the outline of `glyph_id` is flagged as having
+        /// overlapping contours or components, which need a winding fill
+        /// cleanup for good antialiasing, see `BridgeScalerMetrics`. Reads
+        /// the OVERLAP_SIMPLE and OVERLAP_COMPOUND flags of glyf outlines
+        /// without drawing them, and returns true for all glyphs of CFF and
+        /// CFF2 fonts, which have no such flags. Returns false for glyph IDs
+        /// out of range and fonts without outlines.
+        fn glyph_has_overlaps(font_ref: &BridgeFontRef, glyph_id: u16) -> bool;
+        /// Returns true if `glyph_id` has an outline that draws nothing at
+        /// `coords`, such as a space or a composite of empty components.
+        /// Returns false for glyph IDs out of range, glyphs without an
+        /// outline and glyphs that fail to draw.
+        fn is_empty_glyph(
+            font_ref: &BridgeFontRef,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+        ) -> bool;
+        /// Applies `is_empty_glyph` to as many `glyph_ids` as `out_is_empty`
+        /// holds. Returns the number of empty glyphs.
+        fn are_empty_glyphs(
+            font_ref: &BridgeFontRef,
+            coords: &BridgeNormalizedCoords,
+            glyph_ids: &[u16],
+            out_is_empty: &mut [bool],
+        ) -> usize;
+        /// Fills `out` with the bounds of all on-curve and control points of
+        /// the unhinted outline of `glyph_id` at `size` pixels per em, or in
+        /// font units if `size` is 0, in the y-down space of `get_path`.
+        /// Cheaper than drawing the path, as static glyf outlines are not
+        /// drawn at all. Returns false and empty bounds for invalid sizes,
+        /// glyph IDs out of range and outlines that fail to draw.
+        fn glyph_bounds(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+            out: &mut BridgePathBounds,
+        ) -> bool;
+        /// Applies `glyph_bounds` to as many `glyph_ids` as `out_bounds`
+        /// holds. Returns the number of glyphs whose bounds were computed,
+        /// failed glyphs report empty bounds.
+        fn glyph_bounds_batch(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_ids: &[u16],
+            out_bounds: &mut [BridgePathBounds],
+        ) -> usize;
+        /// Returns the advance width of `glyph_id` at `size` pixels per em
+        /// rounded according to `rounding`, or in font units if `size` is 0.
+        /// Returns 0 for negative and non-finite sizes.
+        fn advance_width_or_zero(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+            rounding: SizeRounding,
+        ) -> f32;
+        /// Fills `out_advances` with `advance_width_or_zero` of as many
+        /// `glyph_ids` as both slices hold, setting up the metrics once for
+        /// the whole batch. Returns the number of advances written.
+        fn advance_widths(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_ids: &[u16],
+            out_advances: &mut [f32],
+            rounding: SizeRounding,
+        ) -> usize;
+        /// Returns the advance width of `glyph_id` in font units, with HVAR
+        /// or gvar deltas for `coords` truncated like FreeType, which keeps
+        /// it consistent with `advance_width_or_zero`. Returns 0 for glyph
+        /// IDs out of range.
+        fn advance_width_font_units(
+            font_ref: &BridgeFontRef,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+        ) -> i32;
+        /// Batch variant of `advance_width_font_units`, see `advance_widths`.
+        fn advance_widths_font_units(
+            font_ref: &BridgeFontRef,
+            coords: &BridgeNormalizedCoords,
+            glyph_ids: &[u16],
+            out_advances: &mut [i32],
+        ) -> usize;
+        /// Returns the advance height of `glyph_id` from vmtx at `size`
+        /// pixels per em, or in font units if `size` is 0. Glyph IDs beyond
+        /// numOfLongVerMetrics repeat the last advance. Applies VVAR deltas
+        /// for non-default `coords`. If the font has no vertical metrics,
+        /// returns the typo ascent minus descent as FreeType does and sets
+        /// `out_is_synthesized`. Returns 0 for negative and non-finite sizes.
+        fn advance_height_or_zero(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+            out_is_synthesized: &mut bool,
+        ) -> f32;
+        /// Returns the top side bearing of `glyph_id` from vmtx, scaled like
+        /// `advance_height_or_zero`. Glyph IDs beyond numOfLongVerMetrics
+        /// read the trailing topSideBearings array. Applies VVAR deltas. If
+        /// the font has no vertical metrics, returns the synthesized ascent
+        /// of `advance_height_or_zero` minus the top of the outline.
+        fn top_side_bearing_or_zero(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+        ) -> f32;
+        /// Fills `out_side_bearings` with `top_side_bearing_or_zero` of as
+        /// many `glyph_ids` as both slices hold, loading the outlines once
+        /// for the whole batch. Returns the number of side bearings written.
+        fn top_side_bearings(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_ids: &[u16],
+            out_side_bearings: &mut [f32],
+        ) -> usize;
+        /// Sets `out` to the y coordinate of the vertical origin of
+        /// `glyph_id` from VORG at `size` pixels per em, or in font units if
+        /// `size` is 0, with VVAR deltas for `coords`. Glyphs without an
+        /// entry use defaultVertOriginY. Returns false and sets `out` to 0 if
+        /// the font has no VORG table, so that callers can fall back to
+        /// `top_side_bearing_or_zero`, or for invalid sizes and glyph IDs.
+        fn vertical_origin_y(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+            out: &mut f32,
+        ) -> bool;
+        /// Fills `out` with the vertical origin and advance of `glyph_id` at
+        /// `size` pixels per em, or in font units if `size` is 0. Uses VORG
+        /// and vmtx when present. Otherwise the origin is the synthesized
+        /// ascent of `advance_height_or_zero`, or the top of the outline
+        /// plus its top side bearing if only VORG is missing. Returns false
+        /// and resets `out` for invalid sizes and glyph IDs.
+        fn vertical_glyph_metrics(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+            out: &mut BridgeVerticalGlyphMetrics,
+        ) -> bool;
+        /// Returns the left side bearing of `glyph_id` from hmtx, scaled
+        /// like `advance_width_or_zero`. Glyph IDs beyond numberOfHMetrics
+        /// read the trailing leftSideBearings array. Applies HVAR deltas, or
+        /// gvar phantom point deltas if HVAR has none for the glyph.
+        fn left_side_bearing_or_zero(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+        ) -> f32;
+        /// Reports where `left_side_bearing_or_zero` takes the variation of
+        /// the side bearing of `glyph_id` from at `coords`, for debugging
+        /// mispositioned glyphs. `Hmtx` for glyph IDs out of range.
+        fn left_side_bearing_source(
+            font_ref: &BridgeFontRef,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+        ) -> SideBearingSource;
+        /// Fills `out` with the advance, side bearing, bounds and emptiness
+        /// of `glyph_id` at `size` pixels per em, or in font units if `size`
+        /// is 0, in one call. Returns false and default metrics for invalid
+        /// sizes, glyph IDs out of range and outlines that fail to draw.
+        fn glyph_metrics(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+            out: &mut BridgeGlyphMetrics,
+        ) -> bool;
+        /// Applies `glyph_metrics` to as many `glyph_ids` as `out_metrics`
+        /// holds, setting up the metrics tables and outlines once. Returns
+        /// the number of glyphs whose metrics were computed.
+        fn glyph_metrics_batch(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_ids: &[u16],
+            out_metrics: &mut [BridgeGlyphMetrics],
+        ) -> usize;
+        /// Returns `advance_width_or_zero` widened by `embolden_strength`, for
+        /// callers that opt into advancing emboldened outlines drawn by
+        /// `get_path` with the same strength by that much more. FreeType's
+        /// emboldening leaves advances unchanged. Zero advances stay zero.
+        fn emboldened_advance_width_or_zero(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+            embolden_strength: f32,
+        ) -> f32;
+        /// Returns the advance width of `glyph_id` matching outlines drawn
+        /// with `hinting`: the linear advance for `None` and `Slight`, and
+        /// the hinted advance rounded to whole pixels for `Full`.
+        fn hinted_advance_width_or_zero(
+            font_ref: &BridgeFontRef,
+            outlines: &BridgeOutlineCollection,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+            hinting: HintingLevel,
+        ) -> f32;
+        /// Returns both the linear advance of `glyph_id`, for laying out text
+        /// independently of hinting, and the advance matching outlines drawn
+        /// with `hinting`, for rasterizing it, from one pass over the glyph.
+        /// The two are identical unless `hinting` is `Full` and the font is
+        /// hinted.
+        fn linear_and_hinted_advances(
+            font_ref: &BridgeFontRef,
+            outlines: &BridgeOutlineCollection,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+            hinting: HintingLevel,
+        ) -> BridgeAdvances;
+        /// Sets `out` to the integer advance of `glyph_id` at `ppem` pixels
+        /// per em from the hdmx device record for `ppem`. Returns false and
+        /// sets `out` to 0 if there is no such record, the record size is
+        /// inconsistent with the glyph count of maxp, or the font is
+        /// variable.
+        fn hdmx_advance(font_ref: &BridgeFontRef, ppem: u8, glyph_id: u16, out: &mut u8) -> bool;
+        /// Returns the gasp behavior bits of the first range covering `ppem`:
+        /// 0x1 grid-fit, 0x2 antialias, 0x4 symmetric grid-fit and 0x8
+        /// symmetric smoothing. Bits undefined by the table version are
+        /// cleared. Returns 0x3 if the font has no gasp table or no range
+        /// covers `ppem`.
+        fn gasp_behavior(font_ref: &BridgeFontRef, ppem: u16) -> u16;
+        fn units_per_em_or_zero(font_ref: &BridgeFontRef) -> u16;
+        /// Sets `out_mac_style` and `out_flags` to the raw head macStyle
+        /// and flags, e.g. bit 13 for fonts optimized for ClearType and bit
+        /// 14 for last resort fonts. Returns false and zeroes if there is no
+        /// head table.
+        fn head_style_and_flags(
+            font_ref: &BridgeFontRef,
+            out_mac_style: &mut u16,
+            out_flags: &mut u16,
+        ) -> bool;
+        /// Returns the head lowestRecPPEM, the smallest readable size in
+        /// pixels per em, or 0 if there is no head table.
+        fn lowest_rec_ppem_or_zero(font_ref: &BridgeFontRef) -> u16;
+        /// Returns the font metrics at `size` pixels per em rounded according
+        /// to `rounding`, or in font units if `size` is 0. Returns default
+        /// metrics for negative and non-finite sizes. If `use_vdmx` is set, `hinting` is not `None`
+        /// and `size` is a whole number of pixels covered by the VDMX table,
+        /// ascent and descent are replaced by the VDMX values so that they
+        /// never clip hinted outlines.
+        fn get_skia_metrics(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            hinting: HintingLevel,
+            use_vdmx: bool,
+            rounding: SizeRounding,
+        ) -> Metrics;
+        /// Sets `out` to the VDMX ascent and descent in pixels for `ppem`
+        /// pixels per em at a 1:1 aspect ratio. Returns false and default
+        /// values if the font has no VDMX table or it has no entry for
+        /// `ppem`.
+        fn vdmx_metrics(font_ref: &BridgeFontRef, ppem: u16, out: &mut BridgeVdmxValues) -> bool;
+        /// Fills `out` with the caret slope and offset from hhea at `size`
+        /// pixels per em, or in font units if `size` is 0. Returns false and
+        /// resets `out` if the font has no hhea table or `size` is invalid.
+        fn caret_info(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            out: &mut BridgeCaretInfo,
+        ) -> bool;
+        /// Returns true if the font has vhea and vmtx tables with a nonzero
+        /// numOfLongVerMetrics no larger than the glyph count of maxp, and a
+        /// vmtx large enough for the metrics of every glyph.
+        fn has_vertical_metrics(font_ref: &BridgeFontRef) -> bool;
+        /// Fills `out` with the vertical line metrics from vhea at `size`
+        /// pixels per em, or in font units if `size` is 0. Returns false and
+        /// resets `out` if the font has no vhea table or `size` is invalid.
+        fn vertical_line_metrics(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            out: &mut BridgeVerticalLineMetrics,
+        ) -> bool;
+        /// Fills `out` with the subscript and superscript metrics from OS/2
+        /// at `size` pixels per em, or in font units if `size` is 0. Returns
+        /// false and resets `out` if the font has no OS/2 table or `size` is
+        /// invalid, so the caller can synthesize them.
+        fn get_superscript_subscript_metrics(
+            font_ref: &BridgeFontRef,
+            size: f32,
+            coords: &BridgeNormalizedCoords,
+            out: &mut BridgeScriptMetrics,
+        ) -> bool;
+        /// Returns the glyph count of maxp, raised to the number of glyphs
+        /// with outlines if maxp is missing or reports fewer.
+        fn num_glyphs(font_ref: &BridgeFontRef) -> u16;
+        fn family_name(font_ref: &BridgeFontRef) -> String;
+        /// Returns the typographic family name, name ID 16, falling back to
+        /// the family name, name ID 1, like FreeType's face family name.
+        /// Large families group their members by it, where name ID 1 splits
+        /// them into families of up to four styles. Empty if there is
+        /// neither.
+        fn typographic_family_name(font_ref: &BridgeFontRef) -> String;
+        /// Returns the typographic subfamily name, name ID 17, falling back
+        /// to the subfamily name, name ID 2. Empty if there is neither.
+        fn typographic_subfamily_name(font_ref: &BridgeFontRef) -> String;
+        /// Writes the WWS family name, name ID 21, in English or else the
+        /// first language to `out_string`. Returns false and clears it if
+        /// the font has none, in which case the typographic and then the
+        /// legacy family names apply.
+        fn wws_family_name(font_ref: &BridgeFontRef, out_string: &mut String) -> bool;
+        /// Like `wws_family_name` for the WWS subfamily name, name ID 22.
+        fn wws_subfamily_name(font_ref: &BridgeFontRef, out_string: &mut String) -> bool;
+        /// Returns whether the OS/2 fsSelection WWS bit is set, i.e. the
+        /// typographic or legacy names already only distinguish weight, width
+        /// and slope, so that there are no WWS names. False if there is no
+        /// OS/2 table.
+        fn is_wws_conformant(font_ref: &BridgeFontRef) -> bool;
+        /// Fills `out` with the descriptive name table strings, each the
+        /// string the single name lookups return, in one call.
+        /// Returns false and clears `out` if there is no valid name table.
+        fn font_names(font_ref: &BridgeFontRef, out: &mut BridgeFontNames) -> bool;
+        fn postscript_name(font_ref: &BridgeFontRef, out_string: &mut String) -> bool;
+        /// Writes the PostScript name, name ID 6, to `out_string` with the
+        /// characters PostScript names disallow removed and truncated to 63
+        /// bytes. Synthesizes "Family-Subfamily" from the typographic or
+        /// else legacy names if name ID 6 is missing or empty after that.
+        /// Returns where the name came from, clearing `out_string` if there
+        /// is none.
+        fn postscript_name_or_synthesized(
+            font_ref: &BridgeFontRef,
+            out_string: &mut String,
+        ) -> PostScriptNameSource;
+        /// Writes the version string, name ID 5, in English or else the
+        /// first language to `out_string`. Returns false and clears it if
+        /// the font has none.
+        fn version_string(font_ref: &BridgeFontRef, out_string: &mut String) -> bool;
+        /// Returns the head fontRevision as stored, without rounding to the
+        /// decimal version it usually approximates, or 0.0 if there is no
+        /// head table.
+        fn font_revision(font_ref: &BridgeFontRef) -> f32;
+        /// Writes the PostScript name of the fvar named instance at
+        /// `instance_index` to `out_string`. Uses the name table entry
+        /// referenced by the instance record if present, otherwise synthesizes
+        /// one from the family prefix and the instance's subfamily name.
+        /// Returns false if the index is out of range or no name can be found.
+        fn named_instance_postscript_name(
+            font_ref: &BridgeFontRef,
+            instance_index: usize,
+            out_string: &mut String,
+        ) -> bool;
+
+        /// Returns whether the post table carries glyph names, i.e. is
+        /// format 1.0 or 2.0.
+        fn has_glyph_names(font_ref: &BridgeFontRef) -> bool;
+        /// Writes the post table name of `glyph_id` to `out_string`. Returns
+        /// false for format 3.0 post tables and for glyphs without a
+        /// readable name.
+        fn glyph_name(font_ref: &BridgeFontRef, glyph_id: u16, out_string: &mut String) -> bool;
+
+        /// Receives a slice of palette overrides that will be merged
+        /// with the specified base palette of the font. The result is a
+        /// palette of RGBA, 8-bit per component, colors, consisting of
+        /// palette entries merged with overrides.
+        fn resolve_palette(
+            font_ref: &BridgeFontRef,
+            base_palette: u16,
+            palette_overrides: &[PaletteOverride],
+        ) -> Vec<u32>;
+
+        fn has_colrv1_glyph(font_ref: &BridgeFontRef, glyph_id: u16) -> bool;
+        fn has_colrv0_glyph(font_ref: &BridgeFontRef, glyph_id: u16) -> bool;
+        /// Returns whether the COLR table has version 0 base glyph and layer
+        /// records. False if it is missing or they are empty or malformed.
+        fn has_colrv0_glyphs(font_ref: &BridgeFontRef) -> bool;
+        /// Returns whether the COLR table is of version 1 or later and its
+        /// base glyph list has at least one paint record.
+        fn has_colrv1_glyphs(font_ref: &BridgeFontRef) -> bool;
+        /// Returns whether the font has color bitmaps: CBLC and CBDT tables
+        /// with at least one strike indexing glyphs, or an sbix strike with
+        /// at least one glyph image.
+        fn has_bitmap_glyphs(font_ref: &BridgeFontRef) -> bool;
+        /// Returns whether the SVG table is of version 0 and its document
+        /// list is in bounds and not empty.
+        fn has_svg_glyphs(font_ref: &BridgeFontRef) -> bool;
+        /// Writes the layers of the COLRv0 base glyph `glyph_id` to `out` in
+        /// drawing order. Returns false and clears `out` if the glyph has no
+        /// COLRv0 record or its layer records are out of bounds.
+        fn get_colrv0_layers(
+            font_ref: &BridgeFontRef,
+            glyph_id: u16,
+            out: &mut Vec<BridgeColorLayer>,
+        ) -> bool;
+        fn get_colrv1_clip_box(
+            font_ref: &BridgeFontRef,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+            size: f32,
+            clip_box: &mut ClipBox,
+        ) -> bool;
+
+        fn table_data(font_ref: &BridgeFontRef, tag: u32, offset: usize, data: &mut [u8]) -> usize;
+        /// Returns the data of table `tag` without copying, or an empty slice
+        /// if the table is absent or out of bounds. The slice points into the
+        /// font data `font_ref` was created from and is only valid while that
+        /// data is alive, which is at least the lifetime of `font_ref`.
+        unsafe fn table_data_slice<'a>(font_ref: &'a BridgeFontRef<'a>, tag: u32) -> &'a [u8];
+        /// Returns the number of tables in the font's table directory if
+        /// `out_tags` is empty. Otherwise fills `out_tags` with big-endian
+        /// table tags in directory order and returns the number written.
+        fn table_tags(font_ref: &BridgeFontRef, out_tags: &mut [u32]) -> usize;
+        /// Returns whether the table directory lists `tag`, without
+        /// resolving the table data.
+        fn has_table(font_ref: &BridgeFontRef, tag: u32) -> bool;
+        /// Applies `has_table` to as many `tags` as `out_has_table` holds.
+        /// Returns the number of tables present.
+        fn has_tables(font_ref: &BridgeFontRef, tags: &[u32], out_has_table: &mut [bool]) -> usize;
+        fn variation_position(
+            coords: &BridgeNormalizedCoords,
+            coordinates: &mut [SkiaDesignCoordinate],
+        ) -> isize;
+        /// Copies the raw F2Dot14 bits of the normalized coordinates, one per
+        /// axis in fvar axis order, into `out_coords` for use in cache keys
+        /// and serialization. Copies as many values as `out_coords` can hold
+        /// and returns the total number of coordinates, so an empty slice
+        /// queries the count.
+        fn normalized_coords_to_f2dot14(
+            coords: &BridgeNormalizedCoords,
+            out_coords: &mut [i16],
+        ) -> usize;
+        /// Compares two sets of normalized coordinates, treating coordinates
+        /// missing at the end as the default position.
+        fn normalized_coords_equal(a: &BridgeNormalizedCoords, b: &BridgeNormalizedCoords) -> bool;
+        /// Returns a hash of the normalized coordinates that is stable across
+        /// process runs and consistent with `normalized_coords_equal`.
+        fn normalized_coords_hash(coords: &BridgeNormalizedCoords) -> u64;
+        /// Computes the effective design space position of `coords`, one
+        /// entry per axis in fvar order, inverting the avar and fvar
+        /// normalization. Axes not set in `coords` report their default
+        /// values. Fills as many entries as `out_coordinates` can hold and
+        /// returns the number of axes, so an empty slice queries the count.
+        fn design_position(
+            font_ref: &BridgeFontRef,
+            coords: &BridgeNormalizedCoords,
+            out_coordinates: &mut [SkiaDesignCoordinate],
+        ) -> usize;
+
+        fn populate_axes(font_ref: &BridgeFontRef, axis_wrapper: Pin<&mut AxisWrapper>) -> isize;
+
+        /// Returns the number of variation axes in the font's fvar table, 0
+        /// for non-variable fonts.
+        fn num_axes(font_ref: &BridgeFontRef) -> usize;
+        /// Fills `axis_info` with the design space ranges and the fvar
+        /// HIDDEN_AXIS flag of the font's variation axes in fvar order,
+        /// stopping when either the slice or the axes are exhausted. Returns
+        /// the number of entries written.
+        fn populate_axis_info(font_ref: &BridgeFontRef, axis_info: &mut [BridgeAxisInfo]) -> usize;
+
+        type BridgeLocalizedStrings<'a>;
+        /// Returns an iterator over the name table strings of `name_id` in
+        /// all languages: those of Windows Unicode records first, then of the
+        /// Unicode platform, then of Macintosh records, English first within
+        /// each. Family names, name ID 1, are suffixed to tell the backend
+        /// apart.
+        unsafe fn get_localized_strings<'a>(
+            font_ref: &'a BridgeFontRef<'a>,
+            name_id: u16,
+        ) -> Box<BridgeLocalizedStrings<'a>>;
+        /// Restarts `bridge_localized_strings` on the strings of `name_id`,
+        /// so that one iterator can go through several name IDs.
+        unsafe fn reset_localized_strings<'a>(
+            font_ref: &'a BridgeFontRef<'a>,
+            bridge_localized_strings: &mut BridgeLocalizedStrings<'a>,
+            name_id: u16,
+        );
+        /// Writes the next string to `out_localized_name`, skipping strings
+        /// in a language that were returned before, as fonts repeat them
+        /// for several platforms and encodings. Returns false at the end.
+        fn localized_name_next(
+            bridge_localized_strings: &mut BridgeLocalizedStrings,
+            out_localized_name: &mut BridgeLocalizedName,
+        ) -> bool;
+        /// Returns how many strings `localized_name_next` returns from the
+        /// first on, without advancing.
+        fn localized_strings_count(bridge_localized_strings: &BridgeLocalizedStrings) -> usize;
+        /// Goes back to the first string of the name ID.
+        fn restart_localized_strings(bridge_localized_strings: &mut BridgeLocalizedStrings);
+        /// Returns all strings of `name_id` in one call, in the order and
+        /// without the repeated strings of the `get_localized_strings`
+        /// iterator.
+        fn get_localized_strings_vec(
+            font_ref: &BridgeFontRef,
+            name_id: u16,
+        ) -> Vec<BridgeLocalizedName>;
+        /// Writes the name of the fvar axis at `axis_index` to
+        /// `out_localized_name`, in the language of the BCP-47 tag
+        /// `language_preference` if the font has it, matching the primary
+        /// language subtag if there is no exact match, or else in English or
+        /// the first language. Returns false and clears the name if the axis
+        /// or its name does not exist.
+        fn axis_name(
+            font_ref: &BridgeFontRef,
+            axis_index: usize,
+            language_preference: &str,
+            out_localized_name: &mut BridgeLocalizedName,
+        ) -> bool;
+
+        type BridgeAxisValueNames;
+        /// Returns an iterator over the names of the STAT axis value tables
+        /// in table order, in the language chosen as by `axis_name`. It is
+        /// empty if the font has no axis value tables.
+        fn get_axis_value_names(
+            font_ref: &BridgeFontRef,
+            language_preference: &str,
+        ) -> Box<BridgeAxisValueNames>;
+        fn axis_value_name_next(
+            bridge_axis_value_names: &mut BridgeAxisValueNames,
+            out_axis_value_name: &mut BridgeAxisValueName,
+        ) -> bool;
+
+        type BridgeNormalizedCoords;
+        fn resolve_into_normalized_coords(
+            font_ref: &BridgeFontRef,
+            design_coords: &[SkiaDesignCoordinate],
+        ) -> Box<BridgeNormalizedCoords>;
+        /// Same as `resolve_into_normalized_coords`, additionally reporting
+        /// which of the `design_coords` were clamped or ignored.
+        fn resolve_into_normalized_coords_with_report(
+            font_ref: &BridgeFontRef,
+            design_coords: &[SkiaDesignCoordinate],
+            report: &mut CoordinateResolutionReport,
+        ) -> Box<BridgeNormalizedCoords>;
+        /// Resolves the normalized coordinates of the fvar named instance at
+        /// `instance_index` directly from its coordinate tuple, avoiding a
+        /// round trip through SkiaDesignCoordinate. Returns default
+        /// coordinates if the index is out of range.
+        fn resolve_named_instance_coords(
+            font_ref: &BridgeFontRef,
+            instance_index: usize,
+        ) -> Box<BridgeNormalizedCoords>;
+
+        /// Traverses the COLR paint graph of `glyph_id` at `coords` and
+        /// forwards its operations to `color_painter` in font units with y
+        /// up; the painter scales them to the size and flips y. Returns false
+        /// if the glyph has no COLR record or its paint graph is malformed.
+        fn draw_colr_glyph(
+            font_ref: &BridgeFontRef,
+            coords: &BridgeNormalizedCoords,
+            glyph_id: u16,
+            color_painter: Pin<&mut ColorPainterWrapper>,
+        ) -> bool;
+
+        type BridgeColorStops<'a>;
+        fn next_color_stop(color_stops: &mut BridgeColorStops, stop: &mut ColorStop) -> bool;
+        fn num_color_stops(color_stops: &BridgeColorStops) -> usize;
+
+        /// Fills `font_style` with the `SkFontStyle` weight, width and slant
+        /// of the font from the OS/2 usWeightClass, usWidthClass and
+        /// fsSelection, or from the head macStyle if there is no OS/2 table.
+        /// Weights 1 to 9 are taken as hundreds, and width classes clamped
+        /// to 1 to 9. Returns false and zeroes if neither table is valid.
+        fn font_style(font_ref: &BridgeFontRef, font_style: &mut BridgeFontStyle) -> bool;
+        /// Like `font_style`, but for the instance at `coords`: the weight
+        /// follows wght, the width the nearest width class of the wdth
+        /// percentage, and the slant ital, or slnt as oblique for angles of
+        /// either sign. Axes the font lacks keep the values of `font_style`.
+        fn variable_font_style(
+            font_ref: &BridgeFontRef,
+            coords: &BridgeNormalizedCoords,
+            font_style: &mut BridgeFontStyle,
+        ) -> bool;
+        /// Sets `out_string` to the style name of the instance at `coords`
+        /// assembled from the STAT axis value tables: the names of the
+        /// values matching the position on each axis, in axisOrdering order,
+        /// without elidable ones. Combinations (format 4) take precedence,
+        /// then exact values (formats 1 and 3), then ranges (format 2). If
+        /// all names are elided, this is the elided fallback name. Fonts
+        /// without axis value tables fall back to the subfamily name.
+        /// Returns false and clears `out_string` if there is no name.
+        fn derive_style_name(
+            font_ref: &BridgeFontRef,
+            coords: &BridgeNormalizedCoords,
+            out_string: &mut String,
+        ) -> bool;
+        /// Returns the OS/2 fsType embedding permissions as is, 0 (installable)
+        /// if there is no OS/2 table.
+        fn embedding_flags(font_ref: &BridgeFontRef) -> u16;
+        /// Returns whether the outlines of the font may be embedded in
+        /// documents: false for a restricted license unless a less
+        /// restrictive permission bit is also set, and for bitmap embedding
+        /// only.
+        fn can_embed(font_ref: &BridgeFontRef) -> bool;
+        /// Returns whether an embedded font may be subset, i.e. fsType does
+        /// not set the no subsetting bit.
+        fn can_subset(font_ref: &BridgeFontRef) -> bool;
+        /// Fills `out` with the ten PANOSE classification bytes of OS/2.
+        /// Returns false and zeroes if there is no OS/2 table.
+        fn panose(font_ref: &BridgeFontRef, out: &mut [u8; 10]) -> bool;
+        /// Returns whether the font has serifs for PDF: from the PCLT
+        /// serifStyle if there is a PCLT table, like the FreeType port,
+        /// otherwise from a PANOSE text and display family with a serif
+        /// style other than sans, like the DirectWrite port.
+        fn is_serif(font_ref: &BridgeFontRef) -> bool;
+        /// Returns whether the font is a script font, from the PCLT
+        /// serifStyle or otherwise the PANOSE family kind like `is_serif`.
+        fn is_script(font_ref: &BridgeFontRef) -> bool;
+        /// Fills `out` with the OS/2 ulUnicodeRange1 to 4. Returns false and
+        /// zeroes if there is no OS/2 table.
+        fn unicode_range_bits(font_ref: &BridgeFontRef, out: &mut [u32; 4]) -> bool;
+        /// Fills `out` with the OS/2 ulCodePageRange1 and 2. Returns false
+        /// and zeroes if there is no OS/2 table or its version 0 predates
+        /// the fields.
+        fn codepage_range_bits(font_ref: &BridgeFontRef, out: &mut [u32; 2]) -> bool;
+        /// Returns whether the Unicode range bit `index`, 0 to 127, is set,
+        /// with bit 0 the least significant bit of ulUnicodeRange1. False
+        /// for indices out of range and fonts without OS/2.
+        fn has_unicode_range(font_ref: &BridgeFontRef, index: u32) -> bool;
+        /// Same as `has_unicode_range` for the code page range bits 0 to 63.
+        fn has_codepage_range(font_ref: &BridgeFontRef, index: u32) -> bool;
+        /// Fills `out` with the four bytes of the OS/2 achVendID as is,
+        /// including padding spaces. Returns false and zeroes if there is
+        /// no OS/2 table.
+        fn vendor_id(font_ref: &BridgeFontRef, out: &mut [u8; 4]) -> bool;
+        /// Returns `vendor_id` as a string without trailing spaces, empty if
+        /// there is no OS/2 table. Bytes that are not valid UTF-8 are
+        /// replaced by U+FFFD.
+        fn vendor_id_string(font_ref: &BridgeFontRef) -> String;
+
+        /// Returns the italicAngle from the post table in degrees, negative
+        /// for fonts leaning to the right, or 0.0 if the table is missing.
+        fn italic_angle(font_ref: &BridgeFontRef) -> f32;
+        fn italic_angle_is_nonzero(font_ref: &BridgeFontRef) -> bool;
+        /// Like `italic_angle`, but for fonts with a slnt axis additionally
+        /// applies the slant of `coords` relative to the default instance.
+        fn italic_angle_at(font_ref: &BridgeFontRef, coords: &BridgeNormalizedCoords) -> f32;
+
+        /// Returns true if post marks the font as fixed pitch or, since some
+        /// monospace fonts lack that flag, if the mapped printable ASCII
+        /// glyphs all share the same nonzero advance.
+        fn is_fixed_pitch(font_ref: &BridgeFontRef) -> bool;
+    }
+
+    unsafe extern "C++" {
+
+        include!("src/ports/fontations/src/skpath_bridge.h");
+
+        type PathWrapper;
+
+        #[allow(dead_code)]
+        fn move_to(self: Pin<&mut PathWrapper>, x: f32, y: f32);
+        #[allow(dead_code)]
+        fn line_to(self: Pin<&mut PathWrapper>, x: f32, y: f32);
+        #[allow(dead_code)]
+        fn quad_to(self: Pin<&mut PathWrapper>, cx0: f32, cy0: f32, x: f32, y: f32);
+        #[allow(dead_code)]
+        fn curve_to(
+            self: Pin<&mut PathWrapper>,
+            cx0: f32,
+            cy0: f32,
+            cx1: f32,
+            cy1: f32,
+            x: f32,
+            y: f32,
+        );
+        #[allow(dead_code)]
+        fn close(self: Pin<&mut PathWrapper>);
+        /// Discards everything drawn so far.
+        fn reset(self: Pin<&mut PathWrapper>);
+
+        type GlyphPathsWrapper;
+
+        /// Starts the path of `glyph_id` and returns the wrapper receiving
+        /// its outline.
+        fn begin_glyph(self: Pin<&mut GlyphPathsWrapper>, glyph_id: u16) -> Pin<&mut PathWrapper>;
+        fn end_glyph(
+            self: Pin<&mut GlyphPathsWrapper>,
+            glyph_id: u16,
+            status: PathStatus,
+            scaler_metrics: &BridgeScalerMetrics,
+        );
+
+        type AxisWrapper;
+
+        fn populate_axis(
+            self: Pin<&mut AxisWrapper>,
+            i: usize,
+            axis: u32,
+            min: f32,
+            def: f32,
+            max: f32,
+            hidden: bool,
+        ) -> bool;
+        fn size(self: Pin<&AxisWrapper>) -> usize;
+
+        type ColorPainterWrapper;
+
+        fn push_transform(self: Pin<&mut ColorPainterWrapper>, transform: &Transform);
+        fn pop_transform(self: Pin<&mut ColorPainterWrapper>);
+        fn push_clip_glyph(self: Pin<&mut ColorPainterWrapper>, glyph_id: u16);
+        fn push_clip_rectangle(
+            self: Pin<&mut ColorPainterWrapper>,
+            x_min: f32,
+            y_min: f32,
+            x_max: f32,
+            y_max: f32,
+        );
+        fn pop_clip(self: Pin<&mut ColorPainterWrapper>);
+
+        fn fill_solid(self: Pin<&mut ColorPainterWrapper>, palette_index: u16, alpha: f32);
+        fn fill_linear(
+            self: Pin<&mut ColorPainterWrapper>,
+            fill_linear_params: &FillLinearParams,
+            color_stops: &mut BridgeColorStops,
+            extend_mode: u8,
+        );
+        fn fill_radial(
+            self: Pin<&mut ColorPainterWrapper>,
+            fill_radial_params: &FillRadialParams,
+            color_stops: &mut BridgeColorStops,
+            extend_mode: u8,
+        );
+        fn fill_sweep(
+            self: Pin<&mut ColorPainterWrapper>,
+            fill_sweep_params: &FillSweepParams,
+            color_stops: &mut BridgeColorStops,
+            extend_mode: u8,
+        );
+
+        // Optimized functions.
+        fn fill_glyph_solid(
+            self: Pin<&mut ColorPainterWrapper>,
+            glyph_id: u16,
+            palette_index: u16,
+            alpha: f32,
+        );
+        fn fill_glyph_linear(
+            self: Pin<&mut ColorPainterWrapper>,
+            glyph_id: u16,
+            fill_transform: &Transform,
+            fill_linear_params: &FillLinearParams,
+            color_stops: &mut BridgeColorStops,
+            extend_mode: u8,
+        );
+        fn fill_glyph_radial(
+            self: Pin<&mut ColorPainterWrapper>,
+            glyph_id: u16,
+            fill_transform: &Transform,
+            fill_radial_params: &FillRadialParams,
+            color_stops: &mut BridgeColorStops,
+            extend_mode: u8,
+        );
+        fn fill_glyph_sweep(
+            self: Pin<&mut ColorPainterWrapper>,
+            glyph_id: u16,
+            fill_transform: &Transform,
+            fill_sweep_params: &FillSweepParams,
+            color_stops: &mut BridgeColorStops,
+            extend_mode: u8,
+        );
+
+        fn push_layer(self: Pin<&mut ColorPainterWrapper>, colrv1_composite_mode: u8);
+        fn pop_layer(self: Pin<&mut ColorPainterWrapper>);
+
+    }
+}
+
+impl Default for BridgeFontStyle {
+    fn default() -> Self {
+        Self {
+            weight: 0,
+            slant: 0,
+            width: 0,
+        }
+    }
+}
+
+/// Entry points for the fuzz targets in fuzz/, which exercise the bridge
+/// without its C++ side. Each takes the fuzzer input as font data, so that
+/// the test fonts can seed the corpus.
+#[cfg(fuzzing)]
+pub mod fuzzing {
+    use super::*;
+
+    /// Bounds on the faces and glyphs exercised per input, which keep
+    /// iterations fast.
+    const MAX_FACES: u32 = 4;
+    const MAX_GLYPHS: u16 = 512;
+
+    /// Discards outlines in place of the C++ path wrapper.
+    struct NullPen;
+
+    impl Pen for NullPen {
+        fn move_to(&mut self, _x: f32, _y: f32) {}
+        fn line_to(&mut self, _x: f32, _y: f32) {}
+        fn quad_to(&mut self, _cx0: f32, _cy0: f32, _x: f32, _y: f32) {}
+        fn curve_to(&mut self, _cx0: f32, _cy0: f32, _cx1: f32, _cy1: f32, _x: f32, _y: f32) {}
+        fn close(&mut self) {}
+    }
+
+    fn for_each_face(font_data: &[u8], mut exercise: impl FnMut(&BridgeFontRef)) {
+        for index in 0..font_count(font_data).min(MAX_FACES) {
+            let font_ref = make_font_ref(font_data, index);
+            if font_ref_is_valid(&font_ref) {
+                exercise(&font_ref);
+            }
+        }
+    }
+
+    /// Loads each face and reads its metrics, names and table checksums.
+    pub fn load_font(font_data: &[u8]) {
+        for_each_face(font_data, |font_ref| {
+            let coords = BridgeNormalizedCoords::default();
+            num_glyphs(font_ref);
+            get_skia_metrics(
+                font_ref,
+                16.0,
+                &coords,
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact,
+            );
+            family_name(font_ref);
+            verify_checksums(font_ref);
+            font_digest(font_ref);
+        });
+    }
+
+    /// Maps the printable ASCII codepoints and draws the mapped glyphs and
+    /// the first glyphs of each face, unhinted and hinted.
+    pub fn draw_glyphs(font_data: &[u8]) {
+        for_each_face(font_data, |font_ref| {
+            let outlines = BridgeOutlineCollection::new(font_ref);
+            let coords = BridgeNormalizedCoords::default();
+            let limits = BridgeOutlineLimits::default();
+            let mut scaler_metrics = BridgeScalerMetrics::default();
+            let mapped_glyphs =
+                (0x20..0x7F).map(|codepoint| lookup_glyph_or_zero(font_ref, codepoint));
+            for glyph_id in (0..num_glyphs(font_ref).min(MAX_GLYPHS)).chain(mapped_glyphs) {
+                for hinting in [HintingLevel::None, HintingLevel::Full] {
+                    draw_outline(
+                        &outlines,
+                        glyph_id,
+                        16.0,
+                        &coords,
+                        &limits,
+                        hinting,
+                        &mut NullPen,
+                        &mut scaler_metrics,
+                    );
+                }
+            }
+        });
+    }
+
+    /// Reads each table, and a missing one, at offsets around its bounds.
+    pub fn read_tables(font_data: &[u8]) {
+        for_each_face(font_data, |font_ref| {
+            let mut tags = vec![0; table_tags(font_ref, &mut [])];
+            table_tags(font_ref, &mut tags);
+            tags.push(u32::from_be_bytes(*b"zzzz"));
+            for tag in tags {
+                let len = table_data(font_ref, tag, 0, &mut []);
+                for offset in [
+                    0,
+                    1,
+                    len / 2,
+                    len.saturating_sub(1),
+                    len,
+                    len + 1,
+                    usize::MAX,
+                ] {
+                    let mut data = vec![0; len.saturating_sub(offset).min(64) + 1];
+                    table_data(font_ref, tag, offset, &mut data);
+                }
+            }
+        });
+    }
+}
+
+/// Tests to exercise COLR and CPAL parts of the Fontations FFI.
+/// Run using `$ bazel test --with_fontations //src/ports/fontations:test_ffi`
+#[cfg(test)]
+mod test {
+    use crate::{
+        advance_height_or_zero, advance_width_font_units, advance_width_or_zero, advance_widths,
+        advance_widths_font_units, are_empty_glyphs, axis_name, axis_value_name_next, can_embed,
+        can_subset, caret_info, cmap_subtable_info, codepage_range_bits, count_mapped_unichars,
+        default_outline_limits, derive_style_name, design_position, draw_glyph_run, draw_outline,
+        draw_path_wrapper, embedding_flags, emboldened_advance_width_or_zero,
+        english_or_first_font_name, extract_font_from_collection, family_name, ffi::BridgeAxisInfo,
+        ffi::BridgeFontStyle, ffi::BridgeOutlineLimits, ffi::CoordinateResolutionReport,
+        ffi::Metrics, ffi::PaletteOverride, ffi::SkiaDesignCoordinate, fill_unichar_map,
+        find_vert_origin_y, font_count, font_digest, font_names, font_or_collection,
+        font_ref_collection_index, font_ref_data_size, font_ref_is_renderable, font_ref_is_valid,
+        font_revision, font_style, fontations_ffi_set_panic_callback, gasp_behavior,
+        get_axis_value_names, get_colrv0_layers, get_localized_strings, get_localized_strings_vec,
+        get_outline_collection, get_path_verbs, get_skia_metrics,
+        get_superscript_subscript_metrics, glyph_bounds, glyph_bounds_batch, glyph_has_overlaps,
+        glyph_metrics, glyph_metrics_batch, glyph_name, glyphs_to_unicode, has_bitmap_glyphs,
+        has_codepage_range, has_codepoint, has_colrv0_glyphs, has_colrv1_glyphs, has_glyph_names,
+        has_svg_glyphs, has_symbol_charmap, has_table, has_tables, has_unicode_range,
+        has_vertical_metrics, hdmx_advance, head_style_and_flags, hinted_advance_width_or_zero,
+        identity_path_transform, is_empty_glyph, is_fixed_pitch, is_script, is_serif,
+        is_wws_conformant, italic_angle, italic_angle_at, italic_angle_is_nonzero,
+        left_side_bearing_or_zero, left_side_bearing_source, linear_and_hinted_advances,
+        localized_name_next, localized_strings_count, lookup_glyph_or_zero, lookup_glyph_variant,
+        lookup_glyphs, lowest_rec_ppem_or_zero, make_font_ref, make_font_ref_owned,
+        named_instance_postscript_name, normalized_coords_equal, normalized_coords_hash,
+        normalized_coords_to_f2dot14, num_axes, num_glyphs, outlines_support_hinting,
+        paint_colr_glyph, panose, populate_axis_info, postscript_name_or_synthesized,
+        range_bit_is_set, render_prerequisites, reset_localized_strings,
+        resolve_into_normalized_coords, resolve_into_normalized_coords_with_report,
+        resolve_named_instance_coords, resolve_palette, restart_localized_strings,
+        sanitize_postscript_name, table_checksum, table_data, table_data_slice, table_tags,
+        top_side_bearing_or_zero, top_side_bearings, typographic_family_name,
+        typographic_subfamily_name, unapply_segment_maps, unicode_range_bits, unicode_to_mac_roman,
+        units_per_em_or_zero, variable_font_style, vdmx_metrics, vendor_id, vendor_id_string,
+        verify_checksums, version_string, vertical_glyph_metrics, vertical_line_metrics,
+        vertical_origin_y, width_from_percentage, wws_family_name, wws_subfamily_name,
+        BridgeAdvances, BridgeAxisValueName, BridgeCaretInfo, BridgeCmapSubtableInfo,
+        BridgeColorLayer, BridgeFontNames, BridgeFontRef, BridgeGlyphMetrics, BridgeLocalizedName,
+        BridgeLocalizedStrings, BridgeNormalizedCoords, BridgeOutlineCollection, BridgePathBounds,
+        BridgePathTransform, BridgeRenderPrerequisites, BridgeScalerMetrics, BridgeScriptMetrics,
+        BridgeVdmxValues, BridgeVerticalGlyphMetrics, BridgeVerticalLineMetrics, GlyphRunSink,
+        HintingLevel, LineMetricsSource, OutlineRecorder, OutlineScaler, PathStatus, PathVerb,
+        PathWrapperSink, PostScriptNameSource, SegmentCleanupPen, SideBearingSource, SizeRounding,
+        TransformPen, XxHash64, CHECKSUM_ADJUSTMENT_MAGIC, FS_TYPE_BITMAP_ONLY, FS_TYPE_EDITABLE,
+        FS_TYPE_NO_SUBSETTING, FS_TYPE_PREVIEW_AND_PRINT, FS_TYPE_RESTRICTED_LICENSE,
+        GASP_DEFAULT_BEHAVIOR, GASP_DOGRAY, GASP_GRIDFIT, MAC_ROMAN_TO_UNICODE,
+        MAX_NAME_TABLE_POSTSCRIPT_NAME_LENGTH, MAX_POSTSCRIPT_NAME_LENGTH,
+        NUM_GLYPH_METRICS_SCALERS, NUM_OUTLINE_SCALERS, WIDTH_PERCENTAGES,
+    };
+    use font_types::{BoundingBox, F2Dot14, Fixed, GlyphId, Pen};
+    use read_fonts::{
+        tables::{avar::SegmentMaps, colr::CompositeMode},
+        FontData, FontRead, FontRef, TableProvider,
+    };
+    use skrifa::{
+        color::{Brush, ColorPainter, Extend, Transform},
+        string::StringId,
+        MetadataProvider, Tag,
+    };
+    use std::{
+        cell::Cell,
+        fs,
+        pin::Pin,
+        sync::{Mutex, PoisonError},
+    };
+
+    thread_local! {
+        static INJECT_PANIC: Cell<bool> = const { Cell::new(false) };
+    }
+
+    /// Panics once on the current thread after `inject_panic`, to exercise
+    /// `ffi_guard`.
+    pub(crate) fn maybe_inject_panic() {
+        if INJECT_PANIC.replace(false) {
+            panic!("injected panic");
+        }
+    }
+
+    fn inject_panic() {
+        INJECT_PANIC.set(true);
+    }
+
+    const TEST_FONT_FILENAME: &str = "resources/fonts/test_glyphs-glyf_colr_1_variable.ttf";
+    const TEST_COLLECTION_FILENAME: &str = "resources/fonts/test.ttc";
+    const TEST_CONDENSED_BOLD_ITALIC: &str = "resources/fonts/cond-bold-italic.ttf";
+    const TEST_VARIABLE: &str = "resources/fonts/Variable.ttf";
+    const TEST_COLR: &str = "resources/fonts/colr.ttf";
+    const TEST_ROBOTO: &str = "resources/fonts/Roboto-Regular.ttf";
+    const TEST_CJK_VARIABLE_COLLECTION: &str = "resources/fonts/NotoSansCJK-VF-subset.otf.ttc";
+    const TEST_HINTED_CFF: &str = "resources/fonts/Stroking.otf";
+
+    #[test]
+    fn test_palette_override() {
+        let file_buffer =
+            fs::read(TEST_FONT_FILENAME).expect("COLRv0/v1 test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let override_color = 0xFFEEEEEE;
+        let valid_overrides = [
+            PaletteOverride {
+                index: 9,
+                color_8888: override_color,
+            },
+            PaletteOverride {
+                index: 10,
+                color_8888: override_color,
+            },
+            PaletteOverride {
+                index: 11,
+                color_8888: override_color,
+            },
+        ];
+
+        let palette = resolve_palette(&font_ref, 0, &valid_overrides);
+
+        assert_eq!(palette.len(), 14);
+        assert_eq!(palette[9], override_color);
+        assert_eq!(palette[10], override_color);
+        assert_eq!(palette[11], override_color);
+
+        let out_of_bounds_overrides = [
+            PaletteOverride {
+                index: 15,
+                color_8888: override_color,
+            },
+            PaletteOverride {
+                index: 16,
+                color_8888: override_color,
+            },
+            PaletteOverride {
+                index: 17,
+                color_8888: override_color,
+            },
+        ];
+
+        let palette = resolve_palette(&font_ref, 0, &out_of_bounds_overrides);
+
+        assert_eq!(palette.len(), 14);
+        assert_eq!(
+            (palette[11], palette[12], palette[13],),
+            (0xff68c7e8, 0xffffdc01, 0xff808080)
+        );
+
+        let no_palette = resolve_palette(&font_ref, 10, &out_of_bounds_overrides);
+        assert_eq!(no_palette.len(), 0);
+    }
+
+    #[test]
+    fn test_num_fonts_in_collection() {
+        let collection_buffer = fs::read(TEST_COLLECTION_FILENAME)
+            .expect("Unable to open TrueType collection test file.");
+        let font_buffer =
+            fs::read(TEST_FONT_FILENAME).expect("COLRv0/v1 test font could not be opened.");
+        let garbage: [u8; 12] = [
+            b'0', b'a', b'b', b'0', b'a', b'b', b'0', b'a', b'b', b'0', b'a', b'b',
+        ];
+
+        let mut num_fonts = 0;
+        let result_collection = font_or_collection(&collection_buffer, &mut num_fonts);
+        assert!(result_collection && num_fonts == 2);
+
+        let result_font_file = font_or_collection(&font_buffer, &mut num_fonts);
+        assert!(result_font_file);
+        assert!(num_fonts == 0u32);
+
+        let result_garbage = font_or_collection(&garbage, &mut num_fonts);
+        assert!(!result_garbage);
+    }
+
+    #[test]
+    fn test_font_attributes() {
+        let file_buffer = fs::read(TEST_CONDENSED_BOLD_ITALIC)
+            .expect("Font to test font styles could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let mut style = BridgeFontStyle::default();
+
+        if font_style(font_ref.as_ref(), &mut style) {
+            assert_eq!(style.width, 5); // The font should have condenced width attribute but
+                                        // it's condenced itself so we have the normal width
+            assert_eq!(style.slant, 1); // Skia italic
+            assert_eq!(style.weight, 700); // Skia bold
+        } else {
+            assert!(false);
+        }
+    }
+
+    #[test]
+    fn test_variable_font_attributes() {
+        let file_buffer = fs::read(TEST_VARIABLE)
+            .expect("Font to test font styles could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let mut style = BridgeFontStyle::default();
+
+        assert!(font_style(font_ref.as_ref(), &mut style));
+        assert_eq!(style.width, 5);    // Skia normal
+        assert_eq!(style.slant, 0);    // Skia upright
+        assert_eq!(style.weight, 400); // Skia normal
+    }
+
+    #[test]
+    fn test_axis_info() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        assert_eq!(num_axes(&font_ref), 2);
+
+        let mut axis_info = [BridgeAxisInfo::default(); 2];
+        assert_eq!(populate_axis_info(&font_ref, &mut axis_info), 2);
+        assert_eq!(axis_info[0].tag, u32::from_be_bytes(*b"wght"));
+        assert_eq!(
+            (axis_info[0].min, axis_info[0].def, axis_info[0].max),
+            (100.0, 400.0, 900.0)
+        );
+        assert_eq!(axis_info[1].tag, u32::from_be_bytes(*b"wdth"));
+        assert_eq!(
+            (axis_info[1].min, axis_info[1].def, axis_info[1].max),
+            (50.0, 100.0, 200.0)
+        );
+
+        // A shorter buffer is filled up to its length only.
+        let mut short_axis_info = [BridgeAxisInfo::default(); 1];
+        assert_eq!(populate_axis_info(&font_ref, &mut short_axis_info), 1);
+        assert_eq!(short_axis_info[0].tag, u32::from_be_bytes(*b"wght"));
+    }
+
+    #[test]
+    fn test_axis_info_hidden_flag() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        // The test font's fvar marks the first axis, wght, as hidden.
+        let mut axis_info = [BridgeAxisInfo::default(); 2];
+        assert_eq!(populate_axis_info(&font_ref, &mut axis_info), 2);
+        let tags_and_hidden: Vec<(u32, bool)> = axis_info
+            .iter()
+            .map(|axis| (axis.tag, axis.hidden))
+            .collect();
+        assert_eq!(
+            tags_and_hidden,
+            [
+                (u32::from_be_bytes(*b"wght"), true),
+                (u32::from_be_bytes(*b"wdth"), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_axis_info_static_font() {
+        let file_buffer =
+            fs::read(TEST_CONDENSED_BOLD_ITALIC).expect("Static test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        assert_eq!(num_axes(&font_ref), 0);
+        let mut axis_info = [BridgeAxisInfo::default(); 2];
+        assert_eq!(populate_axis_info(&font_ref, &mut axis_info), 0);
+        assert_eq!(axis_info[0].tag, 0);
+    }
+
+    #[test]
+    fn test_resolve_named_instance_coords() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let font = font_ref.font.as_ref().unwrap();
+        let axes = font.axes();
+        let named_instances = font.named_instances();
+        assert!(!named_instances.is_empty());
+
+        for (instance_index, instance) in named_instances.iter().enumerate() {
+            let design_coords: Vec<SkiaDesignCoordinate> = axes
+                .iter()
+                .zip(instance.user_coords())
+                .map(|(axis, value)| SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(axis.tag().into_bytes()),
+                    value,
+                })
+                .collect();
+            let from_design_coords = resolve_into_normalized_coords(&font_ref, &design_coords);
+            let from_instance = resolve_named_instance_coords(&font_ref, instance_index);
+            assert_eq!(
+                from_instance.normalized_coords.coords(),
+                from_design_coords.normalized_coords.coords()
+            );
+            assert_eq!(
+                from_instance.filtered_user_coords,
+                from_design_coords.filtered_user_coords
+            );
+        }
+
+        let out_of_range = resolve_named_instance_coords(&font_ref, named_instances.len());
+        assert!(out_of_range.normalized_coords.coords().is_empty());
+        assert!(out_of_range.filtered_user_coords.is_empty());
+    }
+
+    #[test]
+    fn test_named_instance_postscript_name_from_name_table() {
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable test collection could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let mut postscript_name = String::new();
+        assert!(named_instance_postscript_name(
+            &font_ref,
+            0,
+            &mut postscript_name
+        ));
+        assert_eq!(postscript_name, "NotoSansCJKjp-Thin");
+        assert!(named_instance_postscript_name(
+            &font_ref,
+            5,
+            &mut postscript_name
+        ));
+        assert_eq!(postscript_name, "NotoSansCJKjp-Bold");
+    }
+
+    #[test]
+    fn test_named_instance_postscript_name_synthesized() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        // The test font's instance records carry no PostScript name IDs.
+        let mut postscript_name = String::new();
+        assert!(named_instance_postscript_name(
+            &font_ref,
+            0,
+            &mut postscript_name
+        ));
+        assert_eq!(postscript_name, "Variable-Thin");
+        assert!(named_instance_postscript_name(
+            &font_ref,
+            4,
+            &mut postscript_name
+        ));
+        assert_eq!(postscript_name, "Variable-Expanded");
+
+        assert!(!named_instance_postscript_name(
+            &font_ref,
+            5,
+            &mut postscript_name
+        ));
+
+        // Instance names are only truncated at the 127 byte limit of
+        // Technical Note #5902, not at the name ID 6 limit.
+        let subfamily_name_id = font_ref
+            .with_font(|f| f.named_instances().get(0))
+            .unwrap()
+            .subfamily_name_id()
+            .to_u16();
+        let long_prefix = "LongPrefix".repeat(8);
+        let font_data = replace_table(
+            &file_buffer,
+            b"name",
+            Some(build_name(&[
+                (3, 1, 0x0409, 25, &long_prefix),
+                (3, 1, 0x0409, subfamily_name_id, "Thin"),
+            ])),
+        );
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(named_instance_postscript_name(
+            &font_ref,
+            0,
+            &mut postscript_name
+        ));
+        assert_eq!(postscript_name, format!("{long_prefix}-Thin"));
+        assert!(postscript_name.len() > MAX_NAME_TABLE_POSTSCRIPT_NAME_LENGTH);
+    }
+
+    #[test]
+    fn test_sanitize_postscript_name() {
+        assert_eq!(
+            sanitize_postscript_name("My Font[wght]/Bold (Ünïcode)%", MAX_POSTSCRIPT_NAME_LENGTH),
+            "MyFontwghtBoldncode"
+        );
+        let long_name = "A".repeat(200);
+        assert_eq!(
+            sanitize_postscript_name(&long_name, MAX_POSTSCRIPT_NAME_LENGTH).len(),
+            MAX_POSTSCRIPT_NAME_LENGTH
+        );
+        assert_eq!(
+            sanitize_postscript_name(&long_name, MAX_NAME_TABLE_POSTSCRIPT_NAME_LENGTH).len(),
+            MAX_NAME_TABLE_POSTSCRIPT_NAME_LENGTH
+        );
+    }
+
+    fn make_design_coordinate(axis: &[u8; 4], value: f32) -> SkiaDesignCoordinate {
+        SkiaDesignCoordinate {
+            axis: u32::from_be_bytes(*axis),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_design_position_defaults() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let coords =
+            resolve_into_normalized_coords(&font_ref, &[make_design_coordinate(b"wdth", 150.0)]);
+        assert_eq!(design_position(&font_ref, &coords, &mut []), 2);
+
+        let mut position = [
+            make_design_coordinate(b"\0\0\0\0", 0.0),
+            make_design_coordinate(b"\0\0\0\0", 0.0),
+        ];
+        assert_eq!(design_position(&font_ref, &coords, &mut position), 2);
+        // The unspecified wght axis reports its default.
+        assert_eq!(position[0].axis, u32::from_be_bytes(*b"wght"));
+        assert_eq!(position[0].value, 400.0);
+        assert_eq!(position[1].axis, u32::from_be_bytes(*b"wdth"));
+        assert!((position[1].value - 150.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_design_position_round_trip_avar() {
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable test collection could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        assert!(font_ref.font.as_ref().unwrap().avar().is_ok());
+
+        for weight in [
+            100.0, 150.0, 250.0, 300.0, 350.0, 400.0, 575.0, 700.0, 899.0, 900.0,
+        ] {
+            let coords = resolve_into_normalized_coords(
+                &font_ref,
+                &[make_design_coordinate(b"wght", weight)],
+            );
+            let mut position = [make_design_coordinate(b"\0\0\0\0", 0.0)];
+            assert_eq!(design_position(&font_ref, &coords, &mut position), 1);
+            assert_eq!(position[0].axis, u32::from_be_bytes(*b"wght"));
+            assert!(
+                (position[0].value - weight).abs() < 0.5,
+                "{} round-tripped to {}",
+                weight,
+                position[0].value
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalized_coords_to_f2dot14() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let design_coords = [
+            make_design_coordinate(b"wght", 650.0),
+            make_design_coordinate(b"wdth", 75.0),
+        ];
+        let first_coords = resolve_into_normalized_coords(&font_ref, &design_coords);
+        let second_coords = resolve_into_normalized_coords(&font_ref, &design_coords);
+
+        assert_eq!(normalized_coords_to_f2dot14(&first_coords, &mut []), 2);
+        let mut first_bits = [0i16; 2];
+        let mut second_bits = [0i16; 2];
+        assert_eq!(
+            normalized_coords_to_f2dot14(&first_coords, &mut first_bits),
+            2
+        );
+        assert_eq!(
+            normalized_coords_to_f2dot14(&second_coords, &mut second_bits),
+            2
+        );
+        assert_eq!(first_bits, second_bits);
+        // wght 650 is halfway between default and max, wdth 75 halfway
+        // between min and default, in fvar axis order.
+        assert_eq!(first_bits, [0x2000, -0x2000]);
+    }
+
+    fn make_segment_maps_data(axis_value_maps: &[(f32, f32)]) -> Vec<u8> {
+        let mut data = (axis_value_maps.len() as u16).to_be_bytes().to_vec();
+        for (from, to) in axis_value_maps {
+            data.extend(F2Dot14::from_f32(*from).to_be_bytes());
+            data.extend(F2Dot14::from_f32(*to).to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_unapply_segment_maps() {
+        let data = make_segment_maps_data(&[(-1.0, -1.0), (0.0, 0.0), (0.5, 0.75), (1.0, 1.0)]);
+        let segment_maps = SegmentMaps::read(FontData::new(&data)).unwrap();
+
+        // Segment boundaries map back exactly.
+        for (from, to) in [(-1.0, -1.0), (0.0, 0.0), (0.5, 0.75), (1.0, 1.0)] {
+            assert_eq!(unapply_segment_maps(&segment_maps, to), from);
+        }
+        // Interior points are inverted per segment.
+        assert_eq!(unapply_segment_maps(&segment_maps, -0.5), -0.5);
+        assert_eq!(unapply_segment_maps(&segment_maps, 0.375), 0.25);
+        assert_eq!(unapply_segment_maps(&segment_maps, 0.875), 0.75);
+
+        // An empty segment map is the identity.
+        let empty_data = make_segment_maps_data(&[]);
+        let empty_segment_maps = SegmentMaps::read(FontData::new(&empty_data)).unwrap();
+        assert_eq!(unapply_segment_maps(&empty_segment_maps, 0.3), 0.3);
+    }
+
+    /// Deterministic xorshift generator so that the sampled positions are
+    /// reproducible across runs.
+    fn sample_unit_interval(state: &mut u32) -> f32 {
+        *state ^= *state << 13;
+        *state ^= *state >> 17;
+        *state ^= *state << 5;
+        (*state as f32) / (u32::MAX as f32)
+    }
+
+    fn assert_random_design_positions_round_trip(font_filename: &str, epsilon: f32) {
+        let file_buffer = fs::read(font_filename).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let axes = font_ref.font.as_ref().unwrap().axes();
+        let mut rng_state = 0x2545_f491;
+        for _ in 0..500 {
+            let design_coords: Vec<SkiaDesignCoordinate> = axes
+                .iter()
+                .map(|axis| SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(axis.tag().into_bytes()),
+                    value: axis.min_value()
+                        + (axis.max_value() - axis.min_value())
+                            * sample_unit_interval(&mut rng_state),
+                })
+                .collect();
+            let coords = resolve_into_normalized_coords(&font_ref, &design_coords);
+            let mut position: Vec<SkiaDesignCoordinate> = (0..axes.len())
+                .map(|_| make_design_coordinate(b"\0\0\0\0", 0.0))
+                .collect();
+            assert_eq!(
+                design_position(&font_ref, &coords, &mut position),
+                axes.len()
+            );
+            for (expected, actual) in design_coords.iter().zip(&position) {
+                assert_eq!(expected.axis, actual.axis);
+                assert!(
+                    (expected.value - actual.value).abs() < epsilon,
+                    "{} round-tripped to {}",
+                    expected.value,
+                    actual.value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_design_position_random_round_trip_without_avar() {
+        assert_random_design_positions_round_trip(TEST_VARIABLE, 0.1);
+    }
+
+    #[test]
+    fn test_design_position_random_round_trip_with_avar() {
+        assert_random_design_positions_round_trip(TEST_CJK_VARIABLE_COLLECTION, 0.5);
+    }
+
+    #[test]
+    fn test_normalized_coords_equal_and_hash() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let coords = resolve_into_normalized_coords(
+            &font_ref,
+            &[
+                make_design_coordinate(b"wght", 650.0),
+                make_design_coordinate(b"wdth", 75.0),
+            ],
+        );
+        let permuted_coords = resolve_into_normalized_coords(
+            &font_ref,
+            &[
+                make_design_coordinate(b"wdth", 75.0),
+                make_design_coordinate(b"wght", 650.0),
+            ],
+        );
+        assert!(normalized_coords_equal(&coords, &permuted_coords));
+        assert_eq!(
+            normalized_coords_hash(&coords),
+            normalized_coords_hash(&permuted_coords)
+        );
+
+        let other_coords =
+            resolve_into_normalized_coords(&font_ref, &[make_design_coordinate(b"wght", 650.0)]);
+        assert!(!normalized_coords_equal(&coords, &other_coords));
+        assert_ne!(
+            normalized_coords_hash(&coords),
+            normalized_coords_hash(&other_coords)
+        );
+
+        // An empty location equals one explicitly resolved to the defaults.
+        let empty_coords = resolve_into_normalized_coords(&font_ref, &[]);
+        let default_coords = resolve_into_normalized_coords(
+            &font_ref,
+            &[
+                make_design_coordinate(b"wght", 400.0),
+                make_design_coordinate(b"wdth", 100.0),
+            ],
+        );
+        let no_coords = BridgeNormalizedCoords::default();
+        assert_eq!(default_coords.normalized_coords.coords().len(), 2);
+        assert!(no_coords.normalized_coords.coords().is_empty());
+        assert!(normalized_coords_equal(&empty_coords, &default_coords));
+        assert!(normalized_coords_equal(&no_coords, &default_coords));
+        assert_eq!(
+            normalized_coords_hash(&no_coords),
+            normalized_coords_hash(&default_coords)
+        );
+        assert!(!normalized_coords_equal(&no_coords, &coords));
+    }
+
+    #[test]
+    fn test_resolve_into_normalized_coords_with_report() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let design_coords = [
+            make_design_coordinate(b"wght", 10000.0),
+            make_design_coordinate(b"zzzz", 1.0),
+            make_design_coordinate(b"wdth", 150.0),
+            make_design_coordinate(b"zzzz", 2.0),
+        ];
+        let mut report = CoordinateResolutionReport::default();
+        let coords =
+            resolve_into_normalized_coords_with_report(&font_ref, &design_coords, &mut report);
+        assert_eq!(report.num_clamped, 1);
+        assert_eq!(report.unknown_axes, [u32::from_be_bytes(*b"zzzz")]);
+
+        // The applied result matches the plain resolution: wght clamped to
+        // its maximum, zzzz ignored.
+        let plain_coords = resolve_into_normalized_coords(&font_ref, &design_coords);
+        assert_eq!(
+            coords.normalized_coords.coords(),
+            plain_coords.normalized_coords.coords()
+        );
+        assert_eq!(
+            coords.filtered_user_coords,
+            plain_coords.filtered_user_coords
+        );
+        assert_eq!(coords.normalized_coords.coords()[0].to_f32(), 1.0);
+
+        let in_range_coords = [make_design_coordinate(b"wght", 900.0)];
+        resolve_into_normalized_coords_with_report(&font_ref, &in_range_coords, &mut report);
+        assert_eq!(report.num_clamped, 0);
+        assert!(report.unknown_axes.is_empty());
+    }
+
+    #[test]
+    fn test_lookup_glyphs() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        // Map a 1000 character text run in a single call.
+        let text: Vec<u32> = "The quick brown fox jumps over the lazy dog. "
+            .chars()
+            .cycle()
+            .take(1000)
+            .map(u32::from)
+            .collect();
+        let mut glyphs = vec![0u16; text.len()];
+        assert_eq!(lookup_glyphs(&font_ref, &text, &mut glyphs), text.len());
+        for (codepoint, glyph) in text.iter().zip(&glyphs) {
+            assert_eq!(*glyph, lookup_glyph_or_zero(&font_ref, *codepoint));
+        }
+
+        // Unmapped codepoints produce 0 and are not counted.
+        let codepoints = [u32::from('A'), 0xE000, u32::from('b'), 0x10FFFF];
+        let mut glyphs = [0xFFFFu16; 4];
+        assert_eq!(lookup_glyphs(&font_ref, &codepoints, &mut glyphs), 2);
+        assert_ne!(glyphs[0], 0);
+        assert_eq!(glyphs[1], 0);
+        assert_ne!(glyphs[2], 0);
+        assert_eq!(glyphs[3], 0);
+
+        // Mismatched lengths map the common prefix only.
+        let mut short_glyphs = [0xFFFFu16; 2];
+        assert_eq!(lookup_glyphs(&font_ref, &codepoints, &mut short_glyphs), 1);
+        assert_eq!(short_glyphs, [glyphs[0], 0]);
+        let mut long_glyphs = [0xFFFFu16; 6];
+        assert_eq!(
+            lookup_glyphs(&font_ref, &codepoints[..1], &mut long_glyphs),
+            1
+        );
+        assert_eq!(long_glyphs[1..], [0xFFFF; 5]);
+    }
+
+    /// Assembles a minimal sfnt from raw table data, for exercising table
+    /// layouts that the fonts in resources/ do not cover.
+    fn build_sfnt(tables: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut tables = tables.to_vec();
+        tables.sort_by_key(|(tag, _)| **tag);
+        let mut font = Vec::new();
+        font.extend(0x00010000u32.to_be_bytes());
+        font.extend((tables.len() as u16).to_be_bytes());
+        // searchRange, entrySelector and rangeShift are not needed for lookups.
+        font.extend([0u8; 6]);
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, data) in &tables {
+            font.extend(*tag);
+            font.extend(0u32.to_be_bytes());
+            font.extend((offset as u32).to_be_bytes());
+            font.extend((data.len() as u32).to_be_bytes());
+            offset += (data.len() + 3) & !3;
+        }
+        for (_, data) in &tables {
+            font.extend(data);
+            font.resize((font.len() + 3) & !3, 0);
+        }
+        font
+    }
+
+    /// Builds a cmap table from (platform ID, encoding ID, subtable) records.
+    fn build_cmap(subtables: &[(u16, u16, Vec<u8>)]) -> Vec<u8> {
+        let mut cmap = Vec::new();
+        cmap.extend(0u16.to_be_bytes());
+        cmap.extend((subtables.len() as u16).to_be_bytes());
+        let mut offset = 4 + 8 * subtables.len();
+        for (platform_id, encoding_id, subtable) in subtables {
+            cmap.extend(platform_id.to_be_bytes());
+            cmap.extend(encoding_id.to_be_bytes());
+            cmap.extend((offset as u32).to_be_bytes());
+            offset += subtable.len();
+        }
+        for (_, _, subtable) in subtables {
+            cmap.extend(subtable);
+        }
+        cmap
+    }
+
+    /// Builds a TrueType font with three glyphs: a triangle, an empty glyph
+    /// and a composite of the empty glyph.
+    fn build_glyf_font() -> Vec<u8> {
+        let mut head = Vec::new();
+        head.extend(0x00010000u32.to_be_bytes());
+        // fontRevision and checksumAdjustment
+        head.extend([0u8; 8]);
+        head.extend(0x5F0F3CF5u32.to_be_bytes());
+        // flags
+        head.extend(0u16.to_be_bytes());
+        head.extend(1000u16.to_be_bytes());
+        // created, modified, bounding box, macStyle, lowestRecPPEM and
+        // fontDirectionHint
+        head.extend([0u8; 30]);
+        // Long loca offsets.
+        head.extend(1i16.to_be_bytes());
+        head.extend(0i16.to_be_bytes());
+
+        let mut maxp = Vec::new();
+        maxp.extend(0x00005000u32.to_be_bytes());
+        maxp.extend(3u16.to_be_bytes());
+
+        let mut hhea = Vec::new();
+        hhea.extend(0x00010000u32.to_be_bytes());
+        hhea.extend([0u8; 30]);
+        hhea.extend(3u16.to_be_bytes());
+
+        let mut hmtx = Vec::new();
+        for _ in 0..3 {
+            hmtx.extend(500u16.to_be_bytes());
+            hmtx.extend(0i16.to_be_bytes());
+        }
+
+        let mut triangle = Vec::new();
+        triangle.extend(1i16.to_be_bytes());
+        for bound in [0i16, 0, 100, 100] {
+            triangle.extend(bound.to_be_bytes());
+        }
+        // endPtsOfContours and instructionLength
+        triangle.extend(2u16.to_be_bytes());
+        triangle.extend(0u16.to_be_bytes());
+        // On curve points with 16-bit coordinate deltas.
+        triangle.extend([1u8; 3]);
+        for delta in [0i16, 100, -50, 0, 0, 100] {
+            triangle.extend(delta.to_be_bytes());
+        }
+        triangle.resize((triangle.len() + 3) & !3, 0);
+
+        let mut composite = Vec::new();
+        composite.extend((-1i16).to_be_bytes());
+        composite.extend([0u8; 8]);
+        // ARGS_ARE_XY_VALUES with byte offsets referencing glyph 1.
+        composite.extend(0x0002u16.to_be_bytes());
+        composite.extend(1u16.to_be_bytes());
+        composite.extend([0u8; 2]);
+
+        let mut loca = Vec::new();
+        for offset in [
+            0,
+            triangle.len(),
+            triangle.len(),
+            triangle.len() + composite.len(),
+        ] {
+            loca.extend((offset as u32).to_be_bytes());
+        }
+        let mut glyf = triangle;
+        glyf.extend(composite);
+
+        build_sfnt(&[
+            (b"glyf", glyf),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"loca", loca),
+            (b"maxp", maxp),
+        ])
+    }
+
+    /// Builds a CFF INDEX with 32-bit offsets.
+    fn build_cff_index(items: &[Vec<u8>]) -> Vec<u8> {
+        let mut index = Vec::new();
+        index.extend((items.len() as u16).to_be_bytes());
+        if items.is_empty() {
+            return index;
+        }
+        index.push(4);
+        let mut offset = 1u32;
+        index.extend(offset.to_be_bytes());
+        for item in items {
+            offset += item.len() as u32;
+            index.extend(offset.to_be_bytes());
+        }
+        for item in items {
+            index.extend(item);
+        }
+        index
+    }
+
+    /// Builds an OpenType font with a CFF table holding `charstrings` and
+    /// the local subroutines `subrs`.
+    fn build_cff_font(charstrings: &[Vec<u8>], subrs: &[Vec<u8>]) -> Vec<u8> {
+        // Encodes a DICT operand in five bytes so that offsets can be
+        // computed before the DICT is written.
+        let dict_int = |value: usize| {
+            let mut operand = vec![29u8];
+            operand.extend((value as i32).to_be_bytes());
+            operand
+        };
+        let header = [1u8, 0, 4, 4];
+        let name_index = build_cff_index(&[b"Test".to_vec()]);
+        // The Top DICT holds a CharStrings and a Private operator.
+        let top_dict_len = 17;
+        let top_dict_index_len = build_cff_index(&[vec![0; top_dict_len]]).len();
+        let string_index = build_cff_index(&[]);
+        let global_subr_index = build_cff_index(&[]);
+        let charstrings_index = build_cff_index(charstrings);
+        let charstrings_offset = header.len()
+            + name_index.len()
+            + top_dict_index_len
+            + string_index.len()
+            + global_subr_index.len();
+        let private_dict_offset = charstrings_offset + charstrings_index.len();
+        // The Private DICT holds a Subrs operator, with the local
+        // subroutines directly following it.
+        let private_dict_len = 6;
+        let mut private_dict = dict_int(private_dict_len);
+        private_dict.push(19);
+
+        let mut top_dict = dict_int(charstrings_offset);
+        top_dict.push(17);
+        top_dict.extend(dict_int(private_dict_len));
+        top_dict.extend(dict_int(private_dict_offset));
+        top_dict.push(18);
+        assert_eq!(top_dict.len(), top_dict_len);
+
+        let mut cff = header.to_vec();
+        cff.extend(name_index);
+        cff.extend(build_cff_index(&[top_dict]));
+        cff.extend(string_index);
+        cff.extend(global_subr_index);
+        cff.extend(charstrings_index);
+        cff.extend(private_dict);
+        cff.extend(build_cff_index(subrs));
+
+        let mut head = Vec::new();
+        head.extend(0x00010000u32.to_be_bytes());
+        // fontRevision and checksumAdjustment
+        head.extend([0u8; 8]);
+        head.extend(0x5F0F3CF5u32.to_be_bytes());
+        // flags
+        head.extend(0u16.to_be_bytes());
+        head.extend(1000u16.to_be_bytes());
+        // created, modified, bounding box, macStyle, lowestRecPPEM,
+        // fontDirectionHint, indexToLocFormat and glyphDataFormat
+        head.extend([0u8; 34]);
+
+        let mut maxp = Vec::new();
+        maxp.extend(0x00005000u32.to_be_bytes());
+        maxp.extend((charstrings.len() as u16).to_be_bytes());
+
+        build_sfnt(&[(b"CFF ", cff), (b"head", head), (b"maxp", maxp)])
+    }
+
+    /// Builds a cmap format 0 subtable from (byte, glyph ID) mappings.
+    fn build_cmap0(mappings: &[(u8, u8)]) -> Vec<u8> {
+        let mut glyph_id_array = [0u8; 256];
+        for (byte, glyph_id) in mappings {
+            glyph_id_array[usize::from(*byte)] = *glyph_id;
+        }
+        let mut subtable = Vec::new();
+        subtable.extend(0u16.to_be_bytes());
+        subtable.extend(262u16.to_be_bytes());
+        subtable.extend(0u16.to_be_bytes());
+        subtable.extend(glyph_id_array);
+        subtable
+    }
+
+    /// Builds a cmap format 4 subtable from (start, end, ID delta) segments,
+    /// with deltas applied modulo 65536. The terminating 0xFFFF segment is appended.
+    fn build_cmap4(segments: &[(u16, u16, u16)]) -> Vec<u8> {
+        let mut segments = segments.to_vec();
+        segments.push((0xFFFF, 0xFFFF, 1));
+        let seg_count = segments.len() as u16;
+        let mut subtable = Vec::new();
+        subtable.extend(4u16.to_be_bytes());
+        subtable.extend((16 + 8 * seg_count).to_be_bytes());
+        subtable.extend(0u16.to_be_bytes());
+        subtable.extend((seg_count * 2).to_be_bytes());
+        // searchRange, entrySelector and rangeShift are not needed for lookups.
+        subtable.extend([0u8; 6]);
+        for (_, end, _) in &segments {
+            subtable.extend(end.to_be_bytes());
+        }
+        // reservedPad
+        subtable.extend(0u16.to_be_bytes());
+        for (start, _, _) in &segments {
+            subtable.extend(start.to_be_bytes());
+        }
+        for (_, _, id_delta) in &segments {
+            subtable.extend(id_delta.to_be_bytes());
+        }
+        // idRangeOffsets
+        subtable.extend(vec![0u8; 2 * segments.len()]);
+        subtable
+    }
+
+    /// Builds a cmap format 12 subtable from (start, end, start glyph ID)
+    /// groups.
+    fn build_cmap12(groups: &[(u32, u32, u32)]) -> Vec<u8> {
+        let mut subtable = Vec::new();
+        subtable.extend(12u16.to_be_bytes());
+        subtable.extend(0u16.to_be_bytes());
+        subtable.extend((16 + 12 * groups.len() as u32).to_be_bytes());
+        subtable.extend(0u32.to_be_bytes());
+        subtable.extend((groups.len() as u32).to_be_bytes());
+        for (start, end, start_glyph_id) in groups {
+            subtable.extend(start.to_be_bytes());
+            subtable.extend(end.to_be_bytes());
+            subtable.extend(start_glyph_id.to_be_bytes());
+        }
+        subtable
+    }
+
+    /// Builds a cmap format 14 subtable with a single variation selector
+    /// record carrying default UVS codepoints and non-default UVS
+    /// (codepoint, glyph ID) mappings.
+    fn build_cmap14(
+        variation_selector: u32,
+        default_uvs: &[u32],
+        non_default_uvs: &[(u32, u16)],
+    ) -> Vec<u8> {
+        let uint24 = |value: u32| value.to_be_bytes()[1..].to_vec();
+        let default_uvs_offset = 10 + 11;
+        let non_default_uvs_offset = default_uvs_offset + 4 + 4 * default_uvs.len();
+        let length = non_default_uvs_offset + 4 + 5 * non_default_uvs.len();
+        let mut subtable = Vec::new();
+        subtable.extend(14u16.to_be_bytes());
+        subtable.extend((length as u32).to_be_bytes());
+        subtable.extend(1u32.to_be_bytes());
+        subtable.extend(uint24(variation_selector));
+        subtable.extend((default_uvs_offset as u32).to_be_bytes());
+        subtable.extend((non_default_uvs_offset as u32).to_be_bytes());
+        subtable.extend((default_uvs.len() as u32).to_be_bytes());
+        for codepoint in default_uvs {
+            subtable.extend(uint24(*codepoint));
+            // additionalCount
+            subtable.push(0);
+        }
+        subtable.extend((non_default_uvs.len() as u32).to_be_bytes());
+        for (codepoint, glyph_id) in non_default_uvs {
+            subtable.extend(uint24(*codepoint));
+            subtable.extend(glyph_id.to_be_bytes());
+        }
+        subtable
+    }
+
+    #[test]
+    fn test_lookup_glyph_variant() {
+        const VS16: u32 = 0xFE0F;
+        const VS15: u32 = 0xFE0E;
+        // U+2764 maps to glyph 1 by default and to glyph 5 with VS16, U+263A
+        // uses the default mapping, glyph 2, with VS16.
+        let cmap = build_cmap(&[
+            (
+                3,
+                10,
+                build_cmap12(&[(0x263A, 0x263A, 2), (0x2764, 0x2764, 1)]),
+            ),
+            (0, 5, build_cmap14(VS16, &[0x263A], &[(0x2764, 5)])),
+        ]);
+        let font_data = build_sfnt(&[(b"cmap", cmap)]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 0x2764), 1);
+        assert_eq!(lookup_glyph_variant(&font_ref, 0x2764, VS16), 5);
+        assert_eq!(lookup_glyph_variant(&font_ref, 0x263A, VS16), 2);
+        // Sequences without a UVS mapping return 0.
+        assert_eq!(lookup_glyph_variant(&font_ref, 0x2764, VS15), 0);
+        assert_eq!(lookup_glyph_variant(&font_ref, 0x2600, VS16), 0);
+    }
+
+    #[test]
+    fn test_lookup_glyph_variant_default_uvs() {
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable test collection could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        // U+662F with the ideographic variation selector U+E0100 is listed as
+        // a default UVS and resolves to the regular mapping.
+        let default_glyph = lookup_glyph_or_zero(&font_ref, 0x662F);
+        assert_ne!(default_glyph, 0);
+        assert_eq!(
+            lookup_glyph_variant(&font_ref, 0x662F, 0xE0100),
+            default_glyph
+        );
+        assert_eq!(lookup_glyph_variant(&font_ref, 0x662F, 0xE0101), 0);
+    }
+
+    #[test]
+    fn test_has_codepoint() {
+        let file_buffer = fs::read(TEST_COLR).expect("COLRv0 test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        // U+0000 is explicitly mapped to .notdef.
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 0), 0);
+        assert!(has_codepoint(&font_ref, 0));
+        assert!(!has_codepoint(&font_ref, 0x4E00));
+    }
+
+    #[test]
+    fn test_has_codepoint_ignores_format_4_sentinel() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        assert!(has_codepoint(&font_ref, u32::from('a')));
+        assert!(!has_codepoint(&font_ref, 0xFFFF));
+    }
+
+    #[test]
+    fn test_fill_unichar_map() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let count = count_mapped_unichars(&font_ref);
+        assert_eq!(count, 215);
+        let mut unichars = vec![0u32; count];
+        let mut glyphs = vec![0u16; count];
+        assert_eq!(
+            fill_unichar_map(&font_ref, &mut unichars, &mut glyphs),
+            count
+        );
+        assert!(unichars.windows(2).all(|pair| pair[0] < pair[1]));
+        for (unichar, glyph) in unichars.iter().zip(&glyphs) {
+            assert_eq!(*glyph, lookup_glyph_or_zero(&font_ref, *unichar));
+        }
+
+        // Shorter buffers receive the lowest codepoints.
+        let mut short_unichars = [0u32; 3];
+        let mut short_glyphs = [0u16; 5];
+        assert_eq!(
+            fill_unichar_map(&font_ref, &mut short_unichars, &mut short_glyphs),
+            3
+        );
+        assert_eq!(short_unichars, unichars[..3]);
+        assert_eq!(short_glyphs[..3], glyphs[..3]);
+    }
+
+    #[test]
+    fn test_fill_unichar_map_supplementary_planes() {
+        let cmap = build_cmap(&[(
+            3,
+            10,
+            build_cmap12(&[(0x41, 0x43, 1), (0x1F600, 0x1F601, 4)]),
+        )]);
+        let font_data = build_sfnt(&[(b"cmap", cmap)]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        assert_eq!(count_mapped_unichars(&font_ref), 5);
+        let mut unichars = [0u32; 5];
+        let mut glyphs = [0u16; 5];
+        assert_eq!(fill_unichar_map(&font_ref, &mut unichars, &mut glyphs), 5);
+        assert_eq!(unichars, [0x41, 0x42, 0x43, 0x1F600, 0x1F601]);
+        assert_eq!(glyphs, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_glyphs_to_unicode() {
+        // Glyph 1 is mapped from three codepoints, glyph 2 from one, glyph 3
+        // from none.
+        let cmap = build_cmap(&[(
+            3,
+            10,
+            build_cmap12(&[
+                (0x41, 0x41, 1),
+                (0x42, 0x42, 2),
+                (0x391, 0x391, 1),
+                (0x1D400, 0x1D400, 1),
+            ]),
+        )]);
+        let font_data = build_sfnt(&[(b"cmap", cmap)]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let mut codepoints = [0xFFFFFFFFu32; 4];
+        glyphs_to_unicode(&font_ref, &mut codepoints);
+        assert_eq!(codepoints, [0, 0x41, 0x42, 0]);
+    }
+
+    #[test]
+    fn test_glyphs_to_unicode_round_trip() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let mut codepoints = vec![0u32; usize::from(num_glyphs(&font_ref))];
+        glyphs_to_unicode(&font_ref, &mut codepoints);
+        for codepoint in ['A', 'z', '0'].map(u32::from) {
+            let glyph_id = lookup_glyph_or_zero(&font_ref, codepoint);
+            assert_eq!(codepoints[usize::from(glyph_id)], codepoint);
+        }
+    }
+
+    #[test]
+    fn test_glyph_name_post_format_2() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        assert!(has_glyph_names(&font_ref));
+        let mut name = String::new();
+        // Standard Macintosh glyph names.
+        assert!(glyph_name(&font_ref, 0, &mut name));
+        assert_eq!(name, ".notdef");
+        assert!(glyph_name(&font_ref, 3, &mut name));
+        assert_eq!(name, "a");
+        // Custom name from the post string data.
+        assert!(glyph_name(&font_ref, 7, &mut name));
+        assert_eq!(name, "tbar");
+        assert!(!glyph_name(&font_ref, num_glyphs(&font_ref), &mut name));
+    }
+
+    #[test]
+    fn test_glyph_name_post_format_3() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        assert!(!has_glyph_names(&font_ref));
+        let mut name = String::new();
+        assert!(!glyph_name(&font_ref, 1, &mut name));
+    }
+
+    #[test]
+    fn test_glyph_name_malformed_post() {
+        let mut post = Vec::new();
+        post.extend(0x00020000u32.to_be_bytes());
+        // italicAngle through maxMemType1.
+        post.extend([0u8; 28]);
+        // Glyph 0 uses a standard name, glyph 1 the only custom name, glyph 2
+        // an index past the end of the string data.
+        post.extend(3u16.to_be_bytes());
+        for name_index in [0u16, 258, 300] {
+            post.extend(name_index.to_be_bytes());
+        }
+        post.push(6);
+        post.extend(b"custom");
+        let font_data = build_sfnt(&[(b"post", post)]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let mut name = String::new();
+        assert!(glyph_name(&font_ref, 0, &mut name));
+        assert_eq!(name, ".notdef");
+        assert!(glyph_name(&font_ref, 1, &mut name));
+        assert_eq!(name, "custom");
+        assert!(!glyph_name(&font_ref, 2, &mut name));
+        assert!(!glyph_name(&font_ref, 3, &mut name));
+    }
+
+    #[test]
+    fn test_symbol_charmap_fallback() {
+        // U+F041..U+F05A map to glyphs 1..26.
+        let cmap = build_cmap(&[(
+            3,
+            0,
+            build_cmap4(&[(0xF041, 0xF05A, 0u16.wrapping_sub(0xF040))]),
+        )]);
+        let font_data = build_sfnt(&[(b"cmap", cmap)]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        assert!(has_symbol_charmap(&font_ref));
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 'A' as u32), 1);
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 0xF041), 1);
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 'Z' as u32), 26);
+        // Only the 0x00-0xFF range is retried in the PUA.
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 0x141), 0);
+
+        let mut glyphs = [0u16; 3];
+        assert_eq!(
+            lookup_glyphs(
+                &font_ref,
+                &['A' as u32, 'a' as u32, 'C' as u32],
+                &mut glyphs
+            ),
+            2
+        );
+        assert_eq!(glyphs, [1, 0, 3]);
+    }
+
+    #[test]
+    fn test_unicode_charmap_is_not_symbol() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        assert!(!has_symbol_charmap(&font_ref));
+    }
+
+    #[test]
+    fn test_unicode_to_mac_roman() {
+        assert_eq!(unicode_to_mac_roman('A' as u32), Some(b'A'));
+        assert_eq!(unicode_to_mac_roman(0xC4), Some(0x80));
+        assert_eq!(unicode_to_mac_roman(0x2C7), Some(0xFF));
+        assert_eq!(unicode_to_mac_roman(0x20AC), Some(0xDB));
+        assert_eq!(unicode_to_mac_roman(0x100), None);
+        for (index, &unicode) in MAC_ROMAN_TO_UNICODE.iter().enumerate() {
+            assert_eq!(
+                unicode_to_mac_roman(unicode.into()),
+                Some(0x80 + index as u8)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mac_roman_charmap_fallback() {
+        // Mac Roman 0x8E is é and 0x9F is ü.
+        let cmap = build_cmap(&[(1, 0, build_cmap0(&[(b'e', 1), (0x8E, 2), (0x9F, 3)]))]);
+        let font_data = build_sfnt(&[(b"cmap", cmap)]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 'e' as u32), 1);
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 'é' as u32), 2);
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 'ü' as u32), 3);
+        // The Mac Roman byte values themselves are not Unicode input.
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 0x8E), 0);
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 'f' as u32), 0);
+
+        let mut glyphs = [0u16; 4];
+        assert_eq!(
+            lookup_glyphs(
+                &font_ref,
+                &['ü' as u32, 'x' as u32, 'é' as u32, 'e' as u32],
+                &mut glyphs
+            ),
+            3
+        );
+        assert_eq!(glyphs, [3, 0, 2, 1]);
+
+        // The other charmap queries use the same subtable.
+        assert!(has_codepoint(&font_ref, 'é' as u32));
+        assert!(!has_codepoint(&font_ref, 0x8E));
+        assert_eq!(count_mapped_unichars(&font_ref), 3);
+        let mut unichars = [0u32; 3];
+        let mut unichar_glyphs = [0u16; 3];
+        assert_eq!(
+            fill_unichar_map(&font_ref, &mut unichars, &mut unichar_glyphs),
+            3
+        );
+        assert_eq!(unichars, ['e' as u32, 'é' as u32, 'ü' as u32]);
+        assert_eq!(unichar_glyphs, [1, 2, 3]);
+        let mut codepoints = [0u32; 4];
+        glyphs_to_unicode(&font_ref, &mut codepoints);
+        assert_eq!(codepoints, [0, 'e' as u32, 'é' as u32, 'ü' as u32]);
+    }
+
+    #[test]
+    fn test_mac_roman_ignored_with_unicode_charmap() {
+        let cmap = build_cmap(&[
+            (1, 0, build_cmap0(&[(0x8E, 2)])),
+            (3, 1, build_cmap4(&[(0xE9, 0xE9, 5u16.wrapping_sub(0xE9))])),
+        ]);
+        let font_data = build_sfnt(&[(b"cmap", cmap)]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        assert_eq!(lookup_glyph_or_zero(&font_ref, 'é' as u32), 5);
+    }
+
+    #[test]
+    fn test_cmap_subtable_info_prefers_format_12() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let mut infos = Vec::new();
+        cmap_subtable_info(&font_ref, &mut infos);
+        let subtables: Vec<_> = infos
+            .iter()
+            .map(|info| {
+                (
+                    info.platform_id,
+                    info.encoding_id,
+                    info.format,
+                    info.selected,
+                )
+            })
+            .collect();
+        assert_eq!(
+            subtables,
+            [(0, 3, 4, false), (3, 1, 4, false), (3, 10, 12, true)]
+        );
+        assert!(infos.iter().all(|info| info.num_entries > 0));
+        assert_eq!(
+            infos[2].num_entries as usize,
+            count_mapped_unichars(&font_ref)
+        );
+    }
+
+    #[test]
+    fn test_cmap_subtable_info_synthetic() {
+        // The Windows Symbol subtable wins over Unicode ones, format 14 is
+        // never selected for nominal mapping.
+        let cmap = build_cmap(&[
+            (0, 3, build_cmap4(&[(0x41, 0x42, 0u16.wrapping_sub(0x40))])),
+            (0, 5, build_cmap14(0xFE00, &[0x41], &[(0x42, 3)])),
+            (1, 0, build_cmap0(&[(0x41, 1)])),
+            (
+                3,
+                0,
+                build_cmap4(&[(0xF041, 0xF041, 0u16.wrapping_sub(0xF040))]),
+            ),
+        ]);
+        let font_data = build_sfnt(&[(b"cmap", cmap)]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let mut infos = vec![BridgeCmapSubtableInfo::default()];
+        cmap_subtable_info(&font_ref, &mut infos);
+        let subtables: Vec<_> = infos
+            .iter()
+            .map(|info| {
+                (
+                    info.platform_id,
+                    info.encoding_id,
+                    info.format,
+                    info.num_entries,
+                    info.selected,
+                )
+            })
+            .collect();
+        assert_eq!(
+            subtables,
+            [
+                (0, 3, 4, 2, false),
+                (0, 5, 14, 2, false),
+                (1, 0, 0, 1, false),
+                (3, 0, 4, 1, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cmap_subtable_info_mac_roman_only() {
+        let cmap = build_cmap(&[(1, 0, build_cmap0(&[(0x8E, 2)]))]);
+        let font_data = build_sfnt(&[(b"cmap", cmap)]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let mut infos = Vec::new();
+        cmap_subtable_info(&font_ref, &mut infos);
+        assert_eq!(infos.len(), 1);
+        assert!(infos[0].selected);
+    }
+
+    #[test]
+    fn test_is_empty_glyph() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let coords = BridgeNormalizedCoords::default();
+
+        let space = lookup_glyph_or_zero(&font_ref, ' ' as u32);
+        let letter = lookup_glyph_or_zero(&font_ref, 'A' as u32);
+        assert_ne!(space, 0);
+        assert!(is_empty_glyph(&font_ref, &coords, space));
+        assert!(!is_empty_glyph(&font_ref, &coords, letter));
+        assert!(!is_empty_glyph(&font_ref, &coords, num_glyphs(&font_ref)));
+
+        let mut is_empty = [true; 3];
+        assert_eq!(
+            are_empty_glyphs(
+                &font_ref,
+                &coords,
+                &[letter, space, num_glyphs(&font_ref)],
+                &mut is_empty
+            ),
+            1
+        );
+        assert_eq!(is_empty, [false, true, false]);
+    }
+
+    #[test]
+    fn test_is_empty_glyph_composite() {
+        let font_data = build_glyf_font();
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let coords = BridgeNormalizedCoords::default();
+
+        assert!(!is_empty_glyph(&font_ref, &coords, 0));
+        assert!(is_empty_glyph(&font_ref, &coords, 1));
+        assert!(is_empty_glyph(&font_ref, &coords, 2));
+        assert!(!is_empty_glyph(&font_ref, &coords, 3));
+    }
+
+    /// Reads the table tags from the table directory at `offset` in `data`.
+    fn read_directory_tags(data: &[u8], offset: usize) -> Vec<u32> {
+        let num_tables = u16::from_be_bytes([data[offset + 4], data[offset + 5]]) as usize;
+        (0..num_tables)
+            .map(|i| {
+                let record = offset + 12 + 16 * i;
+                u32::from_be_bytes(data[record..record + 4].try_into().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_table_tags() {
+        let file_buffer = fs::read(TEST_FONT_FILENAME).expect("Test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let expected_tags = read_directory_tags(&file_buffer, 0);
+        let num_tables = table_tags(&font_ref, &mut []);
+        assert_eq!(num_tables, expected_tags.len());
+        let mut tags = vec![0u32; num_tables];
+        assert_eq!(table_tags(&font_ref, &mut tags), num_tables);
+        assert_eq!(tags, expected_tags);
+        assert!(tags.contains(&u32::from_be_bytes(*b"cmap")));
+
+        let mut partial_tags = [0u32; 2];
+        assert_eq!(table_tags(&font_ref, &mut partial_tags), 2);
+        assert_eq!(partial_tags, expected_tags[..2]);
+    }
+
+    #[test]
+    fn test_table_tags_collection() {
+        let file_buffer =
+            fs::read(TEST_COLLECTION_FILENAME).expect("Test collection could not be opened.");
+        let num_fonts = u32::from_be_bytes(file_buffer[8..12].try_into().unwrap());
+        for index in 0..num_fonts {
+            let font_ref = make_font_ref(&file_buffer, index);
+            assert!(font_ref_is_valid(&font_ref));
+            let offset_pos = 12 + 4 * index as usize;
+            let offset =
+                u32::from_be_bytes(file_buffer[offset_pos..offset_pos + 4].try_into().unwrap());
+            let expected_tags = read_directory_tags(&file_buffer, offset as usize);
+            let mut tags = vec![0u32; table_tags(&font_ref, &mut [])];
+            table_tags(&font_ref, &mut tags);
+            assert_eq!(tags, expected_tags);
+        }
+    }
+
+    #[test]
+    fn test_has_table() {
+        let file_buffer = fs::read(TEST_COLR).expect("COLR test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let tags = [*b"COLR", *b"fvar", *b"cmap", *b"CBDT"].map(u32::from_be_bytes);
+        for tag in tags {
+            let mut data = [];
+            assert_eq!(
+                has_table(&font_ref, tag),
+                table_data(&font_ref, tag, 0, &mut data) > 0
+            );
+        }
+        let mut present = [false; 4];
+        assert_eq!(has_tables(&font_ref, &tags, &mut present), 2);
+        assert_eq!(present, [true, false, true, false]);
+    }
+
+    #[test]
+    fn test_has_table_malformed_directory() {
+        let file_buffer = fs::read(TEST_FONT_FILENAME).expect("Test font could not be opened.");
+        // The table directory claims more records than the data holds.
+        let truncated = &file_buffer[..20];
+        let font_ref = make_font_ref(truncated, 0);
+        let cmap = u32::from_be_bytes(*b"cmap");
+        assert!(!has_table(&font_ref, cmap));
+        let mut present = [true];
+        assert_eq!(has_tables(&font_ref, &[cmap], &mut present), 0);
+        assert_eq!(present, [false]);
+
+        // A directory entry is reported even if its data is out of bounds.
+        let font_data = build_sfnt(&[(b"cmap", vec![0u8; 8])]);
+        let font_data = &font_data[..font_data.len() - 8];
+        let font_ref = make_font_ref(font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        assert!(has_table(&font_ref, cmap));
+        let mut data = [];
+        assert_eq!(table_data(&font_ref, cmap, 0, &mut data), 0);
+    }
+
+    #[test]
+    fn test_table_data_slice() {
+        let file_buffer = fs::read(TEST_FONT_FILENAME).expect("Test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+
+        let mut tags = vec![0u32; table_tags(&font_ref, &mut [])];
+        table_tags(&font_ref, &mut tags);
+        for tag in tags {
+            let slice = table_data_slice(&font_ref, tag);
+            let mut copied = vec![0u8; table_data(&font_ref, tag, 0, &mut [])];
+            assert_eq!(table_data(&font_ref, tag, 0, &mut copied), copied.len());
+            assert_eq!(slice, copied);
+            // The slice borrows the original font data.
+            assert!(file_buffer.as_ptr_range().contains(&slice.as_ptr()));
+        }
+        assert!(table_data_slice(&font_ref, u32::from_be_bytes(*b"zzzz")).is_empty());
+    }
+
+    mod table_data_tests {
+        use super::*;
+
+        const TAG: u32 = u32::from_be_bytes(*b"test");
+
+        fn test_font(table: Vec<u8>) -> Vec<u8> {
+            build_sfnt(&[(b"test", table)])
+        }
+
+        #[test]
+        fn test_full_and_partial_copies() {
+            let font_data = test_font((0..16).collect());
+            let font_ref = make_font_ref(&font_data, 0);
+            assert!(font_ref_is_valid(&font_ref));
+
+            // An empty buffer reports the length remaining after the offset.
+            assert_eq!(table_data(&font_ref, TAG, 0, &mut []), 16);
+            assert_eq!(table_data(&font_ref, TAG, 10, &mut []), 6);
+
+            let mut data = [0u8; 4];
+            assert_eq!(table_data(&font_ref, TAG, 2, &mut data), 4);
+            assert_eq!(data, [2, 3, 4, 5]);
+
+            // Destination buffers larger than the table copy what is left.
+            let mut data = vec![0xFFu8; 1 << 20];
+            assert_eq!(table_data(&font_ref, TAG, 12, &mut data), 4);
+            assert_eq!(data[..5], [12, 13, 14, 15, 0xFF]);
+        }
+
+        #[test]
+        fn test_boundary_offsets() {
+            let font_data = test_font((0..16).collect());
+            let font_ref = make_font_ref(&font_data, 0);
+            assert!(font_ref_is_valid(&font_ref));
+
+            let mut data = [0u8; 4];
+            assert_eq!(table_data(&font_ref, TAG, 15, &mut data), 1);
+            assert_eq!(data[0], 15);
+            for offset in [16, 17, usize::MAX - 1, usize::MAX] {
+                assert_eq!(table_data(&font_ref, TAG, offset, &mut []), 0);
+                assert_eq!(table_data(&font_ref, TAG, offset, &mut data), 0);
+            }
+        }
+
+        #[test]
+        fn test_zero_length_table() {
+            let font_data = build_sfnt(&[(b"aaaa", vec![1, 2, 3, 4]), (b"test", vec![])]);
+            let font_ref = make_font_ref(&font_data, 0);
+            assert!(font_ref_is_valid(&font_ref));
+
+            let mut data = [0u8; 4];
+            assert_eq!(table_data(&font_ref, TAG, 0, &mut []), 0);
+            assert_eq!(table_data(&font_ref, TAG, 0, &mut data), 0);
+            assert_eq!(table_data(&font_ref, TAG, usize::MAX, &mut data), 0);
+        }
+
+        #[test]
+        fn test_corrupted_directory_entry() {
+            // Table record fields follow the 12 byte header: tag, checksum,
+            // offset and length.
+            let mut font_data = test_font((0..16).collect());
+            font_data[24..28].copy_from_slice(&u32::MAX.to_be_bytes());
+            let font_ref = make_font_ref(&font_data, 0);
+            assert!(font_ref_is_valid(&font_ref));
+            let mut data = [0u8; 4];
+            // A table whose length overflows the font data is unavailable.
+            assert_eq!(table_data(&font_ref, TAG, 0, &mut []), 0);
+            assert_eq!(table_data(&font_ref, TAG, 0, &mut data), 0);
+
+            let mut font_data = test_font((0..16).collect());
+            font_data[20..24].copy_from_slice(&(u32::MAX - 8).to_be_bytes());
+            let font_ref = make_font_ref(&font_data, 0);
+            assert!(font_ref_is_valid(&font_ref));
+            assert_eq!(table_data(&font_ref, TAG, 0, &mut []), 0);
+            assert_eq!(table_data(&font_ref, TAG, 4, &mut data), 0);
+        }
+    }
+
+    #[test]
+    fn test_font_count() {
+        let collection_buffer = fs::read(TEST_COLLECTION_FILENAME)
+            .expect("Unable to open TrueType collection test file.");
+        let font_buffer = fs::read(TEST_FONT_FILENAME).expect("Test font could not be opened.");
+        assert_eq!(font_count(&collection_buffer), 2);
+        assert_eq!(font_count(&font_buffer), 1);
+        assert_eq!(font_count(b"0ab0ab0ab0ab"), 0);
+        assert_eq!(font_count(&[]), 0);
+    }
+
+    #[test]
+    fn test_make_font_ref_rejects_out_of_range_index() {
+        let collection_buffer = fs::read(TEST_COLLECTION_FILENAME)
+            .expect("Unable to open TrueType collection test file.");
+        for index in 0..2 {
+            assert!(font_ref_is_valid(&make_font_ref(&collection_buffer, index)));
+        }
+        assert!(!font_ref_is_valid(&make_font_ref(&collection_buffer, 2)));
+        assert!(!font_ref_is_valid(&make_font_ref(
+            &collection_buffer,
+            u32::MAX
+        )));
+
+        let font_buffer = fs::read(TEST_FONT_FILENAME).expect("Test font could not be opened.");
+        assert!(font_ref_is_valid(&make_font_ref(&font_buffer, 0)));
+        assert!(!font_ref_is_valid(&make_font_ref(&font_buffer, 1)));
+    }
+
+    #[test]
+    fn test_extract_font_from_collection() {
+        let collection_buffer = fs::read(TEST_COLLECTION_FILENAME)
+            .expect("Unable to open TrueType collection test file.");
+        let collection_font_ref = make_font_ref(&collection_buffer, 1);
+        assert!(font_ref_is_valid(&collection_font_ref));
+
+        let mut font_data = Vec::new();
+        assert!(extract_font_from_collection(
+            &collection_buffer,
+            1,
+            &mut font_data
+        ));
+        assert_eq!(font_count(&font_data), 1);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        assert_eq!(num_glyphs(&font_ref), num_glyphs(&collection_font_ref));
+
+        let mut tags = vec![0u32; table_tags(&collection_font_ref, &mut [])];
+        table_tags(&collection_font_ref, &mut tags);
+        let mut extracted_tags = vec![0u32; table_tags(&font_ref, &mut [])];
+        table_tags(&font_ref, &mut extracted_tags);
+        assert_eq!(extracted_tags, tags);
+        let head = u32::from_be_bytes(*b"head");
+        for tag in tags.into_iter().filter(|tag| *tag != head) {
+            assert_eq!(
+                table_data_slice(&font_ref, tag),
+                table_data_slice(&collection_font_ref, tag)
+            );
+        }
+        assert_eq!(table_checksum(&font_data), CHECKSUM_ADJUSTMENT_MAGIC);
+    }
+
+    #[test]
+    fn test_extract_font_dedupes_shared_tables() {
+        // Point both table records at the same data.
+        let mut font_data = build_sfnt(&[(b"aaaa", vec![1, 2, 3, 4]), (b"bbbb", vec![5, 6, 7, 8])]);
+        let first_offset: [u8; 4] = font_data[20..24].try_into().unwrap();
+        font_data[36..40].copy_from_slice(&first_offset);
+
+        let mut extracted = Vec::new();
+        assert!(extract_font_from_collection(&font_data, 0, &mut extracted));
+        assert_eq!(extracted.len(), 12 + 2 * 16 + 4);
+        let font_ref = make_font_ref(&extracted, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        assert_eq!(
+            table_data_slice(&font_ref, u32::from_be_bytes(*b"bbbb")),
+            [1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_extract_font_invalid_input() {
+        let collection_buffer = fs::read(TEST_COLLECTION_FILENAME)
+            .expect("Unable to open TrueType collection test file.");
+        let mut font_data = vec![0u8; 4];
+        assert!(!extract_font_from_collection(
+            &collection_buffer,
+            2,
+            &mut font_data
+        ));
+        assert!(font_data.is_empty());
+        assert!(!extract_font_from_collection(
+            b"0ab0ab0ab0ab",
+            0,
+            &mut font_data
+        ));
+
+        // A table record pointing past the end of the data.
+        let mut truncated = build_sfnt(&[(b"aaaa", vec![1, 2, 3, 4])]);
+        truncated.truncate(truncated.len() - 4);
+        assert!(!extract_font_from_collection(&truncated, 0, &mut font_data));
+        assert!(font_data.is_empty());
+    }
+
+    #[test]
+    fn test_verify_checksums() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let result = verify_checksums(&font_ref);
+        assert!(result.verifiable);
+        assert_eq!(result.num_mismatched_tables, 0);
+        assert!(result.checksum_adjustment_valid);
+
+        // Flip the first byte of the first table in the directory.
+        let mut corrupted = file_buffer.clone();
+        let first_table_offset = u32::from_be_bytes(corrupted[20..24].try_into().unwrap());
+        corrupted[first_table_offset as usize] ^= 0x80;
+        let font_ref = make_font_ref(&corrupted, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let result = verify_checksums(&font_ref);
+        assert!(result.verifiable);
+        assert_eq!(result.num_mismatched_tables, 1);
+        assert!(!result.checksum_adjustment_valid);
+    }
+
+    #[test]
+    fn test_verify_checksums_not_verifiable() {
+        let mut font_data = build_sfnt(&[(b"aaaa", vec![1, 2, 3, 4])]);
+        font_data.truncate(font_data.len() - 4);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        assert!(!verify_checksums(&font_ref).verifiable);
+
+        let font_ref = make_font_ref(&font_data[..8], 0);
+        assert!(!verify_checksums(&font_ref).verifiable);
+    }
+
+    #[test]
+    fn test_xxhash64() {
+        assert_eq!(XxHash64::new().finish(), 0xEF46DB3751D8E999);
+        let mut hasher = XxHash64::new();
+        hasher.write(b"abc");
+        assert_eq!(hasher.finish(), 0x44BC2CF5AD770999);
+
+        // Results do not depend on how the input is split.
+        let data: Vec<u8> = (0..200u32).map(|i| (i * 31) as u8).collect();
+        let mut whole = XxHash64::new();
+        whole.write(&data);
+        let mut pieces = XxHash64::new();
+        for piece in data.chunks(7) {
+            pieces.write(piece);
+        }
+        assert_eq!(whole.finish(), pieces.finish());
+    }
+
+    #[test]
+    fn test_font_digest() {
+        let file_buffer = fs::read(TEST_FONT_FILENAME).expect("Test font could not be opened.");
+        let file_buffer_copy = file_buffer.clone();
+        let digest = font_digest(&make_font_ref(&file_buffer, 0));
+        assert_ne!(digest, 0);
+        assert_eq!(font_digest(&make_font_ref(&file_buffer_copy, 0)), digest);
+        assert_eq!(font_digest(&make_font_ref(&file_buffer, 0)), digest);
+
+        let collection_buffer = fs::read(TEST_COLLECTION_FILENAME)
+            .expect("Unable to open TrueType collection test file.");
+        let first_face = font_digest(&make_font_ref(&collection_buffer, 0));
+        let second_face = font_digest(&make_font_ref(&collection_buffer, 1));
+        assert_ne!(first_face, second_face);
+        assert_eq!(
+            font_digest(&make_font_ref(&collection_buffer, 1)),
+            second_face
+        );
+        assert_ne!(first_face, digest);
+
+        // Padding between tables does not affect the digest.
+        let a = build_sfnt(&[(b"aaaa", vec![1, 2, 3]), (b"bbbb", vec![4, 5])]);
+        let mut b = a.clone();
+        let aaaa_offset = u32::from_be_bytes(b[20..24].try_into().unwrap()) as usize;
+        b[aaaa_offset + 3] = 0xFF;
+        assert_eq!(
+            font_digest(&make_font_ref(&a, 0)),
+            font_digest(&make_font_ref(&b, 0))
+        );
+
+        assert_eq!(font_digest(&make_font_ref(&[], 0)), 0);
+    }
+
+    #[test]
+    fn test_font_ref_collection_index_and_size() {
+        let collection_buffer = fs::read(TEST_COLLECTION_FILENAME)
+            .expect("Unable to open TrueType collection test file.");
+        let mut sizes = Vec::new();
+        for index in 0..2 {
+            let font_ref = make_font_ref(&collection_buffer, index);
+            assert!(font_ref_is_valid(&font_ref));
+            assert_eq!(font_ref_collection_index(&font_ref), index);
+            let size = font_ref_data_size(&font_ref);
+            assert!(size > 0 && size < collection_buffer.len());
+            sizes.push(size);
+        }
+        assert_ne!(sizes[0], sizes[1]);
+
+        // A standalone font's data size equals its unpadded file layout.
+        let font_data = build_sfnt(&[(b"aaaa", vec![1, 2, 3]), (b"bbbb", vec![4, 5, 6, 7])]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert_eq!(font_ref_collection_index(&font_ref), 0);
+        assert_eq!(font_ref_data_size(&font_ref), 12 + 2 * 16 + 3 + 4);
+
+        let font_ref = make_font_ref(&collection_buffer, 5);
+        assert!(!font_ref_is_valid(&font_ref));
+        assert_eq!(font_ref_collection_index(&font_ref), 5);
+        assert_eq!(font_ref_data_size(&font_ref), 0);
+    }
+
+    #[test]
+    fn test_make_font_ref_owned() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let borrowed_font_ref = make_font_ref(&file_buffer, 0);
+        let owned_font_ref = make_font_ref_owned(fs::read(TEST_ROBOTO).unwrap(), 0);
+        assert!(font_ref_is_valid(&owned_font_ref));
+
+        let coords = BridgeNormalizedCoords::default();
+        let glyph_id = lookup_glyph_or_zero(&owned_font_ref, 'A' as u32);
+        assert_ne!(glyph_id, 0);
+        assert_eq!(
+            lookup_glyph_or_zero(&borrowed_font_ref, 'A' as u32),
+            glyph_id
+        );
+        assert_eq!(num_glyphs(&owned_font_ref), num_glyphs(&borrowed_font_ref));
+        assert_eq!(
+            advance_width_or_zero(
+                &owned_font_ref,
+                16.0,
+                &coords,
+                glyph_id,
+                SizeRounding::Exact
+            ),
+            advance_width_or_zero(
+                &borrowed_font_ref,
+                16.0,
+                &coords,
+                glyph_id,
+                SizeRounding::Exact
+            )
+        );
+        assert!(!is_empty_glyph(&owned_font_ref, &coords, glyph_id));
+        assert_eq!(
+            font_digest(&owned_font_ref),
+            font_digest(&borrowed_font_ref)
+        );
+    }
+
+    #[test]
+    fn test_make_font_ref_owned_outlives_source() {
+        let owned_font_ref = {
+            let file_buffer =
+                fs::read(TEST_COLLECTION_FILENAME).expect("Test collection could not be opened.");
+            make_font_ref_owned(file_buffer.clone(), 1)
+        };
+        assert!(font_ref_is_valid(&owned_font_ref));
+        assert_eq!(font_ref_collection_index(&owned_font_ref), 1);
+        let outlines = get_outline_collection(&owned_font_ref);
+        let coords = BridgeNormalizedCoords::default();
+        let glyph_id = lookup_glyph_or_zero(&owned_font_ref, 'A' as u32);
+        assert!(outlines
+            .outlines
+            .as_ref()
+            .unwrap()
+            .get(font_types::GlyphId::new(glyph_id))
+            .is_some());
+        assert!(
+            advance_width_or_zero(
+                &owned_font_ref,
+                12.0,
+                &coords,
+                glyph_id,
+                SizeRounding::Exact
+            ) > 0.0
+        );
+        assert!(!is_empty_glyph(&owned_font_ref, &coords, glyph_id));
+
+        assert!(!font_ref_is_valid(&make_font_ref_owned(vec![0u8; 12], 0)));
+    }
+
+    #[test]
+    fn test_make_font_ref_owned_moved() {
+        // Builds the font in memory so that the test also runs under Miri,
+        // which checks reads through the moved reference for aliasing.
+        let font_data = build_glyf_font();
+        let borrowed_font_ref = make_font_ref(&font_data, 0);
+        let owned_font_refs: Vec<_> = (0..2)
+            .map(|_| make_font_ref_owned(font_data.clone(), 0))
+            .collect();
+        for owned_font_ref in owned_font_refs {
+            let moved_font_ref = owned_font_ref;
+            assert!(font_ref_is_valid(&moved_font_ref));
+            assert_eq!(num_glyphs(&moved_font_ref), num_glyphs(&borrowed_font_ref));
+            assert_eq!(
+                font_ref_data_size(&moved_font_ref),
+                font_ref_data_size(&borrowed_font_ref)
+            );
+        }
+    }
+
+    /// Pen counting the drawing commands it receives.
+    #[derive(Default)]
+    struct CountingPen {
+        num_commands: usize,
+    }
+
+    impl Pen for CountingPen {
+        fn move_to(&mut self, _x: f32, _y: f32) {
+            self.num_commands += 1;
+        }
+
+        fn line_to(&mut self, _x: f32, _y: f32) {
+            self.num_commands += 1;
+        }
+
+        fn quad_to(&mut self, _cx0: f32, _cy0: f32, _x: f32, _y: f32) {
+            self.num_commands += 1;
+        }
+
+        fn curve_to(&mut self, _cx0: f32, _cy0: f32, _cx1: f32, _cy1: f32, _x: f32, _y: f32) {
+            self.num_commands += 1;
+        }
+
+        fn close(&mut self) {
+            self.num_commands += 1;
+        }
+    }
+
+    #[test]
+    fn test_draw_outline_status() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let outlines = get_outline_collection(&font_ref);
+        let coords = BridgeNormalizedCoords::default();
+        let mut scaler_metrics = BridgeScalerMetrics::default();
+
+        let mut draw = |glyph_id: u16| {
+            let mut pen = CountingPen::default();
+            let status = draw_outline(
+                &outlines,
+                glyph_id,
+                16.0,
+                &coords,
+                &BridgeOutlineLimits::default(),
+                HintingLevel::None,
+                &mut pen,
+                &mut scaler_metrics,
+            );
+            (status, pen.num_commands)
+        };
+        let (status, num_commands) = draw(lookup_glyph_or_zero(&font_ref, 'A' as u32));
+        assert_eq!(status, PathStatus::Success);
+        assert!(num_commands > 0);
+        assert_eq!(
+            draw(lookup_glyph_or_zero(&font_ref, ' ' as u32)),
+            (PathStatus::EmptyOutline, 0)
+        );
+        assert_eq!(draw(num_glyphs(&font_ref)).0, PathStatus::GlyphOutOfRange);
+        assert_eq!(draw(u16::MAX).0, PathStatus::GlyphOutOfRange);
+    }
+
+    #[test]
+    fn test_draw_outline_malformed_glyph() {
+        let mut font_data = build_glyf_font();
+        // Claim far more points for the triangle than its data holds.
+        let glyf_offset = (0..6)
+            .map(|i| 12 + 16 * i)
+            .find(|record| &font_data[*record..*record + 4] == b"glyf")
+            .map(|record| {
+                u32::from_be_bytes(font_data[record + 8..record + 12].try_into().unwrap())
+            })
+            .unwrap() as usize;
+        let end_points_offset = glyf_offset + 10;
+        font_data[end_points_offset..end_points_offset + 2].copy_from_slice(&200u16.to_be_bytes());
+
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let outlines = get_outline_collection(&font_ref);
+        let coords = BridgeNormalizedCoords::default();
+        let mut scaler_metrics = BridgeScalerMetrics::default();
+        let mut pen = CountingPen::default();
+        assert_eq!(
+            draw_outline(
+                &outlines,
+                0,
+                16.0,
+                &coords,
+                &BridgeOutlineLimits::default(),
+                HintingLevel::None,
+                &mut pen,
+                &mut scaler_metrics
+            ),
+            PathStatus::DrawError
+        );
+        assert_eq!(
+            draw_outline(
+                &outlines,
+                1,
+                16.0,
+                &coords,
+                &BridgeOutlineLimits::default(),
+                HintingLevel::None,
+                &mut pen,
+                &mut scaler_metrics
+            ),
+            PathStatus::EmptyOutline
+        );
+    }
+
+    #[test]
+    fn test_glyph_id_bounds_checks() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let outlines = get_outline_collection(&font_ref);
+        let coords = BridgeNormalizedCoords::default();
+        let mut scaler_metrics = BridgeScalerMetrics::default();
+        let last_glyph = num_glyphs(&font_ref) - 1;
+
+        let mut pen = CountingPen::default();
+        assert!(matches!(
+            draw_outline(
+                &outlines,
+                last_glyph,
+                16.0,
+                &coords,
+                &BridgeOutlineLimits::default(),
+                HintingLevel::None,
+                &mut pen,
+                &mut scaler_metrics
+            ),
+            PathStatus::Success | PathStatus::EmptyOutline
+        ));
+        assert!(
+            advance_width_or_zero(&font_ref, 16.0, &coords, last_glyph, SizeRounding::Exact) > 0.0
+        );
+        for glyph_id in [last_glyph + 1, u16::MAX] {
+            let mut pen = CountingPen::default();
+            assert_eq!(
+                draw_outline(
+                    &outlines,
+                    glyph_id,
+                    16.0,
+                    &coords,
+                    &BridgeOutlineLimits::default(),
+                    HintingLevel::None,
+                    &mut pen,
+                    &mut scaler_metrics
+                ),
+                PathStatus::GlyphOutOfRange
+            );
+            assert_eq!(pen.num_commands, 0);
+            assert_eq!(
+                advance_width_or_zero(&font_ref, 16.0, &coords, glyph_id, SizeRounding::Exact),
+                0.0
+            );
+        }
+
+        let invalid_font_ref = make_font_ref(&[], 0);
+        assert_eq!(num_glyphs(&invalid_font_ref), 0);
+        let invalid_outlines = get_outline_collection(&invalid_font_ref);
+        let mut pen = CountingPen::default();
+        assert_eq!(
+            draw_outline(
+                &invalid_outlines,
+                0,
+                16.0,
+                &coords,
+                &BridgeOutlineLimits::default(),
+                HintingLevel::None,
+                &mut pen,
+                &mut scaler_metrics
+            ),
+            PathStatus::GlyphOutOfRange
+        );
+    }
+
+    static PANIC_MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    unsafe extern "C" fn record_panic_message(message: *const u8, length: usize) {
+        let message = std::slice::from_raw_parts(message, length);
+        PANIC_MESSAGES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(String::from_utf8_lossy(message).into_owned());
+    }
+
+    #[test]
+    fn test_ffi_guard_contains_panics() {
+        fontations_ffi_set_panic_callback(Some(record_panic_message));
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let expected_num_glyphs = num_glyphs(&font_ref);
+        assert_ne!(expected_num_glyphs, 0);
+
+        inject_panic();
+        assert_eq!(num_glyphs(&font_ref), 0);
+        inject_panic();
+        assert!(!font_ref_is_valid(&make_font_ref(&file_buffer, 0)));
+        inject_panic();
+        assert!(family_name(&font_ref).is_empty());
+
+        // The injection is consumed, later calls succeed again.
+        assert_eq!(num_glyphs(&font_ref), expected_num_glyphs);
+        let messages = PANIC_MESSAGES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        assert_eq!(
+            messages.iter().filter(|m| *m == "injected panic").count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_draw_outline_limits() {
+        let font_data = build_glyf_font();
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let outlines = get_outline_collection(&font_ref);
+        let coords = BridgeNormalizedCoords::default();
+        let mut scaler_metrics = BridgeScalerMetrics::default();
+        let default_limits = default_outline_limits();
+
+        let mut draw = |glyph_id: u16, limits: BridgeOutlineLimits| {
+            let mut pen = CountingPen::default();
+            let status = draw_outline(
+                &outlines,
+                glyph_id,
+                16.0,
+                &coords,
+                &limits,
+                HintingLevel::None,
+                &mut pen,
+                &mut scaler_metrics,
+            );
+            (status, pen.num_commands)
+        };
+        let (status, num_commands) = draw(0, default_limits);
+        assert_eq!(status, PathStatus::Success);
+        assert!(num_commands > 2);
+        assert_eq!(draw(2, default_limits), (PathStatus::EmptyOutline, 0));
+
+        // The triangle has three points in one contour. Exceeding either
+        // limit rejects it before anything is drawn.
+        let too_few_points = BridgeOutlineLimits {
+            max_points: 2,
+            ..default_limits
+        };
+        assert_eq!(draw(0, too_few_points), (PathStatus::LimitsExceeded, 0));
+        let no_contours = BridgeOutlineLimits {
+            max_contours: 0,
+            ..default_limits
+        };
+        assert_eq!(draw(0, no_contours), (PathStatus::LimitsExceeded, 0));
+        assert_eq!(draw(1, no_contours), (PathStatus::EmptyOutline, 0));
+
+        // Glyph 2 is a composite of an empty simple glyph.
+        let no_composites = BridgeOutlineLimits {
+            max_composite_depth: 0,
+            ..default_limits
+        };
+        assert_eq!(draw(2, no_composites), (PathStatus::LimitsExceeded, 0));
+        let flat_composites = BridgeOutlineLimits {
+            max_composite_depth: 1,
+            ..default_limits
+        };
+        assert_eq!(draw(2, flat_composites), (PathStatus::EmptyOutline, 0));
+
+        // Segments beyond the limit are dropped.
+        let two_segments = BridgeOutlineLimits {
+            max_path_segments: 2,
+            ..default_limits
+        };
+        assert_eq!(draw(0, two_segments), (PathStatus::LimitsExceeded, 2));
+    }
+
+    #[test]
+    fn test_draw_cff_outline_limits() {
+        // Charstring operands between -107 and 107 take a single byte.
+        let int = |value: i32| (value + 139) as u8;
+        // Local subroutine indices are biased by 107 for small INDEXes.
+        let call_subr = |index: i32| [int(index - 107), 10];
+        const RETURN: u8 = 11;
+        const ENDCHAR: u8 = 14;
+
+        let triangle = vec![
+            int(0),
+            int(0),
+            21,
+            int(100),
+            int(0),
+            5,
+            int(-50),
+            int(100),
+            5,
+            ENDCHAR,
+        ];
+        // Subroutines 0 to 11 each call the next one, nesting 12 deep.
+        let mut subrs: Vec<Vec<u8>> = (1..12)
+            .map(|next| [&call_subr(next)[..], &[RETURN]].concat())
+            .collect();
+        subrs.push(vec![RETURN]);
+        // Subroutine 12 calls itself.
+        subrs.push([&call_subr(12)[..], &[RETURN]].concat());
+        // Subroutines 13 to 20 each call the next one 20 times, which is
+        // within the nesting limit but would take 20^8 calls to evaluate.
+        for next in 14..22 {
+            let calls = (0..20).flat_map(|_| call_subr(next));
+            subrs.push(calls.chain([RETURN]).collect());
+        }
+        subrs.push(vec![RETURN]);
+        let call_glyph = |subr: i32| [&call_subr(subr)[..], &[ENDCHAR]].concat();
+        // 97 stem hints followed by a hint mask, which needs 13 bytes.
+        let mut hinted_triangle: Vec<u8> = vec![int(10); 2 * 97];
+        hinted_triangle.push(1);
+        hinted_triangle.push(19);
+        hinted_triangle.extend([0xFF; 13]);
+        hinted_triangle.extend(&triangle);
+        let charstrings = [
+            triangle,
+            call_glyph(0),
+            call_glyph(12),
+            call_glyph(13),
+            hinted_triangle,
+        ];
+
+        let font_data = build_cff_font(&charstrings, &subrs);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let outlines = get_outline_collection(&font_ref);
+        let coords = BridgeNormalizedCoords::default();
+        let mut scaler_metrics = BridgeScalerMetrics::default();
+        let default_limits = default_outline_limits();
+
+        let mut draw = |glyph_id: u16, limits: BridgeOutlineLimits| {
+            let mut pen = CountingPen::default();
+            let status = draw_outline(
+                &outlines,
+                glyph_id,
+                16.0,
+                &coords,
+                &limits,
+                HintingLevel::None,
+                &mut pen,
+                &mut scaler_metrics,
+            );
+            (status, pen.num_commands)
+        };
+        let (status, num_commands) = draw(0, default_limits);
+        assert_eq!(status, PathStatus::Success);
+        assert!(num_commands > 2);
+        let four_operations = BridgeOutlineLimits {
+            max_charstring_operations: 4,
+            ..default_limits
+        };
+        assert_eq!(draw(0, four_operations).0, PathStatus::Success);
+        let three_operations = BridgeOutlineLimits {
+            max_charstring_operations: 3,
+            ..default_limits
+        };
+        assert_eq!(draw(0, three_operations), (PathStatus::LimitsExceeded, 0));
+
+        // Deep nesting and recursion stop at the depth limit before drawing.
+        for glyph_id in [1, 2] {
+            assert_eq!(
+                draw(glyph_id, default_limits),
+                (PathStatus::LimitsExceeded, 0)
+            );
+        }
+        // Above its own limit, the evaluator fails the glyphs instead.
+        let deep_nesting = BridgeOutlineLimits {
+            max_subroutine_depth: 12,
+            ..default_limits
+        };
+        assert_eq!(draw(1, deep_nesting).0, PathStatus::DrawError);
+
+        // Exponential fan-out stops at the operation limit.
+        assert_eq!(draw(3, default_limits), (PathStatus::LimitsExceeded, 0));
+
+        assert_eq!(draw(4, default_limits), (PathStatus::LimitsExceeded, 0));
+        let more_hints = BridgeOutlineLimits {
+            max_stem_hints: 97,
+            ..default_limits
+        };
+        assert_eq!(draw(4, more_hints).0, PathStatus::Success);
+
+        // Results are cached per glyph and limits, and drawing again gives
+        // the same statuses.
+        let num_verdicts = || {
+            outlines
+                .cff_verdicts
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .len()
+        };
+        let cached = num_verdicts();
+        assert!(cached > 0);
+        assert_eq!(draw(0, three_operations), (PathStatus::LimitsExceeded, 0));
+        assert_eq!(draw(0, four_operations).0, PathStatus::Success);
+        assert_eq!(draw(4, default_limits), (PathStatus::LimitsExceeded, 0));
+        assert_eq!(num_verdicts(), cached);
+    }
+
+    #[test]
+    fn test_size_validation() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let outlines = get_outline_collection(&font_ref);
+        let coords = BridgeNormalizedCoords::default();
+        let mut scaler_metrics = BridgeScalerMetrics::default();
+        let glyph_id = lookup_glyph_or_zero(&font_ref, 'A' as u32);
+        let units_per_em = f32::from(units_per_em_or_zero(&font_ref));
+
+        let subnormal = f32::MIN_POSITIVE / 2.0;
+        assert!(subnormal.is_subnormal());
+        for (size, is_valid) in [
+            (f32::NAN, false),
+            (f32::INFINITY, false),
+            (f32::NEG_INFINITY, false),
+            (-5.0, false),
+            (0.0, true),
+            (subnormal, true),
+        ] {
+            let mut pen = CountingPen::default();
+            let status = draw_outline(
+                &outlines,
+                glyph_id,
+                size,
+                &coords,
+                &BridgeOutlineLimits::default(),
+                HintingLevel::None,
+                &mut pen,
+                &mut scaler_metrics,
+            );
+            let advance =
+                advance_width_or_zero(&font_ref, size, &coords, glyph_id, SizeRounding::Exact);
+            let metrics = get_skia_metrics(
+                &font_ref,
+                size,
+                &coords,
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact,
+            );
+            if is_valid {
+                assert_eq!(status, PathStatus::Success, "size {size}");
+                assert!(pen.num_commands > 0, "size {size}");
+                assert!(advance.is_finite() && advance >= 0.0, "size {size}");
+                assert!(metrics.ascent.is_finite(), "size {size}");
+            } else {
+                assert_eq!(status, PathStatus::InvalidSize, "size {size}");
+                assert_eq!(pen.num_commands, 0, "size {size}");
+                assert_eq!(advance, 0.0, "size {size}");
+                assert_eq!(metrics, Metrics::default(), "size {size}");
+            }
+        }
+
+        // Zero is unscaled, which matches scaling to the font's units per em.
+        assert_eq!(
+            advance_width_or_zero(&font_ref, 0.0, &coords, glyph_id, SizeRounding::Exact),
+            advance_width_or_zero(
+                &font_ref,
+                units_per_em,
+                &coords,
+                glyph_id,
+                SizeRounding::Exact
+            )
+        );
+        assert!(
+            advance_width_or_zero(&font_ref, 0.0, &coords, glyph_id, SizeRounding::Exact) > 100.0
+        );
+        assert_eq!(
+            get_skia_metrics(
+                &font_ref,
+                0.0,
+                &coords,
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact
+            ),
+            get_skia_metrics(
+                &font_ref,
+                units_per_em,
+                &coords,
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact
+            )
+        );
+    }
+
+    /// Builds an MVAR table for a font with `axis_count` axes, adding each
+    /// (value tag, delta) record's delta in full at the maximum of the first
+    /// axis.
+    fn build_mvar(axis_count: u16, records: &[(&[u8; 4], i16)]) -> Vec<u8> {
+        let mut records = records.to_vec();
+        records.sort_by_key(|(tag, _)| **tag);
+        let mut mvar = Vec::new();
+        // Version 1.0, reserved, valueRecordSize, valueRecordCount.
+        for value in [1u16, 0, 0, 8, records.len() as u16] {
+            mvar.extend(value.to_be_bytes());
+        }
+        let store_offset = 12 + 8 * records.len();
+        mvar.extend((store_offset as u16).to_be_bytes());
+        for (index, (tag, _)) in records.iter().enumerate() {
+            mvar.extend(*tag);
+            mvar.extend(0u16.to_be_bytes());
+            mvar.extend((index as u16).to_be_bytes());
+        }
+        let deltas: Vec<i16> = records.iter().map(|(_, delta)| *delta).collect();
+        mvar.extend(build_item_variation_store(axis_count, &deltas));
+        mvar
+    }
+
+    /// Builds an ItemVariationStore with a single item variation data
+    /// subtable whose items have the given deltas, applied in full at the
+    /// maximum of the first of `axis_count` axes.
+    fn build_item_variation_store(axis_count: u16, deltas: &[i16]) -> Vec<u8> {
+        // One region and one word delta per item.
+        let mut store = Vec::new();
+        let region_list_offset = 12u32;
+        let region_list_len = 4 + 6 * u32::from(axis_count);
+        store.extend(1u16.to_be_bytes());
+        store.extend(region_list_offset.to_be_bytes());
+        store.extend(1u16.to_be_bytes());
+        store.extend((region_list_offset + region_list_len).to_be_bytes());
+        store.extend(axis_count.to_be_bytes());
+        store.extend(1u16.to_be_bytes());
+        for axis in 0..axis_count {
+            // Start, peak and end in F2Dot14.
+            let peak: i16 = if axis == 0 { 0x4000 } else { 0 };
+            for coord in [0, peak, peak] {
+                store.extend(coord.to_be_bytes());
+            }
+        }
+        // itemCount, wordDeltaCount, regionIndexCount and regionIndexes.
+        for value in [deltas.len() as u16, 1, 1, 0] {
+            store.extend(value.to_be_bytes());
+        }
+        for delta in deltas {
+            store.extend(delta.to_be_bytes());
+        }
+        store
+    }
+
+    /// Builds an HVAR table for a font with `axis_count` axes that varies
+    /// the advance and left side bearing of each glyph by the corresponding
+    /// entries of `advance_deltas` and `lsb_deltas` like `build_mvar`. Glyphs
+    /// whose advance delta is None map to an item missing from the store.
+    fn build_hvar(axis_count: u16, advance_deltas: &[Option<i16>], lsb_deltas: &[i16]) -> Vec<u8> {
+        let num_glyphs = lsb_deltas.len();
+        assert_eq!(advance_deltas.len(), num_glyphs);
+        // DeltaSetIndexMap format 0 with single byte entries that are all
+        // inner indices.
+        let index_map = |indices: &mut dyn Iterator<Item = u8>| {
+            let mut map = vec![0u8, 0x07];
+            map.extend((num_glyphs as u16).to_be_bytes());
+            map.extend(indices);
+            map
+        };
+        // Advances map to the first items, side bearings to the items
+        // following them.
+        let missing_item = 2 * num_glyphs as u8;
+        let advance_map = index_map(
+            &mut (0..num_glyphs as u8)
+                .zip(advance_deltas)
+                .map(|(index, delta)| if delta.is_some() { index } else { missing_item }),
+        );
+        let lsb_map = index_map(&mut (num_glyphs as u8..missing_item));
+        let mut deltas: Vec<i16> = advance_deltas
+            .iter()
+            .map(|delta| delta.unwrap_or_default())
+            .collect();
+        deltas.extend(lsb_deltas);
+
+        let mut hvar = Vec::new();
+        let advance_map_offset = 20u32;
+        let lsb_map_offset = advance_map_offset + advance_map.len() as u32;
+        let store_offset = lsb_map_offset + lsb_map.len() as u32;
+        hvar.extend(1u16.to_be_bytes());
+        hvar.extend(0u16.to_be_bytes());
+        for offset in [store_offset, advance_map_offset, lsb_map_offset, 0] {
+            hvar.extend(offset.to_be_bytes());
+        }
+        hvar.extend(advance_map);
+        hvar.extend(lsb_map);
+        hvar.extend(build_item_variation_store(axis_count, &deltas));
+        hvar
+    }
+
+    /// Rebuilds `font_data` without the table `tag`.
+    fn strip_table(font_data: &[u8], tag: &[u8; 4]) -> Vec<u8> {
+        replace_table(font_data, tag, None)
+    }
+
+    /// Rebuilds `font_data` with the table `tag` replaced by `data`, or
+    /// removed if `data` is None.
+    fn replace_table(font_data: &[u8], tag: &[u8; 4], data: Option<Vec<u8>>) -> Vec<u8> {
+        let font = FontRef::new(font_data).unwrap();
+        let mut tables: Vec<([u8; 4], Vec<u8>)> = font
+            .table_directory
+            .table_records()
+            .iter()
+            .map(|record| record.tag.get().into_bytes())
+            .filter(|table_tag| table_tag != tag)
+            .map(|table_tag| {
+                let data = font.table_data(Tag::new(&table_tag)).unwrap();
+                (table_tag, data.as_bytes().to_vec())
+            })
+            .collect();
+        tables.extend(data.map(|data| (*tag, data)));
+        let tables: Vec<(&[u8; 4], Vec<u8>)> = tables
+            .iter()
+            .map(|(table_tag, data)| (table_tag, data.clone()))
+            .collect();
+        build_sfnt(&tables)
+    }
+
+    #[test]
+    fn test_render_prerequisites() {
+        let all = BridgeRenderPrerequisites {
+            has_head: true,
+            has_hhea: true,
+            has_maxp: true,
+            has_cmap: true,
+            has_outlines: true,
+        };
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert_eq!(render_prerequisites(&font_ref), all);
+        assert!(font_ref_is_renderable(&font_ref));
+
+        let without_cmap = strip_table(&file_buffer, b"cmap");
+        let font_ref = make_font_ref(&without_cmap, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        assert!(!font_ref_is_renderable(&font_ref));
+        assert_eq!(
+            render_prerequisites(&font_ref),
+            BridgeRenderPrerequisites {
+                has_cmap: false,
+                ..all
+            }
+        );
+
+        let without_glyf = strip_table(&file_buffer, b"glyf");
+        let font_ref = make_font_ref(&without_glyf, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        assert!(!font_ref_is_renderable(&font_ref));
+        assert_eq!(
+            render_prerequisites(&font_ref),
+            BridgeRenderPrerequisites {
+                has_outlines: false,
+                ..all
+            }
+        );
+
+        // CFF outlines suffice in place of glyf.
+        let cff_font = build_cff_font(&[vec![14]], &[]);
+        let font_ref = make_font_ref(&cff_font, 0);
+        assert_eq!(
+            render_prerequisites(&font_ref),
+            BridgeRenderPrerequisites {
+                has_hhea: false,
+                has_cmap: false,
+                ..all
+            }
+        );
+
+        let invalid_font_ref = make_font_ref(&[], 0);
+        assert_eq!(
+            render_prerequisites(&invalid_font_ref),
+            BridgeRenderPrerequisites::default()
+        );
+        assert!(!font_ref_is_renderable(&invalid_font_ref));
+    }
+
+    #[test]
+    fn test_num_glyphs_fallback() {
+        let glyf_font = build_glyf_font();
+        let font_ref = make_font_ref(&glyf_font, 0);
+        assert_eq!(num_glyphs(&font_ref), 3);
+
+        let without_maxp = strip_table(&glyf_font, b"maxp");
+        let font_ref = make_font_ref(&without_maxp, 0);
+        assert_eq!(num_glyphs(&font_ref), 3);
+        assert!(!render_prerequisites(&font_ref).has_maxp);
+
+        let mut maxp = Vec::new();
+        maxp.extend(0x00005000u32.to_be_bytes());
+        maxp.extend(1u16.to_be_bytes());
+        let short_maxp = replace_table(&glyf_font, b"maxp", Some(maxp.clone()));
+        let font_ref = make_font_ref(&short_maxp, 0);
+        assert_eq!(num_glyphs(&font_ref), 3);
+        assert!(render_prerequisites(&font_ref).has_outlines);
+
+        // A larger maxp count is kept, glyphs past the end of loca just
+        // have no outlines.
+        maxp.truncate(4);
+        maxp.extend(5u16.to_be_bytes());
+        let long_maxp = replace_table(&glyf_font, b"maxp", Some(maxp));
+        let font_ref = make_font_ref(&long_maxp, 0);
+        assert_eq!(num_glyphs(&font_ref), 5);
+
+        let cff_font = build_cff_font(&[vec![14], vec![14]], &[]);
+        let without_maxp = strip_table(&cff_font, b"maxp");
+        let font_ref = make_font_ref(&without_maxp, 0);
+        assert_eq!(num_glyphs(&font_ref), 2);
+
+        assert_eq!(num_glyphs(&make_font_ref(&[], 0)), 0);
+    }
+
+    /// Pen recording the points of all drawing commands.
+    #[derive(Default)]
+    struct PointsPen {
+        points: Vec<(f32, f32)>,
+    }
+
+    impl Pen for PointsPen {
+        fn move_to(&mut self, x: f32, y: f32) {
+            self.points.push((x, y));
+        }
+
+        fn line_to(&mut self, x: f32, y: f32) {
+            self.points.push((x, y));
+        }
+
+        fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+            self.points.extend([(cx0, cy0), (x, y)]);
+        }
+
+        fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+            self.points.extend([(cx0, cy0), (cx1, cy1), (x, y)]);
+        }
+
+        fn close(&mut self) {}
+    }
+
+    #[test]
+    fn test_unscaled_outlines_match_scaled() {
+        const SIZE: f32 = 37.0;
+        // Scaled TrueType outlines are rounded to 26.6 fixed point.
+        const TOLERANCE: f32 = 1.0 / 64.0;
+        for (font_path, collection_index) in [(TEST_ROBOTO, 0), (TEST_CJK_VARIABLE_COLLECTION, 0)] {
+            let file_buffer = fs::read(font_path).expect("Test font could not be opened.");
+            let font_ref = make_font_ref(&file_buffer, collection_index);
+            assert!(font_ref_is_valid(&font_ref));
+            let outlines = get_outline_collection(&font_ref);
+            let coords = BridgeNormalizedCoords::default();
+            let mut scaler_metrics = BridgeScalerMetrics::default();
+            let scale = SIZE / f32::from(units_per_em_or_zero(&font_ref));
+            let mut draw = |glyph_id: u16, size: f32| {
+                let mut pen = PointsPen::default();
+                let status = draw_outline(
+                    &outlines,
+                    glyph_id,
+                    size,
+                    &coords,
+                    &BridgeOutlineLimits::default(),
+                    HintingLevel::None,
+                    &mut pen,
+                    &mut scaler_metrics,
+                );
+                assert!(matches!(
+                    status,
+                    PathStatus::Success | PathStatus::EmptyOutline
+                ));
+                pen.points
+            };
+            let glyph_ids: Vec<u16> = (0..num_glyphs(&font_ref)).take(16).collect();
+            let mut num_drawn = 0;
+            for glyph_id in glyph_ids {
+                let unscaled = draw(glyph_id, 0.0);
+                let scaled = draw(glyph_id, SIZE);
+                assert_eq!(unscaled.len(), scaled.len());
+                num_drawn += usize::from(!scaled.is_empty());
+                for ((ux, uy), (sx, sy)) in unscaled.into_iter().zip(scaled) {
+                    assert!((ux * scale - sx).abs() <= TOLERANCE, "glyph {glyph_id}");
+                    assert!((uy * scale - sy).abs() <= TOLERANCE, "glyph {glyph_id}");
+                }
+
+                let unscaled_advance =
+                    advance_width_or_zero(&font_ref, 0.0, &coords, glyph_id, SizeRounding::Exact);
+                let scaled_advance =
+                    advance_width_or_zero(&font_ref, SIZE, &coords, glyph_id, SizeRounding::Exact);
+                assert!((unscaled_advance * scale - scaled_advance).abs() <= TOLERANCE);
+            }
+            assert!(num_drawn > 0, "{font_path}");
+        }
+    }
+
+    /// Returns the bounding box of `points` as (x_min, y_min, x_max, y_max).
+    fn points_bounds(points: &[(f32, f32)]) -> (f32, f32, f32, f32) {
+        points.iter().fold(
+            (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |(x_min, y_min, x_max, y_max), &(x, y)| {
+                (x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y))
+            },
+        )
+    }
+
+    #[test]
+    fn test_oblique_transform() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let outlines = get_outline_collection(&font_ref);
+        let coords = BridgeNormalizedCoords::default();
+        let mut scaler_metrics = BridgeScalerMetrics::default();
+        let glyph_id = lookup_glyph_or_zero(&font_ref, 'H' as u32);
+        let mut draw = |transform: BridgePathTransform| {
+            let mut pen = PointsPen::default();
+            let status = draw_outline(
+                &outlines,
+                glyph_id,
+                0.0,
+                &coords,
+                &BridgeOutlineLimits::default(),
+                HintingLevel::None,
+                &mut TransformPen {
+                    pen: &mut pen,
+                    transform,
+                },
+                &mut scaler_metrics,
+            );
+            assert_eq!(status, PathStatus::Success);
+            pen.points
+        };
+
+        let upright = draw(identity_path_transform());
+        let (x_min, y_min, x_max, y_max) = points_bounds(&upright);
+        assert!(y_min >= 0.0 && y_max > y_min);
+
+        // The stems of 'H' are axis aligned, so shearing the outline turns its
+        // bounding box into a parallelogram whose bottom left and top right
+        // corners span the new bounding box.
+        const SLANT: f32 = 0.25;
+        let oblique = draw(BridgePathTransform {
+            xy: SLANT,
+            ..identity_path_transform()
+        });
+        assert_eq!(upright.len(), oblique.len());
+        assert_eq!(
+            points_bounds(&oblique),
+            (x_min + SLANT * y_min, y_min, x_max + SLANT * y_max, y_max)
+        );
+        for ((x, y), (oblique_x, oblique_y)) in upright.iter().zip(&oblique) {
+            assert_eq!((x + SLANT * y, *y), (*oblique_x, *oblique_y));
+        }
+
+        let translated = draw(BridgePathTransform {
+            dx: 10.0,
+            dy: -20.0,
+            ..identity_path_transform()
+        });
+        assert_eq!(
+            points_bounds(&translated),
+            (x_min + 10.0, y_min - 20.0, x_max + 10.0, y_max - 20.0)
+        );
+    }
+
+    #[test]
+    fn test_embolden_square() {
+        for corners in [
+            [(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)],
+            [(0.0, 0.0), (0.0, 100.0), (100.0, 100.0), (100.0, 0.0)],
+        ] {
+            let mut recorder = OutlineRecorder::default();
+            recorder.move_to(corners[0].0, corners[0].1);
+            for (x, y) in &corners[1..] {
+                recorder.line_to(*x, *y);
+            }
+            recorder.line_to(corners[0].0, corners[0].1);
+            recorder.close();
+            recorder.embolden(10.0);
+            assert_eq!(points_bounds(&recorder.points), (0.0, 0.0, 110.0, 110.0));
+            // The repeated start point moves with it.
+            assert_eq!(recorder.points.first(), recorder.points.last());
+        }
+    }
+
+    #[test]
+    fn test_embolden_outline() {
+        const STRENGTH: f32 = 40.0;
+        for (font_path, codepoint) in [(TEST_ROBOTO, 'o' as u32), (TEST_CJK_VARIABLE_COLLECTION, 0)]
+        {
+            let file_buffer = fs::read(font_path).expect("Test font could not be opened.");
+            let font_ref = make_font_ref(&file_buffer, 0);
+            assert!(font_ref_is_valid(&font_ref));
+            let outlines = get_outline_collection(&font_ref);
+            let coords = BridgeNormalizedCoords::default();
+            let mut scaler_metrics = BridgeScalerMetrics::default();
+            let glyph_id = match codepoint {
+                0 => (1..num_glyphs(&font_ref))
+                    .find(|glyph_id| !is_empty_glyph(&font_ref, &coords, *glyph_id))
+                    .unwrap(),
+                _ => lookup_glyph_or_zero(&font_ref, codepoint),
+            };
+            let mut recorder = OutlineRecorder::default();
+            let status = draw_outline(
+                &outlines,
+                glyph_id,
+                0.0,
+                &coords,
+                &BridgeOutlineLimits::default(),
+                HintingLevel::None,
+                &mut recorder,
+                &mut scaler_metrics,
+            );
+            assert_eq!(status, PathStatus::Success);
+            let (x_min, y_min, x_max, y_max) = points_bounds(&recorder.points);
+            recorder.embolden(STRENGTH);
+            let (bold_x_min, bold_y_min, bold_x_max, bold_y_max) = points_bounds(&recorder.points);
+            let growth = [
+                (bold_x_max - bold_x_min) - (x_max - x_min),
+                (bold_y_max - bold_y_min) - (y_max - y_min),
+            ];
+            for growth in growth {
+                assert!(growth > 0.0, "{font_path}: {growth}");
+            }
+            if codepoint == 'o' as u32 {
+                // The extremes of 'o' lie on smooth curves, which move out by
+                // half the strength on either side.
+                for growth in growth {
+                    assert!((growth - STRENGTH).abs() < 1.0, "{growth}");
+                }
+            }
+
+            let advance =
+                advance_width_or_zero(&font_ref, 0.0, &coords, glyph_id, SizeRounding::Exact);
+            assert_eq!(
+                emboldened_advance_width_or_zero(&font_ref, 0.0, &coords, glyph_id, STRENGTH),
+                advance + STRENGTH
+            );
+            assert_eq!(
+                emboldened_advance_width_or_zero(&font_ref, 0.0, &coords, glyph_id, f32::NAN),
+                advance
+            );
+        }
+
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let outlines = get_outline_collection(&font_ref);
+        let mut recorder = OutlineRecorder::default();
+        let status = draw_outline(
+            &outlines,
+            lookup_glyph_or_zero(&font_ref, ' ' as u32),
+            0.0,
+            &BridgeNormalizedCoords::default(),
+            &BridgeOutlineLimits::default(),
+            HintingLevel::None,
+            &mut recorder,
+            &mut BridgeScalerMetrics::default(),
+        );
+        assert_eq!(status, PathStatus::EmptyOutline);
+        recorder.embolden(STRENGTH);
+        assert!(recorder.verbs.is_empty() && recorder.points.is_empty());
+        assert_eq!(
+            emboldened_advance_width_or_zero(
+                &font_ref,
+                0.0,
+                &BridgeNormalizedCoords::default(),
+                u16::MAX,
+                STRENGTH
+            ),
+            0.0
+        );
+    }
+
+    /// Draws every glyph of `outlines` with `hinting`, returning the points
+    /// of each.
+    fn draw_all_hinted(
+        outlines: &BridgeOutlineCollection,
+        size: f32,
+        hinting: HintingLevel,
+    ) -> Vec<Vec<(f32, f32)>> {
+        let coords = BridgeNormalizedCoords::default();
+        (0..outlines.num_glyphs)
+            .map(|glyph_id| {
+                let mut pen = PointsPen::default();
+                let status = draw_outline(
+                    outlines,
+                    glyph_id,
+                    size,
+                    &coords,
+                    &BridgeOutlineLimits::default(),
+                    hinting,
+                    &mut pen,
+                    &mut BridgeScalerMetrics::default(),
+                );
+                assert!(matches!(
+                    status,
+                    PathStatus::Success | PathStatus::EmptyOutline
+                ));
+                pen.points
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hinting_levels() {
+        let file_buffer =
+            fs::read(TEST_HINTED_CFF).expect("Hinted CFF test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let outlines = get_outline_collection(&font_ref);
+        assert!(outlines_support_hinting(&outlines));
+        let unhinted = draw_all_hinted(&outlines, 12.0, HintingLevel::None);
+        assert!(unhinted.iter().any(|points| !points.is_empty()));
+        for hinting in [HintingLevel::Slight, HintingLevel::Full] {
+            assert_ne!(draw_all_hinted(&outlines, 12.0, hinting), unhinted);
+            // Unscaled outlines are never hinted.
+            assert_eq!(
+                draw_all_hinted(&outlines, 0.0, hinting),
+                draw_all_hinted(&outlines, 0.0, HintingLevel::None)
+            );
+        }
+
+        // TrueType instructions are not executed, so TrueType outlines are
+        // drawn unhinted at every level, with or without hinting tables, and
+        // so are their advances.
+        // TODO(AfterlifeOS/external_skia#synth-44): Expect hinted Roboto
+        // outlines at 12px to differ from unhinted ones once glyf outlines
+        // are hinted.
+        let roboto = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        for glyf_font in [build_glyf_font(), roboto] {
+            let font_ref = make_font_ref(&glyf_font, 0);
+            let outlines = get_outline_collection(&font_ref);
+            assert!(!outlines_support_hinting(&outlines));
+            let coords = BridgeNormalizedCoords::default();
+            let unhinted = draw_all_hinted(&outlines, 12.0, HintingLevel::None);
+            for hinting in [HintingLevel::Slight, HintingLevel::Full] {
+                assert_eq!(draw_all_hinted(&outlines, 12.0, hinting), unhinted);
+                assert_eq!(
+                    hinted_advance_width_or_zero(&font_ref, &outlines, 12.0, &coords, 0, hinting),
+                    advance_width_or_zero(&font_ref, 12.0, &coords, 0, SizeRounding::Exact)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_hinted_advances() {
+        let file_buffer =
+            fs::read(TEST_HINTED_CFF).expect("Hinted CFF test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let outlines = get_outline_collection(&font_ref);
+        let coords = BridgeNormalizedCoords::default();
+        let glyph_id = (0..num_glyphs(&font_ref))
+            .find(|glyph_id| {
+                advance_width_or_zero(&font_ref, 12.0, &coords, *glyph_id, SizeRounding::Exact)
+                    .fract()
+                    != 0.0
+            })
+            .expect("No glyph with a fractional advance.");
+        let linear_advance =
+            advance_width_or_zero(&font_ref, 12.0, &coords, glyph_id, SizeRounding::Exact);
+        let hinted_advance = |size: f32, hinting: HintingLevel| {
+            hinted_advance_width_or_zero(&font_ref, &outlines, size, &coords, glyph_id, hinting)
+        };
+        assert_eq!(hinted_advance(12.0, HintingLevel::None), linear_advance);
+        assert_eq!(hinted_advance(12.0, HintingLevel::Slight), linear_advance);
+        assert_eq!(
+            hinted_advance(12.0, HintingLevel::Full),
+            linear_advance.round()
+        );
+        assert_eq!(
+            hinted_advance(0.0, HintingLevel::Full),
+            advance_width_or_zero(&font_ref, 0.0, &coords, glyph_id, SizeRounding::Exact)
+        );
+        assert_eq!(hinted_advance(-1.0, HintingLevel::Full), 0.0);
+
+        // TrueType advances stay linear, hinting tables notwithstanding.
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let outlines = get_outline_collection(&font_ref);
+        let glyph_id = lookup_glyph_or_zero(&font_ref, 'a' as u32);
+        let linear_advance =
+            advance_width_or_zero(&font_ref, 12.0, &coords, glyph_id, SizeRounding::Exact);
+        assert_ne!(linear_advance.fract(), 0.0);
+        assert_eq!(
+            hinted_advance_width_or_zero(
+                &font_ref,
+                &outlines,
+                12.0,
+                &coords,
+                glyph_id,
+                HintingLevel::Full
+            ),
+            linear_advance
+        );
+    }
+
+    /// Glyph run sink recording the points and status of each glyph.
+    #[derive(Default)]
+    struct RecordingRunSink {
+        glyph_ids: Vec<u16>,
+        points: Vec<Vec<(f32, f32)>>,
+        statuses: Vec<PathStatus>,
+        current: PointsPen,
+    }
+
+    impl GlyphRunSink for RecordingRunSink {
+        // Forwards to `current` unchanged.
+        type Pen<'a> = TransformPen<'a, PointsPen>;
+
+        fn begin_glyph(&mut self, _glyph_id: u16) -> TransformPen<'_, PointsPen> {
+            self.current = PointsPen::default();
+            TransformPen {
+                pen: &mut self.current,
+                transform: identity_path_transform(),
+            }
+        }
+
+        fn end_glyph(
+            &mut self,
+            glyph_id: u16,
+            status: PathStatus,
+            _scaler_metrics: &BridgeScalerMetrics,
+        ) {
+            self.glyph_ids.push(glyph_id);
+            self.points.push(std::mem::take(&mut self.current.points));
+            self.statuses.push(status);
+        }
+    }
+
+    #[test]
+    fn test_draw_glyph_run() {
+        for (font_path, hinting) in [
+            (TEST_ROBOTO, HintingLevel::None),
+            (TEST_HINTED_CFF, HintingLevel::Full),
+        ] {
+            let file_buffer = fs::read(font_path).expect("Test font could not be opened.");
+            let font_ref = make_font_ref(&file_buffer, 0);
+            assert!(font_ref_is_valid(&font_ref));
+            let outlines = get_outline_collection(&font_ref);
+            let coords = BridgeNormalizedCoords::default();
+            let limits = BridgeOutlineLimits::default();
+            // A 200 glyph run cycling through the font, with an invalid glyph
+            // in the middle.
+            let mut glyph_ids: Vec<u16> = (0..199).map(|i| i % num_glyphs(&font_ref)).collect();
+            glyph_ids.insert(100, u16::MAX);
+
+            let mut sink = RecordingRunSink::default();
+            NUM_OUTLINE_SCALERS.with(|count| count.set(0));
+            let num_drawn = draw_glyph_run(
+                &outlines,
+                &glyph_ids,
+                12.0,
+                &coords,
+                &limits,
+                &identity_path_transform(),
+                0.0,
+                hinting,
+                &mut sink,
+            );
+            assert_eq!(NUM_OUTLINE_SCALERS.with(|count| count.get()), 1);
+            assert_eq!(num_drawn, 199);
+            assert_eq!(sink.glyph_ids, glyph_ids);
+            for (i, glyph_id) in glyph_ids.iter().enumerate() {
+                let mut pen = PointsPen::default();
+                let status = draw_outline(
+                    &outlines,
+                    *glyph_id,
+                    12.0,
+                    &coords,
+                    &limits,
+                    hinting,
+                    &mut pen,
+                    &mut BridgeScalerMetrics::default(),
+                );
+                assert_eq!(status, sink.statuses[i], "{font_path}: glyph {glyph_id}");
+                assert_eq!(pen.points, sink.points[i], "{font_path}: glyph {glyph_id}");
+            }
+            assert_eq!(sink.statuses[100], PathStatus::GlyphOutOfRange);
+        }
+
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let outlines = get_outline_collection(&font_ref);
+        let mut sink = RecordingRunSink::default();
+        let num_drawn = draw_glyph_run(
+            &outlines,
+            &[1, 2, 3],
+            -1.0,
+            &BridgeNormalizedCoords::default(),
+            &BridgeOutlineLimits::default(),
+            &identity_path_transform(),
+            0.0,
+            HintingLevel::None,
+            &mut sink,
+        );
+        assert_eq!(num_drawn, 0);
+        assert_eq!(sink.statuses, [PathStatus::InvalidSize; 3]);
+        assert!(sink.points.iter().all(Vec::is_empty));
+    }
+
+    /// Decodes the buffers filled by `get_path_verbs`, undoing the y-flip.
+    fn unpack_path(verbs: &[u8], points: &[f32]) -> OutlineRecorder {
+        let mut recorder = OutlineRecorder::default();
+        let mut points = points.chunks_exact(2).map(|point| (point[0], -point[1]));
+        for verb in verbs {
+            let verb = PathVerb { repr: *verb };
+            recorder.verbs.push(verb);
+            recorder
+                .points
+                .extend(points.by_ref().take(verb.num_points()));
+        }
+        assert_eq!(points.next(), None);
+        recorder
+    }
+
+    #[test]
+    fn test_packed_path_matches_pen() {
+        for (font_path, hinting) in [
+            (TEST_ROBOTO, HintingLevel::None),
+            (TEST_CJK_VARIABLE_COLLECTION, HintingLevel::None),
+            (TEST_HINTED_CFF, HintingLevel::Full),
+        ] {
+            let file_buffer = fs::read(font_path).expect("Test font could not be opened.");
+            let font_ref = make_font_ref(&file_buffer, 0);
+            assert!(font_ref_is_valid(&font_ref));
+            let outlines = get_outline_collection(&font_ref);
+            let coords = BridgeNormalizedCoords::default();
+            let limits = BridgeOutlineLimits::default();
+            let transform = BridgePathTransform {
+                xy: 0.2,
+                ..identity_path_transform()
+            };
+            let mut verbs = Vec::new();
+            let mut points = Vec::new();
+            for glyph_id in 0..num_glyphs(&font_ref).min(64) {
+                for embolden_strength in [0.0, 0.5] {
+                    let scaler =
+                        OutlineScaler::new(&outlines, 24.0, &coords, &limits, hinting).unwrap();
+                    let mut expected = OutlineRecorder::default();
+                    let expected_status = scaler.draw_styled(
+                        glyph_id,
+                        &transform,
+                        embolden_strength,
+                        &mut expected,
+                        &mut BridgeScalerMetrics::default(),
+                    );
+                    let status = get_path_verbs(
+                        &outlines,
+                        glyph_id,
+                        24.0,
+                        &coords,
+                        &limits,
+                        &transform,
+                        embolden_strength,
+                        hinting,
+                        &mut verbs,
+                        &mut points,
+                        &mut BridgeScalerMetrics::default(),
+                    );
+                    assert_eq!(status, expected_status, "{font_path}: glyph {glyph_id}");
+                    let unpacked = unpack_path(&verbs, &points);
+                    assert_eq!(
+                        unpacked.verbs, expected.verbs,
+                        "{font_path}: glyph {glyph_id}"
+                    );
+                    assert_eq!(
+                        unpacked.points, expected.points,
+                        "{font_path}: glyph {glyph_id}"
+                    );
+                }
+            }
+            assert!(!verbs.is_empty());
+            let status = get_path_verbs(
+                &outlines,
+                1,
+                f32::NAN,
+                &coords,
+                &limits,
+                &transform,
+                0.0,
+                hinting,
+                &mut verbs,
+                &mut points,
+                &mut BridgeScalerMetrics::default(),
+            );
+            assert_eq!(status, PathStatus::InvalidSize);
+            assert!(verbs.is_empty() && points.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_get_path_verbs_clears_on_error() {
+        // Charstring operands between -107 and 107 take a single byte.
+        let int = |value: i32| (value + 139) as u8;
+        const RETURN: u8 = 11;
+        // A triangle that calls a missing subroutine after its contour.
+        let broken_triangle = vec![
+            int(0),
+            int(0),
+            21,
+            int(100),
+            int(0),
+            5,
+            int(-50),
+            int(100),
+            5,
+            int(50 - 107),
+            10,
+        ];
+        let font_data = build_cff_font(&[broken_triangle], &[vec![RETURN]]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let outlines = get_outline_collection(&font_ref);
+        let coords = BridgeNormalizedCoords::default();
+        let limits = BridgeOutlineLimits::default();
+
+        // The contour reaches the pen before the evaluation fails.
+        let mut pen = CountingPen::default();
+        assert_eq!(
+            draw_outline(
+                &outlines,
+                0,
+                16.0,
+                &coords,
+                &limits,
+                HintingLevel::None,
+                &mut pen,
+                &mut BridgeScalerMetrics::default()
+            ),
+            PathStatus::DrawError
+        );
+        assert!(pen.num_commands > 0);
+
+        let mut verbs = vec![PathVerb::MoveTo.repr];
+        let mut points = vec![1.0, 2.0];
+        let status = get_path_verbs(
+            &outlines,
+            0,
+            16.0,
+            &coords,
+            &limits,
+            &identity_path_transform(),
+            0.0,
+            HintingLevel::None,
+            &mut verbs,
+            &mut points,
+            &mut BridgeScalerMetrics::default(),
+        );
+        assert_eq!(status, PathStatus::DrawError);
+        assert!(verbs.is_empty() && points.is_empty());
+    }
+
+    #[test]
+    fn test_y_flip_transform() {
+        let flip = BridgePathTransform::Y_FLIP;
+        assert_eq!(flip.map(3.0, 5.0), (3.0, -5.0));
+        assert_eq!(identity_path_transform().then(&flip), flip);
+        assert_eq!(flip.then(&flip), identity_path_transform());
+
+        // Composing an oblique with the flip maps points like applying the
+        // two in turn, slanting to the right in Skia's y-down space.
+        let oblique = BridgePathTransform {
+            xy: 0.25,
+            dx: 2.0,
+            dy: -1.0,
+            ..identity_path_transform()
+        };
+        let composed = oblique.then(&flip);
+        for (x, y) in [(0.0, 0.0), (10.0, 20.0), (-4.0, 8.0), (7.5, -3.5)] {
+            let (ox, oy) = oblique.map(x, y);
+            assert_eq!(composed.map(x, y), flip.map(ox, oy));
+        }
+        assert_eq!(composed.map(0.0, 100.0), (27.0, -99.0));
+    }
+
+    /// Fixture fonts and glyphs whose outlines are checked against the
+    /// expectations in `GOLDEN_OUTLINES_DIR`. Roboto has quadratic glyf
+    /// outlines, the others cubic CFF and CFF2 outlines.
+    const GOLDEN_OUTLINE_FONTS: [(&str, &str, &[u16]); 3] = [
+        (TEST_ROBOTO, "Roboto-Regular.txt", &[36, 51, 55, 69, 75]),
+        (TEST_HINTED_CFF, "Stroking-otf.txt", &[2, 3, 4, 5, 6, 7]),
+        (
+            TEST_CJK_VARIABLE_COLLECTION,
+            "NotoSansCJK-VF-subset.txt",
+            &[1],
+        ),
+    ];
+    const GOLDEN_OUTLINES_DIR: &str = "resources/fontations/outlines";
+
+    /// Path wrapper writing the calls it receives as text, one segment per
+    /// line with all points.
+    #[derive(Default)]
+    struct SerializingPathWrapper {
+        out: String,
+    }
+
+    impl SerializingPathWrapper {
+        fn segment(self: Pin<&mut Self>, verb: char, points: &[f32]) {
+            let out = &mut self.get_mut().out;
+            out.push(verb);
+            for coordinate in points {
+                out.push_str(&format!(" {coordinate}"));
+            }
+            out.push('\n');
+        }
+    }
+
+    impl PathWrapperSink for SerializingPathWrapper {
+        fn move_to(self: Pin<&mut Self>, x: f32, y: f32) {
+            self.segment('M', &[x, y]);
+        }
+
+        fn line_to(self: Pin<&mut Self>, x: f32, y: f32) {
+            self.segment('L', &[x, y]);
+        }
+
+        fn quad_to(self: Pin<&mut Self>, cx0: f32, cy0: f32, x: f32, y: f32) {
+            self.segment('Q', &[cx0, cy0, x, y]);
+        }
+
+        fn curve_to(self: Pin<&mut Self>, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+            self.segment('C', &[cx0, cy0, cx1, cy1, x, y]);
+        }
+
+        fn close(self: Pin<&mut Self>) {
+            self.segment('Z', &[]);
+        }
+
+        fn reset(self: Pin<&mut Self>) {
+            self.segment('R', &[]);
+        }
+    }
+
+    /// Serializes the segments `get_path` passes to its path wrapper for
+    /// `glyph_ids`, one per line with all points, in font units and y-down.
+    fn serialize_outlines(font_ref: &BridgeFontRef, glyph_ids: &[u16]) -> String {
+        let outlines = get_outline_collection(font_ref);
+        let mut path_wrapper = SerializingPathWrapper::default();
+        for glyph_id in glyph_ids {
+            path_wrapper.out.push_str(&format!("glyph {glyph_id}\n"));
+            let status = draw_path_wrapper(
+                &outlines,
+                *glyph_id,
+                0.0,
+                &BridgeNormalizedCoords::default(),
+                &BridgeOutlineLimits::default(),
+                &identity_path_transform(),
+                0.0,
+                HintingLevel::None,
+                false,
+                Pin::new(&mut path_wrapper),
+                &mut BridgeScalerMetrics::default(),
+            );
+            assert_eq!(status, PathStatus::Success, "glyph {glyph_id}");
+        }
+        path_wrapper.out
+    }
+
+    /// Compares emitted outlines with the checked-in expectations. Run with
+    /// FONTATIONS_UPDATE_GOLDENS=1 to rewrite the expectations instead.
+    #[test]
+    fn test_golden_outlines() {
+        let update = std::env::var_os("FONTATIONS_UPDATE_GOLDENS").is_some();
+        for (font_path, golden_name, glyph_ids) in GOLDEN_OUTLINE_FONTS {
+            let file_buffer = fs::read(font_path).expect("Test font could not be opened.");
+            let font_ref = make_font_ref(&file_buffer, 0);
+            assert!(font_ref_is_valid(&font_ref));
+            let actual = serialize_outlines(&font_ref, glyph_ids);
+            let golden_path = format!("{GOLDEN_OUTLINES_DIR}/{golden_name}");
+            if update {
+                fs::write(&golden_path, &actual).expect("Golden outlines could not be written.");
+                continue;
+            }
+            let expected =
+                fs::read_to_string(&golden_path).expect("Golden outlines could not be read.");
+            for (line, (actual, expected)) in actual.lines().zip(expected.lines()).enumerate() {
+                assert_eq!(actual, expected, "{golden_path}:{}", line + 1);
+            }
+            assert_eq!(
+                actual.lines().count(),
+                expected.lines().count(),
+                "{golden_path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_path_bounds() {
+        let oblique = BridgePathTransform {
+            xy: 0.2,
+            ..identity_path_transform()
+        };
+        for (font_path, glyph_ids) in [
+            (TEST_ROBOTO, &[36u16, 51, 55, 69, 75][..]),
+            (TEST_HINTED_CFF, &[2, 3, 4, 5, 6, 7][..]),
+            (TEST_CJK_VARIABLE_COLLECTION, &[1][..]),
+        ] {
+            let file_buffer = fs::read(font_path).expect("Test font could not be opened.");
+            let font_ref = make_font_ref(&file_buffer, 0);
+            assert!(font_ref_is_valid(&font_ref));
+            let outlines = get_outline_collection(&font_ref);
+            let mut verbs = Vec::new();
+            let mut points = Vec::new();
+            for glyph_id in glyph_ids {
+                for (transform, embolden_strength) in [
+                    (identity_path_transform(), 0.0),
+                    (oblique, 0.0),
+                    (identity_path_transform(), 1.0),
+                ] {
+                    let mut scaler_metrics = BridgeScalerMetrics::default();
+                    let status = get_path_verbs(
+                        &outlines,
+                        *glyph_id,
+                        20.0,
+                        &BridgeNormalizedCoords::default(),
+                        &BridgeOutlineLimits::default(),
+                        &transform,
+                        embolden_strength,
+                        HintingLevel::None,
+                        &mut verbs,
+                        &mut points,
+                        &mut scaler_metrics,
+                    );
+                    assert_eq!(status, PathStatus::Success);
+                    let emitted: Vec<(f32, f32)> = points
+                        .chunks_exact(2)
+                        .map(|point| (point[0], point[1]))
+                        .collect();
+                    let (x_min, y_min, x_max, y_max) = points_bounds(&emitted);
+                    assert_eq!(
+                        scaler_metrics.bounds,
+                        BridgePathBounds {
+                            is_empty: false,
+                            x_min,
+                            y_min,
+                            x_max,
+                            y_max,
+                        },
+                        "{font_path}: glyph {glyph_id}"
+                    );
+                    // Glyphs sit on the baseline and extend upwards, which
+                    // is towards negative y after the flip.
+                    assert!(scaler_metrics.bounds.y_min < 0.0);
+                }
+            }
+        }
+
+        // Empty and failing outlines report explicitly empty bounds.
+        let file_buffer = fs::read(TEST_HINTED_CFF).expect("Test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let outlines = get_outline_collection(&font_ref);
+        for (glyph_id, size, expected_status) in [
+            (1, 20.0, PathStatus::EmptyOutline),
+            (u16::MAX, 20.0, PathStatus::GlyphOutOfRange),
+            (2, -1.0, PathStatus::InvalidSize),
+        ] {
+            let mut scaler_metrics = BridgeScalerMetrics::default();
+            scaler_metrics.bounds.add(1.0, 1.0);
+            let status = get_path_verbs(
+                &outlines,
+                glyph_id,
+                size,
+                &BridgeNormalizedCoords::default(),
+                &BridgeOutlineLimits::default(),
+                &identity_path_transform(),
+                0.0,
+                HintingLevel::None,
+                &mut Vec::new(),
+                &mut Vec::new(),
+                &mut scaler_metrics,
+            );
+            assert_eq!(status, expected_status);
+            assert_eq!(scaler_metrics.bounds, BridgePathBounds::default());
+            assert!(scaler_metrics.bounds.is_empty);
+        }
+    }
+
+    #[test]
+    fn test_glyph_has_overlaps() {
+        // Only glyph 6 of Variable.ttf sets OVERLAP_SIMPLE.
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let outlines = get_outline_collection(&font_ref);
+        for glyph_id in 0..num_glyphs(&font_ref) {
+            assert_eq!(glyph_has_overlaps(&font_ref, glyph_id), glyph_id == 6);
+            // The packed path carries the same flag.
+            let mut scaler_metrics = BridgeScalerMetrics::default();
+            get_path_verbs(
+                &outlines,
+                glyph_id,
+                0.0,
+                &BridgeNormalizedCoords::default(),
+                &BridgeOutlineLimits::default(),
+                &identity_path_transform(),
+                0.0,
+                HintingLevel::None,
+                &mut Vec::new(),
+                &mut Vec::new(),
+                &mut scaler_metrics,
+            );
+            assert_eq!(scaler_metrics.has_overlaps, glyph_id == 6);
+        }
+        assert!(!glyph_has_overlaps(&font_ref, num_glyphs(&font_ref)));
+
+        // Composites set OVERLAP_COMPOUND on their components.
+        let file_buffer =
+            fs::read(TEST_CONDENSED_BOLD_ITALIC).expect("Test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(glyph_has_overlaps(&font_ref, 24));
+        assert!(!glyph_has_overlaps(&font_ref, 0));
+
+        // CFF outlines have no overlap flags.
+        let file_buffer = fs::read(TEST_HINTED_CFF).expect("Test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(glyph_has_overlaps(&font_ref, 2));
+        assert!(!glyph_has_overlaps(&font_ref, u16::MAX));
+    }
+
+    #[test]
+    fn test_segment_cleanup() {
+        let mut recorder = OutlineRecorder::default();
+        let mut pen = SegmentCleanupPen::new(&mut recorder);
+        // Consecutive moves collapse into the last one.
+        pen.move_to(0.0, 0.0);
+        pen.move_to(1.0, 1.0);
+        // Segments not moving the pen are dropped, also within epsilon.
+        pen.line_to(1.0, 1.0);
+        pen.line_to(10.0, 1.0);
+        pen.quad_to(10.0, 1.0, 10.0, 1.0);
+        pen.curve_to(10.0, 1.0, 10.0005, 1.0, 10.0, 1.0002);
+        pen.line_to(10.0, 10.0);
+        // A quad with an off-point control point moves the pen.
+        pen.quad_to(5.0, 15.0, 10.0, 10.0);
+        // The open contour is closed before the next one.
+        pen.move_to(20.0, 20.0);
+        pen.line_to(30.0, 20.0);
+        pen.line_to(30.0, 30.0);
+        pen.close();
+        pen.close();
+        // An empty contour vanishes.
+        pen.move_to(5.0, 5.0);
+        pen.line_to(5.0, 5.0);
+        pen.close();
+        pen.move_to(40.0, 40.0);
+
+        assert_eq!(
+            recorder.verbs,
+            [
+                PathVerb::MoveTo,
+                PathVerb::LineTo,
+                PathVerb::LineTo,
+                PathVerb::QuadTo,
+                PathVerb::Close,
+                PathVerb::MoveTo,
+                PathVerb::LineTo,
+                PathVerb::LineTo,
+                PathVerb::Close,
+            ]
+        );
+        assert_eq!(
+            recorder.points,
+            [
+                (1.0, 1.0),
+                (10.0, 1.0),
+                (10.0, 10.0),
+                (5.0, 15.0),
+                (10.0, 10.0),
+                (20.0, 20.0),
+                (30.0, 20.0),
+                (30.0, 30.0),
+            ]
+        );
+
+        // Real outlines without degenerate segments pass unchanged, and the
+        // default leaves outlines as drawn.
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let outlines = get_outline_collection(&font_ref);
+        let draw = |clean_up: bool| {
+            let mut recorder = OutlineRecorder::default();
+            let status = if clean_up {
+                draw_outline(
+                    &outlines,
+                    lookup_glyph_or_zero(&font_ref, 'B' as u32),
+                    16.0,
+                    &BridgeNormalizedCoords::default(),
+                    &BridgeOutlineLimits::default(),
+                    HintingLevel::None,
+                    &mut SegmentCleanupPen::new(&mut recorder),
+                    &mut BridgeScalerMetrics::default(),
+                )
+            } else {
+                draw_outline(
+                    &outlines,
+                    lookup_glyph_or_zero(&font_ref, 'B' as u32),
+                    16.0,
+                    &BridgeNormalizedCoords::default(),
+                    &BridgeOutlineLimits::default(),
+                    HintingLevel::None,
+                    &mut recorder,
+                    &mut BridgeScalerMetrics::default(),
+                )
+            };
+            assert_eq!(status, PathStatus::Success);
+            recorder
+        };
+        let (cleaned, drawn) = (draw(true), draw(false));
+        assert_eq!(cleaned.verbs, drawn.verbs);
+        assert_eq!(cleaned.points, drawn.points);
+    }
+
+    #[test]
+    fn test_underline_metrics() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let post = FontRef::new(&file_buffer).unwrap().post().unwrap();
+        let (position, thickness) = (
+            post.underline_position().to_i16() as f32,
+            post.underline_thickness().to_i16() as f32,
+        );
+        assert!(thickness > 0.0);
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &BridgeNormalizedCoords::default(),
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert!(metrics.underline_position_is_valid && metrics.underline_thickness_is_valid);
+        assert_eq!(
+            (metrics.underline_position, metrics.underline_thickness),
+            (position, thickness)
+        );
+        let units_per_em = units_per_em_or_zero(&font_ref) as f32;
+        let metrics = get_skia_metrics(
+            &font_ref,
+            20.0,
+            &BridgeNormalizedCoords::default(),
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert_eq!(metrics.underline_thickness, thickness * 20.0 / units_per_em);
+
+        // MVAR moves the underline down and thickens it towards the maximum
+        // weight.
+        let with_mvar = replace_table(
+            &file_buffer,
+            b"MVAR",
+            Some(build_mvar(2, &[(b"undo", -60), (b"unds", 40)])),
+        );
+        let font_ref = make_font_ref(&with_mvar, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        for (weight, fraction) in [(400.0, 0.0), (650.0, 0.5), (900.0, 1.0)] {
+            let coords = resolve_into_normalized_coords(
+                &font_ref,
+                &[SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(*b"wght"),
+                    value: weight,
+                }],
+            );
+            let metrics = get_skia_metrics(
+                &font_ref,
+                0.0,
+                &coords,
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact,
+            );
+            assert!(metrics.underline_position_is_valid && metrics.underline_thickness_is_valid);
+            assert!(
+                (metrics.underline_position - (position - 60.0 * fraction)).abs() < 0.01,
+                "wght {weight}: {}",
+                metrics.underline_position
+            );
+            assert!(
+                (metrics.underline_thickness - (thickness + 40.0 * fraction)).abs() < 0.01,
+                "wght {weight}: {}",
+                metrics.underline_thickness
+            );
+        }
+
+        // Without a post table there is no underline.
+        let without_post = strip_table(&file_buffer, b"post");
+        let font_ref = make_font_ref(&without_post, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &BridgeNormalizedCoords::default(),
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert!(!metrics.underline_position_is_valid && !metrics.underline_thickness_is_valid);
+        assert_eq!(
+            (metrics.underline_position, metrics.underline_thickness),
+            (0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_strikeout_metrics() {
+        let file_buffer =
+            fs::read(TEST_CONDENSED_BOLD_ITALIC).expect("Static test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let os2 = FontRef::new(&file_buffer).unwrap().os2().unwrap();
+        let (position, thickness) = (
+            os2.y_strikeout_position() as f32,
+            os2.y_strikeout_size() as f32,
+        );
+        assert!(position > 0.0 && thickness > 0.0);
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &BridgeNormalizedCoords::default(),
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert!(metrics.strikeout_position_is_valid && metrics.strikeout_thickness_is_valid);
+        assert_eq!(
+            (metrics.strikeout_position, metrics.strikeout_thickness),
+            (position, thickness)
+        );
+        let units_per_em = units_per_em_or_zero(&font_ref) as f32;
+        let metrics = get_skia_metrics(
+            &font_ref,
+            32.0,
+            &BridgeNormalizedCoords::default(),
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert_eq!(metrics.strikeout_position, position * 32.0 / units_per_em);
+        assert_eq!(metrics.strikeout_thickness, thickness * 32.0 / units_per_em);
+
+        // Without OS/2 there is no strikeout.
+        let without_os2 = strip_table(&file_buffer, b"OS/2");
+        let font_ref = make_font_ref(&without_os2, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &BridgeNormalizedCoords::default(),
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert!(!metrics.strikeout_position_is_valid && !metrics.strikeout_thickness_is_valid);
+        assert_eq!(
+            (metrics.strikeout_position, metrics.strikeout_thickness),
+            (0.0, 0.0)
+        );
+        assert!(metrics.underline_position_is_valid);
+
+        // MVAR raises and thickens the strikeout towards the maximum weight.
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let os2 = FontRef::new(&file_buffer).unwrap().os2().unwrap();
+        let (position, thickness) = (
+            os2.y_strikeout_position() as f32,
+            os2.y_strikeout_size() as f32,
+        );
+        let with_mvar = replace_table(
+            &file_buffer,
+            b"MVAR",
+            Some(build_mvar(2, &[(b"stro", 30), (b"strs", 20)])),
+        );
+        let font_ref = make_font_ref(&with_mvar, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        for (weight, fraction) in [(400.0, 0.0), (650.0, 0.5), (900.0, 1.0)] {
+            let coords = resolve_into_normalized_coords(
+                &font_ref,
+                &[SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(*b"wght"),
+                    value: weight,
+                }],
+            );
+            let metrics = get_skia_metrics(
+                &font_ref,
+                0.0,
+                &coords,
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact,
+            );
+            assert!(metrics.strikeout_position_is_valid && metrics.strikeout_thickness_is_valid);
+            assert!(
+                (metrics.strikeout_position - (position + 30.0 * fraction)).abs() < 0.01,
+                "wght {weight}: {}",
+                metrics.strikeout_position
+            );
+            assert!(
+                (metrics.strikeout_thickness - (thickness + 20.0 * fraction)).abs() < 0.01,
+                "wght {weight}: {}",
+                metrics.strikeout_thickness
+            );
+        }
+    }
+
+    /// Returns a copy of the OS/2 table of `font_data` with USE_TYPO_METRICS
+    /// in fsSelection set to `use_typo_metrics`.
+    fn os2_with_use_typo_metrics(font_data: &[u8], use_typo_metrics: bool) -> Vec<u8> {
+        let font = FontRef::new(font_data).unwrap();
+        let mut os2 = font
+            .table_data(Tag::new(b"OS/2"))
+            .unwrap()
+            .as_bytes()
+            .to_vec();
+        // fsSelection is at offset 62, USE_TYPO_METRICS is bit 7.
+        if use_typo_metrics {
+            os2[63] |= 0x80;
+        } else {
+            os2[63] &= !0x80;
+        }
+        os2
+    }
+
+    #[test]
+    fn test_use_typo_metrics() {
+        let coords = BridgeNormalizedCoords::default();
+        // Roboto does not set USE_TYPO_METRICS and has hhea metrics that
+        // differ from the typo metrics.
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &coords,
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert_eq!(metrics.line_metrics_source, LineMetricsSource::Hhea);
+        assert_eq!(
+            (metrics.ascent, metrics.descent, metrics.leading),
+            (1900.0, -500.0, 0.0)
+        );
+
+        let with_typo = replace_table(
+            &file_buffer,
+            b"OS/2",
+            Some(os2_with_use_typo_metrics(&file_buffer, true)),
+        );
+        let font_ref = make_font_ref(&with_typo, 0);
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &coords,
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert_eq!(metrics.line_metrics_source, LineMetricsSource::Typo);
+        assert_eq!(
+            (metrics.ascent, metrics.descent, metrics.leading),
+            (1536.0, -512.0, 102.0)
+        );
+
+        // Variable.ttf sets the bit, its hhea descent is 0.
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &coords,
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert_eq!(metrics.line_metrics_source, LineMetricsSource::Typo);
+        assert_eq!(
+            (metrics.ascent, metrics.descent, metrics.leading),
+            (1638.0, -410.0, 377.0)
+        );
+
+        let without_typo = replace_table(
+            &file_buffer,
+            b"OS/2",
+            Some(os2_with_use_typo_metrics(&file_buffer, false)),
+        );
+        let font_ref = make_font_ref(&without_typo, 0);
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &coords,
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert_eq!(metrics.line_metrics_source, LineMetricsSource::Hhea);
+        assert_eq!(
+            (metrics.ascent, metrics.descent, metrics.leading),
+            (1638.0, 0.0, 377.0)
+        );
+    }
+
+    /// Rebuilds `font_data` with 16-bit values at byte offsets into the
+    /// table `tag` overwritten.
+    fn patch_table(font_data: &[u8], tag: &[u8; 4], values: &[(usize, i16)]) -> Vec<u8> {
+        let font = FontRef::new(font_data).unwrap();
+        let mut data = font.table_data(Tag::new(tag)).unwrap().as_bytes().to_vec();
+        for (offset, value) in values {
+            data[*offset..*offset + 2].copy_from_slice(&value.to_be_bytes());
+        }
+        replace_table(font_data, tag, Some(data))
+    }
+
+    #[test]
+    fn test_line_metrics_fallback() {
+        // hhea ascender and descender.
+        const HHEA_ZERO: [(usize, i16); 2] = [(4, 0), (6, 0)];
+        // OS/2 sTypoAscender and sTypoDescender.
+        const TYPO_ZERO: [(usize, i16); 2] = [(68, 0), (70, 0)];
+        // OS/2 usWinAscent and usWinDescent.
+        const WIN_ZERO: [(usize, i16); 2] = [(74, 0), (76, 0)];
+
+        // Roboto does not set USE_TYPO_METRICS. Its hhea metrics are
+        // 1900/-500/0, typo metrics 1536/-512/102 and win metrics 1946/512.
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let zero_hhea = patch_table(&file_buffer, b"hhea", &HHEA_ZERO);
+        let zero_typo = patch_table(&zero_hhea, b"OS/2", &TYPO_ZERO);
+        let zero_win = patch_table(&zero_typo, b"OS/2", &WIN_ZERO);
+        let no_os2 = strip_table(&zero_hhea, b"OS/2");
+        for (font_data, source, expected) in [
+            (&file_buffer, LineMetricsSource::Hhea, (1900.0, -500.0, 0.0)),
+            (&zero_hhea, LineMetricsSource::Typo, (1536.0, -512.0, 102.0)),
+            // Win descent is positive downwards and gets negated.
+            (&zero_typo, LineMetricsSource::Win, (1946.0, -512.0, 0.0)),
+            (
+                &zero_win,
+                LineMetricsSource::UnitsPerEm,
+                (1638.4, -409.6, 0.0),
+            ),
+            (
+                &no_os2,
+                LineMetricsSource::UnitsPerEm,
+                (1638.4, -409.6, 0.0),
+            ),
+        ] {
+            let font_ref = make_font_ref(font_data, 0);
+            assert!(font_ref_is_valid(&font_ref));
+            let metrics = get_skia_metrics(
+                &font_ref,
+                0.0,
+                &BridgeNormalizedCoords::default(),
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact,
+            );
+            assert_eq!(metrics.line_metrics_source, source);
+            assert_eq!(
+                (metrics.ascent, metrics.descent, metrics.leading),
+                expected,
+                "{source:?}"
+            );
+            // Synthesized metrics scale like the others.
+            let metrics = get_skia_metrics(
+                &font_ref,
+                20.48,
+                &BridgeNormalizedCoords::default(),
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact,
+            );
+            assert!(
+                (metrics.ascent - expected.0 / 100.0).abs() < 1e-3,
+                "{source:?}"
+            );
+            assert!(
+                (metrics.descent - expected.1 / 100.0).abs() < 1e-3,
+                "{source:?}"
+            );
+        }
+
+        // USE_TYPO_METRICS with zero typo metrics falls back to the em.
+        let typo_zero_with_bit = patch_table(&file_buffer, b"OS/2", &TYPO_ZERO);
+        let typo_zero_with_bit = replace_table(
+            &typo_zero_with_bit,
+            b"OS/2",
+            Some(os2_with_use_typo_metrics(&typo_zero_with_bit, true)),
+        );
+        let font_ref = make_font_ref(&typo_zero_with_bit, 0);
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &BridgeNormalizedCoords::default(),
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert_eq!(metrics.line_metrics_source, LineMetricsSource::UnitsPerEm);
+        assert_eq!((metrics.ascent, metrics.descent), (1638.4, -409.6));
+    }
+
+    #[test]
+    fn test_synthesized_x_height_and_cap_height() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = BridgeNormalizedCoords::default();
+        let from_table = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &coords,
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert!(from_table.x_height > 0.0 && from_table.cap_height > 0.0);
+        assert!(!from_table.x_height_is_synthesized && !from_table.cap_height_is_synthesized);
+
+        // Zero OS/2 sxHeight and sCapHeight, as in fonts with OS/2 versions
+        // before 2.
+        let without_heights = patch_table(&file_buffer, b"OS/2", &[(86, 0), (88, 0)]);
+        let font_ref = make_font_ref(&without_heights, 0);
+        for size in [0.0, 12.0, 100.0] {
+            let scale = if size == 0.0 { 1.0 } else { size / 2048.0 };
+            let synthesized = get_skia_metrics(
+                &font_ref,
+                size,
+                &coords,
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact,
+            );
+            assert!(synthesized.x_height_is_synthesized && synthesized.cap_height_is_synthesized);
+            // The outlines match the table values up to overshoot.
+            let tolerance = 20.0 * scale;
+            assert!(
+                (synthesized.x_height - from_table.x_height * scale).abs() <= tolerance,
+                "size {size}: {} vs {}",
+                synthesized.x_height,
+                from_table.x_height * scale
+            );
+            assert!(
+                (synthesized.cap_height - from_table.cap_height * scale).abs() <= tolerance,
+                "size {size}: {} vs {}",
+                synthesized.cap_height,
+                from_table.cap_height * scale
+            );
+        }
+
+        // Nothing is synthesized without outlines.
+        let without_outlines = strip_table(&without_heights, b"glyf");
+        let font_ref = make_font_ref(&without_outlines, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &coords,
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert_eq!((metrics.x_height, metrics.cap_height), (0.0, 0.0));
+        assert!(!metrics.x_height_is_synthesized && !metrics.cap_height_is_synthesized);
+    }
+
+    #[test]
+    fn test_metrics_bounds_validity() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let head = FontRef::new(&file_buffer).unwrap().head().unwrap();
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &BridgeNormalizedCoords::default(),
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert!(metrics.bounds_is_valid);
+        assert_eq!(
+            (metrics.x_min, metrics.bottom, metrics.x_max, metrics.top),
+            (
+                head.x_min() as f32,
+                head.y_min() as f32,
+                head.x_max() as f32,
+                head.y_max() as f32
+            )
+        );
+
+        // A zero bounding box is still valid.
+        let zero_bounds = patch_table(&file_buffer, b"head", &[(36, 0), (38, 0), (40, 0), (42, 0)]);
+        let font_ref = make_font_ref(&zero_bounds, 0);
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &BridgeNormalizedCoords::default(),
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert!(metrics.bounds_is_valid);
+        assert_eq!(
+            (metrics.x_min, metrics.bottom, metrics.x_max, metrics.top),
+            (0.0, 0.0, 0.0, 0.0)
+        );
+
+        let without_head = strip_table(&file_buffer, b"head");
+        let font_ref = make_font_ref(&without_head, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        let metrics = get_skia_metrics(
+            &font_ref,
+            0.0,
+            &BridgeNormalizedCoords::default(),
+            HintingLevel::None,
+            false,
+            SizeRounding::Exact,
+        );
+        assert!(!metrics.bounds_is_valid);
+        assert_eq!(
+            (metrics.x_min, metrics.bottom, metrics.x_max, metrics.top),
+            (0.0, 0.0, 0.0, 0.0)
+        );
+        // Other metrics are still reported.
+        assert_eq!(metrics.ascent, 1900.0);
+    }
+
+    #[test]
+    fn test_italic_angle() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert_eq!(italic_angle(&font_ref), 0.0);
+        assert!(!italic_angle_is_nonzero(&font_ref));
+
+        // italicAngle is a 16.16 Fixed, -12.0 has an integer part of -12.
+        let oblique = patch_table(&file_buffer, b"post", &[(4, -12), (6, 0)]);
+        let font_ref = make_font_ref(&oblique, 0);
+        assert_eq!(italic_angle(&font_ref), -12.0);
+        assert!(italic_angle_is_nonzero(&font_ref));
+        assert_eq!(
+            italic_angle_at(&font_ref, &BridgeNormalizedCoords::default()),
+            -12.0
+        );
+
+        let without_post = strip_table(&oblique, b"post");
+        let font_ref = make_font_ref(&without_post, 0);
+        assert_eq!(italic_angle(&font_ref), 0.0);
+        assert!(!italic_angle_is_nonzero(&font_ref));
+    }
+
+    #[test]
+    fn test_italic_angle_slnt_axis() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        // Turn the wdth axis, the second axis record at offset 36, into a
+        // slnt axis ranging from -20 to 0.
+        let slnt_tag = b"slnt";
+        let slanted = patch_table(
+            &file_buffer,
+            b"fvar",
+            &[
+                (36, i16::from_be_bytes([slnt_tag[0], slnt_tag[1]])),
+                (38, i16::from_be_bytes([slnt_tag[2], slnt_tag[3]])),
+                (40, -20),
+                (44, 0),
+                (48, 0),
+            ],
+        );
+        let font_ref = make_font_ref(&slanted, 0);
+        assert_eq!(italic_angle(&font_ref), 0.0);
+        assert_eq!(
+            italic_angle_at(&font_ref, &BridgeNormalizedCoords::default()),
+            0.0
+        );
+
+        let slnt = || SkiaDesignCoordinate {
+            axis: u32::from_be_bytes(*slnt_tag),
+            value: -12.0,
+        };
+        let coords = resolve_into_normalized_coords(&font_ref, &[slnt()]);
+        assert!((italic_angle_at(&font_ref, &coords) - -12.0).abs() < 0.01);
+        // post still describes the default instance only.
+        assert_eq!(italic_angle(&font_ref), 0.0);
+
+        let oblique = patch_table(&slanted, b"post", &[(4, -12), (6, 0)]);
+        let font_ref = make_font_ref(&oblique, 0);
+        let coords = resolve_into_normalized_coords(&font_ref, &[slnt()]);
+        assert!((italic_angle_at(&font_ref, &coords) - -24.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_is_fixed_pitch() {
+        // Funkster is monospace and sets the post flag.
+        let file_buffer = fs::read("resources/fonts/Funkster.ttf")
+            .expect("Funkster test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(is_fixed_pitch(&font_ref));
+        let without_post = strip_table(&file_buffer, b"post");
+        assert!(is_fixed_pitch(&make_font_ref(&without_post, 0)));
+
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(!is_fixed_pitch(&font_ref));
+        let without_post = strip_table(&file_buffer, b"post");
+        assert!(!is_fixed_pitch(&make_font_ref(&without_post, 0)));
+        // The post flag alone is trusted.
+        let flagged = patch_table(&file_buffer, b"post", &[(12, 0), (14, 1)]);
+        assert!(is_fixed_pitch(&make_font_ref(&flagged, 0)));
+
+        // Ahem is monospace but leaves the post flag unset.
+        let file_buffer =
+            fs::read("resources/fonts/ahem.ttf").expect("Ahem test font could not be opened.");
+        let font = FontRef::new(&file_buffer).unwrap();
+        assert_eq!(font.post().unwrap().is_fixed_pitch(), 0);
+        assert!(is_fixed_pitch(&make_font_ref(&file_buffer, 0)));
+
+        // Too few sampled glyphs to tell.
+        let file_buffer = fs::read("resources/fonts/hintgasp.ttf")
+            .expect("hintgasp test font could not be opened.");
+        let without_post = strip_table(&file_buffer, b"post");
+        assert!(!is_fixed_pitch(&make_font_ref(&without_post, 0)));
+    }
+
+    #[test]
+    fn test_caret_info() {
+        let file_buffer = fs::read(TEST_CONDENSED_BOLD_ITALIC)
+            .expect("Condensed bold italic test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let mut caret = BridgeCaretInfo::default();
+        assert!(caret_info(
+            &font_ref,
+            0.0,
+            &BridgeNormalizedCoords::default(),
+            &mut caret
+        ));
+        assert_eq!(
+            caret,
+            BridgeCaretInfo {
+                slope_rise: 2048.0,
+                slope_run: 435.0,
+                offset: 0.0
+            }
+        );
+        // The slope of a 12 degree italic.
+        assert!(((caret.slope_run / caret.slope_rise).atan().to_degrees() - 12.0).abs() < 0.1);
+
+        let units_per_em = units_per_em_or_zero(&font_ref) as f32;
+        assert!(caret_info(
+            &font_ref,
+            100.0,
+            &BridgeNormalizedCoords::default(),
+            &mut caret
+        ));
+        assert_eq!(caret.slope_rise, 2048.0 * 100.0 / units_per_em);
+        assert_eq!(caret.slope_run, 435.0 * 100.0 / units_per_em);
+
+        assert!(!caret_info(
+            &font_ref,
+            -1.0,
+            &BridgeNormalizedCoords::default(),
+            &mut caret
+        ));
+        assert_eq!(caret, BridgeCaretInfo::default());
+
+        let without_hhea = strip_table(&file_buffer, b"hhea");
+        let font_ref = make_font_ref(&without_hhea, 0);
+        assert!(!caret_info(
+            &font_ref,
+            0.0,
+            &BridgeNormalizedCoords::default(),
+            &mut caret
+        ));
+        assert_eq!(caret, BridgeCaretInfo::default());
+    }
+
+    #[test]
+    fn test_caret_info_mvar() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        // Slant the caret and shift it towards the maximum weight.
+        let with_mvar = replace_table(
+            &file_buffer,
+            b"MVAR",
+            Some(build_mvar(2, &[(b"hcrn", 200), (b"hcof", -40)])),
+        );
+        let font_ref = make_font_ref(&with_mvar, 0);
+        for (weight, fraction) in [(400.0, 0.0), (650.0, 0.5), (900.0, 1.0)] {
+            let coords = resolve_into_normalized_coords(
+                &font_ref,
+                &[SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(*b"wght"),
+                    value: weight,
+                }],
+            );
+            let mut caret = BridgeCaretInfo::default();
+            assert!(caret_info(&font_ref, 0.0, &coords, &mut caret));
+            assert_eq!(caret.slope_rise, 1.0);
+            assert!(
+                (caret.slope_run - 200.0 * fraction).abs() < 0.01,
+                "wght {weight}: {}",
+                caret.slope_run
+            );
+            assert!(
+                (caret.offset + 40.0 * fraction).abs() < 0.01,
+                "wght {weight}: {}",
+                caret.offset
+            );
+        }
+    }
+
+    #[test]
+    fn test_superscript_subscript_metrics() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let mut script_metrics = BridgeScriptMetrics::default();
+        assert!(get_superscript_subscript_metrics(
+            &font_ref,
+            100.0,
+            &BridgeNormalizedCoords::default(),
+            &mut script_metrics
+        ));
+        let scale = 100.0 / 2048.0;
+        assert_eq!(
+            script_metrics,
+            BridgeScriptMetrics {
+                subscript_x_size: 1434.0 * scale,
+                subscript_y_size: 1331.0 * scale,
+                subscript_x_offset: 0.0,
+                subscript_y_offset: 287.0 * scale,
+                superscript_x_size: 1434.0 * scale,
+                superscript_y_size: 1331.0 * scale,
+                superscript_x_offset: 0.0,
+                superscript_y_offset: 977.0 * scale,
+            }
+        );
+
+        let without_os2 = strip_table(&file_buffer, b"OS/2");
+        let font_ref = make_font_ref(&without_os2, 0);
+        assert!(!get_superscript_subscript_metrics(
+            &font_ref,
+            100.0,
+            &BridgeNormalizedCoords::default(),
+            &mut script_metrics
+        ));
+        assert_eq!(script_metrics, BridgeScriptMetrics::default());
+    }
+
+    #[test]
+    fn test_superscript_subscript_metrics_mvar() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font = FontRef::new(&file_buffer).unwrap();
+        let os2 = font.os2().unwrap();
+        let (subscript_y_offset, superscript_y_size) = (
+            os2.y_subscript_y_offset() as f32,
+            os2.y_superscript_y_size() as f32,
+        );
+        let with_mvar = replace_table(
+            &file_buffer,
+            b"MVAR",
+            Some(build_mvar(2, &[(b"sbyo", 30), (b"spys", -50)])),
+        );
+        let font_ref = make_font_ref(&with_mvar, 0);
+        for (weight, fraction) in [(400.0, 0.0), (650.0, 0.5), (900.0, 1.0)] {
+            let coords = resolve_into_normalized_coords(
+                &font_ref,
+                &[SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(*b"wght"),
+                    value: weight,
+                }],
+            );
+            let mut script_metrics = BridgeScriptMetrics::default();
+            assert!(get_superscript_subscript_metrics(
+                &font_ref,
+                0.0,
+                &coords,
+                &mut script_metrics
+            ));
+            assert!(
+                (script_metrics.subscript_y_offset - (subscript_y_offset + 30.0 * fraction)).abs()
+                    < 0.01,
+                "wght {weight}: {}",
+                script_metrics.subscript_y_offset
+            );
+            assert!(
+                (script_metrics.superscript_y_size - (superscript_y_size - 50.0 * fraction)).abs()
+                    < 0.01,
+                "wght {weight}: {}",
+                script_metrics.superscript_y_size
+            );
+        }
+    }
+
+    #[test]
+    fn test_glyph_bounds_contain_path() {
+        const TOLERANCE: f32 = 0.001;
+        let wght_900 = [SkiaDesignCoordinate {
+            axis: u32::from_be_bytes(*b"wght"),
+            value: 900.0,
+        }];
+        for (font_path, variation) in [
+            (TEST_ROBOTO, &[][..]),
+            (TEST_HINTED_CFF, &[][..]),
+            (TEST_VARIABLE, &[][..]),
+            (TEST_VARIABLE, &wght_900[..]),
+            (TEST_CJK_VARIABLE_COLLECTION, &wght_900[..]),
+        ] {
+            let file_buffer = fs::read(font_path).expect("Test font could not be opened.");
+            let font_ref = make_font_ref(&file_buffer, 0);
+            assert!(font_ref_is_valid(&font_ref));
+            let outlines = get_outline_collection(&font_ref);
+            let coords = resolve_into_normalized_coords(&font_ref, variation);
+            let glyph_ids: Vec<u16> = (0..num_glyphs(&font_ref)).collect();
+            let mut batch_bounds = vec![BridgePathBounds::default(); glyph_ids.len()];
+            assert_eq!(
+                glyph_bounds_batch(&font_ref, 20.0, &coords, &glyph_ids, &mut batch_bounds),
+                glyph_ids.len()
+            );
+            let mut verbs = Vec::new();
+            let mut points = Vec::new();
+            for glyph_id in glyph_ids {
+                let mut bounds = BridgePathBounds::default();
+                assert!(glyph_bounds(
+                    &font_ref,
+                    20.0,
+                    &coords,
+                    glyph_id,
+                    &mut bounds
+                ));
+                assert_eq!(bounds, batch_bounds[glyph_id as usize]);
+                let status = get_path_verbs(
+                    &outlines,
+                    glyph_id,
+                    20.0,
+                    &coords,
+                    &BridgeOutlineLimits::default(),
+                    &identity_path_transform(),
+                    0.0,
+                    HintingLevel::None,
+                    &mut verbs,
+                    &mut points,
+                    &mut BridgeScalerMetrics::default(),
+                );
+                assert!(matches!(
+                    status,
+                    PathStatus::Success | PathStatus::EmptyOutline
+                ));
+                assert_eq!(
+                    bounds.is_empty,
+                    points.is_empty(),
+                    "{font_path} glyph {glyph_id}"
+                );
+                for point in points.chunks_exact(2) {
+                    assert!(
+                        point[0] >= bounds.x_min - TOLERANCE
+                            && point[0] <= bounds.x_max + TOLERANCE
+                            && point[1] >= bounds.y_min - TOLERANCE
+                            && point[1] <= bounds.y_max + TOLERANCE,
+                        "{font_path} glyph {glyph_id}: {point:?} outside {bounds:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_glyph_bounds_scaling() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = BridgeNormalizedCoords::default();
+        let glyph_h = lookup_glyph_or_zero(&font_ref, 'H' as u32);
+        let mut unscaled = BridgePathBounds::default();
+        assert!(glyph_bounds(
+            &font_ref,
+            0.0,
+            &coords,
+            glyph_h,
+            &mut unscaled
+        ));
+        assert!(!unscaled.is_empty);
+        // Glyphs sit on the baseline, which is the bottom in y-down space.
+        assert_eq!(unscaled.y_max, 0.0);
+        assert!(unscaled.y_min < 0.0);
+        let mut scaled = BridgePathBounds::default();
+        assert!(glyph_bounds(
+            &font_ref,
+            2048.0,
+            &coords,
+            glyph_h,
+            &mut scaled
+        ));
+        assert_eq!(scaled, unscaled);
+
+        // The space glyph has no outline.
+        let space = lookup_glyph_or_zero(&font_ref, ' ' as u32);
+        assert!(glyph_bounds(&font_ref, 20.0, &coords, space, &mut scaled));
+        assert!(scaled.is_empty);
+
+        for (size, glyph_id) in [
+            (-1.0, glyph_h),
+            (f32::NAN, glyph_h),
+            (20.0, num_glyphs(&font_ref)),
+        ] {
+            let mut bounds = unscaled;
+            assert!(!glyph_bounds(
+                &font_ref,
+                size,
+                &coords,
+                glyph_id,
+                &mut bounds
+            ));
+            assert_eq!(bounds, BridgePathBounds::default());
+        }
+        let mut batch_bounds = [unscaled; 2];
+        assert_eq!(
+            glyph_bounds_batch(
+                &font_ref,
+                20.0,
+                &coords,
+                &[glyph_h, num_glyphs(&font_ref)],
+                &mut batch_bounds
+            ),
+            1
+        );
+        assert!(!batch_bounds[0].is_empty);
+        assert_eq!(batch_bounds[1], BridgePathBounds::default());
+    }
+
+    #[test]
+    fn test_left_side_bearing() {
+        // colr.ttf has a single long metric, so all other glyphs take their
+        // side bearing from leftSideBearings, and these differ from the
+        // outline's xMin.
+        let file_buffer = fs::read(TEST_COLR).expect("COLR test font could not be opened.");
+        let font = FontRef::new(&file_buffer).unwrap();
+        let hmtx = font.hmtx().unwrap();
+        assert_eq!(hmtx.h_metrics().len(), 1);
+        let (loca, glyf) = (font.loca(None).unwrap(), font.glyf().unwrap());
+        let x_min = loca
+            .get_glyf(GlyphId::new(2), &glyf)
+            .unwrap()
+            .unwrap()
+            .x_min();
+        assert_eq!((hmtx.left_side_bearings()[1].get(), x_min), (219, 119));
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = BridgeNormalizedCoords::default();
+        assert_eq!(left_side_bearing_or_zero(&font_ref, 0.0, &coords, 2), 219.0);
+        let units_per_em = units_per_em_or_zero(&font_ref) as f32;
+        assert!(
+            (left_side_bearing_or_zero(&font_ref, 20.0, &coords, 2) - 219.0 * 20.0 / units_per_em)
+                .abs()
+                < 1.0 / 64.0
+        );
+
+        let num_glyphs = num_glyphs(&font_ref);
+        for (size, glyph_id) in [(-1.0, 2), (f32::NAN, 2), (0.0, num_glyphs)] {
+            assert_eq!(
+                left_side_bearing_or_zero(&font_ref, size, &coords, glyph_id),
+                0.0
+            );
+        }
+    }
+
+    #[test]
+    fn test_left_side_bearing_variations() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let lsb_deltas: Vec<i16> = (0..num_glyphs(&font_ref) as i16)
+            .map(|glyph_id| 10 * glyph_id - 30)
+            .collect();
+        let with_hvar = replace_table(
+            &file_buffer,
+            b"HVAR",
+            Some(build_hvar(2, &vec![Some(0); lsb_deltas.len()], &lsb_deltas)),
+        );
+        let font_ref = make_font_ref(&with_hvar, 0);
+        assert!(font_ref_is_valid(&font_ref));
+        for (weight, fraction) in [(400.0, 0.0), (650.0, 0.5), (900.0, 1.0)] {
+            let coords = resolve_into_normalized_coords(
+                &font_ref,
+                &[SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(*b"wght"),
+                    value: weight,
+                }],
+            );
+            for (glyph_id, delta) in lsb_deltas.iter().enumerate() {
+                let glyph_id = glyph_id as u16;
+                let default_lsb = left_side_bearing_or_zero(
+                    &font_ref,
+                    0.0,
+                    &BridgeNormalizedCoords::default(),
+                    glyph_id,
+                );
+                // Deltas are truncated to whole font units, like FreeType.
+                let expected = default_lsb + (f32::from(*delta) * fraction).trunc();
+                assert!(
+                    (left_side_bearing_or_zero(&font_ref, 0.0, &coords, glyph_id) - expected).abs()
+                        < 0.01,
+                    "wght {weight} glyph {glyph_id}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_advance_widths() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let num_glyphs = num_glyphs(&font_ref);
+        // Cycle through all glyphs and a few IDs out of range.
+        let glyph_ids: Vec<u16> = (0..500u16).map(|i| i % (num_glyphs + 3)).collect();
+        let coords = resolve_into_normalized_coords(
+            &font_ref,
+            &[SkiaDesignCoordinate {
+                axis: u32::from_be_bytes(*b"wght"),
+                value: 700.0,
+            }],
+        );
+        for size in [0.0, 12.0, 100.0] {
+            let mut advances = vec![-1.0; glyph_ids.len()];
+            assert_eq!(
+                advance_widths(
+                    &font_ref,
+                    size,
+                    &coords,
+                    &glyph_ids,
+                    &mut advances,
+                    SizeRounding::Exact
+                ),
+                500
+            );
+            for (glyph_id, advance) in glyph_ids.iter().zip(&advances) {
+                assert_eq!(
+                    *advance,
+                    advance_width_or_zero(&font_ref, size, &coords, *glyph_id, SizeRounding::Exact),
+                    "size {size} glyph {glyph_id}"
+                );
+                if *glyph_id >= num_glyphs {
+                    assert_eq!(*advance, 0.0);
+                }
+            }
+        }
+
+        // Mismatched lengths process the shorter of the two.
+        let mut advances = [-1.0; 4];
+        assert_eq!(
+            advance_widths(
+                &font_ref,
+                12.0,
+                &coords,
+                &glyph_ids[..2],
+                &mut advances,
+                SizeRounding::Exact
+            ),
+            2
+        );
+        assert_eq!(advances[2..], [-1.0, -1.0]);
+        assert_eq!(
+            advance_widths(
+                &font_ref,
+                12.0,
+                &coords,
+                &glyph_ids,
+                &mut advances,
+                SizeRounding::Exact
+            ),
+            4
+        );
+
+        let mut advances = [-1.0; 4];
+        assert_eq!(
+            advance_widths(
+                &font_ref,
+                -1.0,
+                &coords,
+                &glyph_ids[..4],
+                &mut advances,
+                SizeRounding::Exact
+            ),
+            4
+        );
+        assert_eq!(advances, [0.0; 4]);
+    }
+
+    #[test]
+    fn test_advance_width_font_units() {
+        for (font_path, variation) in [
+            (TEST_ROBOTO, &[][..]),
+            (TEST_HINTED_CFF, &[][..]),
+            (
+                TEST_VARIABLE,
+                &[SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(*b"wght"),
+                    value: 650.0,
+                }][..],
+            ),
+        ] {
+            let file_buffer = fs::read(font_path).expect("Test font could not be opened.");
+            let font_ref = make_font_ref(&file_buffer, 0);
+            let coords = resolve_into_normalized_coords(&font_ref, variation);
+            let units_per_em = units_per_em_or_zero(&font_ref) as f32;
+            let glyph_ids: Vec<u16> = (0..num_glyphs(&font_ref) + 1).collect();
+            let mut advances = vec![-1; glyph_ids.len()];
+            assert_eq!(
+                advance_widths_font_units(&font_ref, &coords, &glyph_ids, &mut advances),
+                glyph_ids.len()
+            );
+            for (glyph_id, advance) in glyph_ids.iter().zip(&advances) {
+                assert_eq!(
+                    *advance,
+                    advance_width_font_units(&font_ref, &coords, *glyph_id)
+                );
+                for size in [9.0, 12.0, 16.5, 100.0] {
+                    // Scaled advances are rounded to 26.6 fixed point.
+                    let scaled = *advance as f32 * size / units_per_em;
+                    assert!(
+                        (scaled
+                            - advance_width_or_zero(
+                                &font_ref,
+                                size,
+                                &coords,
+                                *glyph_id,
+                                SizeRounding::Exact
+                            ))
+                        .abs()
+                            <= 0.5 / 64.0,
+                        "{font_path} glyph {glyph_id} size {size}"
+                    );
+                }
+            }
+            assert_eq!(advances.last(), Some(&0));
+        }
+    }
+
+    #[test]
+    fn test_glyph_metrics_bundle() {
+        let wght_900 = [SkiaDesignCoordinate {
+            axis: u32::from_be_bytes(*b"wght"),
+            value: 900.0,
+        }];
+        for (font_path, variation) in [
+            (TEST_ROBOTO, &[][..]),
+            (TEST_HINTED_CFF, &[][..]),
+            (TEST_VARIABLE, &wght_900[..]),
+        ] {
+            let file_buffer = fs::read(font_path).expect("Test font could not be opened.");
+            let font_ref = make_font_ref(&file_buffer, 0);
+            let coords = resolve_into_normalized_coords(&font_ref, variation);
+            let glyph_ids: Vec<u16> = (0..num_glyphs(&font_ref) + 1).collect();
+            for size in [0.0, 20.0] {
+                let mut batch_metrics = vec![BridgeGlyphMetrics::default(); glyph_ids.len()];
+                NUM_GLYPH_METRICS_SCALERS.with(|count| count.set(0));
+                assert_eq!(
+                    glyph_metrics_batch(&font_ref, size, &coords, &glyph_ids, &mut batch_metrics),
+                    glyph_ids.len() - 1
+                );
+                assert_eq!(NUM_GLYPH_METRICS_SCALERS.with(|count| count.get()), 1);
+                for (glyph_id, batch) in glyph_ids.iter().zip(&batch_metrics) {
+                    let glyph_id = *glyph_id;
+                    let mut metrics = BridgeGlyphMetrics::default();
+                    let valid = glyph_metrics(&font_ref, size, &coords, glyph_id, &mut metrics);
+                    assert_eq!(metrics, *batch);
+                    if !valid {
+                        assert_eq!(glyph_id, num_glyphs(&font_ref));
+                        assert_eq!(metrics, BridgeGlyphMetrics::default());
+                        continue;
+                    }
+                    let mut bounds = BridgePathBounds::default();
+                    assert!(glyph_bounds(
+                        &font_ref,
+                        size,
+                        &coords,
+                        glyph_id,
+                        &mut bounds
+                    ));
+                    assert_eq!(
+                        metrics,
+                        BridgeGlyphMetrics {
+                            advance_width: advance_width_or_zero(
+                                &font_ref,
+                                size,
+                                &coords,
+                                glyph_id,
+                                SizeRounding::Exact
+                            ),
+                            left_side_bearing: left_side_bearing_or_zero(
+                                &font_ref, size, &coords, glyph_id
+                            ),
+                            bounds,
+                            is_empty: is_empty_glyph(&font_ref, &coords, glyph_id),
+                        },
+                        "{font_path} glyph {glyph_id}"
+                    );
+                }
+            }
+        }
+
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let mut batch_metrics = [BridgeGlyphMetrics::default(); 2];
+        assert_eq!(
+            glyph_metrics_batch(
+                &font_ref,
+                -1.0,
+                &BridgeNormalizedCoords::default(),
+                &[1, 2],
+                &mut batch_metrics
+            ),
+            0
+        );
+    }
+
+    #[test]
+    fn test_linear_and_hinted_advances() {
+        let file_buffer =
+            fs::read(TEST_HINTED_CFF).expect("Hinted CFF test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let outlines = get_outline_collection(&font_ref);
+        let coords = BridgeNormalizedCoords::default();
+        let glyph_id = (0..num_glyphs(&font_ref))
+            .find(|glyph_id| {
+                advance_width_or_zero(&font_ref, 11.0, &coords, *glyph_id, SizeRounding::Exact)
+                    .fract()
+                    != 0.0
+            })
+            .expect("No glyph with a fractional advance.");
+        let advances = linear_and_hinted_advances(
+            &font_ref,
+            &outlines,
+            11.0,
+            &coords,
+            glyph_id,
+            HintingLevel::Full,
+        );
+        let linear_advance =
+            advance_width_or_zero(&font_ref, 11.0, &coords, glyph_id, SizeRounding::Exact);
+        assert_ne!(linear_advance.fract(), 0.0);
+        assert_eq!(
+            advances,
+            BridgeAdvances {
+                linear: linear_advance,
+                hinted: hinted_advance_width_or_zero(
+                    &font_ref,
+                    &outlines,
+                    11.0,
+                    &coords,
+                    glyph_id,
+                    HintingLevel::Full
+                ),
+            }
+        );
+        assert_ne!(advances.linear, advances.hinted);
+        assert_eq!(advances.hinted, advances.hinted.round());
+
+        for hinting in [HintingLevel::None, HintingLevel::Slight] {
+            let advances =
+                linear_and_hinted_advances(&font_ref, &outlines, 11.0, &coords, glyph_id, hinting);
+            assert_eq!(advances.linear, advances.hinted);
+        }
+
+        // Both advances of TrueType glyphs are linear, since their
+        // instructions are not executed.
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let outlines = get_outline_collection(&font_ref);
+        for glyph_id in 0..num_glyphs(&font_ref) {
+            let advances = linear_and_hinted_advances(
+                &font_ref,
+                &outlines,
+                11.0,
+                &coords,
+                glyph_id,
+                HintingLevel::Full,
+            );
+            assert_eq!(advances.linear, advances.hinted);
+            assert_eq!(
+                advances.linear,
+                advance_width_or_zero(&font_ref, 11.0, &coords, glyph_id, SizeRounding::Exact)
+            );
+        }
+    }
+
+    /// Builds an hdmx table with a device record per (pixel size, widths)
+    /// entry, using `record_size` or else the padded size of the records.
+    fn build_hdmx(records: &[(u8, &[u8])], record_size: Option<usize>) -> Vec<u8> {
+        let num_widths = records
+            .iter()
+            .map(|(_, widths)| widths.len())
+            .max()
+            .unwrap_or(0);
+        let padded_size = (num_widths + 2 + 3) & !3;
+        let record_size = record_size.unwrap_or(padded_size);
+        let mut hdmx = Vec::new();
+        hdmx.extend(0u16.to_be_bytes());
+        hdmx.extend((records.len() as i16).to_be_bytes());
+        hdmx.extend((record_size as i32).to_be_bytes());
+        for (pixel_size, widths) in records {
+            let mut record = vec![*pixel_size, widths.iter().copied().max().unwrap_or(0)];
+            record.extend(*widths);
+            record.resize(padded_size, 0);
+            hdmx.extend(record);
+        }
+        hdmx
+    }
+
+    #[test]
+    fn test_hdmx_advance() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let num_glyphs = FontRef::new(&file_buffer)
+            .unwrap()
+            .maxp()
+            .unwrap()
+            .num_glyphs();
+        // Roboto has a single device record for 9 ppem.
+        let mut advance = 0xFF;
+        assert!(hdmx_advance(&font_ref, 9, 0, &mut advance));
+        assert_eq!(advance, 4);
+        assert!(hdmx_advance(&font_ref, 9, 3, &mut advance));
+        assert_eq!(advance, 2);
+        assert!(!hdmx_advance(&font_ref, 10, 3, &mut advance));
+        assert_eq!(advance, 0);
+        assert!(!hdmx_advance(&font_ref, 9, num_glyphs, &mut advance));
+
+        let widths_12: Vec<u8> = (0..num_glyphs)
+            .map(|glyph_id| (glyph_id % 7) as u8 + 3)
+            .collect();
+        let widths_16: Vec<u8> = (0..num_glyphs)
+            .map(|glyph_id| (glyph_id % 5) as u8 + 6)
+            .collect();
+        let with_hdmx = replace_table(
+            &file_buffer,
+            b"hdmx",
+            Some(build_hdmx(&[(12, &widths_12), (16, &widths_16)], None)),
+        );
+        let font_ref = make_font_ref(&with_hdmx, 0);
+        for (ppem, widths) in [(12, &widths_12), (16, &widths_16)] {
+            for glyph_id in 0..num_glyphs {
+                assert!(hdmx_advance(&font_ref, ppem, glyph_id, &mut advance));
+                assert_eq!(
+                    advance, widths[glyph_id as usize],
+                    "ppem {ppem} glyph {glyph_id}"
+                );
+            }
+        }
+        assert!(!hdmx_advance(&font_ref, 9, 0, &mut advance));
+        assert!(!hdmx_advance(&font_ref, 14, 0, &mut advance));
+
+        // Records too small to hold a width for every glyph are rejected.
+        let inconsistent = replace_table(
+            &file_buffer,
+            b"hdmx",
+            Some(build_hdmx(&[(12, &widths_12)], Some(num_glyphs as usize))),
+        );
+        let font_ref = make_font_ref(&inconsistent, 0);
+        assert!(!hdmx_advance(&font_ref, 12, 0, &mut advance));
+
+        // Records running past the end of the table are not read.
+        let mut truncated = build_hdmx(&[(12, &widths_12), (16, &widths_16)], None);
+        truncated.truncate(truncated.len() - 4);
+        let truncated = replace_table(&file_buffer, b"hdmx", Some(truncated));
+        let font_ref = make_font_ref(&truncated, 0);
+        assert!(hdmx_advance(&font_ref, 16, 0, &mut advance));
+        assert!(!hdmx_advance(&font_ref, 16, num_glyphs - 1, &mut advance));
+
+        let without_hdmx = strip_table(&file_buffer, b"hdmx");
+        let font_ref = make_font_ref(&without_hdmx, 0);
+        assert!(!hdmx_advance(&font_ref, 9, 0, &mut advance));
+    }
+
+    #[test]
+    fn test_hdmx_advance_variable_font() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let num_glyphs = FontRef::new(&file_buffer)
+            .unwrap()
+            .maxp()
+            .unwrap()
+            .num_glyphs();
+        let widths = vec![5; num_glyphs as usize];
+        let with_hdmx = replace_table(
+            &file_buffer,
+            b"hdmx",
+            Some(build_hdmx(&[(12, &widths)], None)),
+        );
+        let mut advance = 0;
+        assert!(!hdmx_advance(
+            &make_font_ref(&with_hdmx, 0),
+            12,
+            0,
+            &mut advance
+        ));
+        let without_fvar = strip_table(&with_hdmx, b"fvar");
+        assert!(hdmx_advance(
+            &make_font_ref(&without_fvar, 0),
+            12,
+            0,
+            &mut advance
+        ));
+        assert_eq!(advance, 5);
+    }
+
+    /// Builds a VDMX table with the given (ratio record, group index) pairs
+    /// and groups of (yPelHeight, yMax, yMin) entries.
+    fn build_vdmx(ratios: &[([u8; 4], usize)], groups: &[&[(u16, i16, i16)]]) -> Vec<u8> {
+        let mut vdmx = Vec::new();
+        for value in [1u16, groups.len() as u16, ratios.len() as u16] {
+            vdmx.extend(value.to_be_bytes());
+        }
+        for (ratio, _) in ratios {
+            vdmx.extend(ratio);
+        }
+        let mut group_offsets = Vec::new();
+        let mut group_data = Vec::new();
+        let groups_start = vdmx.len() + 2 * ratios.len();
+        for entries in groups {
+            group_offsets.push((groups_start + group_data.len()) as u16);
+            let start_size = entries.first().map_or(0, |(ppem, _, _)| *ppem as u8);
+            let end_size = entries.last().map_or(0, |(ppem, _, _)| *ppem as u8);
+            group_data.extend((entries.len() as u16).to_be_bytes());
+            group_data.extend([start_size, end_size]);
+            for (ppem, y_max, y_min) in entries.iter() {
+                group_data.extend(ppem.to_be_bytes());
+                group_data.extend(y_max.to_be_bytes());
+                group_data.extend(y_min.to_be_bytes());
+            }
+        }
+        for (_, group_index) in ratios {
+            vdmx.extend(group_offsets[*group_index].to_be_bytes());
+        }
+        vdmx.extend(group_data);
+        vdmx
+    }
+
+    #[test]
+    fn test_vdmx_metrics() {
+        const SQUARE: [u8; 4] = [1, 1, 1, 1];
+        const ALL_RATIOS: [u8; 4] = [0, 0, 0, 0];
+        const WIDE: [u8; 4] = [1, 2, 1, 1];
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let square_group: &[(u16, i16, i16)] = &[(12, 13, -4), (16, 17, -5)];
+        let with_vdmx = replace_table(
+            &file_buffer,
+            b"VDMX",
+            Some(build_vdmx(&[(SQUARE, 0)], &[square_group])),
+        );
+        let font_ref = make_font_ref(&with_vdmx, 0);
+        let mut values = BridgeVdmxValues::default();
+        assert!(vdmx_metrics(&font_ref, 12, &mut values));
+        assert_eq!(
+            values,
+            BridgeVdmxValues {
+                y_max: 13,
+                y_min: -4
+            }
+        );
+        assert!(vdmx_metrics(&font_ref, 16, &mut values));
+        assert_eq!(
+            values,
+            BridgeVdmxValues {
+                y_max: 17,
+                y_min: -5
+            }
+        );
+        for ppem in [11, 14, 17] {
+            assert!(!vdmx_metrics(&font_ref, ppem, &mut values), "ppem {ppem}");
+            assert_eq!(values, BridgeVdmxValues::default());
+        }
+
+        // A record covering 1:1 is preferred over one matching all ratios,
+        // records for other ratios are skipped.
+        let other_group: &[(u16, i16, i16)] = &[(12, 20, -8)];
+        let fallback_group: &[(u16, i16, i16)] = &[(12, 14, -6)];
+        let groups = [square_group, other_group, fallback_group];
+        for (ratios, expected) in [
+            (
+                &[(WIDE, 1), (ALL_RATIOS, 2), (SQUARE, 0)][..],
+                Some((13, -4)),
+            ),
+            (&[(WIDE, 1), (ALL_RATIOS, 2)][..], Some((14, -6))),
+            (&[(WIDE, 1)][..], None),
+        ] {
+            let with_vdmx = replace_table(&file_buffer, b"VDMX", Some(build_vdmx(ratios, &groups)));
+            let font_ref = make_font_ref(&with_vdmx, 0);
+            assert_eq!(
+                vdmx_metrics(&font_ref, 12, &mut values).then_some((values.y_max, values.y_min)),
+                expected
+            );
+        }
+
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(!vdmx_metrics(&font_ref, 12, &mut values));
+    }
+
+    #[test]
+    fn test_vdmx_line_metrics() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let with_vdmx = replace_table(
+            &file_buffer,
+            b"VDMX",
+            Some(build_vdmx(
+                &[([1, 1, 1, 1], 0)],
+                &[&[(12, 13, -4), (16, 17, -5)]],
+            )),
+        );
+        let font_ref = make_font_ref(&with_vdmx, 0);
+        let coords = BridgeNormalizedCoords::default();
+        for (size, expected) in [(12.0, (13.0, -4.0)), (16.0, (17.0, -5.0))] {
+            let linear = get_skia_metrics(
+                &font_ref,
+                size,
+                &coords,
+                HintingLevel::None,
+                true,
+                SizeRounding::Exact,
+            );
+            for hinting in [HintingLevel::Slight, HintingLevel::Full] {
+                let metrics =
+                    get_skia_metrics(&font_ref, size, &coords, hinting, true, SizeRounding::Exact);
+                assert_eq!((metrics.ascent, metrics.descent), expected);
+                assert_eq!(metrics.leading, linear.leading);
+                assert_eq!(
+                    get_skia_metrics(
+                        &font_ref,
+                        size,
+                        &coords,
+                        hinting,
+                        false,
+                        SizeRounding::Exact
+                    ),
+                    linear
+                );
+            }
+            assert_ne!((linear.ascent, linear.descent), expected);
+        }
+        // Sizes without an entry, fractional sizes and unscaled metrics keep
+        // the table values.
+        for size in [0.0, 12.5, 14.0] {
+            assert_eq!(
+                get_skia_metrics(
+                    &font_ref,
+                    size,
+                    &coords,
+                    HintingLevel::Full,
+                    true,
+                    SizeRounding::Exact
+                ),
+                get_skia_metrics(
+                    &font_ref,
+                    size,
+                    &coords,
+                    HintingLevel::None,
+                    false,
+                    SizeRounding::Exact
+                ),
+                "size {size}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_gasp_behavior() {
+        // Version 1: antialias only up to 8 ppem, then everything.
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        for (ppem, expected) in [(0, 0x2), (8, 0x2), (9, 0xF), (u16::MAX, 0xF)] {
+            assert_eq!(gasp_behavior(&font_ref, ppem), expected, "ppem {ppem}");
+        }
+
+        // Version 0: a single range with antialiasing only.
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        for ppem in [0, 12, u16::MAX] {
+            assert_eq!(gasp_behavior(&font_ref, ppem), GASP_DOGRAY);
+        }
+    }
+
+    #[test]
+    fn test_gasp_behavior_fallbacks() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let gasp = |version: u16, ranges: &[(u16, u16)]| {
+            let mut gasp = Vec::new();
+            for value in [version, ranges.len() as u16] {
+                gasp.extend(value.to_be_bytes());
+            }
+            for (range_max_ppem, behavior) in ranges {
+                gasp.extend(range_max_ppem.to_be_bytes());
+                gasp.extend(behavior.to_be_bytes());
+            }
+            replace_table(&file_buffer, b"gasp", Some(gasp))
+        };
+
+        // Version 0 predates the symmetric bits.
+        let font_data = gasp(0, &[(u16::MAX, 0xF)]);
+        assert_eq!(gasp_behavior(&make_font_ref(&font_data, 0), 20), 0x3);
+
+        // Sizes beyond the last range use the default.
+        let font_data = gasp(1, &[(8, 0x2), (16, 0x1)]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert_eq!(gasp_behavior(&font_ref, 16), GASP_GRIDFIT);
+        assert_eq!(gasp_behavior(&font_ref, 17), GASP_DEFAULT_BEHAVIOR);
+
+        let font_data = replace_table(&file_buffer, b"gasp", None);
+        assert_eq!(
+            gasp_behavior(&make_font_ref(&font_data, 0), 12),
+            GASP_DEFAULT_BEHAVIOR
+        );
+    }
+
+    /// Advances of 'A', 'i' and 'W' in Roboto with `FT_LOAD_NO_HINTING`, and
+    /// the ascender and descender of its `FT_Size_Metrics`, which FreeType
+    /// grid-fits to whole pixels, in 26.6 pixels. From FreeType 2.12.1 with
+    /// `FT_Set_Char_Size` at the size rounded to 1/64 pixel.
+    const ROBOTO_FREETYPE_26_6: [(f32, [i32; 3], i32, i32); 4] = [
+        (9.5, [397, 148, 539], 576, -192),
+        (11.1, [463, 172, 630], 704, -192),
+        (13.37, [558, 208, 759], 832, -256),
+        (21.7, [906, 337, 1232], 1344, -384),
+    ];
+
+    #[test]
+    fn test_fixed_26_6_advances_match_freetype() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = BridgeNormalizedCoords::default();
+        let glyph_ids = ['A', 'i', 'W'].map(|c| lookup_glyph_or_zero(&font_ref, c as u32));
+        for (size, expected, _, _) in ROBOTO_FREETYPE_26_6 {
+            let expected = expected.map(|advance| advance as f32 / 64.0);
+            let advances = glyph_ids.map(|glyph_id| {
+                advance_width_or_zero(
+                    &font_ref,
+                    size,
+                    &coords,
+                    glyph_id,
+                    SizeRounding::Fixed26Dot6,
+                )
+            });
+            assert_eq!(advances, expected, "size {size}");
+            let mut advances = [0.0; 3];
+            advance_widths(
+                &font_ref,
+                size,
+                &coords,
+                &glyph_ids,
+                &mut advances,
+                SizeRounding::Fixed26Dot6,
+            );
+            assert_eq!(advances, expected, "size {size}");
+        }
+    }
+
+    #[test]
+    fn test_fixed_26_6_metrics_match_freetype() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = BridgeNormalizedCoords::default();
+        for (size, _, ascent, descent) in ROBOTO_FREETYPE_26_6 {
+            let metrics = get_skia_metrics(
+                &font_ref,
+                size,
+                &coords,
+                HintingLevel::None,
+                false,
+                SizeRounding::Fixed26Dot6,
+            );
+            assert_eq!(metrics.ascent, ascent as f32 / 64.0, "size {size}");
+            assert_eq!(metrics.descent, descent as f32 / 64.0, "size {size}");
+            // Every length is a whole number of 1/64 pixels.
+            for length in [
+                metrics.top,
+                metrics.bottom,
+                metrics.x_height,
+                metrics.cap_height,
+                metrics.underline_thickness,
+            ] {
+                assert_eq!((length * 64.0).fract(), 0.0, "size {size}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_size_rounding_modes() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = BridgeNormalizedCoords::default();
+        let glyph_id = lookup_glyph_or_zero(&font_ref, 'W' as u32);
+        let advance =
+            |size, rounding| advance_width_or_zero(&font_ref, size, &coords, glyph_id, rounding);
+        let metrics = |size, rounding| {
+            get_skia_metrics(
+                &font_ref,
+                size,
+                &coords,
+                HintingLevel::None,
+                false,
+                rounding,
+            )
+        };
+
+        assert_eq!(
+            advance(13.37, SizeRounding::IntegerPpem),
+            advance(13.0, SizeRounding::Exact)
+        );
+        assert_eq!(
+            metrics(13.6, SizeRounding::IntegerPpem),
+            metrics(14.0, SizeRounding::Exact)
+        );
+        // The exact size keeps the fraction, the 26.6 size is within half of
+        // 1/64 pixel of it.
+        let exact = advance(13.37, SizeRounding::Exact);
+        assert_ne!(exact, advance(13.0, SizeRounding::Exact));
+        assert_ne!(exact, advance(13.37, SizeRounding::Fixed26Dot6));
+        assert!((exact - advance(13.37, SizeRounding::Fixed26Dot6)).abs() < 1.0 / 64.0);
+
+        // Tiny sizes round up to the smallest size of each mode rather than
+        // to unscaled font units.
+        assert_eq!(
+            advance(0.1, SizeRounding::IntegerPpem),
+            advance(1.0, SizeRounding::Exact)
+        );
+        assert_eq!(
+            advance(0.001, SizeRounding::Fixed26Dot6),
+            advance(1.0 / 64.0, SizeRounding::Fixed26Dot6)
+        );
+        for rounding in [
+            SizeRounding::Exact,
+            SizeRounding::Fixed26Dot6,
+            SizeRounding::IntegerPpem,
+        ] {
+            assert_eq!(advance(0.0, rounding), advance(0.0, SizeRounding::Exact));
+            assert_eq!(metrics(0.0, rounding), metrics(0.0, SizeRounding::Exact));
+            for size in [-1.0, f32::NAN, f32::INFINITY] {
+                assert_eq!(advance(size, rounding), 0.0);
+                assert_eq!(metrics(size, rounding), Metrics::default());
+            }
+        }
+    }
+
+    /// Builds a VVAR table for a font with `axis_count` axes that varies the
+    /// advance height, top side bearing and vertical origin of each glyph by
+    /// the corresponding entries of `advance_deltas`, `tsb_deltas` and
+    /// `v_org_deltas` like `build_mvar`. Without an advance height mapping
+    /// glyph IDs index the advance deltas directly, the other mappings are
+    /// omitted if their deltas are empty.
+    fn build_vvar(
+        axis_count: u16,
+        advance_deltas: &[i16],
+        tsb_deltas: &[i16],
+        v_org_deltas: &[i16],
+    ) -> Vec<u8> {
+        // DeltaSetIndexMap format 0 with single byte inner indices of the
+        // items following the preceding deltas.
+        let mut maps = Vec::new();
+        let mut map_offsets = [0u32; 2];
+        let mut first_item = advance_deltas.len();
+        for (deltas, map_offset) in [tsb_deltas, v_org_deltas].iter().zip(&mut map_offsets) {
+            if deltas.is_empty() {
+                continue;
+            }
+            *map_offset = 24 + maps.len() as u32;
+            maps.extend([0u8, 0x07]);
+            maps.extend((deltas.len() as u16).to_be_bytes());
+            maps.extend((0..deltas.len()).map(|index| (first_item + index) as u8));
+            first_item += deltas.len();
+        }
+        let [tsb_map_offset, v_org_map_offset] = map_offsets;
+        let mut vvar = Vec::new();
+        vvar.extend(1u16.to_be_bytes());
+        vvar.extend(0u16.to_be_bytes());
+        for offset in [
+            24 + maps.len() as u32,
+            0,
+            tsb_map_offset,
+            0,
+            v_org_map_offset,
+        ] {
+            vvar.extend(offset.to_be_bytes());
+        }
+        vvar.extend(maps);
+        let deltas: Vec<i16> = [advance_deltas, tsb_deltas, v_org_deltas].concat();
+        vvar.extend(build_item_variation_store(axis_count, &deltas));
+        vvar
+    }
+
+    #[test]
+    fn test_advance_height() {
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable collection could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = BridgeNormalizedCoords::default();
+        let mut is_synthesized = true;
+        // vmtx has a single long metric of 1000 units, which glyph 1
+        // repeats.
+        for glyph_id in [0, 1] {
+            assert_eq!(
+                advance_height_or_zero(&font_ref, 0.0, &coords, glyph_id, &mut is_synthesized),
+                1000.0
+            );
+            assert!(!is_synthesized);
+            assert_eq!(
+                advance_height_or_zero(&font_ref, 20.0, &coords, glyph_id, &mut is_synthesized),
+                20.0
+            );
+        }
+        assert_eq!(
+            advance_height_or_zero(&font_ref, 20.0, &coords, 2, &mut is_synthesized),
+            0.0
+        );
+        assert_eq!(
+            advance_height_or_zero(&font_ref, -1.0, &coords, 1, &mut is_synthesized),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_advance_height_variations() {
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable collection could not be opened.");
+        let mut font_data = Vec::new();
+        assert!(extract_font_from_collection(
+            &file_buffer,
+            0,
+            &mut font_data
+        ));
+        let with_vvar = replace_table(
+            &font_data,
+            b"VVAR",
+            Some(build_vvar(1, &[-100, 50], &[], &[])),
+        );
+        let font_ref = make_font_ref(&with_vvar, 0);
+        let heavy = resolve_into_normalized_coords(
+            &font_ref,
+            &[SkiaDesignCoordinate {
+                axis: u32::from_be_bytes(*b"wght"),
+                value: 900.0,
+            }],
+        );
+        let mut is_synthesized = true;
+        for (glyph_id, expected) in [(0, 900.0), (1, 1050.0)] {
+            assert_eq!(
+                advance_height_or_zero(&font_ref, 0.0, &heavy, glyph_id, &mut is_synthesized),
+                expected
+            );
+            assert!(!is_synthesized);
+            assert_eq!(
+                advance_height_or_zero(
+                    &font_ref,
+                    0.0,
+                    &BridgeNormalizedCoords::default(),
+                    glyph_id,
+                    &mut is_synthesized
+                ),
+                1000.0
+            );
+        }
     }
 
-    struct BridgeLocalizedName {
-        string: String,
-        language: String,
+    #[test]
+    fn test_advance_height_fallback() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font = FontRef::new(&file_buffer).unwrap();
+        assert!(font.vhea().is_err());
+        let os2 = font.os2().unwrap();
+        let typo_height = f32::from(os2.s_typo_ascender()) - f32::from(os2.s_typo_descender());
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = BridgeNormalizedCoords::default();
+        let mut is_synthesized = false;
+        assert_eq!(
+            advance_height_or_zero(&font_ref, 0.0, &coords, 36, &mut is_synthesized),
+            typo_height
+        );
+        assert!(is_synthesized);
+        assert_eq!(
+            advance_height_or_zero(&font_ref, 2048.0 / 10.0, &coords, 36, &mut is_synthesized),
+            typo_height / 10.0
+        );
+
+        // Without OS/2 the hhea metrics are used.
+        let without_os2 = strip_table(&file_buffer, b"OS/2");
+        let font_ref = make_font_ref(&without_os2, 0);
+        is_synthesized = false;
+        assert_eq!(
+            advance_height_or_zero(&font_ref, 0.0, &coords, 36, &mut is_synthesized),
+            1900.0 + 500.0
+        );
+        assert!(is_synthesized);
     }
 
-    struct SkiaDesignCoordinate {
-        axis: u32,
-        value: f32,
+    #[test]
+    fn test_top_side_bearing() {
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable collection could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = BridgeNormalizedCoords::default();
+        // Glyph 0 is in the long metrics, glyph 1 in topSideBearings.
+        for (glyph_id, expected) in [(0, 0.0), (1, 98.0)] {
+            assert_eq!(
+                top_side_bearing_or_zero(&font_ref, 0.0, &coords, glyph_id),
+                expected
+            );
+            assert_eq!(
+                top_side_bearing_or_zero(&font_ref, 500.0, &coords, glyph_id),
+                expected / 2.0
+            );
+        }
+        assert_eq!(top_side_bearing_or_zero(&font_ref, 10.0, &coords, 2), 0.0);
+        assert_eq!(top_side_bearing_or_zero(&font_ref, -1.0, &coords, 1), 0.0);
+
+        let mut font_data = Vec::new();
+        assert!(extract_font_from_collection(
+            &file_buffer,
+            0,
+            &mut font_data
+        ));
+        let with_vvar = replace_table(
+            &font_data,
+            b"VVAR",
+            Some(build_vvar(1, &[0, 0], &[7, -8], &[])),
+        );
+        let font_ref = make_font_ref(&with_vvar, 0);
+        let heavy = resolve_into_normalized_coords(
+            &font_ref,
+            &[SkiaDesignCoordinate {
+                axis: u32::from_be_bytes(*b"wght"),
+                value: 900.0,
+            }],
+        );
+        assert_eq!(top_side_bearing_or_zero(&font_ref, 0.0, &heavy, 0), 7.0);
+        assert_eq!(top_side_bearing_or_zero(&font_ref, 0.0, &heavy, 1), 90.0);
+        assert_eq!(top_side_bearing_or_zero(&font_ref, 0.0, &coords, 1), 98.0);
+    }
+
+    #[test]
+    fn test_top_side_bearing_synthesized() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let typo_ascender = FontRef::new(&file_buffer)
+            .unwrap()
+            .os2()
+            .unwrap()
+            .s_typo_ascender();
+        let coords = BridgeNormalizedCoords::default();
+        let glyph_ids: Vec<u16> = (0..num_glyphs(&font_ref) + 2).collect();
+        let mut side_bearings = vec![-1.0; glyph_ids.len()];
+        assert_eq!(
+            top_side_bearings(&font_ref, 0.0, &coords, &glyph_ids, &mut side_bearings),
+            glyph_ids.len()
+        );
+        for (glyph_id, side_bearing) in glyph_ids.iter().zip(&side_bearings) {
+            let mut bounds = BridgePathBounds::default();
+            let expected = if glyph_bounds(&font_ref, 0.0, &coords, *glyph_id, &mut bounds) {
+                f32::from(typo_ascender) + bounds.y_min
+            } else {
+                0.0
+            };
+            assert_eq!(*side_bearing, expected, "glyph {glyph_id}");
+            assert_eq!(
+                top_side_bearing_or_zero(&font_ref, 0.0, &coords, *glyph_id),
+                expected
+            );
+        }
+        // 'A' reaches the cap height, below the ascender.
+        let glyph_id = lookup_glyph_or_zero(&font_ref, 'A' as u32);
+        let side_bearing = top_side_bearing_or_zero(&font_ref, 20.48, &coords, glyph_id);
+        assert!(side_bearing > 0.0);
+        assert!(
+            (side_bearing - side_bearings[usize::from(glyph_id)] / 100.0).abs() < 1e-4,
+            "{side_bearing}"
+        );
+    }
+
+    /// Builds a VORG table with `default_origin` and `(glyph ID, origin)`
+    /// entries.
+    fn build_vorg(default_origin: i16, entries: &[(u16, i16)]) -> Vec<u8> {
+        let mut vorg = Vec::new();
+        vorg.extend(1u16.to_be_bytes());
+        vorg.extend(0u16.to_be_bytes());
+        vorg.extend(default_origin.to_be_bytes());
+        vorg.extend((entries.len() as u16).to_be_bytes());
+        for (glyph_id, origin) in entries {
+            vorg.extend(glyph_id.to_be_bytes());
+            vorg.extend(origin.to_be_bytes());
+        }
+        vorg
+    }
+
+    #[test]
+    fn test_vertical_origin_y() {
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable collection could not be opened.");
+        let coords = BridgeNormalizedCoords::default();
+        let mut origin = -1.0;
+        // The fixture's VORG only has a default origin of 880.
+        let font_ref = make_font_ref(&file_buffer, 0);
+        for glyph_id in [0, 1] {
+            assert!(vertical_origin_y(
+                &font_ref,
+                0.0,
+                &coords,
+                glyph_id,
+                &mut origin
+            ));
+            assert_eq!(origin, 880.0);
+            assert!(vertical_origin_y(
+                &font_ref,
+                500.0,
+                &coords,
+                glyph_id,
+                &mut origin
+            ));
+            assert_eq!(origin, 440.0);
+        }
+        assert!(!vertical_origin_y(
+            &font_ref,
+            500.0,
+            &coords,
+            2,
+            &mut origin
+        ));
+        assert_eq!(origin, 0.0);
+        assert!(!vertical_origin_y(&font_ref, -1.0, &coords, 0, &mut origin));
+
+        let mut font_data = Vec::new();
+        assert!(extract_font_from_collection(
+            &file_buffer,
+            0,
+            &mut font_data
+        ));
+        let with_entries = replace_table(&font_data, b"VORG", Some(build_vorg(880, &[(1, 900)])));
+        let font_ref = make_font_ref(&with_entries, 0);
+        for (glyph_id, expected) in [(0, 880.0), (1, 900.0)] {
+            assert!(vertical_origin_y(
+                &font_ref,
+                0.0,
+                &coords,
+                glyph_id,
+                &mut origin
+            ));
+            assert_eq!(origin, expected);
+        }
+
+        let without_vorg = strip_table(&font_data, b"VORG");
+        let font_ref = make_font_ref(&without_vorg, 0);
+        origin = -1.0;
+        assert!(!vertical_origin_y(&font_ref, 0.0, &coords, 0, &mut origin));
+        assert_eq!(origin, 0.0);
+    }
+
+    #[test]
+    fn test_vertical_origin_y_lookup() {
+        let entries: Vec<(u16, i16)> = (0..50).map(|i| (i * 3 + 1, 800 + i as i16)).collect();
+        let vorg = build_vorg(880, &entries);
+        for glyph_id in 0..160 {
+            let expected = entries
+                .iter()
+                .find(|(entry_glyph_id, _)| *entry_glyph_id == glyph_id)
+                .map_or(880, |(_, origin)| *origin);
+            assert_eq!(
+                find_vert_origin_y(FontData::new(&vorg), glyph_id),
+                Some(expected)
+            );
+        }
+        assert_eq!(find_vert_origin_y(FontData::new(&vorg[..6]), 0), None);
+    }
+
+    #[test]
+    fn test_vertical_origin_y_variations() {
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable collection could not be opened.");
+        let mut font_data = Vec::new();
+        assert!(extract_font_from_collection(
+            &file_buffer,
+            0,
+            &mut font_data
+        ));
+        let with_vvar = replace_table(
+            &font_data,
+            b"VVAR",
+            Some(build_vvar(1, &[0, 0], &[], &[20, -30])),
+        );
+        let font_ref = make_font_ref(&with_vvar, 0);
+        let heavy = resolve_into_normalized_coords(
+            &font_ref,
+            &[SkiaDesignCoordinate {
+                axis: u32::from_be_bytes(*b"wght"),
+                value: 900.0,
+            }],
+        );
+        let mut origin = 0.0;
+        for (glyph_id, expected) in [(0, 900.0), (1, 850.0)] {
+            assert!(vertical_origin_y(
+                &font_ref,
+                0.0,
+                &heavy,
+                glyph_id,
+                &mut origin
+            ));
+            assert_eq!(origin, expected);
+        }
+        // Top side bearings are unaffected.
+        assert_eq!(top_side_bearing_or_zero(&font_ref, 0.0, &heavy, 1), 98.0);
+    }
+
+    #[test]
+    fn test_has_vertical_metrics() {
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable collection could not be opened.");
+        for index in 0..5 {
+            assert!(has_vertical_metrics(&make_font_ref(&file_buffer, index)));
+        }
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        assert!(!has_vertical_metrics(&make_font_ref(&file_buffer, 0)));
+
+        // The fixture has two glyphs and one long metric.
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable collection could not be opened.");
+        let mut font_data = Vec::new();
+        assert!(extract_font_from_collection(
+            &file_buffer,
+            0,
+            &mut font_data
+        ));
+        for num_long_metrics in [0, 3] {
+            let font_data = patch_table(&font_data, b"vhea", &[(34, num_long_metrics)]);
+            assert!(!has_vertical_metrics(&make_font_ref(&font_data, 0)));
+        }
+        let vmtx = FontRef::new(&font_data)
+            .unwrap()
+            .table_data(Tag::new(b"vmtx"))
+            .unwrap()
+            .as_bytes()
+            .to_vec();
+        let truncated = replace_table(&font_data, b"vmtx", Some(vmtx[..4].to_vec()));
+        assert!(!has_vertical_metrics(&make_font_ref(&truncated, 0)));
+        for tag in [b"vhea", b"vmtx"] {
+            let stripped = strip_table(&font_data, tag);
+            assert!(!has_vertical_metrics(&make_font_ref(&stripped, 0)));
+        }
+    }
+
+    #[test]
+    fn test_vertical_line_metrics() {
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable collection could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = BridgeNormalizedCoords::default();
+        let mut metrics = BridgeVerticalLineMetrics::default();
+        assert!(vertical_line_metrics(&font_ref, 0.0, &coords, &mut metrics));
+        assert_eq!(
+            metrics,
+            BridgeVerticalLineMetrics {
+                ascender: 500.0,
+                descender: -500.0,
+                line_gap: 0.0,
+            }
+        );
+        assert!(vertical_line_metrics(
+            &font_ref,
+            20.0,
+            &coords,
+            &mut metrics
+        ));
+        assert_eq!(
+            metrics,
+            BridgeVerticalLineMetrics {
+                ascender: 10.0,
+                descender: -10.0,
+                line_gap: 0.0,
+            }
+        );
+        assert!(!vertical_line_metrics(
+            &font_ref,
+            -1.0,
+            &coords,
+            &mut metrics
+        ));
+        assert_eq!(metrics, BridgeVerticalLineMetrics::default());
+
+        // MVAR deltas apply at non-default locations.
+        let mut font_data = Vec::new();
+        assert!(extract_font_from_collection(
+            &file_buffer,
+            0,
+            &mut font_data
+        ));
+        let with_mvar = replace_table(
+            &font_data,
+            b"MVAR",
+            Some(build_mvar(1, &[(b"vasc", 40), (b"vlgp", 100)])),
+        );
+        let font_ref = make_font_ref(&with_mvar, 0);
+        let heavy = resolve_into_normalized_coords(
+            &font_ref,
+            &[SkiaDesignCoordinate {
+                axis: u32::from_be_bytes(*b"wght"),
+                value: 900.0,
+            }],
+        );
+        assert!(vertical_line_metrics(&font_ref, 0.0, &heavy, &mut metrics));
+        assert_eq!(
+            metrics,
+            BridgeVerticalLineMetrics {
+                ascender: 540.0,
+                descender: -500.0,
+                line_gap: 100.0,
+            }
+        );
+
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        metrics.ascender = 1.0;
+        assert!(!vertical_line_metrics(
+            &font_ref,
+            0.0,
+            &coords,
+            &mut metrics
+        ));
+        assert_eq!(metrics, BridgeVerticalLineMetrics::default());
+    }
+
+    #[test]
+    fn test_vertical_glyph_metrics_synthesized() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let os2 = FontRef::new(&file_buffer).unwrap().os2().unwrap();
+        let ascent = f32::from(os2.s_typo_ascender());
+        let descent = f32::from(os2.s_typo_descender());
+        let coords = BridgeNormalizedCoords::default();
+        let mut metrics = BridgeVerticalGlyphMetrics::default();
+        for codepoint in ['A', 'i', ' '] {
+            let glyph_id = lookup_glyph_or_zero(&font_ref, codepoint as u32);
+            let advance_width =
+                advance_width_or_zero(&font_ref, 0.0, &coords, glyph_id, SizeRounding::Exact);
+            assert!(vertical_glyph_metrics(
+                &font_ref,
+                0.0,
+                &coords,
+                glyph_id,
+                &mut metrics
+            ));
+            assert_eq!(
+                metrics,
+                BridgeVerticalGlyphMetrics {
+                    origin_x: advance_width / 2.0,
+                    origin_y: ascent,
+                    advance: ascent - descent,
+                    is_synthesized: true,
+                }
+            );
+            assert!(vertical_glyph_metrics(
+                &font_ref,
+                204.8,
+                &coords,
+                glyph_id,
+                &mut metrics
+            ));
+            assert!((metrics.origin_y - ascent / 10.0).abs() < 1e-3);
+            assert!((metrics.advance - (ascent - descent) / 10.0).abs() < 1e-3);
+        }
+        assert!(!vertical_glyph_metrics(
+            &font_ref,
+            0.0,
+            &coords,
+            1000,
+            &mut metrics
+        ));
+        assert_eq!(metrics, BridgeVerticalGlyphMetrics::default());
+        assert!(!vertical_glyph_metrics(
+            &font_ref,
+            -1.0,
+            &coords,
+            1,
+            &mut metrics
+        ));
+    }
+
+    #[test]
+    fn test_vertical_glyph_metrics_from_tables() {
+        let file_buffer = fs::read(TEST_CJK_VARIABLE_COLLECTION)
+            .expect("CJK variable collection could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = BridgeNormalizedCoords::default();
+        let mut metrics = BridgeVerticalGlyphMetrics::default();
+        let glyph_id = 1;
+        let advance_width =
+            advance_width_or_zero(&font_ref, 0.0, &coords, glyph_id, SizeRounding::Exact);
+        assert!(vertical_glyph_metrics(
+            &font_ref,
+            0.0,
+            &coords,
+            glyph_id,
+            &mut metrics
+        ));
+        assert_eq!(
+            metrics,
+            BridgeVerticalGlyphMetrics {
+                origin_x: advance_width / 2.0,
+                origin_y: 880.0,
+                advance: 1000.0,
+                is_synthesized: false,
+            }
+        );
+        assert!(vertical_glyph_metrics(
+            &font_ref,
+            500.0,
+            &coords,
+            glyph_id,
+            &mut metrics
+        ));
+        assert_eq!((metrics.origin_y, metrics.advance), (440.0, 500.0));
+
+        // Without VORG the origin is the top of the outline plus its top
+        // side bearing.
+        let mut font_data = Vec::new();
+        assert!(extract_font_from_collection(
+            &file_buffer,
+            0,
+            &mut font_data
+        ));
+        let without_vorg = strip_table(&font_data, b"VORG");
+        let font_ref = make_font_ref(&without_vorg, 0);
+        let mut bounds = BridgePathBounds::default();
+        assert!(glyph_bounds(&font_ref, 0.0, &coords, glyph_id, &mut bounds));
+        assert!(vertical_glyph_metrics(
+            &font_ref,
+            0.0,
+            &coords,
+            glyph_id,
+            &mut metrics
+        ));
+        assert_eq!(metrics.origin_y, 98.0 - bounds.y_min);
+        assert_eq!(metrics.advance, 1000.0);
+        assert!(!metrics.is_synthesized);
+    }
+
+    /// Builds a BASE table with a horizontal axis listing `baseline_tags`,
+    /// and for each `(script tag, coordinates)` in `scripts` a format 1
+    /// coordinate per baseline tag.
+    fn build_base(baseline_tags: &[&[u8; 4]], scripts: &[(&[u8; 4], &[i16])]) -> Vec<u8> {
+        let mut base = Vec::new();
+        // Version 1.0, with the horizontal axis right after the header.
+        for value in [1u16, 0, 8, 0] {
+            base.extend(value.to_be_bytes());
+        }
+        let tag_list_len = 2 + 4 * baseline_tags.len();
+        base.extend(4u16.to_be_bytes());
+        base.extend((4 + tag_list_len as u16).to_be_bytes());
+        base.extend((baseline_tags.len() as u16).to_be_bytes());
+        for tag in baseline_tags {
+            base.extend(*tag);
+        }
+        let mut script_data = Vec::new();
+        base.extend((scripts.len() as u16).to_be_bytes());
+        let scripts_start = 2 + 6 * scripts.len();
+        for (tag, coords) in scripts {
+            base.extend(*tag);
+            base.extend(((scripts_start + script_data.len()) as u16).to_be_bytes());
+            // BaseScript with only BaseValues, then BaseValues with the
+            // coordinates after its offsets.
+            for value in [6u16, 0, 0, 0, coords.len() as u16] {
+                script_data.extend(value.to_be_bytes());
+            }
+            for index in 0..coords.len() {
+                script_data.extend(((4 + 2 * coords.len() + 4 * index) as u16).to_be_bytes());
+            }
+            for coord in coords.iter() {
+                script_data.extend(1u16.to_be_bytes());
+                script_data.extend(coord.to_be_bytes());
+            }
+        }
+        base.extend(script_data);
+        base
+    }
+
+    #[test]
+    fn test_ideographic_bottom() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Roboto test font could not be opened.");
+        let coords = BridgeNormalizedCoords::default();
+        let metrics = |font_data: &[u8], size| {
+            get_skia_metrics(
+                &make_font_ref(font_data, 0),
+                size,
+                &coords,
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact,
+            )
+        };
+
+        // Without BASE the descent is used.
+        for size in [0.0, 20.48] {
+            let without_base = metrics(&file_buffer, size);
+            assert_eq!(without_base.ideographic_bottom, without_base.descent);
+            assert!(without_base.ideographic_bottom_is_synthesized);
+        }
+
+        let with_base = replace_table(
+            &file_buffer,
+            b"BASE",
+            Some(build_base(
+                &[b"ideo", b"romn"],
+                &[
+                    (b"DFLT", &[-100, 0]),
+                    (b"hani", &[-120, 0]),
+                    (b"latn", &[-90, 0]),
+                ],
+            )),
+        );
+        let from_base = metrics(&with_base, 0.0);
+        assert_eq!(from_base.ideographic_bottom, -120.0);
+        assert!(!from_base.ideographic_bottom_is_synthesized);
+        assert_eq!(metrics(&with_base, 1024.0).ideographic_bottom, -60.0);
+        let fixed = get_skia_metrics(
+            &make_font_ref(&with_base, 0),
+            20.48,
+            &coords,
+            HintingLevel::None,
+            false,
+            SizeRounding::Fixed26Dot6,
+        );
+        assert_eq!((fixed.ideographic_bottom * 64.0).fract(), 0.0);
+
+        // Scripts without a value for 'ideo' are skipped, a BASE table
+        // without 'ideo' falls back to the descent.
+        for (font_data, expected) in [
+            (
+                replace_table(
+                    &file_buffer,
+                    b"BASE",
+                    Some(build_base(&[b"romn", b"ideo"], &[(b"latn", &[0, -80])])),
+                ),
+                Some(-80.0),
+            ),
+            (
+                replace_table(
+                    &file_buffer,
+                    b"BASE",
+                    Some(build_base(&[b"romn", b"ideo"], &[(b"DFLT", &[0])])),
+                ),
+                None,
+            ),
+            (
+                replace_table(
+                    &file_buffer,
+                    b"BASE",
+                    Some(build_base(&[b"romn"], &[(b"DFLT", &[0])])),
+                ),
+                None,
+            ),
+        ] {
+            let metrics = metrics(&font_data, 0.0);
+            assert_eq!(
+                (!metrics.ideographic_bottom_is_synthesized).then_some(metrics.ideographic_bottom),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_metrics_mvar() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let with_mvar = replace_table(
+            &file_buffer,
+            b"MVAR",
+            Some(build_mvar(
+                2,
+                &[
+                    (b"hasc", 100),
+                    (b"hdsc", -50),
+                    (b"hlgp", 30),
+                    (b"xhgt", 40),
+                    (b"cpht", 60),
+                    (b"undo", -20),
+                    (b"unds", 10),
+                    (b"stro", 25),
+                    (b"strs", 15),
+                ],
+            )),
+        );
+        let font_ref = make_font_ref(&with_mvar, 0);
+        let metrics_at = |weight| {
+            let coords = resolve_into_normalized_coords(
+                &font_ref,
+                &[SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(*b"wght"),
+                    value: weight,
+                }],
+            );
+            get_skia_metrics(
+                &font_ref,
+                0.0,
+                &coords,
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact,
+            )
+        };
+        let light = metrics_at(100.0);
+        assert_eq!(
+            light,
+            get_skia_metrics(
+                &make_font_ref(&file_buffer, 0),
+                0.0,
+                &BridgeNormalizedCoords::default(),
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact,
+            )
+        );
+        let heavy = metrics_at(900.0);
+        // Variable.ttf sets USE_TYPO_METRICS, the typo metrics share the
+        // hhea metric tags.
+        assert_eq!(heavy.line_metrics_source, LineMetricsSource::Typo);
+        for (name, light, heavy, delta) in [
+            ("ascent", light.ascent, heavy.ascent, 100.0),
+            ("descent", light.descent, heavy.descent, -50.0),
+            ("leading", light.leading, heavy.leading, 30.0),
+            ("x_height", light.x_height, heavy.x_height, 40.0),
+            ("cap_height", light.cap_height, heavy.cap_height, 60.0),
+            (
+                "underline_position",
+                light.underline_position,
+                heavy.underline_position,
+                -20.0,
+            ),
+            (
+                "underline_thickness",
+                light.underline_thickness,
+                heavy.underline_thickness,
+                10.0,
+            ),
+            (
+                "strikeout_position",
+                light.strikeout_position,
+                heavy.strikeout_position,
+                25.0,
+            ),
+            (
+                "strikeout_thickness",
+                light.strikeout_thickness,
+                heavy.strikeout_thickness,
+                15.0,
+            ),
+        ] {
+            assert_eq!(heavy - light, delta, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_win_metrics_mvar() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        // Clear USE_TYPO_METRICS and zero the hhea and typo metrics so that
+        // the win metrics are used.
+        let fs_selection = FontRef::new(&file_buffer)
+            .unwrap()
+            .os2()
+            .unwrap()
+            .fs_selection()
+            .bits();
+        let win_only = patch_table(
+            &patch_table(&file_buffer, b"hhea", &[(4, 0), (6, 0)]),
+            b"OS/2",
+            &[(62, (fs_selection & !0x80) as i16), (68, 0), (70, 0)],
+        );
+        let win_only = replace_table(
+            &win_only,
+            b"MVAR",
+            Some(build_mvar(
+                2,
+                &[
+                    (b"hasc", 1000),
+                    (b"hdsc", -1000),
+                    (b"hcla", 70),
+                    (b"hcld", 30),
+                ],
+            )),
+        );
+        let font_ref = make_font_ref(&win_only, 0);
+        let metrics_at = |weight| {
+            let coords = resolve_into_normalized_coords(
+                &font_ref,
+                &[SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(*b"wght"),
+                    value: weight,
+                }],
+            );
+            get_skia_metrics(
+                &font_ref,
+                0.0,
+                &coords,
+                HintingLevel::None,
+                false,
+                SizeRounding::Exact,
+            )
+        };
+        let light = metrics_at(100.0);
+        let heavy = metrics_at(900.0);
+        assert_eq!(light.line_metrics_source, LineMetricsSource::Win);
+        assert_eq!(heavy.ascent - light.ascent, 70.0);
+        assert_eq!(heavy.descent - light.descent, -30.0);
+    }
+
+    /// Unscaled advances of the first glyphs of Variable.ttf with HVAR
+    /// deltas of `10 * glyph_id + 5` at the maximum weight, as FreeType's
+    /// `FT_Get_Advance` reports them at the listed (wght, wdth) design
+    /// coordinates. HVAR replaces gvar for advances, so width has no effect.
+    const VARIABLE_HVAR_FREETYPE: [((f32, f32), [f32; 8]); 3] = [
+        (
+            (900.0, 50.0),
+            [753.0, 15.0, 707.0, 1245.0, 1160.0, 1140.0, 438.0, 448.0],
+        ),
+        (
+            (650.0, 100.0),
+            [751.0, 8.0, 695.0, 1228.0, 1138.0, 1113.0, 406.0, 411.0],
+        ),
+        (
+            (250.0, 150.0),
+            [748.0, 0.0, 682.0, 1210.0, 1115.0, 1085.0, 373.0, 373.0],
+        ),
+    ];
+
+    /// Unscaled advances of the first glyphs of Variable.ttf, which has no
+    /// HVAR, as FreeType derives them from the gvar phantom points.
+    const VARIABLE_GVAR_FREETYPE: [((f32, f32), [f32; 8]); 3] = [
+        (
+            (900.0, 50.0),
+            [748.0, 0.0, 682.0, 1110.0, 1015.0, 985.0, 368.0, 368.0],
+        ),
+        (
+            (650.0, 100.0),
+            [748.0, 0.0, 682.0, 1210.0, 1115.0, 1085.0, 401.0, 401.0],
+        ),
+        (
+            (250.0, 150.0),
+            [748.0, 0.0, 682.0, 1260.0, 1165.0, 1135.0, 380.0, 380.0],
+        ),
+    ];
+
+    fn variable_design_coords(
+        font_ref: &BridgeFontRef,
+        (weight, width): (f32, f32),
+    ) -> Box<BridgeNormalizedCoords> {
+        resolve_into_normalized_coords(
+            font_ref,
+            &[
+                SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(*b"wght"),
+                    value: weight,
+                },
+                SkiaDesignCoordinate {
+                    axis: u32::from_be_bytes(*b"wdth"),
+                    value: width,
+                },
+            ],
+        )
+    }
+
+    /// Checks that the single, batched and glyph metrics advances of the
+    /// first glyphs all match `expected` in font units.
+    fn assert_unscaled_advances(
+        font_ref: &BridgeFontRef,
+        coords: &BridgeNormalizedCoords,
+        expected: &[f32],
+        context: &str,
+    ) {
+        let glyph_ids: Vec<u16> = (0..expected.len() as u16).collect();
+        let mut batched = vec![0.0; expected.len()];
+        assert_eq!(
+            advance_widths(
+                font_ref,
+                0.0,
+                coords,
+                &glyph_ids,
+                &mut batched,
+                SizeRounding::Exact
+            ),
+            expected.len()
+        );
+        for (glyph_id, expected) in glyph_ids.iter().copied().zip(expected) {
+            let single =
+                advance_width_or_zero(font_ref, 0.0, coords, glyph_id, SizeRounding::Exact);
+            let mut metrics = BridgeGlyphMetrics::default();
+            assert!(glyph_metrics(font_ref, 0.0, coords, glyph_id, &mut metrics));
+            assert_eq!(single, *expected, "{context} glyph {glyph_id}");
+            assert_eq!(
+                batched[glyph_id as usize], *expected,
+                "{context} glyph {glyph_id}"
+            );
+            assert_eq!(
+                metrics.advance_width, *expected,
+                "{context} glyph {glyph_id}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_hvar_advances_match_freetype() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let num_glyphs = num_glyphs(&make_font_ref(&file_buffer, 0)) as usize;
+        let advance_deltas: Vec<Option<i16>> = (0..num_glyphs as i16)
+            .map(|glyph_id| Some(10 * glyph_id + 5))
+            .collect();
+        let with_hvar = replace_table(
+            &file_buffer,
+            b"HVAR",
+            Some(build_hvar(2, &advance_deltas, &vec![0; num_glyphs])),
+        );
+        let font_ref = make_font_ref(&with_hvar, 0);
+        for (design_coords, expected) in VARIABLE_HVAR_FREETYPE {
+            let coords = variable_design_coords(&font_ref, design_coords);
+            assert_unscaled_advances(&font_ref, &coords, &expected, &format!("{design_coords:?}"));
+        }
+    }
+
+    #[test]
+    fn test_gvar_advances_match_freetype() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(font_ref.with_font(|f| Some(f.hvar().is_err())).unwrap());
+        for (design_coords, expected) in VARIABLE_GVAR_FREETYPE {
+            let coords = variable_design_coords(&font_ref, design_coords);
+            assert_unscaled_advances(&font_ref, &coords, &expected, &format!("{design_coords:?}"));
+            // Scaled advances follow the same phantom points, to within the
+            // 26.6 precision of the metrics scale.
+            let scaled = advance_width_or_zero(&font_ref, 20.48, &coords, 3, SizeRounding::Exact);
+            assert!(
+                (scaled - expected[3] / 100.0).abs() < 1.0 / 64.0,
+                "{design_coords:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_partial_hvar_advances_fall_back_to_gvar() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let num_glyphs = num_glyphs(&make_font_ref(&file_buffer, 0)) as usize;
+        // Glyphs 3 and 5 map to an item the variation store lacks.
+        let advance_deltas: Vec<Option<i16>> = (0..num_glyphs as i16)
+            .map(|glyph_id| (glyph_id != 3 && glyph_id != 5).then_some(10 * glyph_id + 5))
+            .collect();
+        let partial_hvar = replace_table(
+            &file_buffer,
+            b"HVAR",
+            Some(build_hvar(2, &advance_deltas, &vec![0; num_glyphs])),
+        );
+        let font_ref = make_font_ref(&partial_hvar, 0);
+        for ((design_coords, with_hvar), (_, with_gvar)) in
+            VARIABLE_HVAR_FREETYPE.iter().zip(VARIABLE_GVAR_FREETYPE)
+        {
+            let coords = variable_design_coords(&font_ref, *design_coords);
+            let mut expected = *with_hvar;
+            expected[3] = with_gvar[3];
+            expected[5] = with_gvar[5];
+            assert_unscaled_advances(&font_ref, &coords, &expected, &format!("{design_coords:?}"));
+        }
+    }
+
+    #[test]
+    fn test_static_advances_match_freetype() {
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test advances could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let expected = [
+            908.0, 0.0, 0.0, 507.0, 507.0, 527.0, 655.0, 1261.0, 1150.0, 1500.0, 1273.0, 357.0,
+        ];
+        assert_unscaled_advances(
+            &font_ref,
+            &BridgeNormalizedCoords::default(),
+            &expected,
+            "default",
+        );
+        // Coordinates mean nothing to a font without variations.
+        let coords = variable_design_coords(&font_ref, (900.0, 50.0));
+        assert_unscaled_advances(&font_ref, &coords, &expected, "wght 900");
+    }
+
+    /// Horizontal bearings of the first glyphs of Variable.ttf at wght 900,
+    /// wdth 50 as reported by FreeType. Glyphs 1 and 2 are empty.
+    const VARIABLE_GVAR_BEARINGS_FREETYPE: [f32; 8] = [68.0, 0.0, 0.0, 51.0, 165.0, 78.0, 0.0, 0.0];
+
+    #[test]
+    fn test_phantom_point_side_bearings() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let num_glyphs = num_glyphs(&make_font_ref(&file_buffer, 0)) as usize;
+        // An HVAR table without side bearing mappings leaves them to gvar,
+        // like having no HVAR table at all.
+        let advance_deltas = vec![Some(0); num_glyphs];
+        let mut hvar = build_hvar(2, &advance_deltas, &vec![0; num_glyphs]);
+        hvar[12..16].fill(0);
+        let without_lsb_mapping = replace_table(&file_buffer, b"HVAR", Some(hvar));
+        for font_data in [&file_buffer, &without_lsb_mapping] {
+            let font_ref = make_font_ref(font_data, 0);
+            let default_coords = BridgeNormalizedCoords::default();
+            let coords = variable_design_coords(&font_ref, (900.0, 50.0));
+            let mut num_varied = 0;
+            for glyph_id in 0..num_glyphs as u16 {
+                assert_eq!(
+                    left_side_bearing_source(&font_ref, &default_coords, glyph_id),
+                    SideBearingSource::Hmtx
+                );
+                assert_eq!(
+                    left_side_bearing_source(&font_ref, &coords, glyph_id),
+                    SideBearingSource::PhantomPoints
+                );
+                let mut metrics = BridgeGlyphMetrics::default();
+                assert!(glyph_metrics(
+                    &font_ref,
+                    0.0,
+                    &coords,
+                    glyph_id,
+                    &mut metrics
+                ));
+                let lsb = left_side_bearing_or_zero(&font_ref, 0.0, &coords, glyph_id);
+                assert_eq!(metrics.left_side_bearing, lsb);
+                // The side bearing is the left edge of the varied outline,
+                // which FreeType reports as the horizontal bearing.
+                if !metrics.bounds.is_empty {
+                    assert_eq!(lsb, metrics.bounds.x_min, "glyph {glyph_id}");
+                    if let Some(expected) = VARIABLE_GVAR_BEARINGS_FREETYPE.get(glyph_id as usize) {
+                        assert_eq!(lsb, *expected, "glyph {glyph_id}");
+                    }
+                }
+                if lsb != left_side_bearing_or_zero(&font_ref, 0.0, &default_coords, glyph_id) {
+                    num_varied += 1;
+                }
+            }
+            assert!(num_varied > 0);
+        }
+    }
+
+    #[test]
+    fn test_left_side_bearing_source() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        let num_glyphs = num_glyphs(&make_font_ref(&file_buffer, 0)) as usize;
+        let mut advance_deltas = vec![Some(0); num_glyphs];
+        advance_deltas[3] = None;
+        let with_hvar = replace_table(
+            &file_buffer,
+            b"HVAR",
+            Some(build_hvar(2, &advance_deltas, &vec![10; num_glyphs])),
+        );
+        let font_ref = make_font_ref(&with_hvar, 0);
+        let coords = variable_design_coords(&font_ref, (900.0, 50.0));
+        // Side bearings follow HVAR independently of the advances.
+        assert_eq!(
+            left_side_bearing_source(&font_ref, &coords, 3),
+            SideBearingSource::Hvar
+        );
+        assert_eq!(
+            left_side_bearing_source(&font_ref, &coords, num_glyphs as u16),
+            SideBearingSource::Hmtx
+        );
+
+        // Static fonts only have hmtx.
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test side bearings could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert_eq!(
+            left_side_bearing_source(&font_ref, &coords, 3),
+            SideBearingSource::Hmtx
+        );
+
+        // CFF2 fonts have no phantom points to fall back to, and the HVAR
+        // table of the test font has no side bearing mapping.
+        let file_buffer =
+            fs::read(TEST_CJK_VARIABLE_COLLECTION).expect("CJK test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let coords = resolve_into_normalized_coords(
+            &font_ref,
+            &[SkiaDesignCoordinate {
+                axis: u32::from_be_bytes(*b"wght"),
+                value: 900.0,
+            }],
+        );
+        assert_eq!(
+            left_side_bearing_source(&font_ref, &coords, 1),
+            SideBearingSource::Hmtx
+        );
+    }
+
+    fn style_of(font_data: &[u8]) -> Option<(i32, i32, i32)> {
+        let mut style = BridgeFontStyle::default();
+        font_style(&make_font_ref(font_data, 0), &mut style).then_some((
+            style.weight,
+            style.width,
+            style.slant,
+        ))
+    }
+
+    #[test]
+    fn test_font_style() {
+        let regular = fs::read(TEST_ROBOTO).expect("Font to test font styles could not be opened.");
+        assert_eq!(style_of(&regular), Some((400, 5, 0)));
+        let bold_italic = fs::read(TEST_CONDENSED_BOLD_ITALIC)
+            .expect("Font to test font styles could not be opened.");
+        assert_eq!(style_of(&bold_italic), Some((700, 5, 1)));
+
+        // OS/2 usWeightClass, usWidthClass and fsSelection.
+        let condensed = patch_table(&regular, b"OS/2", &[(6, 3)]);
+        assert_eq!(style_of(&condensed), Some((400, 3, 0)));
+        let out_of_range = patch_table(&regular, b"OS/2", &[(4, 1200), (6, 12)]);
+        assert_eq!(style_of(&out_of_range), Some((1000, 9, 0)));
+        let invalid_width = patch_table(&regular, b"OS/2", &[(6, 0)]);
+        assert_eq!(style_of(&invalid_width), Some((400, 5, 0)));
+        // Weights on the 1 to 9 scale of some legacy fonts.
+        let legacy_weight = patch_table(&regular, b"OS/2", &[(4, 7)]);
+        assert_eq!(style_of(&legacy_weight), Some((700, 5, 0)));
+        // Version 4 has the same layout as the version 3 table of the font.
+        let oblique = patch_table(&regular, b"OS/2", &[(0, 4), (62, 0x0201)]);
+        assert_eq!(style_of(&oblique), Some((400, 5, 2)));
+        let italic = patch_table(&regular, b"OS/2", &[(62, 0x0001)]);
+        assert_eq!(style_of(&italic), Some((400, 5, 1)));
+        // OBLIQUE is ignored before version 4.
+        let oblique_v3 = patch_table(&oblique, b"OS/2", &[(0, 3)]);
+        assert_eq!(style_of(&oblique_v3), Some((400, 5, 1)));
+
+        // Without OS/2, weight and slant follow head.macStyle.
+        let without_os2 = strip_table(&bold_italic, b"OS/2");
+        assert_eq!(style_of(&without_os2), Some((700, 5, 1)));
+        let regular_without_os2 = strip_table(&regular, b"OS/2");
+        assert_eq!(style_of(&regular_without_os2), Some((400, 5, 0)));
+        let italic_mac_style = patch_table(&regular_without_os2, b"head", &[(44, 0x0002)]);
+        assert_eq!(style_of(&italic_mac_style), Some((400, 5, 1)));
+
+        let without_tables = strip_table(&without_os2, b"head");
+        assert_eq!(style_of(&without_tables), None);
+        assert_eq!(style_of(b"not a font"), None);
+    }
+
+    fn variable_style_at(font_data: &[u8], design_coords: &[(&[u8; 4], f32)]) -> (i32, i32, i32) {
+        let font_ref = make_font_ref(font_data, 0);
+        let design_coords: Vec<SkiaDesignCoordinate> = design_coords
+            .iter()
+            .map(|(axis, value)| SkiaDesignCoordinate {
+                axis: u32::from_be_bytes(**axis),
+                value: *value,
+            })
+            .collect();
+        let coords = resolve_into_normalized_coords(&font_ref, &design_coords);
+        let mut style = BridgeFontStyle::default();
+        assert!(variable_font_style(&font_ref, &coords, &mut style));
+        (style.weight, style.width, style.slant)
+    }
+
+    /// Rebuilds the Variable.ttf `font_data` with its second axis, wdth,
+    /// replaced by `tag` ranging over the integers `min`, `default`, `max`.
+    fn replace_second_axis(
+        font_data: &[u8],
+        tag: &[u8; 4],
+        (min, default, max): (i16, i16, i16),
+    ) -> Vec<u8> {
+        // The axis records start at offset 16 and are 20 bytes long.
+        let tag_halves = [
+            i16::from_be_bytes([tag[0], tag[1]]),
+            i16::from_be_bytes([tag[2], tag[3]]),
+        ];
+        patch_table(
+            font_data,
+            b"fvar",
+            &[
+                (36, tag_halves[0]),
+                (38, tag_halves[1]),
+                (40, min),
+                (44, default),
+                (48, max),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_width_from_percentage() {
+        for (width, percentage) in (1..=9).zip(WIDTH_PERCENTAGES) {
+            assert_eq!(width_from_percentage(percentage), width);
+        }
+        assert_eq!(width_from_percentage(10.0), 1);
+        assert_eq!(width_from_percentage(80.0), 3);
+        assert_eq!(width_from_percentage(82.0), 4);
+        assert_eq!(width_from_percentage(175.0), 9);
+        assert_eq!(width_from_percentage(174.0), 8);
+        assert_eq!(width_from_percentage(1000.0), 9);
+    }
+
+    #[test]
+    fn test_variable_font_style() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        assert_eq!(variable_style_at(&file_buffer, &[]), (400, 5, 0));
+        for ((weight, percentage), expected) in [
+            ((650.0, 80.0), (650, 3, 0)),
+            ((100.0, 50.0), (100, 1, 0)),
+            ((900.0, 200.0), (900, 9, 0)),
+            ((400.0, 112.5), (400, 6, 0)),
+            ((249.6, 150.0), (250, 8, 0)),
+        ] {
+            assert_eq!(
+                variable_style_at(&file_buffer, &[(b"wght", weight), (b"wdth", percentage)]),
+                expected,
+                "wght {weight} wdth {percentage}"
+            );
+        }
+        // The style of static fonts does not depend on coordinates.
+        let file_buffer = fs::read(TEST_CONDENSED_BOLD_ITALIC)
+            .expect("Font to test font styles could not be opened.");
+        assert_eq!(
+            variable_style_at(&file_buffer, &[(b"wght", 100.0)]),
+            (700, 5, 1)
+        );
+    }
+
+    #[test]
+    fn test_variable_font_slant() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Variable test font could not be opened.");
+        // slnt is specified as counter-clockwise degrees, so leaning forward
+        // is negative, but some fonts lean forward with positive angles.
+        let backward_slnt = replace_second_axis(&file_buffer, b"slnt", (-20, 0, 0));
+        let forward_slnt = replace_second_axis(&file_buffer, b"slnt", (0, 0, 20));
+        for (font_data, angle) in [(&backward_slnt, -10.0), (&forward_slnt, 10.0)] {
+            assert_eq!(variable_style_at(font_data, &[]), (400, 5, 0));
+            assert_eq!(
+                variable_style_at(font_data, &[(b"wght", 650.0), (b"slnt", angle)]),
+                (650, 5, 2),
+                "slnt {angle}"
+            );
+        }
+
+        let ital = replace_second_axis(&file_buffer, b"ital", (0, 0, 1));
+        assert_eq!(variable_style_at(&ital, &[]), (400, 5, 0));
+        assert_eq!(variable_style_at(&ital, &[(b"ital", 1.0)]), (400, 5, 1));
+        // Axes that are present decide the slant over fsSelection.
+        let italic_selection = patch_table(&ital, b"OS/2", &[(62, 0x0001)]);
+        assert_eq!(variable_style_at(&italic_selection, &[]), (400, 5, 0));
+        assert_eq!(
+            variable_style_at(&italic_selection, &[(b"ital", 1.0)]),
+            (400, 5, 1)
+        );
+        // Without slant axes, fsSelection decides.
+        let italic_selection = patch_table(&file_buffer, b"OS/2", &[(62, 0x0001)]);
+        assert_eq!(
+            variable_style_at(&italic_selection, &[(b"wght", 900.0)]),
+            (900, 5, 1)
+        );
+    }
+
+    #[test]
+    fn test_embedding_permissions() {
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Font to test fsType could not be opened.");
+        let with_fs_type =
+            |fs_type: u16| patch_table(&file_buffer, b"OS/2", &[(8, fs_type as i16)]);
+        for (fs_type, embeddable, subsettable) in [
+            // Installable.
+            (0x0000, true, true),
+            (FS_TYPE_RESTRICTED_LICENSE, false, true),
+            (FS_TYPE_PREVIEW_AND_PRINT, true, true),
+            (FS_TYPE_EDITABLE, true, true),
+            (FS_TYPE_NO_SUBSETTING, true, false),
+            (FS_TYPE_BITMAP_ONLY, false, true),
+            (FS_TYPE_EDITABLE | FS_TYPE_NO_SUBSETTING, true, false),
+            (
+                FS_TYPE_RESTRICTED_LICENSE | FS_TYPE_NO_SUBSETTING,
+                false,
+                false,
+            ),
+            // Legacy fonts setting several permissions get the least
+            // restrictive one.
+            (
+                FS_TYPE_RESTRICTED_LICENSE | FS_TYPE_PREVIEW_AND_PRINT,
+                true,
+                true,
+            ),
+            (FS_TYPE_RESTRICTED_LICENSE | FS_TYPE_EDITABLE, true, true),
+            (
+                FS_TYPE_RESTRICTED_LICENSE | FS_TYPE_BITMAP_ONLY,
+                false,
+                true,
+            ),
+            (FS_TYPE_EDITABLE | FS_TYPE_BITMAP_ONLY, false, true),
+        ] {
+            let font_data = with_fs_type(fs_type);
+            let font_ref = make_font_ref(&font_data, 0);
+            assert_eq!(embedding_flags(&font_ref), fs_type);
+            assert_eq!(can_embed(&font_ref), embeddable, "fsType {fs_type:#06x}");
+            assert_eq!(can_subset(&font_ref), subsettable, "fsType {fs_type:#06x}");
+        }
+
+        // Fonts without OS/2 are installable.
+        let font_data = strip_table(&with_fs_type(FS_TYPE_RESTRICTED_LICENSE), b"OS/2");
+        let font_ref = make_font_ref(&font_data, 0);
+        assert_eq!(embedding_flags(&font_ref), 0);
+        assert!(can_embed(&font_ref));
+        assert!(can_subset(&font_ref));
+    }
+
+    fn panose_of(font_data: &[u8]) -> Option<[u8; 10]> {
+        let mut out = [0xFF; 10];
+        let has_panose = panose(&make_font_ref(font_data, 0), &mut out);
+        assert!(has_panose || out == [0; 10]);
+        has_panose.then_some(out)
+    }
+
+    #[test]
+    fn test_panose() {
+        let sans = fs::read(TEST_ROBOTO).expect("Font to test PANOSE could not be opened.");
+        assert_eq!(panose_of(&sans), Some([2, 0, 0, 0, 0, 0, 0, 0, 0, 0]));
+        let cjk_sans =
+            fs::read(TEST_CJK_VARIABLE_COLLECTION).expect("CJK test font could not be opened.");
+        // Normal sans serif style.
+        assert_eq!(panose_of(&cjk_sans), Some([2, 11, 2, 0, 0, 0, 0, 0, 0, 0]));
+        let monospace =
+            fs::read("resources/fonts/Em.ttf").expect("Monospace test font could not be opened.");
+        assert!(is_fixed_pitch(&make_font_ref(&monospace, 0)));
+        // Monospaced proportion.
+        assert_eq!(panose_of(&monospace), Some([2, 0, 5, 9, 0, 0, 0, 0, 0, 0]));
+        for font_data in [&sans, &cjk_sans, &monospace] {
+            let font_ref = make_font_ref(font_data, 0);
+            assert!(!is_serif(&font_ref));
+            assert!(!is_script(&font_ref));
+        }
+
+        assert_eq!(panose_of(&strip_table(&sans, b"OS/2")), None);
+        assert_eq!(panose_of(b"not a font"), None);
+    }
+
+    #[test]
+    fn test_serif_and_script_classification() {
+        let sans = fs::read(TEST_ROBOTO).expect("Font to test PANOSE could not be opened.");
+        let classify = |font_data: &[u8]| {
+            let font_ref = make_font_ref(font_data, 0);
+            (is_serif(&font_ref), is_script(&font_ref))
+        };
+        // The PANOSE of Times New Roman, with cove serifs.
+        let serif = patch_table(
+            &sans,
+            b"OS/2",
+            &[
+                (32, 0x0202),
+                (34, 0x0603),
+                (36, 0x0504),
+                (38, 0x0502),
+                (40, 0x0304),
+            ],
+        );
+        assert_eq!(panose_of(&serif), Some([2, 2, 6, 3, 5, 4, 5, 2, 3, 4]));
+        assert_eq!(classify(&serif), (true, false));
+        // Sans serif styles of text fonts.
+        for serif_style in [0, 1, 11, 12, 13] {
+            let font_data = patch_table(&sans, b"OS/2", &[(32, 0x0200 | serif_style)]);
+            assert_eq!(
+                classify(&font_data),
+                (false, false),
+                "serif style {serif_style}"
+            );
+        }
+        // Serif styles only apply to text fonts.
+        let decorative = patch_table(&sans, b"OS/2", &[(32, 0x0402)]);
+        assert_eq!(classify(&decorative), (false, false));
+        let script = patch_table(&sans, b"OS/2", &[(32, 0x0302)]);
+        assert_eq!(classify(&script), (false, true));
+
+        // A PCLT table decides over PANOSE, masking the upper serifStyle bits.
+        let with_pclt = |font_data: &[u8], serif_style: u8| {
+            let mut pclt = vec![0; 54];
+            pclt[0..4].copy_from_slice(&0x00010000u32.to_be_bytes());
+            pclt[52] = serif_style;
+            replace_table(font_data, b"PCLT", Some(pclt))
+        };
+        assert_eq!(classify(&with_pclt(&sans, 0x40 | 3)), (true, false));
+        assert_eq!(classify(&with_pclt(&serif, 0x40 | 10)), (false, true));
+        assert_eq!(classify(&with_pclt(&serif, 0x40)), (false, false));
+        assert_eq!(classify(&with_pclt(&script, 7)), (false, false));
+
+        assert_eq!(classify(&strip_table(&serif, b"OS/2")), (false, false));
+    }
+
+    #[test]
+    fn test_range_bit_is_set() {
+        let ranges = [0x8000_0001, 0x0000_0040];
+        assert!(range_bit_is_set(&ranges, 0));
+        assert!(!range_bit_is_set(&ranges, 1));
+        assert!(range_bit_is_set(&ranges, 31));
+        assert!(!range_bit_is_set(&ranges, 32));
+        assert!(range_bit_is_set(&ranges, 38));
+        assert!(!range_bit_is_set(&ranges, 64));
+        assert!(!range_bit_is_set(&ranges, u32::MAX));
+        assert!(!range_bit_is_set(&[], 0));
+    }
+
+    #[test]
+    fn test_unicode_and_codepage_ranges() {
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test OS/2 ranges could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let mut unicode_ranges = [0; 4];
+        assert!(unicode_range_bits(&font_ref, &mut unicode_ranges));
+        assert_eq!(unicode_ranges, [0x8000_0027, 0x0000_0043, 0, 0]);
+        let mut codepage_ranges = [0; 2];
+        assert!(codepage_range_bits(&font_ref, &mut codepage_ranges));
+        assert_eq!(codepage_ranges, [0x2000_019F, 0]);
+
+        // Basic Latin, Latin-1 Supplement, Latin Extended-A, Greek, General
+        // Punctuation, Superscripts and Subscripts, Currency Symbols and
+        // Mathematical Operators.
+        let unicode_bits = [0, 1, 2, 5, 31, 32, 33, 38];
+        for index in 0..128 {
+            assert_eq!(
+                has_unicode_range(&font_ref, index),
+                unicode_bits.contains(&index),
+                "Unicode range {index}"
+            );
+        }
+        // Latin 1, Latin 2, Cyrillic, Greek, Turkish, Baltic, Vietnamese and
+        // Macintosh Character Set.
+        let codepage_bits = [0, 1, 2, 3, 4, 7, 8, 29];
+        for index in 0..64 {
+            assert_eq!(
+                has_codepage_range(&font_ref, index),
+                codepage_bits.contains(&index),
+                "code page range {index}"
+            );
+        }
+        assert!(!has_unicode_range(&font_ref, 128));
+        assert!(!has_codepage_range(&font_ref, 64));
+
+        // Version 0 has Unicode but no code page ranges.
+        let version_0 = patch_table(&file_buffer, b"OS/2", &[(0, 0)]);
+        let font_ref = make_font_ref(&version_0, 0);
+        assert!(unicode_range_bits(&font_ref, &mut unicode_ranges));
+        assert_eq!(unicode_ranges, [0x8000_0027, 0x0000_0043, 0, 0]);
+        assert!(has_unicode_range(&font_ref, 31));
+        codepage_ranges = [1, 1];
+        assert!(!codepage_range_bits(&font_ref, &mut codepage_ranges));
+        assert_eq!(codepage_ranges, [0, 0]);
+        assert!(!has_codepage_range(&font_ref, 0));
+
+        let without_os2 = strip_table(&file_buffer, b"OS/2");
+        let font_ref = make_font_ref(&without_os2, 0);
+        unicode_ranges = [1; 4];
+        assert!(!unicode_range_bits(&font_ref, &mut unicode_ranges));
+        assert_eq!(unicode_ranges, [0; 4]);
+        assert!(!codepage_range_bits(&font_ref, &mut codepage_ranges));
+        assert!(!has_unicode_range(&font_ref, 0));
     }
 
-    struct BridgeScalerMetrics {
-        has_overlaps: bool,
+    #[test]
+    fn test_head_style_and_flags() {
+        let file_buffer = fs::read(TEST_CONDENSED_BOLD_ITALIC)
+            .expect("Font to test head fields could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let (mut mac_style, mut flags) = (0, 0);
+        assert!(head_style_and_flags(&font_ref, &mut mac_style, &mut flags));
+        // Bold and italic.
+        assert_eq!(mac_style, 0x0003);
+        // Baseline and left sidebearing at 0, integer ppem scaling and
+        // instructions that may alter advances.
+        assert_eq!(flags, 0x001B);
+        assert_eq!(lowest_rec_ppem_or_zero(&font_ref), 9);
+
+        // ClearType optimized and last resort font bits.
+        let patched = patch_table(
+            &file_buffer,
+            b"head",
+            &[(16, 0x601B), (44, 0x0002), (46, 6)],
+        );
+        let font_ref = make_font_ref(&patched, 0);
+        assert!(head_style_and_flags(&font_ref, &mut mac_style, &mut flags));
+        assert_eq!((mac_style, flags), (0x0002, 0x601B));
+        assert_eq!(lowest_rec_ppem_or_zero(&font_ref), 6);
+
+        let without_head = strip_table(&file_buffer, b"head");
+        let font_ref = make_font_ref(&without_head, 0);
+        assert!(!head_style_and_flags(&font_ref, &mut mac_style, &mut flags));
+        assert_eq!((mac_style, flags), (0, 0));
+        assert_eq!(lowest_rec_ppem_or_zero(&font_ref), 0);
     }
 
-    struct PaletteOverride {
-        index: u16,
-        color_8888: u32,
+    /// Builds a format 0 name table with `records` of (platform ID, encoding
+    /// ID, language ID, name ID, string), encoding Unicode and Windows strings
+    /// as UTF-16, Macintosh Roman ones as Mac Roman and others as ASCII.
+    /// Records must be sorted.
+    fn build_name(records: &[(u16, u16, u16, u16, &str)]) -> Vec<u8> {
+        let encoded: Vec<_> = records
+            .iter()
+            .map(
+                |&(platform_id, encoding_id, language_id, name_id, string)| {
+                    let encoded: Vec<u8> = match (platform_id, encoding_id) {
+                        (0 | 3, _) => string.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+                        (1, 0) => string
+                            .chars()
+                            .map(|c| unicode_to_mac_roman(c.into()).unwrap())
+                            .collect(),
+                        _ => string.bytes().collect(),
+                    };
+                    (platform_id, encoding_id, language_id, name_id, encoded)
+                },
+            )
+            .collect();
+        let records: Vec<_> = encoded
+            .iter()
+            .map(
+                |(platform_id, encoding_id, language_id, name_id, encoded)| {
+                    (
+                        *platform_id,
+                        *encoding_id,
+                        *language_id,
+                        *name_id,
+                        encoded.as_slice(),
+                    )
+                },
+            )
+            .collect();
+        build_name_bytes(&records)
     }
 
-    struct ClipBox {
-        x_min: f32,
-        y_min: f32,
-        x_max: f32,
-        y_max: f32,
+    /// Builds a format 0 name table like `build_name` from already encoded
+    /// strings.
+    fn build_name_bytes(records: &[(u16, u16, u16, u16, &[u8])]) -> Vec<u8> {
+        let mut strings = Vec::new();
+        let mut name = Vec::new();
+        name.extend(0u16.to_be_bytes());
+        name.extend((records.len() as u16).to_be_bytes());
+        name.extend((6 + 12 * records.len() as u16).to_be_bytes());
+        for &(platform_id, encoding_id, language_id, name_id, encoded) in records {
+            for value in [platform_id, encoding_id, language_id, name_id] {
+                name.extend(value.to_be_bytes());
+            }
+            name.extend((encoded.len() as u16).to_be_bytes());
+            name.extend((strings.len() as u16).to_be_bytes());
+            strings.extend_from_slice(encoded);
+        }
+        name.extend(strings);
+        name
     }
 
-    struct Transform {
-        xx: f32,
-        xy: f32,
-        yx: f32,
-        yy: f32,
-        dx: f32,
-        dy: f32,
+    #[test]
+    fn test_font_revision_and_version_string() {
+        let file_buffer = fs::read(TEST_CONDENSED_BOLD_ITALIC)
+            .expect("Font to test versions could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let mut version = String::new();
+        assert!(version_string(&font_ref, &mut version));
+        assert_eq!(version, "Version 3.008; 2023");
+        // Stored as 0x0003020C.
+        assert_eq!(font_revision(&font_ref), 3.0 + 0x020C as f32 / 65536.0);
+
+        // Revision 2.001 truncated to 0x00020041 by some tools and rounded to
+        // 0x00020042 by others.
+        let name = build_name(&[
+            (1, 0, 0, 5, "Version 2.001"),
+            (3, 1, 0x0409, 5, "Version 2.001"),
+        ]);
+        let with_name = replace_table(&file_buffer, b"name", Some(name));
+        for fraction in [0x0041, 0x0042] {
+            let font_data = patch_table(&with_name, b"head", &[(4, 2), (6, fraction)]);
+            let font_ref = make_font_ref(&font_data, 0);
+            assert!(version_string(&font_ref, &mut version));
+            assert_eq!(version, "Version 2.001");
+            let revision = font_revision(&font_ref);
+            assert_eq!(revision, 2.0 + fraction as f32 / 65536.0);
+            assert!((revision - 2.001).abs() < 1.0 / 65536.0);
+            assert_ne!(revision, (0x0002_0000 | fraction as u32) as f32);
+        }
+
+        // Revisions from 256 on are rounded once.
+        let font_data = patch_table(&file_buffer, b"head", &[(4, 0x7FFF), (6, -1)]);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert_eq!(font_revision(&font_ref), 32768.0);
+
+        let without_tables = strip_table(&strip_table(&file_buffer, b"head"), b"name");
+        let font_ref = make_font_ref(&without_tables, 0);
+        assert!(!version_string(&font_ref, &mut version));
+        assert!(version.is_empty());
+        assert_eq!(font_revision(&font_ref), 0.0);
     }
 
-    struct FillLinearParams {
-        x0: f32,
-        y0: f32,
-        x1: f32,
-        y1: f32,
+    #[test]
+    fn test_vendor_id() {
+        let mut id = [0; 4];
+        for (font_filename, expected_id, expected_string) in [
+            (TEST_ROBOTO, b"GOOG", "GOOG"),
+            ("resources/fonts/ahem.ttf", b"W3C ", "W3C"),
+            ("resources/fonts/Roboto2-Regular_NoEmbed.ttf", b"    ", ""),
+        ] {
+            let file_buffer =
+                fs::read(font_filename).expect("Font to test vendor IDs could not be opened.");
+            let font_ref = make_font_ref(&file_buffer, 0);
+            assert!(vendor_id(&font_ref, &mut id));
+            assert_eq!(&id, expected_id);
+            assert_eq!(vendor_id_string(&font_ref), expected_string);
+        }
+
+        // Only trailing spaces are padding.
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test vendor IDs could not be opened.");
+        let leading_space = patch_table(
+            &file_buffer,
+            b"OS/2",
+            &[
+                (58, i16::from_be_bytes(*b" A")),
+                (60, i16::from_be_bytes(*b"B ")),
+            ],
+        );
+        assert_eq!(vendor_id_string(&make_font_ref(&leading_space, 0)), " AB");
+
+        let without_os2 = strip_table(&file_buffer, b"OS/2");
+        let font_ref = make_font_ref(&without_os2, 0);
+        assert!(!vendor_id(&font_ref, &mut id));
+        assert_eq!(id, [0; 4]);
+        assert_eq!(vendor_id_string(&font_ref), "");
     }
 
-    struct FillRadialParams {
-        x0: f32,
-        y0: f32,
-        r0: f32,
-        x1: f32,
-        y1: f32,
-        r1: f32,
+    /// An axis value table of (flags, valueNameID, axis positions, range): a
+    /// single position with no range is format 1, with a range (min, max)
+    /// around the nominal position format 2, and several positions format 4.
+    type TestAxisValue<'a> = (u16, u16, &'a [(u16, f32)], Option<(f32, f32)>);
+
+    /// Builds a STAT table, version 1.1, for the design axes `axes` of tag and
+    /// axisOrdering.
+    fn build_stat(
+        axes: &[(&[u8; 4], u16)],
+        values: &[TestAxisValue],
+        elided_fallback_name_id: u16,
+    ) -> Vec<u8> {
+        let fixed = |value: f32| Fixed::from_f64(value.into()).to_be_bytes();
+        let mut axis_value_tables: Vec<Vec<u8>> = Vec::new();
+        for (flags, name_id, positions, range) in values {
+            let mut table = Vec::new();
+            match (positions, range) {
+                ([(axis_index, value)], range) => {
+                    table.extend(if range.is_some() { 2u16 } else { 1u16 }.to_be_bytes());
+                    table.extend(axis_index.to_be_bytes());
+                    table.extend(flags.to_be_bytes());
+                    table.extend(name_id.to_be_bytes());
+                    table.extend(fixed(*value));
+                    if let Some((min, max)) = range {
+                        table.extend(fixed(*min));
+                        table.extend(fixed(*max));
+                    }
+                }
+                (positions, None) => {
+                    table.extend(4u16.to_be_bytes());
+                    table.extend((positions.len() as u16).to_be_bytes());
+                    table.extend(flags.to_be_bytes());
+                    table.extend(name_id.to_be_bytes());
+                    for (axis_index, value) in positions.iter() {
+                        table.extend(axis_index.to_be_bytes());
+                        table.extend(fixed(*value));
+                    }
+                }
+                _ => panic!("Combinations have no range."),
+            }
+            axis_value_tables.push(table);
+        }
+
+        let design_axes_offset = 20u32;
+        let axis_values_offset = design_axes_offset + 8 * axes.len() as u32;
+        let mut stat = Vec::new();
+        stat.extend(1u16.to_be_bytes());
+        stat.extend(1u16.to_be_bytes());
+        stat.extend(8u16.to_be_bytes());
+        stat.extend((axes.len() as u16).to_be_bytes());
+        stat.extend(design_axes_offset.to_be_bytes());
+        stat.extend((values.len() as u16).to_be_bytes());
+        stat.extend(axis_values_offset.to_be_bytes());
+        stat.extend(elided_fallback_name_id.to_be_bytes());
+        for (tag, axis_ordering) in axes {
+            stat.extend(*tag);
+            stat.extend(256u16.to_be_bytes());
+            stat.extend(axis_ordering.to_be_bytes());
+        }
+        // Offsets to the axis value tables are relative to the offset array.
+        let mut offset = 2 * values.len() as u16;
+        for table in &axis_value_tables {
+            stat.extend(offset.to_be_bytes());
+            offset += table.len() as u16;
+        }
+        stat.extend(axis_value_tables.concat());
+        stat
     }
 
-    struct FillSweepParams {
-        x0: f32,
-        y0: f32,
-        start_angle: f32,
-        end_angle: f32,
+    fn style_name_at(font_ref: &BridgeFontRef, (weight, width): (f32, f32)) -> String {
+        let coords = variable_design_coords(font_ref, (weight, width));
+        let mut style_name = String::new();
+        assert!(derive_style_name(font_ref, &coords, &mut style_name));
+        style_name
     }
 
-    // This type is used to mirror SkFontStyle values for Weight, Slant and Width
-    pub struct BridgeFontStyle {
-        pub weight: i32,
-        pub slant: i32,
-        pub width: i32,
+    #[test]
+    fn test_derive_style_name_from_font_stat() {
+        // Weights Thin 100, Normal 400 (elidable, linked to 700) and Heavy
+        // 700; widths Condensed 50, Normal 100 (elidable, linked to 150) and
+        // Expanded 200. The elided fallback name is Normal.
+        let file_buffer =
+            fs::read(TEST_VARIABLE).expect("Font to test style names could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        for (position, expected_name) in [
+            ((400.0, 100.0), "Normal"),
+            ((100.0, 100.0), "Thin"),
+            ((700.0, 100.0), "Heavy"),
+            ((400.0, 50.0), "Condensed"),
+            ((100.0, 200.0), "Thin Expanded"),
+            ((700.0, 50.0), "Heavy Condensed"),
+            // Linked values do not name positions, and axes without a
+            // matching value are left out.
+            ((400.0, 150.0), "Normal"),
+            ((550.0, 200.0), "Expanded"),
+            ((900.0, 100.0), "Normal"),
+        ] {
+            assert_eq!(
+                style_name_at(&font_ref, position),
+                expected_name,
+                "at {position:?}"
+            );
+        }
+
+        // Axes that the font cannot vary are at the first value listed. The
+        // condensed width is elidable in this font.
+        let file_buffer = fs::read(TEST_CONDENSED_BOLD_ITALIC)
+            .expect("Font to test style names could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let mut style_name = String::new();
+        assert!(derive_style_name(
+            &font_ref,
+            &BridgeNormalizedCoords::default(),
+            &mut style_name
+        ));
+        assert_eq!(style_name, "Bold Italic");
     }
 
-    extern "Rust" {
-        type BridgeFontRef<'a>;
-        unsafe fn make_font_ref<'a>(font_data: &'a [u8], index: u32) -> Box<BridgeFontRef<'a>>;
-        // Returns whether BridgeFontRef is a valid font containing at
-        // least a valid sfnt structure from which tables can be
-        // accessed. This is what instantiation in make_font_ref checks
-        // for. (see FontRef::new in read_fonts's lib.rs). Implemented
-        // by returning whether the option is Some() and thus whether a
-        // FontRef instantiation succeeded and a table directory was
-        // accessible.
-        fn font_ref_is_valid(bridge_font_ref: &BridgeFontRef) -> bool;
+    #[test]
+    fn test_derive_style_name_from_built_stat() {
+        let file_buffer =
+            fs::read(TEST_VARIABLE).expect("Font to test style names could not be opened.");
+        let names = build_name(&[
+            (3, 1, 0x409, 2, "Normal"),
+            (3, 1, 0x409, 300, "Light"),
+            (3, 1, 0x409, 301, "Regular"),
+            (3, 1, 0x409, 302, "Bold"),
+            (3, 1, 0x409, 303, "Demi"),
+            (3, 1, 0x409, 304, "Wide"),
+            (3, 1, 0x409, 305, "Medium"),
+            (3, 1, 0x409, 306, "Poster"),
+            (3, 1, 0x409, 307, "Book"),
+        ]);
+        // Width is named before weight.
+        let stat = build_stat(
+            &[(b"wght", 1), (b"wdth", 0)],
+            &[
+                (0, 300, &[(0, 300.0)], Some((100.0, 350.0))),
+                (2, 301, &[(0, 400.0)], Some((350.0, 600.0))),
+                (0, 302, &[(0, 700.0)], Some((600.0, 900.0))),
+                (0, 303, &[(0, 650.0)], None),
+                (2, 305, &[(1, 100.0)], None),
+                (0, 304, &[(1, 200.0)], None),
+                (0, 306, &[(0, 700.0), (1, 200.0)], None),
+            ],
+            307,
+        );
+        let font_data = replace_table(
+            &replace_table(&file_buffer, b"name", Some(names)),
+            b"STAT",
+            Some(stat),
+        );
+        let font_ref = make_font_ref(&font_data, 0);
+        for (position, expected_name) in [
+            // All names elided.
+            ((400.0, 100.0), "Book"),
+            ((500.0, 100.0), "Book"),
+            // Ranges.
+            ((250.0, 100.0), "Light"),
+            ((100.0, 100.0), "Light"),
+            ((800.0, 100.0), "Bold"),
+            ((900.0, 100.0), "Bold"),
+            // Exact values over ranges.
+            ((650.0, 100.0), "Demi"),
+            // Axis ordering.
+            ((800.0, 200.0), "Wide Bold"),
+            ((500.0, 200.0), "Wide"),
+            // Combinations over single values.
+            ((700.0, 200.0), "Poster"),
+            ((700.0, 100.0), "Bold"),
+        ] {
+            assert_eq!(
+                style_name_at(&font_ref, position),
+                expected_name,
+                "at {position:?}"
+            );
+        }
+    }
 
-        type BridgeOutlineCollection<'a>;
-        unsafe fn get_outline_collection<'a>(
-            font_ref: &'a BridgeFontRef<'a>,
-        ) -> Box<BridgeOutlineCollection<'a>>;
+    #[test]
+    fn test_derive_style_name_without_stat() {
+        let file_buffer =
+            fs::read(TEST_VARIABLE).expect("Font to test style names could not be opened.");
+        let without_stat = strip_table(&file_buffer, b"STAT");
+        let font_ref = make_font_ref(&without_stat, 0);
+        assert_eq!(style_name_at(&font_ref, (100.0, 100.0)), "Normal");
 
-        /// Returns true on a font or collection, sets `num_fonts``
-        /// to 0 if single font file, and to > 0 for a TrueType collection.
-        /// Returns false if the data cannot be interpreted as a font or collection.
-        unsafe fn font_or_collection<'a>(font_data: &'a [u8], num_fonts: &mut u32) -> bool;
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test style names could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let mut style_name = String::from("stale");
+        assert!(derive_style_name(
+            &font_ref,
+            &BridgeNormalizedCoords::default(),
+            &mut style_name
+        ));
+        assert_eq!(style_name, "Regular");
 
-        fn lookup_glyph_or_zero(font_ref: &BridgeFontRef, codepoint: u32) -> u16;
-        fn get_path(
-            outlines: &BridgeOutlineCollection,
-            glyph_id: u16,
-            size: f32,
-            coords: &BridgeNormalizedCoords,
-            path_wrapper: Pin<&mut PathWrapper>,
-            scaler_metrics: &mut BridgeScalerMetrics,
-        ) -> bool;
-        fn advance_width_or_zero(
-            font_ref: &BridgeFontRef,
-            size: f32,
-            coords: &BridgeNormalizedCoords,
-            glyph_id: u16,
-        ) -> f32;
-        fn units_per_em_or_zero(font_ref: &BridgeFontRef) -> u16;
-        fn get_skia_metrics(
-            font_ref: &BridgeFontRef,
-            size: f32,
-            coords: &BridgeNormalizedCoords,
-        ) -> Metrics;
-        fn num_glyphs(font_ref: &BridgeFontRef) -> u16;
-        fn family_name(font_ref: &BridgeFontRef) -> String;
-        fn postscript_name(font_ref: &BridgeFontRef, out_string: &mut String) -> bool;
+        let without_name = strip_table(&file_buffer, b"name");
+        let font_ref = make_font_ref(&without_name, 0);
+        assert!(!derive_style_name(
+            &font_ref,
+            &BridgeNormalizedCoords::default(),
+            &mut style_name
+        ));
+        assert!(style_name.is_empty());
+    }
 
-        /// Receives a slice of palette overrides that will be merged
-        /// with the specified base palette of the font. The result is a
-        /// palette of RGBA, 8-bit per component, colors, consisting of
-        /// palette entries merged with overrides.
-        fn resolve_palette(
-            font_ref: &BridgeFontRef,
-            base_palette: u16,
-            palette_overrides: &[PaletteOverride],
-        ) -> Vec<u32>;
+    /// Variable.ttf with its axis and axis value names in English and
+    /// German.
+    fn multilingual_variable_font() -> Vec<u8> {
+        let file_buffer =
+            fs::read(TEST_VARIABLE).expect("Font to test axis names could not be opened.");
+        let mut records = Vec::new();
+        for (name_id, english, german) in [
+            (256, "Weight", "Strichstärke"),
+            (257, "Thin", "Dünn"),
+            (258, "Normal", "Normal"),
+            (259, "Heavy", "Fett"),
+            (260, "Width", "Breite"),
+            (261, "Condensed", "Schmal"),
+            (262, "Expanded", "Breit"),
+        ] {
+            records.push((3, 1, 0x407, name_id, german));
+            records.push((3, 1, 0x409, name_id, english));
+        }
+        replace_table(&file_buffer, b"name", Some(build_name(&records)))
+    }
 
-        fn has_colrv1_glyph(font_ref: &BridgeFontRef, glyph_id: u16) -> bool;
-        fn has_colrv0_glyph(font_ref: &BridgeFontRef, glyph_id: u16) -> bool;
-        fn get_colrv1_clip_box(
-            font_ref: &BridgeFontRef,
-            coords: &BridgeNormalizedCoords,
-            glyph_id: u16,
-            size: f32,
-            clip_box: &mut ClipBox,
-        ) -> bool;
+    #[test]
+    fn test_axis_name() {
+        let font_data = multilingual_variable_font();
+        let font_ref = make_font_ref(&font_data, 0);
+        let mut name = BridgeLocalizedName::default();
+        for (axis_index, language_preference, expected_name, expected_language) in [
+            (0, "de-DE", "Strichstärke", "de-DE"),
+            (1, "de-DE", "Breite", "de-DE"),
+            (1, "DE-de", "Breite", "de-DE"),
+            // Primary language subtags match other regions.
+            (1, "de-AT", "Breite", "de-DE"),
+            (1, "de", "Breite", "de-DE"),
+            (0, "en-US", "Weight", "en-US"),
+            (1, "en-GB", "Width", "en-US"),
+            // English if the preference is not available.
+            (0, "fr-FR", "Weight", "en-US"),
+            (0, "", "Weight", "en-US"),
+        ] {
+            assert!(axis_name(
+                &font_ref,
+                axis_index,
+                language_preference,
+                &mut name
+            ));
+            assert_eq!(name.string, expected_name, "for {language_preference}");
+            assert_eq!(
+                name.language, expected_language,
+                "for {language_preference}"
+            );
+        }
+        assert!(!axis_name(&font_ref, 2, "en-US", &mut name));
+        assert!(name.string.is_empty() && name.language.is_empty());
 
-        fn table_data(font_ref: &BridgeFontRef, tag: u32, offset: usize, data: &mut [u8]) -> usize;
-        fn table_tags(font_ref: &BridgeFontRef, tags: &mut [u32]) -> u16;
-        fn variation_position(
-            coords: &BridgeNormalizedCoords,
-            coordinates: &mut [SkiaDesignCoordinate],
-        ) -> isize;
+        // The first language without English.
+        let file_buffer =
+            fs::read(TEST_VARIABLE).expect("Font to test axis names could not be opened.");
+        let german_only = replace_table(
+            &file_buffer,
+            b"name",
+            Some(build_name(&[(3, 1, 0x407, 260, "Breite")])),
+        );
+        let font_ref = make_font_ref(&german_only, 0);
+        assert!(axis_name(&font_ref, 1, "fr-FR", &mut name));
+        assert_eq!(name.string, "Breite");
+        assert!(!axis_name(&font_ref, 0, "de-DE", &mut name));
 
-        fn populate_axes(font_ref: &BridgeFontRef, axis_wrapper: Pin<&mut AxisWrapper>) -> isize;
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test axis names could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(!axis_name(&font_ref, 0, "en-US", &mut name));
+    }
 
-        type BridgeLocalizedStrings<'a>;
-        unsafe fn get_localized_strings<'a>(
-            font_ref: &'a BridgeFontRef<'a>,
-        ) -> Box<BridgeLocalizedStrings<'a>>;
-        fn localized_name_next(
-            bridge_localized_strings: &mut BridgeLocalizedStrings,
-            out_localized_name: &mut BridgeLocalizedName,
-        ) -> bool;
+    fn axis_value_names(
+        font_ref: &BridgeFontRef,
+        language_preference: &str,
+    ) -> Vec<(u32, f32, bool, String)> {
+        let mut names = get_axis_value_names(font_ref, language_preference);
+        let mut name = BridgeAxisValueName::default();
+        let mut result = Vec::new();
+        while axis_value_name_next(&mut names, &mut name) {
+            result.push((name.axis, name.value, name.is_elidable, name.string.clone()));
+        }
+        result
+    }
 
-        type BridgeNormalizedCoords;
-        fn resolve_into_normalized_coords(
-            font_ref: &BridgeFontRef,
-            design_coords: &[SkiaDesignCoordinate],
-        ) -> Box<BridgeNormalizedCoords>;
+    #[test]
+    fn test_axis_value_names() {
+        let font_data = multilingual_variable_font();
+        let font_ref = make_font_ref(&font_data, 0);
+        let wght = u32::from_be_bytes(*b"wght");
+        let wdth = u32::from_be_bytes(*b"wdth");
+        assert_eq!(
+            axis_value_names(&font_ref, "de-DE"),
+            [
+                (wght, 100.0, false, "Dünn".to_string()),
+                (wght, 400.0, true, "Normal".to_string()),
+                (wght, 700.0, false, "Fett".to_string()),
+                (wdth, 50.0, false, "Schmal".to_string()),
+                (wdth, 100.0, true, "Normal".to_string()),
+                (wdth, 200.0, false, "Breit".to_string()),
+            ]
+        );
+        let english: Vec<String> = axis_value_names(&font_ref, "en")
+            .into_iter()
+            .map(|(_, _, _, name)| name)
+            .collect();
+        assert_eq!(
+            english,
+            ["Thin", "Normal", "Heavy", "Condensed", "Normal", "Expanded"]
+        );
 
-        fn draw_colr_glyph(
-            font_ref: &BridgeFontRef,
-            coords: &BridgeNormalizedCoords,
-            glyph_id: u16,
-            color_painter: Pin<&mut ColorPainterWrapper>,
-        ) -> bool;
+        let mut names = get_axis_value_names(&font_ref, "de-DE");
+        let mut name = BridgeAxisValueName::default();
+        assert!(axis_value_name_next(&mut names, &mut name));
+        assert_eq!(name.language, "de-DE");
 
-        type BridgeColorStops<'a>;
-        fn next_color_stop(color_stops: &mut BridgeColorStops, stop: &mut ColorStop) -> bool;
-        fn num_color_stops(color_stops: &BridgeColorStops) -> usize;
+        // Combinations name no single axis.
+        let file_buffer =
+            fs::read(TEST_VARIABLE).expect("Font to test axis value names could not be opened.");
+        let stat = build_stat(
+            &[(b"wght", 0), (b"wdth", 1)],
+            &[(0, 259, &[(0, 700.0), (1, 200.0)], None)],
+            258,
+        );
+        let with_combination = replace_table(&file_buffer, b"STAT", Some(stat));
+        let font_ref = make_font_ref(&with_combination, 0);
+        assert_eq!(
+            axis_value_names(&font_ref, "en-US"),
+            [(0, 0.0, false, "Heavy".to_string())]
+        );
 
-        fn get_font_style(font_ref: &BridgeFontRef, font_style: &mut BridgeFontStyle) -> bool;
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test axis value names could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(axis_value_names(&font_ref, "en-US").is_empty());
     }
 
-    unsafe extern "C++" {
+    fn collect_localized_names(
+        bridge_localized_strings: &mut BridgeLocalizedStrings,
+    ) -> Vec<(String, String)> {
+        let mut name = BridgeLocalizedName::default();
+        let mut names = Vec::new();
+        while localized_name_next(bridge_localized_strings, &mut name) {
+            names.push((name.string.clone(), name.language.clone()));
+        }
+        names
+    }
 
-        include!("src/ports/fontations/src/skpath_bridge.h");
+    #[test]
+    fn test_localized_strings_of_name_ids() {
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test localized strings could not be opened.");
+        let multilingual = replace_table(
+            &file_buffer,
+            b"name",
+            Some(build_name(&[
+                (1, 0, 0, 1, "Roboto"),
+                (3, 1, 0x409, 1, "Roboto"),
+                (3, 1, 0x407, 2, "Standard"),
+                (3, 1, 0x409, 2, "Regular"),
+                (3, 1, 0x40C, 2, "Normal"),
+                (3, 1, 0x409, 13, "Licensed under the Apache License"),
+                (3, 1, 0x407, 13, "Lizenziert unter der Apache-Lizenz"),
+                (3, 1, 0x409, 16, "Roboto Text"),
+            ])),
+        );
+        let font_ref = make_font_ref(&multilingual, 0);
 
-        type PathWrapper;
+        // Only family names carry the suffix.
+        let mut localized_strings = get_localized_strings(&font_ref, 1);
+        assert_eq!(
+            collect_localized_names(&mut localized_strings),
+            [
+                ("Roboto (Fontations)".to_string(), "en-US".to_string()),
+                ("Roboto (Fontations)".to_string(), "en".to_string()),
+            ]
+        );
+        let mut localized_strings = get_localized_strings(&font_ref, 2);
+        assert_eq!(
+            collect_localized_names(&mut localized_strings),
+            [
+                ("Regular".to_string(), "en-US".to_string()),
+                ("Standard".to_string(), "de-DE".to_string()),
+                ("Normal".to_string(), "fr-FR".to_string()),
+            ]
+        );
 
-        #[allow(dead_code)]
-        fn move_to(self: Pin<&mut PathWrapper>, x: f32, y: f32);
-        #[allow(dead_code)]
-        fn line_to(self: Pin<&mut PathWrapper>, x: f32, y: f32);
-        #[allow(dead_code)]
-        fn quad_to(self: Pin<&mut PathWrapper>, cx0: f32, cy0: f32, x: f32, y: f32);
-        #[allow(dead_code)]
-        fn curve_to(
-            self: Pin<&mut PathWrapper>,
-            cx0: f32,
-            cy0: f32,
-            cx1: f32,
-            cy1: f32,
-            x: f32,
-            y: f32,
+        // The same iterator for further name IDs.
+        reset_localized_strings(&font_ref, &mut localized_strings, 13);
+        assert_eq!(
+            collect_localized_names(&mut localized_strings),
+            [
+                (
+                    "Licensed under the Apache License".to_string(),
+                    "en-US".to_string()
+                ),
+                (
+                    "Lizenziert unter der Apache-Lizenz".to_string(),
+                    "de-DE".to_string()
+                ),
+            ]
         );
-        #[allow(dead_code)]
-        fn close(self: Pin<&mut PathWrapper>);
+        reset_localized_strings(&font_ref, &mut localized_strings, 16);
+        assert_eq!(
+            collect_localized_names(&mut localized_strings),
+            [("Roboto Text".to_string(), "en-US".to_string())]
+        );
+        reset_localized_strings(&font_ref, &mut localized_strings, 17);
+        assert!(collect_localized_names(&mut localized_strings).is_empty());
+    }
 
-        type AxisWrapper;
+    #[test]
+    fn test_typographic_family_and_subfamily_names() {
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test family names could not be opened.");
+        let with_typographic_names = replace_table(
+            &file_buffer,
+            b"name",
+            Some(build_name(&[
+                (3, 1, 0x409, 1, "Roboto Condensed Light"),
+                (3, 1, 0x409, 2, "Regular"),
+                (3, 1, 0x407, 16, "Roboto Schmal"),
+                (3, 1, 0x409, 16, "Roboto Condensed"),
+                (3, 1, 0x409, 17, "Light"),
+            ])),
+        );
+        let font_ref = make_font_ref(&with_typographic_names, 0);
+        assert_eq!(typographic_family_name(&font_ref), "Roboto Condensed");
+        assert_eq!(typographic_subfamily_name(&font_ref), "Light");
+        assert_eq!(family_name(&font_ref), "Roboto Condensed Light");
 
-        fn populate_axis(
-            self: Pin<&mut AxisWrapper>,
-            i: usize,
-            axis: u32,
-            min: f32,
-            def: f32,
-            max: f32,
-            hidden: bool,
-        ) -> bool;
-        fn size(self: Pin<&AxisWrapper>) -> usize;
+        // Each falls back on its own.
+        let family_only = replace_table(
+            &file_buffer,
+            b"name",
+            Some(build_name(&[
+                (3, 1, 0x409, 1, "Roboto Condensed Light"),
+                (3, 1, 0x409, 2, "Regular"),
+                (3, 1, 0x409, 16, "Roboto Condensed"),
+            ])),
+        );
+        let font_ref = make_font_ref(&family_only, 0);
+        assert_eq!(typographic_family_name(&font_ref), "Roboto Condensed");
+        assert_eq!(typographic_subfamily_name(&font_ref), "Regular");
 
-        type ColorPainterWrapper;
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert_eq!(typographic_family_name(&font_ref), "Roboto");
+        assert_eq!(typographic_subfamily_name(&font_ref), "Regular");
 
-        fn push_transform(self: Pin<&mut ColorPainterWrapper>, transform: &Transform);
-        fn pop_transform(self: Pin<&mut ColorPainterWrapper>);
-        fn push_clip_glyph(self: Pin<&mut ColorPainterWrapper>, glyph_id: u16);
-        fn push_clip_rectangle(
-            self: Pin<&mut ColorPainterWrapper>,
-            x_min: f32,
-            y_min: f32,
-            x_max: f32,
-            y_max: f32,
+        let without_name = strip_table(&file_buffer, b"name");
+        let font_ref = make_font_ref(&without_name, 0);
+        assert!(typographic_family_name(&font_ref).is_empty());
+        assert!(typographic_subfamily_name(&font_ref).is_empty());
+    }
+
+    #[test]
+    fn test_wws_family_and_subfamily_names() {
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test WWS names could not be opened.");
+        let with_wws_names = replace_table(
+            &file_buffer,
+            b"name",
+            Some(build_name(&[
+                (3, 1, 0x409, 1, "Roboto Display Light"),
+                (3, 1, 0x409, 2, "Regular"),
+                (3, 1, 0x409, 16, "Roboto"),
+                (3, 1, 0x409, 17, "Display Light"),
+                (3, 1, 0x407, 21, "Roboto Anzeige"),
+                (3, 1, 0x409, 21, "Roboto Display"),
+                (3, 1, 0x409, 22, "Light"),
+            ])),
         );
-        fn pop_clip(self: Pin<&mut ColorPainterWrapper>);
+        let font_ref = make_font_ref(&with_wws_names, 0);
+        let mut name = String::new();
+        assert!(wws_family_name(&font_ref, &mut name));
+        assert_eq!(name, "Roboto Display");
+        assert!(wws_subfamily_name(&font_ref, &mut name));
+        assert_eq!(name, "Light");
+        assert!(!is_wws_conformant(&font_ref));
 
-        fn fill_solid(self: Pin<&mut ColorPainterWrapper>, palette_index: u16, alpha: f32);
-        fn fill_linear(
-            self: Pin<&mut ColorPainterWrapper>,
-            fill_linear_params: &FillLinearParams,
-            color_stops: &mut BridgeColorStops,
-            extend_mode: u8,
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(!wws_family_name(&font_ref, &mut name));
+        assert!(name.is_empty());
+        assert!(!wws_subfamily_name(&font_ref, &mut name));
+
+        let fs_selection = FontRef::new(&file_buffer)
+            .unwrap()
+            .os2()
+            .unwrap()
+            .fs_selection()
+            .bits();
+        let conformant = patch_table(
+            &file_buffer,
+            b"OS/2",
+            &[(62, (fs_selection | 0x100) as i16)],
         );
-        fn fill_radial(
-            self: Pin<&mut ColorPainterWrapper>,
-            fill_radial_params: &FillRadialParams,
-            color_stops: &mut BridgeColorStops,
-            extend_mode: u8,
+        assert!(is_wws_conformant(&make_font_ref(&conformant, 0)));
+
+        let without_os2 = strip_table(&file_buffer, b"OS/2");
+        assert!(!is_wws_conformant(&make_font_ref(&without_os2, 0)));
+    }
+
+    #[test]
+    fn test_font_names() {
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test font names could not be opened.");
+        let richly_named = replace_table(
+            &file_buffer,
+            b"name",
+            Some(build_name(&[
+                (0, 3, 0, 0, "Copyright 2011 Google Inc."),
+                (
+                    3,
+                    1,
+                    0x409,
+                    0,
+                    "Copyright 2011 Google Inc. All Rights Reserved.",
+                ),
+                (3, 1, 0x409, 1, "Roboto"),
+                (1, 0, 2, 3, "Roboto Standard 2.137"),
+                (1, 0, 0, 3, "Roboto Regular 2.137"),
+                (3, 1, 0x409, 4, "Roboto Regular"),
+                (3, 1, 0x40C, 5, "Version 2.137"),
+                (3, 1, 0x409, 8, "Google"),
+                (3, 1, 0x407, 9, "Christian Robertson"),
+                (3, 1, 0x409, 10, "A neo-grotesque sans serif."),
+                (3, 1, 0x407, 10, "Eine neo-groteske Serifenlose."),
+                (
+                    3,
+                    1,
+                    0x409,
+                    13,
+                    "Licensed under the Apache License, Version 2.0",
+                ),
+            ])),
         );
-        fn fill_sweep(
-            self: Pin<&mut ColorPainterWrapper>,
-            fill_sweep_params: &FillSweepParams,
-            color_stops: &mut BridgeColorStops,
-            extend_mode: u8,
+        let font_ref = make_font_ref(&richly_named, 0);
+        let mut names = BridgeFontNames::default();
+        assert!(font_names(&font_ref, &mut names));
+        // en-US over no language.
+        assert_eq!(
+            names.copyright,
+            "Copyright 2011 Google Inc. All Rights Reserved."
+        );
+        // en over other languages of the same platform.
+        assert_eq!(names.unique_id, "Roboto Regular 2.137");
+        assert_eq!(names.full_name, "Roboto Regular");
+        // The first language without English.
+        assert_eq!(names.version, "Version 2.137");
+        assert_eq!(names.manufacturer, "Google");
+        assert_eq!(names.designer, "Christian Robertson");
+        assert_eq!(names.description, "A neo-grotesque sans serif.");
+        assert_eq!(
+            names.license,
+            "Licensed under the Apache License, Version 2.0"
+        );
+        assert_eq!(
+            names.present,
+            1 << 0 | 1 << 3 | 1 << 4 | 1 << 5 | 1 << 8 | 1 << 9 | 1 << 10 | 1 << 13
         );
 
-        // Optimized functions.
-        fn fill_glyph_solid(
-            self: Pin<&mut ColorPainterWrapper>,
-            glyph_id: u16,
-            palette_index: u16,
-            alpha: f32,
+        // The same strings as looking the IDs up one by one.
+        for font_filename in [
+            TEST_ROBOTO,
+            "resources/fonts/ahem.ttf",
+            TEST_CONDENSED_BOLD_ITALIC,
+        ] {
+            let file_buffer =
+                fs::read(font_filename).expect("Font to test font names could not be opened.");
+            let font_ref = make_font_ref(&file_buffer, 0);
+            assert!(font_names(&font_ref, &mut names));
+            for (name_id, string) in [
+                (StringId::COPYRIGHT_NOTICE, &names.copyright),
+                (StringId::UNIQUE_ID, &names.unique_id),
+                (StringId::FULL_NAME, &names.full_name),
+                (StringId::VERSION_STRING, &names.version),
+                (StringId::MANUFACTURER, &names.manufacturer),
+                (StringId::DESIGNER, &names.designer),
+                (StringId::DESCRIPTION, &names.description),
+                (StringId::LICENSE_DESCRIPTION, &names.license),
+            ] {
+                let expected = english_or_first_font_name(&font_ref, name_id);
+                let is_present = names.present & 1 << name_id.to_u16() != 0;
+                assert_eq!(is_present, expected.is_some(), "{font_filename} {name_id}");
+                assert_eq!(
+                    expected.unwrap_or_default(),
+                    *string,
+                    "{font_filename} {name_id}"
+                );
+            }
+        }
+
+        // Language IDs without a known language rank like no language, and
+        // language tags compare case-insensitively, as for the single lookups.
+        let font_data = replace_table(
+            &file_buffer,
+            b"name",
+            Some(build_name(&[
+                (3, 1, 0x0407, 8, "German"),
+                (3, 1, 0x7FFF, 8, "Unknown"),
+            ])),
         );
-        fn fill_glyph_linear(
-            self: Pin<&mut ColorPainterWrapper>,
-            glyph_id: u16,
-            fill_transform: &Transform,
-            fill_linear_params: &FillLinearParams,
-            color_stops: &mut BridgeColorStops,
-            extend_mode: u8,
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_names(&font_ref, &mut names));
+        assert_eq!(names.manufacturer, "Unknown");
+        assert_eq!(
+            english_or_first_font_name(&font_ref, StringId::MANUFACTURER).as_deref(),
+            Some("Unknown")
         );
-        fn fill_glyph_radial(
-            self: Pin<&mut ColorPainterWrapper>,
-            glyph_id: u16,
-            fill_transform: &Transform,
-            fill_radial_params: &FillRadialParams,
-            color_stops: &mut BridgeColorStops,
-            extend_mode: u8,
+        let font_data = replace_table(
+            &file_buffer,
+            b"name",
+            Some(build_name_with_language_tags(
+                &[(3, 1, 0x8000, 8, "Other"), (3, 1, 0x8001, 8, "English")],
+                &["de", "EN-us"],
+            )),
         );
-        fn fill_glyph_sweep(
-            self: Pin<&mut ColorPainterWrapper>,
-            glyph_id: u16,
-            fill_transform: &Transform,
-            fill_sweep_params: &FillSweepParams,
-            color_stops: &mut BridgeColorStops,
-            extend_mode: u8,
+        let font_ref = make_font_ref(&font_data, 0);
+        assert!(font_names(&font_ref, &mut names));
+        assert_eq!(names.manufacturer, "English");
+        assert_eq!(
+            english_or_first_font_name(&font_ref, StringId::MANUFACTURER).as_deref(),
+            Some("English")
         );
 
-        fn push_layer(self: Pin<&mut ColorPainterWrapper>, colrv1_composite_mode: u8);
-        fn pop_layer(self: Pin<&mut ColorPainterWrapper>);
+        let without_name = strip_table(&file_buffer, b"name");
+        let font_ref = make_font_ref(&without_name, 0);
+        assert!(!font_names(&font_ref, &mut names));
+        assert!(names.copyright.is_empty() && names.license.is_empty());
+        assert_eq!(names.present, 0);
+    }
 
+    /// Builds a version 1 name table whose records may refer to the
+    /// `language_tags` by the language IDs from 0x8000 on.
+    fn build_name_with_language_tags(
+        records: &[(u16, u16, u16, u16, &str)],
+        language_tags: &[&str],
+    ) -> Vec<u8> {
+        let encode = |string: &str| -> Vec<u8> {
+            string.encode_utf16().flat_map(u16::to_be_bytes).collect()
+        };
+        let mut strings = Vec::new();
+        let mut name = Vec::new();
+        name.extend(1u16.to_be_bytes());
+        name.extend((records.len() as u16).to_be_bytes());
+        let storage_offset = 6 + 12 * records.len() + 2 + 4 * language_tags.len();
+        name.extend((storage_offset as u16).to_be_bytes());
+        for (platform_id, encoding_id, language_id, name_id, string) in records {
+            let encoded = encode(string);
+            for value in [*platform_id, *encoding_id, *language_id, *name_id] {
+                name.extend(value.to_be_bytes());
+            }
+            name.extend((encoded.len() as u16).to_be_bytes());
+            name.extend((strings.len() as u16).to_be_bytes());
+            strings.extend(encoded);
+        }
+        name.extend((language_tags.len() as u16).to_be_bytes());
+        for language_tag in language_tags {
+            let encoded = encode(language_tag);
+            name.extend((encoded.len() as u16).to_be_bytes());
+            name.extend((strings.len() as u16).to_be_bytes());
+            strings.extend(encoded);
+        }
+        name.extend(strings);
+        name
     }
-}
 
-impl Default for BridgeFontStyle {
-    fn default() -> Self {
-        Self {
-            weight: 0,
-            slant: 0,
-            width: 0,
+    #[test]
+    fn test_localized_name_languages() {
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test name languages could not be opened.");
+        let names = build_name(&[
+            (1, 0, 0, 2, "Regular"),
+            (1, 0, 2, 2, "Standard"),
+            (1, 0, 21, 2, "Niyamit"),
+            (3, 1, 0x0407, 2, "Standard"),
+            (3, 1, 0x0416, 2, "Normal"),
+            (3, 1, 0x0816, 2, "Normal"),
+            (3, 1, 0x0804, 2, "常规"),
+            (3, 1, 0x7FFF, 2, "Unknown"),
+            (0, 3, 0, 2, "Unicode"),
+        ]);
+        let with_names = replace_table(&file_buffer, b"name", Some(names));
+        let font_ref = make_font_ref(&with_names, 0);
+        let mut localized_strings = get_localized_strings(&font_ref, 2);
+        let mut name = BridgeLocalizedName::default();
+        for (expected_language, expected_platform_id, expected_language_id) in [
+            // Windows LCIDs with their sublanguages, unknown ones first as
+            // they might be English.
+            ("", 3, 0x7FFF),
+            ("de-DE", 3, 0x0407),
+            ("pt-BR", 3, 0x0416),
+            ("pt-PT", 3, 0x0816),
+            ("zh-Hans", 3, 0x0804),
+            ("", 0, 0),
+            // Macintosh language codes.
+            ("en", 1, 0),
+            ("de", 1, 2),
+            ("hi", 1, 21),
+        ] {
+            assert!(localized_name_next(&mut localized_strings, &mut name));
+            assert_eq!(
+                name.language, expected_language,
+                "for {expected_language_id:#x}"
+            );
+            assert_eq!(name.platform_id, expected_platform_id);
+            assert_eq!(name.language_id, expected_language_id);
+        }
+        assert!(!localized_name_next(&mut localized_strings, &mut name));
+
+        // Language tags of version 1 name tables in canonical case.
+        let names = build_name_with_language_tags(
+            &[
+                (3, 1, 0x8000, 2, "常規"),
+                (3, 1, 0x8001, 2, "Normal"),
+                (3, 1, 0x8002, 2, "Regular"),
+            ],
+            &["ZH-hant-tw", "sr_latn", "EN"],
+        );
+        let with_names = replace_table(&file_buffer, b"name", Some(names));
+        let font_ref = make_font_ref(&with_names, 0);
+        let mut localized_strings = get_localized_strings(&font_ref, 2);
+        for (expected_language, expected_language_id) in
+            [("en", 0x8002), ("zh-Hant-TW", 0x8000), ("sr-Latn", 0x8001)]
+        {
+            assert!(localized_name_next(&mut localized_strings, &mut name));
+            assert_eq!(name.language, expected_language);
+            assert_eq!(name.language_id, expected_language_id);
         }
     }
-}
 
-/// Tests to exercise COLR and CPAL parts of the Fontations FFI.
-/// Run using `$ bazel test --with_fontations //src/ports/fontations:test_ffi`
-#[cfg(test)]
-mod test {
-    use crate::{
-        ffi::BridgeFontStyle, ffi::PaletteOverride, font_or_collection, font_ref_is_valid,
-        get_font_style, make_font_ref, resolve_palette,
-    };
-    use std::fs;
+    #[test]
+    fn test_axis_name_raw_ids() {
+        let font_data = multilingual_variable_font();
+        let font_ref = make_font_ref(&font_data, 0);
+        let mut name = BridgeLocalizedName::default();
+        assert!(axis_name(&font_ref, 1, "de", &mut name));
+        assert_eq!((name.platform_id, name.language_id), (3, 0x0407));
+        assert!(axis_name(&font_ref, 1, "fr", &mut name));
+        assert_eq!((name.platform_id, name.language_id), (3, 0x0409));
+    }
 
-    const TEST_FONT_FILENAME: &str = "resources/fonts/test_glyphs-glyf_colr_1_variable.ttf";
-    const TEST_COLLECTION_FILENAME: &str = "resources/fonts/test.ttc";
-    const TEST_CONDENSED_BOLD_ITALIC: &str = "resources/fonts/cond-bold-italic.ttf";
-    const TEST_VARIABLE: &str = "resources/fonts/Variable.ttf";
+    #[test]
+    fn test_localized_strings_count_and_restart() {
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test localized strings could not be opened.");
+        // Macintosh English and Windows LCID 0 both are "en".
+        let names = build_name(&[
+            (1, 0, 0, 1, "Roboto"),
+            (3, 1, 0x0409, 1, "Roboto"),
+            (3, 10, 0x0409, 1, "Roboto"),
+            (3, 1, 0, 1, "Roboto"),
+            (3, 1, 0x0407, 1, "Roboto"),
+            (3, 1, 0x0409, 1, "Roboto Sans"),
+            (3, 1, 0x0409, 2, "Regular"),
+        ]);
+        let with_duplicates = replace_table(&file_buffer, b"name", Some(names));
+        let font_ref = make_font_ref(&with_duplicates, 0);
+        let mut localized_strings = get_localized_strings(&font_ref, 1);
+        // Windows Unicode records come first, and the repeated Macintosh
+        // one is skipped.
+        let expected = [
+            ("Roboto (Fontations)".to_string(), "en-US".to_string()),
+            ("Roboto Sans (Fontations)".to_string(), "en-US".to_string()),
+            ("Roboto (Fontations)".to_string(), "en".to_string()),
+            ("Roboto (Fontations)".to_string(), "de-DE".to_string()),
+        ];
+        assert_eq!(localized_strings_count(&localized_strings), expected.len());
+        assert_eq!(collect_localized_names(&mut localized_strings), expected);
+        // Counting neither consumes nor depends on what was consumed.
+        assert_eq!(localized_strings_count(&localized_strings), expected.len());
+
+        restart_localized_strings(&mut localized_strings);
+        let mut name = BridgeLocalizedName::default();
+        assert!(localized_name_next(&mut localized_strings, &mut name));
+        assert_eq!(name.language, "en-US");
+        restart_localized_strings(&mut localized_strings);
+        assert_eq!(collect_localized_names(&mut localized_strings), expected);
+
+        reset_localized_strings(&font_ref, &mut localized_strings, 2);
+        assert_eq!(localized_strings_count(&localized_strings), 1);
+        restart_localized_strings(&mut localized_strings);
+        assert_eq!(
+            collect_localized_names(&mut localized_strings),
+            [("Regular".to_string(), "en-US".to_string())]
+        );
+
+        let without_name = strip_table(&file_buffer, b"name");
+        let font_ref = make_font_ref(&without_name, 0);
+        let mut localized_strings = get_localized_strings(&font_ref, 1);
+        assert_eq!(localized_strings_count(&localized_strings), 0);
+        restart_localized_strings(&mut localized_strings);
+        assert!(!localized_name_next(&mut localized_strings, &mut name));
+    }
 
     #[test]
-    fn test_palette_override() {
+    fn test_name_record_platform_preference() {
         let file_buffer =
-            fs::read(TEST_FONT_FILENAME).expect("COLRv0/v1 test font could not be opened.");
-        let font_ref = make_font_ref(&file_buffer, 0);
-        assert!(font_ref_is_valid(&font_ref));
+            fs::read(TEST_ROBOTO).expect("Font to test name records could not be opened.");
+        // A transliterated Macintosh record ahead of the Windows one.
+        let names = build_name(&[
+            (1, 0, 0, 1, "Roboto?"),
+            (1, 0, 0, 2, "Reg."),
+            (0, 3, 0, 2, "Regular "),
+            (3, 1, 0x0409, 1, "Roboto"),
+            (1, 0, 0, 4, "Roboto Reg."),
+            (0, 3, 0, 4, "Roboto Regular"),
+            (3, 0, 0x0409, 5, "Version 2.137 (symbol)"),
+            (1, 0, 0, 5, "Version 2.137"),
+            (3, 1, 0x0407, 9, "Christian Robertson (de)"),
+            (1, 0, 0, 9, "Christian Robertson"),
+        ]);
+        let with_names = replace_table(&file_buffer, b"name", Some(names));
+        let font_ref = make_font_ref(&with_names, 0);
+        // Windows Unicode over Macintosh.
+        assert_eq!(
+            english_or_first_font_name(&font_ref, StringId::FAMILY_NAME).as_deref(),
+            Some("Roboto")
+        );
+        assert_eq!(typographic_family_name(&font_ref), "Roboto");
+        // The Unicode platform over Macintosh.
+        assert_eq!(
+            english_or_first_font_name(&font_ref, StringId::SUBFAMILY_NAME).as_deref(),
+            Some("Regular ")
+        );
+        // Macintosh over Windows symbol.
+        let mut version = String::new();
+        assert!(version_string(&font_ref, &mut version));
+        assert_eq!(version, "Version 2.137");
+        // Platforms over English, in the order of the localized strings.
+        let mut names = BridgeFontNames::default();
+        assert!(font_names(&font_ref, &mut names));
+        assert_eq!(names.full_name, "Roboto Regular");
+        assert_eq!(names.version, "Version 2.137");
+        assert_eq!(names.designer, "Christian Robertson (de)");
 
-        let override_color = 0xFFEEEEEE;
-        let valid_overrides = [
-            PaletteOverride {
-                index: 9,
-                color_8888: override_color,
-            },
-            PaletteOverride {
-                index: 10,
-                color_8888: override_color,
-            },
-            PaletteOverride {
-                index: 11,
-                color_8888: override_color,
-            },
-        ];
+        let mut localized_strings = get_localized_strings(&font_ref, 1);
+        assert_eq!(
+            collect_localized_names(&mut localized_strings),
+            [
+                ("Roboto (Fontations)".to_string(), "en-US".to_string()),
+                ("Roboto? (Fontations)".to_string(), "en".to_string()),
+            ]
+        );
+        let mut localized_strings = get_localized_strings(&font_ref, 9);
+        let mut name = BridgeLocalizedName::default();
+        assert!(localized_name_next(&mut localized_strings, &mut name));
+        assert_eq!(
+            (name.string.as_str(), name.platform_id),
+            ("Christian Robertson (de)", 3)
+        );
+    }
 
-        let palette = resolve_palette(&font_ref, 0, &valid_overrides);
+    #[test]
+    fn test_name_record_encodings() {
+        let file_buffer = fs::read(TEST_VARIABLE).expect("Font to test names could not be opened.");
+        let name = build_name_bytes(&[
+            // "Café Crème" and "Ünïcode" in Mac Roman.
+            (1, 0, 0, 1, b"Caf\x8e Cr\x8fme"),
+            (1, 0, 0, 4, b"\x86n\x95code"),
+            // Two Shift JIS characters and a half-width katakana.
+            (1, 1, 11, 2, b"Mincho \x96\xbe\x92\xa9 \xb1"),
+            // Windows Shift JIS and a truncated UTF-16 string.
+            (3, 1, 0x0409, 9, b"\x00A\x00"),
+            (3, 2, 0x0411, 2, b"\x00W\x96\xbe"),
+        ]);
+        let font_data = replace_table(&file_buffer, b"name", Some(name));
+        let font_ref = make_font_ref(&font_data, 0);
 
-        assert_eq!(palette.len(), 14);
-        assert_eq!(palette[9], override_color);
-        assert_eq!(palette[10], override_color);
-        assert_eq!(palette[11], override_color);
+        assert_eq!(family_name(&font_ref), "Café Crème");
+        let mut names = BridgeFontNames::default();
+        assert!(font_names(&font_ref, &mut names));
+        assert_eq!(names.full_name, "Ünïcode");
+        assert_eq!(names.designer, "A\u{FFFD}");
 
-        let out_of_bounds_overrides = [
-            PaletteOverride {
-                index: 15,
-                color_8888: override_color,
-            },
-            PaletteOverride {
-                index: 16,
-                color_8888: override_color,
-            },
-            PaletteOverride {
-                index: 17,
-                color_8888: override_color,
-            },
-        ];
+        let mut localized_strings = get_localized_strings(&font_ref, 1);
+        assert_eq!(
+            collect_localized_names(&mut localized_strings),
+            [("Café Crème (Fontations)".to_string(), "en".to_string())]
+        );
+        let mut localized_strings = get_localized_strings(&font_ref, 2);
+        let mut name = BridgeLocalizedName::default();
+        assert!(localized_name_next(&mut localized_strings, &mut name));
+        assert_eq!(
+            (name.string.as_str(), name.language.as_str()),
+            ("Mincho \u{FFFD}\u{FFFD} \u{FFFD}", "ja")
+        );
+        // Windows Shift JIS ranks below Macintosh.
+        assert!(localized_name_next(&mut localized_strings, &mut name));
+        assert_eq!((name.string.as_str(), name.platform_id), ("W\u{FFFD}", 3));
+        assert!(!localized_name_next(&mut localized_strings, &mut name));
+    }
 
-        let palette = resolve_palette(&font_ref, 0, &out_of_bounds_overrides);
+    #[test]
+    fn test_postscript_name_or_synthesized() {
+        let file_buffer =
+            fs::read(TEST_ROBOTO).expect("Font to test PostScript names could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let mut postscript_name = String::new();
+        assert_eq!(
+            postscript_name_or_synthesized(&font_ref, &mut postscript_name),
+            PostScriptNameSource::NameTable
+        );
+        assert_eq!(postscript_name, "Roboto-Regular");
 
-        assert_eq!(palette.len(), 14);
+        let postscript_name_of = |records: &[(u16, u16, u16, u16, &str)]| {
+            let font_data = replace_table(&file_buffer, b"name", Some(build_name(records)));
+            let font_ref = make_font_ref(&font_data, 0);
+            let mut postscript_name = String::new();
+            let source = postscript_name_or_synthesized(&font_ref, &mut postscript_name);
+            (postscript_name, source)
+        };
         assert_eq!(
-            (palette[11], palette[12], palette[13],),
-            (0xff68c7e8, 0xffffdc01, 0xff808080)
+            postscript_name_of(&[
+                (3, 1, 0x0409, 1, "Noto Sans"),
+                (3, 1, 0x0409, 2, "Bold Italic"),
+                (3, 1, 0x0409, 6, "Noto Sans [Bold Italic]"),
+            ]),
+            (
+                "NotoSansBoldItalic".to_string(),
+                PostScriptNameSource::Sanitized
+            )
+        );
+        // No name ID 6, or none left after sanitizing.
+        let synthesized = (
+            "NotoSans-BoldItalic".to_string(),
+            PostScriptNameSource::Synthesized,
+        );
+        assert_eq!(
+            postscript_name_of(&[
+                (3, 1, 0x0409, 1, "Noto Sans"),
+                (3, 1, 0x0409, 2, "Bold Italic"),
+            ]),
+            synthesized
+        );
+        assert_eq!(
+            postscript_name_of(&[
+                (3, 1, 0x0409, 1, "Noto Sans"),
+                (3, 1, 0x0409, 2, "Bold Italic"),
+                (3, 1, 0x0409, 6, " ()"),
+            ]),
+            synthesized
+        );
+        assert_eq!(
+            postscript_name_of(&[
+                (3, 1, 0x0409, 1, "Noto Sans Display Cond"),
+                (3, 1, 0x0409, 2, "Regular"),
+                (3, 1, 0x0409, 16, "Noto Sans Display"),
+                (3, 1, 0x0409, 17, "Condensed Ünïcode"),
+            ]),
+            (
+                "NotoSansDisplay-Condensedncode".to_string(),
+                PostScriptNameSource::Synthesized
+            )
+        );
+        assert_eq!(
+            postscript_name_of(&[(3, 1, 0x0409, 1, "Noto Sans")]),
+            ("NotoSans".to_string(), PostScriptNameSource::Synthesized)
         );
+        let long_family = "Long Family ".repeat(10);
+        let (long_name, _) =
+            postscript_name_of(&[(3, 1, 0x0409, 1, &long_family), (3, 1, 0x0409, 2, "Bold")]);
+        assert_eq!(long_name.len(), MAX_NAME_TABLE_POSTSCRIPT_NAME_LENGTH);
 
-        let no_palette = resolve_palette(&font_ref, 10, &out_of_bounds_overrides);
-        assert_eq!(no_palette.len(), 0);
+        let font_data = replace_table(&file_buffer, b"name", None);
+        let font_ref = make_font_ref(&font_data, 0);
+        assert_eq!(
+            postscript_name_or_synthesized(&font_ref, &mut postscript_name),
+            PostScriptNameSource::Missing
+        );
+        assert!(postscript_name.is_empty());
     }
 
     #[test]
-    fn test_num_fonts_in_collection() {
-        let collection_buffer = fs::read(TEST_COLLECTION_FILENAME)
-            .expect("Unable to open TrueType collection test file.");
-        let font_buffer =
-            fs::read(TEST_FONT_FILENAME).expect("COLRv0/v1 test font could not be opened.");
-        let garbage: [u8; 12] = [
-            b'0', b'a', b'b', b'0', b'a', b'b', b'0', b'a', b'b', b'0', b'a', b'b',
+    fn test_localized_strings_vec_matches_iterator() {
+        let fonts = [
+            fs::read(TEST_ROBOTO).expect("Font to test names could not be opened."),
+            fs::read("resources/fonts/ahem.ttf").expect("Font to test names could not be opened."),
+            multilingual_variable_font(),
         ];
+        for file_buffer in &fonts {
+            let font_ref = make_font_ref(file_buffer, 0);
+            for name_id in [1, 2, 4, 16, 256, 1000] {
+                let mut localized_strings = get_localized_strings(&font_ref, name_id);
+                let mut names = Vec::new();
+                let mut name = BridgeLocalizedName::default();
+                while localized_name_next(&mut localized_strings, &mut name) {
+                    names.push(std::mem::take(&mut name));
+                }
+                assert_eq!(get_localized_strings_vec(&font_ref, name_id), names);
+            }
+        }
+        let font_ref = make_font_ref(&fonts[2], 0);
+        let names = get_localized_strings_vec(&font_ref, 256);
+        assert_eq!(
+            names
+                .iter()
+                .map(|name| (name.string.as_str(), name.language.as_str()))
+                .collect::<Vec<_>>(),
+            [("Weight", "en-US"), ("Strichstärke", "de-DE")]
+        );
+        assert!(get_localized_strings_vec(&font_ref, 1000).is_empty());
+    }
 
-        let mut num_fonts = 0;
-        let result_collection = font_or_collection(&collection_buffer, &mut num_fonts);
-        assert!(result_collection && num_fonts == 2);
-
-        let result_font_file = font_or_collection(&font_buffer, &mut num_fonts);
-        assert!(result_font_file);
-        assert!(num_fonts == 0u32);
+    #[test]
+    fn test_color_glyph_formats() {
+        let formats = |font_data: &[u8]| {
+            let font_ref = make_font_ref(font_data, 0);
+            [
+                has_colrv0_glyphs(&font_ref),
+                has_colrv1_glyphs(&font_ref),
+                has_bitmap_glyphs(&font_ref),
+                has_svg_glyphs(&font_ref),
+            ]
+        };
+        let read = |path: &str| fs::read(path).expect("Color test font could not be opened.");
+        assert_eq!(formats(&read(TEST_ROBOTO)), [false, false, false, false]);
+        assert_eq!(formats(&read(TEST_COLR)), [true, false, false, false]);
+        assert_eq!(
+            formats(&read("resources/fonts/cbdt.ttf")),
+            [false, false, true, false]
+        );
+        assert_eq!(
+            formats(&read("resources/fonts/sbix.ttf")),
+            [false, false, true, false]
+        );
+        assert_eq!(
+            formats(&read("resources/fonts/SampleSVG.ttf")),
+            [false, false, false, true]
+        );
+        let colrv1 = read("resources/fonts/test_glyphs-glyf_colr_1.ttf");
+        assert!(formats(&colrv1)[1]);
 
-        let result_garbage = font_or_collection(&garbage, &mut num_fonts);
-        assert!(!result_garbage);
+        // Version 1 with an empty base glyph list.
+        let colr = FontRef::new(&colrv1)
+            .unwrap()
+            .table_data(Tag::new(b"COLR"))
+            .unwrap();
+        let base_glyph_list_offset = colr.read_at::<u32>(14).unwrap() as usize;
+        let empty_colrv1 = patch_table(
+            &colrv1,
+            b"COLR",
+            &[(base_glyph_list_offset, 0), (base_glyph_list_offset + 2, 0)],
+        );
+        assert!(!formats(&empty_colrv1)[1]);
+        // An SVG table without documents.
+        let sample_svg = read("resources/fonts/SampleSVG.ttf");
+        let svg = FontRef::new(&sample_svg)
+            .unwrap()
+            .table_data(Tag::new(b"SVG "))
+            .unwrap();
+        let document_list_offset = svg.read_at::<u32>(2).unwrap() as usize;
+        let empty_svg = patch_table(&sample_svg, b"SVG ", &[(document_list_offset, 0)]);
+        assert!(!formats(&empty_svg)[3]);
+        // Strikes that index no glyphs.
+        let cbdt = read("resources/fonts/cbdt.ttf");
+        let cblc = FontRef::new(&cbdt)
+            .unwrap()
+            .table_data(Tag::new(b"CBLC"))
+            .unwrap();
+        let num_sizes = cblc.read_at::<u32>(4).unwrap() as usize;
+        // numberOfIndexSubtables of each BitmapSize record.
+        let no_index_subtables: Vec<_> = (0..num_sizes)
+            .flat_map(|size| [(8 + 48 * size + 8, 0), (8 + 48 * size + 10, 0)])
+            .collect();
+        let empty_cbdt = patch_table(&cbdt, b"CBLC", &no_index_subtables);
+        assert!(!formats(&empty_cbdt)[2]);
     }
 
     #[test]
-    fn test_font_attributes() {
-        let file_buffer = fs::read(TEST_CONDENSED_BOLD_ITALIC)
-            .expect("Font to test font styles could not be opened.");
+    fn test_colrv0_layers() {
+        let file_buffer = fs::read(TEST_COLR).expect("COLRv0 test font could not be opened.");
         let font_ref = make_font_ref(&file_buffer, 0);
-        assert!(font_ref_is_valid(&font_ref));
+        let layer = |glyph_id, palette_index| BridgeColorLayer {
+            glyph_id,
+            palette_index,
+            uses_foreground_color: palette_index == 0xFFFF,
+        };
+        let mut layers = Vec::new();
+        assert!(get_colrv0_layers(&font_ref, 2, &mut layers));
+        assert_eq!(layers, [layer(7, 0), layer(8, 0xFFFF)]);
+        assert!(layers[1].uses_foreground_color);
+        assert!(get_colrv0_layers(&font_ref, 3, &mut layers));
+        assert_eq!(
+            layers,
+            [
+                layer(9, 1),
+                layer(10, 2),
+                layer(11, 0xFFFF),
+                layer(12, 0xFFFF),
+                layer(13, 0xFFFF)
+            ]
+        );
+        assert!(get_colrv0_layers(&font_ref, 0, &mut layers));
+        assert_eq!(layers.len(), 3);
+
+        // Layer glyphs have no record of their own.
+        assert!(!get_colrv0_layers(&font_ref, 4, &mut layers));
+        assert!(layers.is_empty());
+        let file_buffer = fs::read(TEST_ROBOTO).expect("Font without COLR could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        assert!(!get_colrv0_layers(&font_ref, 2, &mut layers));
+    }
 
-        let mut font_style = BridgeFontStyle::default();
+    /// A `ColorPainter` operation with the arguments the bridge passes on.
+    #[derive(Debug, PartialEq)]
+    enum PaintOperation {
+        PushTransform([f32; 6]),
+        PopTransform,
+        PushClipGlyph(u16),
+        PushClipRectangle([f32; 4]),
+        PopClip,
+        FillSolid(u16, f32),
+        /// The points, the color stops as (offset, palette index, alpha) and
+        /// the extend mode of a gradient.
+        FillGradient(Vec<f32>, Vec<(f32, u16, f32)>, u8),
+        PushLayer(u8),
+        PopLayer,
+    }
 
-        if get_font_style(font_ref.as_ref(), &mut font_style) {
-            assert_eq!(font_style.width, 5); // The font should have condenced width attribute but
-                                             // it's condenced itself so we have the normal width
-            assert_eq!(font_style.slant, 1); // Skia italic
-            assert_eq!(font_style.weight, 700); // Skia bold
-        } else {
-            assert!(false);
+    #[derive(Default)]
+    struct RecordingPainter {
+        operations: Vec<PaintOperation>,
+    }
+
+    impl ColorPainter for RecordingPainter {
+        fn push_transform(&mut self, t: Transform) {
+            self.operations.push(PaintOperation::PushTransform([
+                t.xx, t.yx, t.xy, t.yy, t.dx, t.dy,
+            ]));
+        }
+
+        fn pop_transform(&mut self) {
+            self.operations.push(PaintOperation::PopTransform);
+        }
+
+        fn push_clip_glyph(&mut self, glyph_id: GlyphId) {
+            self.operations
+                .push(PaintOperation::PushClipGlyph(glyph_id.to_u16()));
+        }
+
+        fn push_clip_box(&mut self, clip_box: BoundingBox<f32>) {
+            self.operations.push(PaintOperation::PushClipRectangle([
+                clip_box.x_min,
+                clip_box.y_min,
+                clip_box.x_max,
+                clip_box.y_max,
+            ]));
+        }
+
+        fn pop_clip(&mut self) {
+            self.operations.push(PaintOperation::PopClip);
+        }
+
+        fn fill(&mut self, brush: Brush) {
+            let gradient = |points: Vec<f32>, color_stops: &[skrifa::color::ColorStop], extend| {
+                let stops = color_stops
+                    .iter()
+                    .map(|stop| (stop.offset, stop.palette_index, stop.alpha))
+                    .collect();
+                PaintOperation::FillGradient(points, stops, extend)
+            };
+            self.operations.push(match brush {
+                Brush::Solid {
+                    palette_index,
+                    alpha,
+                } => PaintOperation::FillSolid(palette_index, alpha),
+                Brush::LinearGradient {
+                    p0,
+                    p1,
+                    color_stops,
+                    extend,
+                } => gradient(vec![p0.x, p0.y, p1.x, p1.y], color_stops, extend as u8),
+                Brush::RadialGradient {
+                    c0,
+                    r0,
+                    c1,
+                    r1,
+                    color_stops,
+                    extend,
+                } => gradient(
+                    vec![c0.x, c0.y, r0, c1.x, c1.y, r1],
+                    color_stops,
+                    extend as u8,
+                ),
+                Brush::SweepGradient {
+                    c0,
+                    start_angle,
+                    end_angle,
+                    color_stops,
+                    extend,
+                } => gradient(
+                    vec![c0.x, c0.y, start_angle, end_angle],
+                    color_stops,
+                    extend as u8,
+                ),
+            });
+        }
+
+        fn push_layer(&mut self, composite_mode: CompositeMode) {
+            self.operations
+                .push(PaintOperation::PushLayer(composite_mode as u8));
+        }
+
+        fn pop_layer(&mut self) {
+            self.operations.push(PaintOperation::PopLayer);
         }
     }
 
     #[test]
-    fn test_variable_font_attributes() {
-        let file_buffer = fs::read(TEST_VARIABLE)
-            .expect("Font to test font styles could not be opened.");
+    fn test_paint_colrv1_glyph() {
+        use PaintOperation::*;
+        let paint = |font_ref: &BridgeFontRef, coords: &BridgeNormalizedCoords, glyph_id| {
+            let mut painter = RecordingPainter::default();
+            let painted = paint_colr_glyph(font_ref, coords, glyph_id, &mut painter);
+            (painted, painter.operations)
+        };
+        let file_buffer = fs::read("resources/fonts/test_glyphs-glyf_colr_1.ttf")
+            .expect("COLRv1 test font could not be opened.");
         let font_ref = make_font_ref(&file_buffer, 0);
-        assert!(font_ref_is_valid(&font_ref));
+        let coords = BridgeNormalizedCoords::default();
 
-        let mut font_style = BridgeFontStyle::default();
+        // A clip box around a glyph filled with a repeating linear gradient,
+        // in font units with y up.
+        assert_eq!(
+            paint(&font_ref, &coords, 8),
+            (
+                true,
+                vec![
+                    PushClipRectangle([100.0, 250.0, 900.0, 950.0]),
+                    PushClipGlyph(8),
+                    FillGradient(
+                        vec![100.0, 250.0, 900.0, 250.0],
+                        vec![(0.0, 0, 1.0), (1.0, 4, 1.0)],
+                        Extend::Repeat as u8
+                    ),
+                    PopClip,
+                    PopClip,
+                ]
+            )
+        );
+        // Composited layers, one of them transformed, with 16 bit alpha.
+        let (painted, operations) = paint(&font_ref, &coords, 84);
+        assert!(painted);
+        assert_eq!(
+            operations,
+            [
+                PushLayer(CompositeMode::SrcOver as u8),
+                PushClipGlyph(3),
+                FillSolid(4, 0.5),
+                PopClip,
+                PushLayer(CompositeMode::DestOver as u8),
+                PushTransform([0.5, 0.0, 0.0, 1.5, 250.0, -250.0]),
+                PushClipGlyph(3),
+                FillSolid(1, F2Dot14::from_f32(0.7).to_f32()),
+                PopClip,
+                PopTransform,
+                PopLayer,
+                PopLayer,
+            ]
+        );
+        // Glyphs without a COLR record paint nothing.
+        assert_eq!(paint(&font_ref, &coords, 0), (false, vec![]));
 
-        assert!(get_font_style(font_ref.as_ref(), &mut font_style));
-        assert_eq!(font_style.width, 5);    // Skia normal
-        assert_eq!(font_style.slant, 0);    // Skia upright
-        assert_eq!(font_style.weight, 400); // Skia normal
+        // Variation deltas apply at the given location.
+        let file_buffer =
+            fs::read(TEST_FONT_FILENAME).expect("Variable COLRv1 test font could not be opened.");
+        let font_ref = make_font_ref(&file_buffer, 0);
+        let start_point = |coords: &BridgeNormalizedCoords| {
+            paint(&font_ref, coords, 90)
+                .1
+                .into_iter()
+                .find_map(|operation| match operation {
+                    FillGradient(points, ..) => Some((points[0], points[1])),
+                    _ => None,
+                })
+        };
+        assert_eq!(start_point(&coords), Some((0.0, 1024.0)));
+        let coords =
+            resolve_into_normalized_coords(&font_ref, &[make_design_coordinate(b"GRX0", 1000.0)]);
+        assert_eq!(start_point(&coords), Some((1000.0, 1024.0)));
     }
 }
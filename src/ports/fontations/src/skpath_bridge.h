@@ -19,6 +19,24 @@ public:
     virtual void quad_to(float cx0, float cy0, float x, float y) = 0;
     virtual void curve_to(float cx0, float cy0, float cx1, float cy1, float x, float y) = 0;
     virtual void close() = 0;
+    virtual void reset() = 0;
+};
+
+enum class PathStatus : uint8_t;
+struct BridgeScalerMetrics;
+
+/** C++ pure virtual interface type, exposed to Rust side to write the outlines
+ * of a run of glyphs to a separate path each. */
+class GlyphPathsWrapper {
+public:
+    virtual ~GlyphPathsWrapper() = default;
+    /** Starts a new path for glyph_id and returns the wrapper for drawing it. */
+    virtual PathWrapper& begin_glyph(uint16_t glyph_id) = 0;
+    /** Finishes the path of glyph_id, which is only valid for a status of
+     * Success or EmptyOutline. */
+    virtual void end_glyph(uint16_t glyph_id,
+                           PathStatus status,
+                           const BridgeScalerMetrics& scaler_metrics) = 0;
 };
 
 /** C++ pure virtual interface type, exposed to Rust side to be able to write
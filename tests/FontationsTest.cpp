@@ -8,6 +8,8 @@
 #include "include/core/SkStream.h"
 #include "include/core/SkTypeface.h"
 #include "include/ports/SkTypeface_fontations.h"
+#include "include/private/base/SkTo.h"
+#include "src/ports/SkTypeface_fontations_priv.h"
 #include "tests/Test.h"
 #include "tools/Resources.h"
 
@@ -18,6 +20,8 @@ const char kFontResource[] = "fonts/ahem.ttf";
 const char kTtcResource[] = "fonts/test.ttc";
 const char kVariableResource[] = "fonts/test_glyphs-glyf_colr_1_variable.ttf";
 constexpr size_t kNumVariableAxes = 44;
+const char kOutlineResource[] = "fonts/Roboto-Regular.ttf";
+constexpr float kOutlineSize = 12.0f;
 
 struct AxisExpectation {
     SkFourByteTag tag;
@@ -259,3 +263,112 @@ DEF_TEST(Fontations_VariationParameters_BufferTooSmall, reporter) {
     REPORTER_ASSERT(reporter,
                     variableTypeface->getVariationDesignParameters(axes, kArrayTooSmall) == -1);
 }
+
+DEF_TEST(Fontations_PackedVerbsMatchPath, reporter) {
+    sk_sp<SkTypeface> typeface(
+            SkTypeface_Make_Fontations(GetResourceAsStream(kOutlineResource), SkFontArguments()));
+    REPORTER_ASSERT(reporter, typeface);
+    SkTypeface_Fontations* fontationsTypeface = static_cast<SkTypeface_Fontations*>(typeface.get());
+    const fontations_ffi::BridgeOutlineCollection& outlines = fontationsTypeface->getOutlines();
+    const fontations_ffi::BridgeNormalizedCoords& coords =
+            fontationsTypeface->getBridgeNormalizedCoords();
+    fontations_ffi::BridgeOutlineLimits limits = fontations_ffi::default_outline_limits();
+    fontations_ffi::BridgePathTransform transform = fontations_ffi::identity_path_transform();
+
+    rust::Vec<uint8_t> verbs;
+    rust::Vec<float> points;
+    constexpr uint16_t kNumGlyphs = 50;
+    for (uint16_t glyphId = 0; glyphId < kNumGlyphs; ++glyphId) {
+        sk_fontations::PathGeometrySink pathSink;
+        fontations_ffi::BridgeScalerMetrics pathMetrics;
+        fontations_ffi::PathStatus pathStatus =
+                fontations_ffi::get_path(outlines,
+                                         glyphId,
+                                         kOutlineSize,
+                                         coords,
+                                         limits,
+                                         transform,
+                                         /*embolden_strength=*/0,
+                                         fontations_ffi::HintingLevel::None,
+                                         /*clean_up_segments=*/false,
+                                         pathSink,
+                                         pathMetrics);
+        fontations_ffi::BridgeScalerMetrics packedMetrics;
+        fontations_ffi::PathStatus packedStatus =
+                fontations_ffi::get_path_verbs(outlines,
+                                               glyphId,
+                                               kOutlineSize,
+                                               coords,
+                                               limits,
+                                               transform,
+                                               /*embolden_strength=*/0,
+                                               fontations_ffi::HintingLevel::None,
+                                               verbs,
+                                               points,
+                                               packedMetrics);
+        REPORTER_ASSERT(reporter, packedStatus == pathStatus);
+        REPORTER_ASSERT(reporter, packedMetrics.has_overlaps == pathMetrics.has_overlaps);
+        REPORTER_ASSERT(reporter,
+                        sk_fontations::PathFromPackedVerbs(verbs, points) ==
+                                std::move(pathSink).into_inner(),
+                        "glyph %d",
+                        glyphId);
+    }
+}
+
+DEF_TEST(Fontations_GlyphRunPathsMatchPath, reporter) {
+    sk_sp<SkTypeface> typeface(
+            SkTypeface_Make_Fontations(GetResourceAsStream(kOutlineResource), SkFontArguments()));
+    REPORTER_ASSERT(reporter, typeface);
+    SkTypeface_Fontations* fontationsTypeface = static_cast<SkTypeface_Fontations*>(typeface.get());
+    const fontations_ffi::BridgeOutlineCollection& outlines = fontationsTypeface->getOutlines();
+    const fontations_ffi::BridgeNormalizedCoords& coords =
+            fontationsTypeface->getBridgeNormalizedCoords();
+    fontations_ffi::BridgeOutlineLimits limits = fontations_ffi::default_outline_limits();
+    fontations_ffi::BridgePathTransform transform = fontations_ffi::identity_path_transform();
+
+    // An out-of-range glyph in the middle of the run must not stop it.
+    const uint16_t kOutOfRangeGlyph = SkTo<uint16_t>(typeface->countGlyphs());
+    const uint16_t glyphIds[] = {1, 2, 3, kOutOfRangeGlyph, 4, 5, 6};
+    sk_fontations::GlyphPathsSink pathsSink;
+    size_t numDrawn = fontations_ffi::get_paths(outlines,
+                                                rust::Slice<const uint16_t>(glyphIds,
+                                                                            std::size(glyphIds)),
+                                                kOutlineSize,
+                                                coords,
+                                                limits,
+                                                transform,
+                                                /*embolden_strength=*/0,
+                                                fontations_ffi::HintingLevel::None,
+                                                pathsSink);
+    REPORTER_ASSERT(reporter, pathsSink.paths().size() == std::size(glyphIds));
+    REPORTER_ASSERT(reporter, pathsSink.statuses().size() == std::size(glyphIds));
+    REPORTER_ASSERT(reporter, numDrawn == std::size(glyphIds) - 1);
+
+    for (size_t i = 0; i < std::size(glyphIds); ++i) {
+        sk_fontations::PathGeometrySink pathSink;
+        fontations_ffi::BridgeScalerMetrics scalerMetrics;
+        fontations_ffi::PathStatus status =
+                fontations_ffi::get_path(outlines,
+                                         glyphIds[i],
+                                         kOutlineSize,
+                                         coords,
+                                         limits,
+                                         transform,
+                                         /*embolden_strength=*/0,
+                                         fontations_ffi::HintingLevel::None,
+                                         /*clean_up_segments=*/false,
+                                         pathSink,
+                                         scalerMetrics);
+        SkPath path;
+        if (status == fontations_ffi::PathStatus::Success ||
+            status == fontations_ffi::PathStatus::EmptyOutline) {
+            path = std::move(pathSink).into_inner();
+            sk_fontations::ResolveOverlaps(scalerMetrics, &path);
+        }
+        REPORTER_ASSERT(reporter, pathsSink.statuses()[i] == status, "glyph %d", glyphIds[i]);
+        REPORTER_ASSERT(reporter, pathsSink.paths()[i] == path, "glyph %d", glyphIds[i]);
+    }
+    REPORTER_ASSERT(reporter,
+                    pathsSink.statuses()[3] == fontations_ffi::PathStatus::GlyphOutOfRange);
+}